@@ -0,0 +1,239 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::document::FamilyDocument;
+
+/// 归档目录
+///
+/// 位于数据文件同级的 `archives/` 目录下
+pub fn archive_dir(data_file: &str) -> PathBuf {
+    Path::new(data_file)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("archives")
+}
+
+/// 指定年份的归档文件路径，如 `archives/offspring_tree_1920.json`
+pub fn archive_path(data_file: &str, year: i32) -> PathBuf {
+    archive_file(&archive_dir(data_file), year)
+}
+
+/// 归档目录 `dir` 下指定年份的归档文件路径
+pub fn archive_file(dir: &Path, year: i32) -> PathBuf {
+    dir.join(format!("offspring_tree_{}.json", year))
+}
+
+/// 将当前数据写入指定年份的归档（已存在则覆盖）
+///
+/// # Returns
+/// 归档文件路径。
+pub fn write_archive(data_file: &str, year: i32, doc: &FamilyDocument) -> io::Result<PathBuf> {
+    write_archive_in(&archive_dir(data_file), year, doc)
+}
+
+/// 将当前数据写入归档目录 `dir` 下指定年份的归档（已存在则覆盖）
+pub fn write_archive_in(dir: &Path, year: i32, doc: &FamilyDocument) -> io::Result<PathBuf> {
+    let path = archive_file(dir, year);
+    fs::create_dir_all(dir)?;
+    doc.save(&path)?;
+    Ok(path)
+}
+
+/// 列出所有归档
+///
+/// # Returns
+/// 按年份升序排列的 `(年份, 文件路径)`；目录不存在时为空。
+pub fn list_archives(data_file: &str) -> Vec<(i32, PathBuf)> {
+    list_archives_in(&archive_dir(data_file))
+}
+
+/// 列出归档目录 `dir` 下的所有归档，返回值同 [`list_archives`]
+pub fn list_archives_in(dir: &Path) -> Vec<(i32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut archives: Vec<(i32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let year = file_name
+                .to_str()?
+                .strip_prefix("offspring_tree_")?
+                .strip_suffix(".json")?
+                .parse()
+                .ok()?;
+            Some((year, entry.path()))
+        })
+        .collect();
+
+    archives.sort_by_key(|(year, _)| *year);
+    archives
+}
+
+/// 读取归档文件
+pub fn load_archive(path: &Path) -> Option<FamilyDocument> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// 后台归档写入器
+///
+/// 归档文件可能有数 MB，在网络文件系统上同步写入会让 REPL 卡顿数秒。
+/// 提交时在当前线程序列化（保证归档的是提交那一刻的数据），
+/// 写入交给后台线程按提交顺序完成；内容与上次写入相同时跳过写入。
+/// 完成情况通过 [`ArchiveWriter::poll`] 取回。
+pub struct ArchiveWriter {
+    jobs: Option<Sender<Job>>,
+    outcomes: Receiver<Outcome>,
+    pending: usize,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// 一次归档写入的结果
+pub enum Outcome {
+    Written(PathBuf),
+    /// 内容与已有归档相同，未重写
+    Unchanged(PathBuf),
+    Failed(PathBuf, io::Error),
+}
+
+struct Job {
+    path: PathBuf,
+    json: String,
+}
+
+impl ArchiveWriter {
+    pub fn new() -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (outcome_tx, outcome_rx) = mpsc::channel();
+
+        let worker = thread::spawn(move || {
+            // 本次会话中各归档最近一次写入内容的哈希
+            let mut written: HashMap<PathBuf, u64> = HashMap::new();
+            for job in job_rx {
+                let outcome = write_job(&job, &mut written);
+                if outcome_tx.send(outcome).is_err() {
+                    break;
+                }
+            }
+        });
+
+        ArchiveWriter {
+            jobs: Some(job_tx),
+            outcomes: outcome_rx,
+            pending: 0,
+            worker: Some(worker),
+        }
+    }
+
+    /// 提交一次归档，写入归档文件 `path`（见 [`archive_file`]）
+    ///
+    /// # Returns
+    /// 归档文件路径；序列化失败时返回错误。
+    pub fn submit(&mut self, path: PathBuf, doc: &FamilyDocument) -> Result<PathBuf, String> {
+        let json = serde_json::to_string_pretty(doc).map_err(|e| e.to_string())?;
+
+        let sender = self.jobs.as_ref().ok_or("归档线程已结束")?;
+        sender
+            .send(Job {
+                path: path.clone(),
+                json,
+            })
+            .map_err(|_| "归档线程已结束".to_string())?;
+
+        self.pending += 1;
+        Ok(path)
+    }
+
+    /// 尚未完成的写入数
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// 取回已完成的写入结果，不阻塞
+    pub fn poll(&mut self) -> Vec<Outcome> {
+        let outcomes: Vec<Outcome> = self.outcomes.try_iter().collect();
+        self.pending -= outcomes.len();
+        outcomes
+    }
+
+    /// 等待全部写入完成
+    pub fn finish(mut self) -> Vec<Outcome> {
+        self.jobs.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+        self.poll()
+    }
+}
+
+impl Default for ArchiveWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ArchiveWriter {
+    fn drop(&mut self) {
+        // 关闭任务通道后等待后台线程写完，避免退出时丢失归档
+        self.jobs.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Written(path) => write!(f, "🗃️ 已归档到 {}", path.display()),
+            Outcome::Unchanged(path) => {
+                write!(f, "🗃️ 归档内容未变化，跳过写入 {}", path.display())
+            }
+            Outcome::Failed(path, e) => write!(f, "❌ 归档 {} 失败：{}", path.display(), e),
+        }
+    }
+}
+
+/// 执行一次写入；内容哈希与上次写入或磁盘上的文件相同时跳过
+fn write_job(job: &Job, written: &mut HashMap<PathBuf, u64>) -> Outcome {
+    let hash = content_hash(job.json.as_bytes());
+
+    let unchanged = match written.get(&job.path) {
+        Some(&last) => last == hash && job.path.exists(),
+        // 会话中首次写入该归档时与磁盘上的旧文件比较
+        None => fs::read(&job.path).is_ok_and(|old| content_hash(&old) == hash),
+    };
+    if unchanged {
+        written.insert(job.path.clone(), hash);
+        return Outcome::Unchanged(job.path.clone());
+    }
+
+    let result = job
+        .path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&job.path, &job.json));
+
+    match result {
+        Ok(()) => {
+            written.insert(job.path.clone(), hash);
+            Outcome::Written(job.path.clone())
+        }
+        Err(e) => Outcome::Failed(job.path.clone(), e),
+    }
+}
+
+fn content_hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,226 @@
+use std::fmt::Write;
+
+use crate::model::FamilyMember;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 年份与年龄的四则运算
+///
+/// 支持整数、`+ - * /`、括号与负号，以及按姓名取成员字段的函数：
+/// - `birth(姓名)` 出生年；
+/// - `age(姓名)` 当前年份下的年龄，需设置年份，已故成员无年龄；
+/// - `power(姓名)` 威望加成；
+/// - `year` 当前年份。
+///
+/// 除法为整除。
+pub struct Calculation {
+    pub expression: String,
+    pub value: i64,
+    /// 取到的成员字段，如 `birth(张三) = 1240`，按出现顺序
+    pub lookups: Vec<(String, i64)>,
+}
+
+/// 表达式的逐字符解析器，边解析边求值
+struct Evaluator<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    source: &'a str,
+    tree: &'a FamilyMember,
+    year: Option<i32>,
+    lookups: Vec<(String, i64)>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Calculation {
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        for (call, value) in &self.lookups {
+            writeln!(out, "  {} = {}", call, value).unwrap();
+        }
+        writeln!(out, "{} = {}", self.expression, self.value).unwrap();
+        out
+    }
+}
+
+impl Evaluator<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("应为 {}", c)))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!(
+            "表达式【{}】第 {} 个字符处{}",
+            self.source,
+            self.pos + 1,
+            message
+        )
+    }
+
+    /// 加减
+    fn expression(&mut self) -> Result<i64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value = value.checked_add(self.term()?).ok_or("结果溢出")?;
+            } else if self.eat('-') {
+                value = value.checked_sub(self.term()?).ok_or("结果溢出")?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// 乘除
+    fn term(&mut self) -> Result<i64, String> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value = value.checked_mul(self.factor()?).ok_or("结果溢出")?;
+            } else if self.eat('/') {
+                let divisor = self.factor()?;
+                if divisor == 0 {
+                    return Err(self.error("除数为 0"));
+                }
+                value /= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// 数字、括号、负号、`year` 或函数调用
+    fn factor(&mut self) -> Result<i64, String> {
+        self.skip_spaces();
+        if self.eat('-') {
+            return self
+                .factor()?
+                .checked_neg()
+                .ok_or_else(|| "结果溢出".into());
+        }
+        if self.eat('(') {
+            let value = self.expression()?;
+            self.expect(')')?;
+            return Ok(value);
+        }
+
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let digits: String = self.chars[start..self.pos].iter().collect();
+                digits.parse().map_err(|_| self.error("数字过大"))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                let word: String = self.chars[start..self.pos].iter().collect();
+                if word == "year" {
+                    return self.year().map(i64::from);
+                }
+                self.call(&word)
+            }
+            _ => Err(self.error("应为数字、括号、year 或 birth/age/power(姓名)")),
+        }
+    }
+
+    /// `函数(姓名)`，姓名为括号内去掉首尾空白与引号的全部文字
+    fn call(&mut self, function: &str) -> Result<i64, String> {
+        if !matches!(function, "birth" | "age" | "power") {
+            return Err(self.error(&format!(
+                "未知函数 {}，可用：birth、age、power、year",
+                function
+            )));
+        }
+        self.expect('(')?;
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != ')') {
+            self.pos += 1;
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        self.expect(')')?;
+
+        let name = raw.trim().trim_matches(|c| c == '\'' || c == '"');
+        let member = self
+            .tree
+            .find_member_by_name(name)
+            .ok_or_else(|| format!("未找到成员【{}】", name))?;
+        let value = match function {
+            "birth" => i64::from(member.birth_year),
+            "power" => i64::from(member.hoser_power_add),
+            _ if member.is_dead => return Err(format!("【{}】已故，没有年龄", name)),
+            _ => i64::from(self.year()? - member.birth_year),
+        };
+        self.lookups
+            .push((format!("{}({})", function, name), value));
+        Ok(value)
+    }
+
+    fn year(&self) -> Result<i32, String> {
+        self.year
+            .ok_or_else(|| "请先设置年份：year <年份>".to_string())
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 求值表达式，成员字段按姓名在 `tree` 中查找
+pub fn evaluate(
+    expression: &str,
+    tree: &FamilyMember,
+    year: Option<i32>,
+) -> Result<Calculation, String> {
+    let mut evaluator = Evaluator {
+        chars: expression.trim().chars().collect(),
+        pos: 0,
+        source: expression.trim(),
+        tree,
+        year,
+        lookups: Vec::new(),
+    };
+    let value = evaluator.expression()?;
+    evaluator.skip_spaces();
+    if evaluator.pos < evaluator.chars.len() {
+        return Err(evaluator.error("有多余的内容"));
+    }
+
+    Ok(Calculation {
+        expression: expression.trim().to_string(),
+        value,
+        lookups: evaluator.lookups,
+    })
+}
@@ -0,0 +1,276 @@
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::FamilyMember;
+
+/// 职位目录中的一项
+///
+/// 记录职位名称及任职限制（最低年龄、是否要求在世）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionRule {
+    pub title: String,
+
+    #[serde(default)]
+    pub min_age: Option<u16>,
+    #[serde(default)]
+    pub alive_only: bool,
+}
+
+/// 职位目录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PositionCatalog {
+    rules: Vec<PositionRule>,
+}
+
+/// 出缺的职位：目录中没有在世成员担任的职位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vacancy {
+    pub title: String,
+    /// 最近一位已故的任职者，从未有人担任时为 `None`
+    pub former: Option<String>,
+}
+
+/// 职位继任的排序依据
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankBy {
+    /// 距家主代数少者优先
+    #[default]
+    Generation,
+    /// 年长者优先
+    Age,
+    /// 威望加成高者优先
+    Power,
+}
+
+/// 职位继任候选人
+#[derive(Debug, Clone)]
+pub struct Candidate<'a> {
+    pub member: &'a FamilyMember,
+    /// 距家主的代数
+    pub depth: usize,
+    /// 是否为前任的后代（子承父职，排在前面）
+    pub heir_of_former: bool,
+    /// 满足任职条件但需提示的警告（如未设置年份无法判断年龄）
+    pub warnings: Vec<String>,
+}
+
+impl FromStr for RankBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "generation" => Ok(RankBy::Generation),
+            "age" => Ok(RankBy::Age),
+            "power" => Ok(RankBy::Power),
+            _ => Err(format!(
+                "未知排序依据【{}】，可选：generation, age, power",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RankBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RankBy::Generation => "代际",
+            RankBy::Age => "年龄",
+            RankBy::Power => "威望",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl PositionCatalog {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn get(&self, title: &str) -> Option<&PositionRule> {
+        self.rules.iter().find(|r| r.title == title)
+    }
+
+    /// 添加或覆盖职位规则
+    pub fn upsert(&mut self, rule: PositionRule) {
+        match self.rules.iter_mut().find(|r| r.title == rule.title) {
+            Some(existing) => *existing = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    /// 移除职位规则
+    pub fn remove(&mut self, title: &str) -> Result<(), String> {
+        let before = self.rules.len();
+        self.rules.retain(|r| r.title != title);
+        if self.rules.len() == before {
+            Err(format!("职位目录中没有【{}】", title))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 检查成员是否满足职位的任职限制
+    ///
+    /// 不在目录中的职位不受限制。未设置当前年份时无法计算年龄，
+    /// 年龄限制会被跳过并以警告返回。
+    ///
+    /// # Returns
+    /// * `Ok(warnings)` - 满足限制，附带需提示的警告
+    /// * `Err(violations)` - 不满足的限制
+    pub fn check(
+        &self,
+        member: &FamilyMember,
+        title: &str,
+        current_year: Option<i32>,
+    ) -> Result<Vec<String>, Vec<String>> {
+        let Some(rule) = self.get(title) else {
+            return Ok(Vec::new());
+        };
+
+        let mut warnings = Vec::new();
+        let mut violations = Vec::new();
+
+        if rule.alive_only && member.is_dead {
+            violations.push(format!("【{}】要求在世，而【{}】已故", title, member.name));
+        }
+
+        if let Some(min_age) = rule.min_age {
+            match current_year {
+                Some(year) => {
+                    let age = year - member.birth_year;
+                    if age < i32::from(min_age) {
+                        violations.push(format!(
+                            "【{}】要求年满 {} 岁，而【{}】{} 年仅 {} 岁",
+                            title, min_age, member.name, year, age
+                        ));
+                    }
+                }
+                None => warnings.push(format!(
+                    "未设置当前年份，跳过【{}】的年龄限制（{} 岁）",
+                    title, min_age
+                )),
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// 出缺的职位
+    ///
+    /// 目录中的职位没有在世成员担任即为出缺；前任取最近任职的已故成员。
+    pub fn vacancies(&self, tree: &FamilyMember) -> Vec<Vacancy> {
+        self.rules
+            .iter()
+            .filter(|r| holder(tree, &r.title).is_none())
+            .map(|r| Vacancy {
+                title: r.title.clone(),
+                former: former_holder(tree, &r.title).map(|m| m.name.clone()),
+            })
+            .collect()
+    }
+
+    /// 职位继任候选人，按排序依据从优到劣
+    ///
+    /// 候选人须在世、当前没有职位，并满足目录中的任职条件（不在目录中的职位不受限制）。
+    /// 前任的后代排在最前，其余依次按 `by`、代际、年龄、威望排序。
+    ///
+    /// # param
+    /// * `current_year` - 用于判断年龄限制，未设置时年龄限制以警告跳过
+    pub fn candidates<'a>(
+        &self,
+        tree: &'a FamilyMember,
+        title: &str,
+        current_year: Option<i32>,
+        by: RankBy,
+    ) -> Vec<Candidate<'a>> {
+        let former = former_holder(tree, title).map(|m| m.name.as_str());
+
+        let mut candidates = Vec::new();
+        let mut stack = vec![(tree, 0, false)];
+        while let Some((member, depth, under_former)) = stack.pop() {
+            let is_former = Some(member.name.as_str()) == former;
+            for child in member.children.iter().rev() {
+                stack.push((child, depth + 1, under_former || is_former));
+            }
+            if member.is_dead || member.position.is_some() {
+                continue;
+            }
+            if let Ok(warnings) = self.check(member, title, current_year) {
+                candidates.push(Candidate {
+                    member,
+                    depth,
+                    heir_of_former: under_former,
+                    warnings,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            let generation = a.depth.cmp(&b.depth);
+            let age = a.member.birth_year.cmp(&b.member.birth_year);
+            let power = b.member.hoser_power_add.cmp(&a.member.hoser_power_add);
+            let ranked = match by {
+                RankBy::Generation => generation.then(age).then(power),
+                RankBy::Age => age.then(generation).then(power),
+                RankBy::Power => power.then(generation).then(age),
+            };
+            b.heir_of_former.cmp(&a.heir_of_former).then(ranked)
+        });
+        candidates
+    }
+
+    /// 职位目录的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.rules.is_empty() {
+            writeln!(out, "职位目录为空").unwrap();
+            return out;
+        }
+
+        for rule in &self.rules {
+            let mut limits = Vec::new();
+            if let Some(min_age) = rule.min_age {
+                limits.push(format!("年满 {} 岁", min_age));
+            }
+            if rule.alive_only {
+                limits.push("须在世".to_string());
+            }
+
+            if limits.is_empty() {
+                writeln!(out, "  {}", rule.title).unwrap();
+            } else {
+                writeln!(out, "  {}：{}", rule.title, limits.join("，")).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// 在世的任职者
+pub fn holder<'a>(tree: &'a FamilyMember, title: &str) -> Option<&'a FamilyMember> {
+    tree.members()
+        .into_iter()
+        .find(|m| !m.is_dead && m.position.as_deref() == Some(title))
+}
+
+/// 最近一位已故的任职者，按任职记录的年份判断，没有记录时按出生年份
+pub fn former_holder<'a>(tree: &'a FamilyMember, title: &str) -> Option<&'a FamilyMember> {
+    tree.members()
+        .into_iter()
+        .filter(|m| m.is_dead && m.position.as_deref() == Some(title))
+        .max_by_key(|m| {
+            m.position_history
+                .iter()
+                .filter(|r| r.title == title)
+                .map(|r| r.year)
+                .max()
+                .unwrap_or(m.birth_year)
+        })
+}
@@ -0,0 +1,434 @@
+use std::fmt;
+use std::fmt::Write;
+
+use crate::model::{validate_year, FamilyMember, Generation, MemberType, ID_PREFIX, LIFESPAN};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// 数据自相矛盾，须修正
+    Error,
+    /// 可疑但可能属实，建议核对
+    Warning,
+}
+
+/// 校验规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// 出生年超出允许的范围
+    Year,
+    /// 子嗣早于父辈出生
+    BirthOrder,
+    /// 称谓的代际与树中深度不符
+    Generation,
+    /// 内系/外系与经由的性别不符
+    Lineage,
+    /// 姓名重复
+    Duplicate,
+    /// ID 重复
+    DuplicateId,
+    /// 已故父辈在寿终年龄之后才有子嗣
+    ///
+    /// 数据中没有卒年，只能按年龄推断：子嗣出生时父辈已满 [`LIFESPAN`] 岁。
+    /// 寿终之前去世的父辈查不出来。
+    DeadParent,
+    /// 威望加成超出文档设置的范围（`check power-range`），未设置时不检查
+    Power,
+}
+
+/// 一条校验结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub rule: Rule,
+    /// 从家主到问题成员的姓名
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+/// 全部校验结果，错误在前，同级按先序排列
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Rule {
+    pub fn label(self) -> &'static str {
+        match self {
+            Rule::Year => "年份",
+            Rule::BirthOrder => "出生先后",
+            Rule::Generation => "称谓代际",
+            Rule::Lineage => "内外系",
+            Rule::Duplicate => "重名",
+            Rule::DuplicateId => "ID 重复",
+            Rule::DeadParent => "已故父辈（按年龄推断）",
+            Rule::Power => "威望",
+        }
+    }
+}
+
+impl Finding {
+    pub fn describe(&self) -> String {
+        format!(
+            "[{}] {}｜{}：{}",
+            self.severity,
+            self.rule.label(),
+            self.path.join(" → "),
+            self.message
+        )
+    }
+}
+
+impl Report {
+    /// 相对改动前的结果新出现的问题，用于编辑、合并后只提示由改动带来的问题
+    pub fn introduced<'a>(&'a self, before: &Report) -> Vec<&'a Finding> {
+        self.findings
+            .iter()
+            .filter(|f| !before.findings.contains(f))
+            .collect()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// 问题总数与各级数量，如 `共 3 个问题：错误 2 个，警告 1 个`
+    pub fn summary(&self) -> String {
+        if self.findings.is_empty() {
+            return "未发现问题".to_string();
+        }
+        format!(
+            "共 {} 个问题：错误 {} 个，警告 {} 个",
+            self.findings.len(),
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        for finding in &self.findings {
+            writeln!(out, "{}", finding.describe()).unwrap();
+        }
+        writeln!(out, "{}", self.summary()).unwrap();
+        out
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "错误"),
+            Severity::Warning => write!(f, "警告"),
+        }
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 全面校验家族树
+///
+/// 检查出生年无效、子嗣早于父辈出生、称谓代际与深度不符、内外系与经由的性别不符
+/// （只报告源头，不重复报告随之不符的后代）、重名、ID 重复、已故父辈在满
+/// [`LIFESPAN`] 岁后才有子嗣，以及威望加成超出 `power_range`（`None` 时不检查）。
+/// 数据中没有卒年，已故父辈只能按寿终年龄推断。
+pub fn check(tree: &FamilyMember, power_range: Option<(i32, i32)>) -> Report {
+    let mut findings = Vec::new();
+    let mut path = Vec::new();
+    walk(tree, None, 0, power_range, &mut path, &mut findings);
+
+    let duplicates = tree.duplicates();
+    let mut names: Vec<(&str, Vec<String>)> = Vec::new();
+    if !duplicates.is_empty() {
+        collect_paths(tree, &mut Vec::new(), &mut names);
+    }
+    for duplicate in &duplicates {
+        let occurrences = names.iter().filter(|(n, _)| *n == duplicate.name);
+        let total = duplicate.addresses.len();
+        for (i, ((_, path), address)) in occurrences.zip(&duplicate.addresses).enumerate() {
+            let hint = match address {
+                Some(address) => format!("可用 {} 指定", address),
+                None => "无法按路径或 ID 区分，请先改名其中一处".to_string(),
+            };
+            findings.push(Finding {
+                severity: Severity::Error,
+                rule: Rule::Duplicate,
+                path: path.to_vec(),
+                message: format!(
+                    "【{}】共出现 {} 次（第 {} 处），{}",
+                    duplicate.name,
+                    total,
+                    i + 1,
+                    hint
+                ),
+            });
+        }
+    }
+
+    let mut ids: Vec<(u64, Vec<String>)> = Vec::new();
+    collect_ids(tree, &mut Vec::new(), &mut ids);
+    for (i, (id, path)) in ids.iter().enumerate() {
+        if let Some((_, first)) = ids[..i].iter().find(|(other, _)| other == id) {
+            findings.push(Finding {
+                severity: Severity::Error,
+                rule: Rule::DuplicateId,
+                path: path.clone(),
+                message: format!(
+                    "ID {}{} 已被【{}】使用，按 ID 只会找到该成员",
+                    ID_PREFIX,
+                    id,
+                    first.join(" → ")
+                ),
+            });
+        }
+    }
+
+    // 稳定排序：错误在前，同级保持先序
+    findings.sort_by_key(|f| f.severity);
+    Report { findings }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn walk<'a>(
+    member: &'a FamilyMember,
+    parent: Option<&FamilyMember>,
+    depth: usize,
+    power_range: Option<(i32, i32)>,
+    path: &mut Vec<&'a str>,
+    findings: &mut Vec<Finding>,
+) {
+    path.push(&member.name);
+    let mut report = |severity, rule, message| {
+        findings.push(Finding {
+            severity,
+            rule,
+            path: path.iter().map(|n| n.to_string()).collect(),
+            message,
+        })
+    };
+    let t = &member.member_type;
+
+    if let Err(e) = validate_year(member.birth_year) {
+        report(Severity::Error, Rule::Year, format!("出生年无效：{}", e));
+    }
+
+    let expected = Generation::from_u8(u8::try_from(depth).unwrap_or(u8::MAX));
+    if t.generation == Generation::其他 {
+        report(
+            Severity::Error,
+            Rule::Generation,
+            format!("称谓【{}】无法识别，按深度应属{:?}一代", t, expected),
+        );
+    } else if t.generation != expected {
+        report(
+            Severity::Error,
+            Rule::Generation,
+            format!(
+                "称谓为{}，但位于家主下第 {} 代，应属{:?}一代",
+                t, depth, expected
+            ),
+        );
+    }
+
+    if let Some(parent) = parent {
+        let lineage = MemberType::child_of(&parent.member_type, t.gender).lineage;
+        if t.lineage != lineage {
+            report(
+                Severity::Error,
+                Rule::Lineage,
+                format!(
+                    "称谓为{}，但父辈【{}】为{}，应为{}",
+                    t, parent.name, parent.member_type, lineage
+                ),
+            );
+        }
+
+        if member.birth_year < parent.birth_year {
+            report(
+                Severity::Error,
+                Rule::BirthOrder,
+                format!(
+                    "{} 年出生，早于父辈【{}】（{} 年）",
+                    member.birth_year, parent.name, parent.birth_year
+                ),
+            );
+        } else if parent.is_dead && member.birth_year - parent.birth_year >= LIFESPAN {
+            report(
+                Severity::Warning,
+                Rule::DeadParent,
+                format!(
+                    "{} 年出生时，已故的父辈【{}】已 {} 岁，按寿终年龄 {} 岁推断应已去世",
+                    member.birth_year,
+                    parent.name,
+                    member.birth_year - parent.birth_year,
+                    LIFESPAN
+                ),
+            );
+        }
+    }
+
+    if let Some((min, max)) = power_range {
+        if !(min..=max).contains(&member.hoser_power_add) {
+            report(
+                Severity::Warning,
+                Rule::Power,
+                format!(
+                    "威望加成 {} 超出设定范围 {} ~ {}",
+                    member.hoser_power_add, min, max
+                ),
+            );
+        }
+    }
+
+    for child in &member.children {
+        walk(child, Some(member), depth + 1, power_range, path, findings);
+    }
+    path.pop();
+}
+
+/// 先序收集有 ID 的成员的 ID 与从家主起的路径
+fn collect_ids(member: &FamilyMember, path: &mut Vec<String>, out: &mut Vec<(u64, Vec<String>)>) {
+    path.push(member.name.clone());
+    if let Some(id) = member.id {
+        out.push((id, path.clone()));
+    }
+    for child in &member.children {
+        collect_ids(child, path, out);
+    }
+    path.pop();
+}
+
+/// 先序收集每个成员的姓名与从家主起的路径
+fn collect_paths<'a>(
+    member: &'a FamilyMember,
+    path: &mut Vec<String>,
+    out: &mut Vec<(&'a str, Vec<String>)>,
+) {
+    path.push(member.name.clone());
+    out.push((&member.name, path.clone()));
+    for child in &member.children {
+        collect_paths(child, path, out);
+    }
+    path.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::MAX_YEAR;
+    use crate::testing::sample;
+
+    fn rules(report: &Report) -> Vec<Rule> {
+        report.findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn consistent_tree_has_no_findings() {
+        let report = check(&sample(), Some((-100, 100)));
+        assert!(report.findings.is_empty(), "{}", report.describe());
+        assert_eq!(report.summary(), "未发现问题");
+    }
+
+    #[test]
+    fn reports_invalid_birth_year() {
+        let mut tree = sample();
+        tree.find_member_by_name_mut("李四").unwrap().birth_year = MAX_YEAR + 1;
+        let report = check(&tree, None);
+        assert_eq!(rules(&report), [Rule::Year]);
+        assert_eq!(report.findings[0].path, ["张大", "张二", "张小红", "李四"]);
+    }
+
+    #[test]
+    fn introduced_lists_only_new_findings() {
+        let mut before = sample();
+        before.find_member_by_name_mut("王五").unwrap().birth_year = 1210;
+        let mut after = before.clone();
+        after.find_member_by_name_mut("张三").unwrap().birth_year = 1210;
+
+        let (before, after) = (check(&before, None), check(&after, None));
+        let introduced = after.introduced(&before);
+        assert_eq!(introduced.len(), 1);
+        assert_eq!(introduced[0].path, ["张大", "张二", "张三"]);
+        assert!(before.introduced(&before).is_empty());
+    }
+
+    #[test]
+    fn reports_birth_order_and_generation_as_errors() {
+        let mut tree = sample();
+        let member = tree.find_member_by_name_mut("张三").unwrap();
+        member.birth_year = 1210;
+        member.member_type = "曾孙".parse().unwrap();
+
+        let report = check(&tree, None);
+        assert_eq!(rules(&report), [Rule::Generation, Rule::BirthOrder]);
+        assert!(report
+            .findings
+            .iter()
+            .all(|f| f.severity == Severity::Error));
+        assert_eq!(report.findings[0].path, ["张大", "张二", "张三"]);
+    }
+
+    #[test]
+    fn reports_only_the_source_of_a_lineage_mismatch() {
+        let mut tree = sample();
+        tree.find_member_by_name_mut("王五").unwrap().member_type = "孙".parse().unwrap();
+
+        let report = check(&tree, None);
+        assert_eq!(rules(&report), [Rule::Lineage]);
+    }
+
+    #[test]
+    fn reports_each_duplicate_with_its_address() {
+        let mut tree = sample();
+        tree.find_member_by_name_mut("王五").unwrap().name = "张三".to_string();
+
+        let report = check(&tree, None);
+        assert_eq!(rules(&report), [Rule::Duplicate, Rule::Duplicate]);
+        assert!(report.findings[1].message.contains("张丽/张三"));
+    }
+
+    #[test]
+    fn dead_parent_is_an_age_heuristic() {
+        let mut tree = sample();
+        // 张丽 1222 年生、已故；王五 1245 年生时她 23 岁，不报告
+        assert!(check(&tree, None).findings.is_empty());
+
+        tree.find_member_by_name_mut("王五").unwrap().birth_year = 1222 + LIFESPAN;
+        let report = check(&tree, None);
+        assert_eq!(rules(&report), [Rule::DeadParent]);
+        assert_eq!(report.findings[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn power_is_checked_only_against_a_configured_range() {
+        let mut tree = sample();
+        tree.find_member_by_name_mut("张三")
+            .unwrap()
+            .hoser_power_add = 500;
+
+        assert!(check(&tree, None).findings.is_empty());
+        let report = check(&tree, Some((0, 10)));
+        assert_eq!(rules(&report), [Rule::Power]);
+        assert!(report.findings[0].message.contains("0 ~ 10"));
+    }
+}
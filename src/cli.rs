@@ -0,0 +1,214 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use crate::config::{self, Config};
+use crate::example;
+
+/// 指定数据文件的环境变量
+pub const DATA_ENV: &str = "ZZ_SIM_FAMILY_DATA";
+/// 未通过任何方式指定时使用的数据文件，位于当前目录
+pub const DEFAULT_DATA_FILE: &str = "offspring_tree.json";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+// 命令行参数（文档注释会被 clap 用作帮助文字，说明写在 long_about 中）
+#[derive(Debug, Parser)]
+#[command(
+    name = "zz-sim-data-tool",
+    version,
+    about = "祖宗模拟器数据处理 CLI",
+    long_about = "祖宗模拟器数据处理 CLI\n\n\
+                  不带子命令时进入交互会话；会话命令可直接作为子命令执行一次，\n\
+                  --exec、--script 为批处理，--frontend 为前端协议。",
+    help_template = "{about}\n\n用法：{usage}\n\n{all-args}{after-help}",
+    after_help = "其余子命令按会话命令执行一次后退出（选项须写在命令之前），命令末尾加 --save\n\
+                  在成功后保存，如：zz-sim-data-tool count、zz-sim-data-tool -y die 张三 --save\n\n\
+                  数据文件按以下顺序确定：--file、环境变量 ZZ_SIM_FAMILY_DATA、\n\
+                  配置文件 ~/.config/zz-sim/config.toml 中的 data_file、当前目录下的 offspring_tree.json",
+    next_help_heading = "选项",
+    subcommand_help_heading = "子命令",
+    subcommand_value_name = "子命令",
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true
+)]
+pub struct Cli {
+    /// 数据文件路径，优先于环境变量与配置文件
+    #[arg(short, long, global = true, value_name = "路径")]
+    pub file: Option<PathBuf>,
+
+    /// 以单行 JSON 协议与前端通信
+    #[arg(long, conflicts_with_all = ["exec", "script"])]
+    pub frontend: bool,
+
+    /// 执行一条命令，可重复
+    #[arg(short, long, value_name = "命令")]
+    pub exec: Vec<String>,
+
+    /// 执行脚本文件中的命令，`-` 为标准输入，可重复；与 --exec 按出现顺序执行
+    #[arg(long, value_name = "文件")]
+    pub script: Vec<String>,
+
+    /// 需要确认的操作自动确认（批处理模式）
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// 不着色输出（也可设置环境变量 NO_COLOR）
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// 显示帮助
+    #[arg(short, long, action = ArgAction::Help, global = true)]
+    help: Option<bool>,
+
+    /// 显示版本
+    #[arg(short = 'V', long, action = ArgAction::Version)]
+    version: Option<bool>,
+
+    /// `--exec` 与 `--script` 按出现顺序合并，由 [`parse`] 填入
+    #[arg(skip)]
+    pub batch: Vec<BatchSource>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// 子命令：`init` 与一次性命令加载会话，其余不加载
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 交互式新建家族数据文件并进入会话
+    Init {
+        /// 新建的数据文件，缺省按 --file、环境变量、配置文件的顺序确定
+        #[arg(value_name = "路径")]
+        path: Option<PathBuf>,
+    },
+    /// 诊断环境与数据文件
+    Doctor,
+    /// 生成示例家族数据
+    Example {
+        /// 输出文件
+        #[arg(value_name = "路径", default_value = DEFAULT_DATA_FILE)]
+        path: PathBuf,
+        /// 人数
+        #[arg(long, value_name = "人数", default_value_t = example::DEFAULT_SIZE)]
+        size: usize,
+    },
+    /// 在 JSON、YAML 与 MessagePack 之间转换数据文件
+    Convert {
+        /// 输入文件
+        #[arg(value_name = "输入文件")]
+        input: PathBuf,
+        /// 输出文件
+        #[arg(value_name = "输出文件")]
+        output: PathBuf,
+    },
+    /// 其余子命令作为会话命令执行一次，如 `count`、`show 张三`、`die 张三 --save`
+    #[command(external_subcommand)]
+    Once(Vec<String>),
+}
+
+/// 批处理模式的命令来源
+#[derive(Debug, Clone)]
+pub enum BatchSource {
+    /// `--exec <命令>`
+    Exec(String),
+    /// `--script <文件>`，`-` 为标准输入
+    Script(String),
+}
+
+/// 数据文件路径的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataSource {
+    Flag,
+    Env,
+    /// 配置文件路径
+    Config(PathBuf),
+    Default,
+}
+
+/// 确定下来的数据文件
+#[derive(Debug, Clone)]
+pub struct DataFile {
+    pub path: String,
+    pub source: DataSource,
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataSource::Flag => write!(f, "命令行参数 --file"),
+            DataSource::Env => write!(f, "环境变量 {}", DATA_ENV),
+            DataSource::Config(path) => write!(f, "配置文件 {}", path.display()),
+            DataSource::Default => write!(f, "默认位置"),
+        }
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 解析命令行参数，参数有误时打印用法并退出
+pub fn parse() -> Cli {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // clap 分别收集两种参数，按下标还原出现顺序
+    let mut batch: Vec<(usize, BatchSource)> = Vec::new();
+    for (index, command) in matches
+        .indices_of("exec")
+        .into_iter()
+        .flatten()
+        .zip(&cli.exec)
+    {
+        batch.push((index, BatchSource::Exec(command.clone())));
+    }
+    for (index, path) in matches
+        .indices_of("script")
+        .into_iter()
+        .flatten()
+        .zip(&cli.script)
+    {
+        batch.push((index, BatchSource::Script(path.clone())));
+    }
+    batch.sort_by_key(|(index, _)| *index);
+    cli.batch = batch.into_iter().map(|(_, source)| source).collect();
+    cli
+}
+
+/// 确定数据文件：`--file`、环境变量、配置文件中的 `data_file`、默认位置，依次取第一个有值的
+pub fn resolve_data_file(flag: Option<&PathBuf>, config: &Config) -> DataFile {
+    if let Some(path) = flag {
+        return DataFile {
+            path: path.display().to_string(),
+            source: DataSource::Flag,
+        };
+    }
+    if let Ok(path) = env::var(DATA_ENV) {
+        if !path.trim().is_empty() {
+            return DataFile {
+                path,
+                source: DataSource::Env,
+            };
+        }
+    }
+    if let (Some(path), Some(file)) = (config.data_file(), config::config_path()) {
+        return DataFile {
+            path,
+            source: DataSource::Config(file),
+        };
+    }
+    DataFile {
+        path: DEFAULT_DATA_FILE.to_string(),
+        source: DataSource::Default,
+    }
+}
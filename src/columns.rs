@@ -0,0 +1,364 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::model::ID_PREFIX;
+use crate::render::{fit_widths, pad, truncate, Row};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 平铺列表中可选的列
+///
+/// 除成员自身字段外，还包括按家族树与当前年份推算的派生列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Column {
+    Name,
+    Birth,
+    Type,
+    Status,
+    Position,
+    Power,
+    Children,
+    Parent,
+    /// 当前年份减出生年；未设置年份或已故时为空
+    Age,
+    /// 后代人数（不含本人）
+    Descendants,
+    /// 本人与所有后代的威望加成之和
+    SubtreePower,
+    /// 距列表根节点的层数（根为 0）
+    Depth,
+    /// 成员 ID，显示为 `#12`
+    Id,
+}
+
+/// 平铺列表的显示选项：`--columns`、`--profile`、`--sort`、`--desc`
+#[derive(Debug, Clone)]
+pub struct ListOptions {
+    pub columns: Vec<Column>,
+    pub sort: Option<Column>,
+    pub descending: bool,
+    /// 表格总宽上限（通常为终端宽度），不由参数解析，`None` 为不限
+    pub max_width: Option<usize>,
+}
+
+/// 单元格的值，排序时数字按数值比较
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Text(String),
+    Number(i64),
+    Empty,
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .into_iter()
+            .find(|c| c.key() == s)
+            .ok_or_else(|| {
+                let keys: Vec<&str> = Column::ALL.iter().map(|c| c.key()).collect();
+                format!("未知列【{}】，可选：{}", s, keys.join(", "))
+            })
+    }
+}
+
+impl TryFrom<String> for Column {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Column> for String {
+    fn from(column: Column) -> Self {
+        column.key().to_string()
+    }
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Cell::Text(s) => write!(f, "{}", s),
+            Cell::Number(n) => write!(f, "{}", n),
+            Cell::Empty => write!(f, "-"),
+        }
+    }
+}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    /// 空值排在最后
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Cell::Number(a), Cell::Number(b)) => a.cmp(b),
+            (Cell::Text(a), Cell::Text(b)) => a.cmp(b),
+            (Cell::Empty, Cell::Empty) => Ordering::Equal,
+            (Cell::Empty, _) => Ordering::Greater,
+            (_, Cell::Empty) => Ordering::Less,
+            (Cell::Number(_), Cell::Text(_)) => Ordering::Less,
+            (Cell::Text(_), Cell::Number(_)) => Ordering::Greater,
+        }
+    }
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Column {
+    pub const ALL: [Column; 13] = [
+        Column::Name,
+        Column::Birth,
+        Column::Type,
+        Column::Status,
+        Column::Position,
+        Column::Power,
+        Column::Children,
+        Column::Parent,
+        Column::Age,
+        Column::Descendants,
+        Column::SubtreePower,
+        Column::Depth,
+        Column::Id,
+    ];
+
+    /// 未指定 `--columns` 时的列，与 `show` 的表格一致
+    pub const DEFAULT: [Column; 7] = [
+        Column::Name,
+        Column::Birth,
+        Column::Type,
+        Column::Status,
+        Column::Position,
+        Column::Power,
+        Column::Children,
+    ];
+
+    /// 命令行中使用的列名
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Birth => "birth",
+            Column::Type => "type",
+            Column::Status => "status",
+            Column::Position => "position",
+            Column::Power => "power",
+            Column::Children => "children",
+            Column::Parent => "parent",
+            Column::Age => "age",
+            Column::Descendants => "descendants",
+            Column::SubtreePower => "subtree_power",
+            Column::Depth => "depth",
+            Column::Id => "id",
+        }
+    }
+
+    /// 表头
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Name => "姓名",
+            Column::Birth => "出生",
+            Column::Type => "类别",
+            Column::Status => "状态",
+            Column::Position => "职位",
+            Column::Power => "威望+",
+            Column::Children => "子嗣",
+            Column::Parent => "父辈",
+            Column::Age => "年龄",
+            Column::Descendants => "后代",
+            Column::SubtreePower => "支系威望",
+            Column::Depth => "层级",
+            Column::Id => "ID",
+        }
+    }
+
+    /// 解析逗号分隔的列名，如 `name,age,subtree_power`
+    pub fn parse_list(s: &str) -> Result<Vec<Column>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// 计算一行在该列的值
+    pub fn value(self, row: &Row, current_year: Option<i32>) -> Cell {
+        let m = row.member;
+        match self {
+            Column::Name => Cell::Text(m.name.clone()),
+            Column::Birth => Cell::Number(i64::from(m.birth_year)),
+            Column::Type => Cell::Text(m.member_type.to_string()),
+            Column::Status => Cell::Text(m.status().to_string()),
+            Column::Position => m.position.clone().map_or(Cell::Empty, Cell::Text),
+            Column::Power => Cell::Number(i64::from(m.hoser_power_add)),
+            Column::Children => Cell::Number(m.children.len() as i64),
+            Column::Parent => row
+                .parent
+                .map_or(Cell::Empty, |p| Cell::Text(p.name.clone())),
+            Column::Age => match current_year {
+                Some(year) if !m.is_dead => Cell::Number(i64::from(year - m.birth_year)),
+                _ => Cell::Empty,
+            },
+            Column::Descendants => Cell::Number(m.total_count() as i64 - 1),
+            Column::SubtreePower => Cell::Number(m.subtree_power()),
+            Column::Depth => Cell::Number(row.depth as i64),
+            Column::Id => {
+                m.id.map_or(Cell::Empty, |id| Cell::Text(format!("{}{}", ID_PREFIX, id)))
+            }
+        }
+    }
+}
+
+impl ListOptions {
+    /// 解析命令参数
+    ///
+    /// 未识别的参数视为错误，由调用方先取走自己的参数。
+    ///
+    /// # param
+    /// * `profiles` - `--profile` 可引用的命名列方案
+    pub fn parse(args: &[&str], profiles: &BTreeMap<String, Vec<Column>>) -> Result<Self, String> {
+        let mut options = ListOptions {
+            columns: Column::DEFAULT.to_vec(),
+            sort: None,
+            descending: false,
+            max_width: None,
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match *arg {
+                "--columns" => {
+                    let value = iter.next().ok_or("--columns 缺少列名")?;
+                    options.columns = Column::parse_list(value)?;
+                    if options.columns.is_empty() {
+                        return Err("--columns 至少需要一列".to_string());
+                    }
+                }
+                "--profile" => {
+                    let name = iter.next().ok_or("--profile 缺少方案名")?;
+                    options.columns = profiles
+                        .get(*name)
+                        .cloned()
+                        .ok_or_else(|| format!("没有名为【{}】的列方案", name))?;
+                }
+                "--sort" => {
+                    let value = iter.next().ok_or("--sort 缺少列名")?;
+                    options.sort = Some(value.parse()?);
+                }
+                "--desc" => options.descending = true,
+                other => return Err(format!("未知参数【{}】", other)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// 按列渲染平铺表格
+    ///
+    /// 不指定排序列时保持传入顺序；空值总是排在最后。
+    /// 超过 `max_width` 时收窄文字列，放不下的文字以 `…` 截断，数字列保持完整。
+    pub fn render(&self, rows: &[Row], current_year: Option<i32>) -> String {
+        render(
+            rows,
+            &self.columns,
+            self.sort.map(|c| (c, self.descending)),
+            current_year,
+            self.max_width,
+        )
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn render(
+    rows: &[Row],
+    columns: &[Column],
+    sort: Option<(Column, bool)>,
+    current_year: Option<i32>,
+    max_width: Option<usize>,
+) -> String {
+    let mut cells: Vec<Vec<Cell>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|c| c.value(row, current_year)).collect())
+        .collect();
+
+    if let Some((column, descending)) = sort {
+        let sort_cells: Vec<Cell> = rows
+            .iter()
+            .map(|row| column.value(row, current_year))
+            .collect();
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        // 稳定排序，降序时空值仍排在最后
+        order.sort_by(|&a, &b| match (&sort_cells[a], &sort_cells[b]) {
+            (x, y) if descending && *x != Cell::Empty && *y != Cell::Empty => y.cmp(x),
+            (x, y) => x.cmp(y),
+        });
+        let mut taken: Vec<Option<Vec<Cell>>> = cells.into_iter().map(Some).collect();
+        cells = order.into_iter().filter_map(|i| taken[i].take()).collect();
+    }
+
+    let text: Vec<Vec<String>> = cells
+        .iter()
+        .map(|row| row.iter().map(Cell::to_string).collect())
+        .collect();
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            text.iter()
+                .map(|row| row[i].width())
+                .chain([c.title().width()])
+                .max()
+                .unwrap_or(0)
+                + 2
+        })
+        .collect();
+
+    if let Some(max_width) = max_width {
+        let minimums: Vec<usize> = columns.iter().map(|c| c.title().width() + 2).collect();
+        let shrinkable: Vec<usize> = (0..columns.len())
+            .filter(|&i| !cells.iter().any(|row| matches!(row[i], Cell::Number(_))))
+            .collect();
+        fit_widths(&mut widths, &minimums, &shrinkable, max_width);
+    }
+
+    let border = "━".repeat(widths.iter().sum::<usize>().max(20));
+    let mut out = String::new();
+    writeln!(out, "{border}").unwrap();
+    let header: String = columns
+        .iter()
+        .zip(&widths)
+        .map(|(c, w)| pad(c.title(), *w))
+        .collect();
+    writeln!(out, "{}", header.trim_end()).unwrap();
+    writeln!(out, "{border}").unwrap();
+    for row in &text {
+        let line: String = row
+            .iter()
+            .zip(&widths)
+            .map(|(s, w)| pad(&truncate(s, w.saturating_sub(2)), *w))
+            .collect();
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+    out
+}
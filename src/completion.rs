@@ -0,0 +1,170 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+/// 全部命令关键字
+const COMMANDS: &[&str] = &[
+    "add",
+    "age",
+    "ancestors",
+    "autosave",
+    "calc",
+    "calendar",
+    "catalog",
+    "check",
+    "clear",
+    "compare-branches",
+    "config",
+    "count",
+    "cousins",
+    "delete",
+    "die",
+    "diff",
+    "doctor",
+    "edit",
+    "edit-file",
+    "example",
+    "exists",
+    "exit",
+    "explain",
+    "export",
+    "find",
+    "fix",
+    "found",
+    "fold",
+    "format",
+    "health-weights",
+    "help",
+    "import",
+    "info",
+    "inherit",
+    "limits",
+    "list",
+    "lock",
+    "merge-members",
+    "meta",
+    "missing",
+    "move",
+    "path",
+    "perf",
+    "pick",
+    "position",
+    "positions",
+    "profile",
+    "protect",
+    "prune",
+    "query",
+    "quit",
+    "record",
+    "recover",
+    "redo",
+    "relationship",
+    "rename",
+    "replay",
+    "report",
+    "sandbox",
+    "save",
+    "search",
+    "show",
+    "siblings",
+    "stats",
+    "status",
+    "succession",
+    "suggest-prune",
+    "summary",
+    "theme",
+    "tutorial",
+    "undo",
+    "unlock",
+    "verify-save",
+    "watch-game",
+    "why-not-heir",
+    "year",
+];
+
+/// 第一个参数为成员姓名的命令
+const NAME_COMMANDS: &[&str] = &[
+    "exists",
+    "show",
+    "info",
+    "path",
+    "ancestors",
+    "relationship",
+    "siblings",
+    "cousins",
+    "die",
+    "missing",
+    "found",
+    "rename",
+    "edit",
+    "position",
+    "inherit",
+    "lock",
+    "unlock",
+    "merge-members",
+];
+
+/// REPL 的 Tab 补全
+///
+/// 第一个词补全命令，[`NAME_COMMANDS`] 的第一个参数补全成员姓名。
+/// 姓名随家族树变化，每次读取输入前由 [`set_names`](Self::set_names) 刷新。
+#[derive(Default)]
+pub struct ReplHelper {
+    names: Vec<String>,
+}
+
+impl ReplHelper {
+    pub fn set_names(&mut self, names: Vec<String>) {
+        self.names = names;
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let prefix = &before[start..];
+        let preceding: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let candidates: Vec<&str> = match preceding.as_slice() {
+            [] => COMMANDS.to_vec(),
+            [command] if NAME_COMMANDS.contains(&command.to_lowercase().as_str()) => {
+                self.names.iter().map(String::as_str).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(prefix))
+            .map(|c| Pair {
+                display: c.to_string(),
+                replacement: format!("{} ", c),
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,289 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use zz_sim_data_tool::render::ColumnWidths;
+
+/// 目前支持的界面语言
+const LANGUAGES: [&str; 1] = ["zh"];
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 用户配置，保存在 [`config_path`] 指向的 TOML 文件中
+///
+/// 只存放与具体家族无关的默认值；随数据文件保存的设置（纪年、继承规则等）见 `settings`。
+/// 文件不存在时全部取默认值，启动时读取一次，`config set` 修改后立即生效并写回文件。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 默认数据文件，`--file` 与环境变量 ZZ_SIM_FAMILY_DATA 优先
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<String>,
+    /// `count` 是否计入已故成员及其子孙
+    pub count_dead: bool,
+    /// 交互模式下是否询问确认，关闭后需确认的操作直接执行
+    pub confirm: bool,
+    /// 归档目录，相对路径相对于数据文件所在目录；缺省为数据文件旁的 `archives/`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_dir: Option<String>,
+    /// 界面语言，目前只有 `zh`
+    pub language: String,
+    /// 每累计多少条改动自动保存，缺省不自动保存
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autosave: Option<usize>,
+    /// 交互模式下超过一屏的 show、list、find 输出是否分页
+    pub pager: bool,
+    /// 终端支持时 show 表格是否着色
+    pub color: bool,
+    /// 终端表格的列宽上限（TOML 中表须写在最后）
+    pub columns: ColumnWidths,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Config {
+    /// 可用 `config get/set` 读写的键
+    pub const KEYS: [&'static str; 15] = [
+        "data_file",
+        "count_dead",
+        "confirm",
+        "archive_dir",
+        "language",
+        "autosave",
+        "pager",
+        "color",
+        "columns.name",
+        "columns.birth",
+        "columns.type",
+        "columns.status",
+        "columns.position",
+        "columns.power",
+        "columns.children",
+    ];
+
+    /// 读取配置文件，文件不存在时返回默认配置
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = config_path().filter(|p| p.exists()) else {
+            return Ok(Config::default());
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("读取配置文件 {} 失败：{}", path.display(), e))?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| format!("配置文件 {} 格式不正确：{}", path.display(), e))?;
+        check_language(&config.language)
+            .map_err(|e| format!("配置文件 {} 中 {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// 写回配置文件（整体重写，文件中的注释不会保留）
+    ///
+    /// # Returns
+    /// 配置文件路径。
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = config_path().ok_or("未设置 HOME，无法确定配置文件位置")?;
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建 {} 失败：{}", dir.display(), e))?;
+        }
+        fs::write(&path, content).map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// 配置的数据文件，`~/` 展开为主目录
+    pub fn data_file(&self) -> Option<String> {
+        self.data_file
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| expand_home(p).display().to_string())
+    }
+
+    /// `data_file` 对应的归档目录，未配置时为 `None`（使用数据文件旁的 `archives/`）
+    pub fn archive_dir_for(&self, data_file: &str) -> Option<PathBuf> {
+        let dir = expand_home(
+            self.archive_dir
+                .as_deref()
+                .filter(|d| !d.trim().is_empty())?,
+        );
+        if dir.is_absolute() {
+            Some(dir)
+        } else {
+            Some(
+                Path::new(data_file)
+                    .parent()
+                    .unwrap_or(Path::new("."))
+                    .join(dir),
+            )
+        }
+    }
+
+    /// 读取一项配置，未设置的可选项显示为 `-`
+    pub fn get(&self, key: &str) -> Result<String, String> {
+        let optional = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        let c = &self.columns;
+        Ok(match key {
+            "data_file" => optional(&self.data_file),
+            "count_dead" => on_off(self.count_dead).to_string(),
+            "confirm" => on_off(self.confirm).to_string(),
+            "archive_dir" => optional(&self.archive_dir),
+            "language" => self.language.clone(),
+            "autosave" => self.autosave.map_or("off".to_string(), |n| n.to_string()),
+            "pager" => on_off(self.pager).to_string(),
+            "color" => on_off(self.color).to_string(),
+            "columns.name" => c.name.to_string(),
+            "columns.birth" => c.birth.to_string(),
+            "columns.type" => c.member_type.to_string(),
+            "columns.status" => c.status.to_string(),
+            "columns.position" => c.position.to_string(),
+            "columns.power" => c.power.to_string(),
+            "columns.children" => c.children.to_string(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// 设置一项配置，值的格式有误时不做改动
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let flag = |v: &str| match v {
+            "on" | "true" => Ok(true),
+            "off" | "false" => Ok(false),
+            _ => Err(format!("{} 应为 on 或 off", key)),
+        };
+        let width = |v: &str| match v.parse::<usize>() {
+            Ok(n) if (1..=200).contains(&n) => Ok(n),
+            _ => Err(format!("{} 应为 1 ~ 200 的列宽", key)),
+        };
+        let c = &mut self.columns;
+        match key {
+            "data_file" => self.data_file = Some(value.to_string()),
+            "count_dead" => self.count_dead = flag(value)?,
+            "confirm" => self.confirm = flag(value)?,
+            "archive_dir" => self.archive_dir = Some(value.to_string()),
+            "language" => {
+                check_language(value)?;
+                self.language = value.to_string();
+            }
+            "autosave" => {
+                self.autosave = match value {
+                    "off" => None,
+                    n => match n.parse::<usize>() {
+                        Ok(n) if n > 0 => Some(n),
+                        _ => return Err("autosave 应为正整数或 off".to_string()),
+                    },
+                }
+            }
+            "pager" => self.pager = flag(value)?,
+            "color" => self.color = flag(value)?,
+            "columns.name" => c.name = width(value)?,
+            "columns.birth" => c.birth = width(value)?,
+            "columns.type" => c.member_type = width(value)?,
+            "columns.status" => c.status = width(value)?,
+            "columns.position" => c.position = width(value)?,
+            "columns.power" => c.power = width(value)?,
+            "columns.children" => c.children = width(value)?,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// 恢复一项配置的默认值
+    pub fn unset(&mut self, key: &str) -> Result<(), String> {
+        let default = Config::default();
+        let value = default.get(key)?;
+        match key {
+            "data_file" => self.data_file = None,
+            "archive_dir" => self.archive_dir = None,
+            "autosave" => self.autosave = None,
+            _ => self.set(key, &value)?,
+        }
+        Ok(())
+    }
+
+    /// 列出全部配置项
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        match config_path() {
+            Some(path) if path.exists() => {
+                writeln!(out, "用户配置（{}）：", path.display()).unwrap()
+            }
+            Some(path) => {
+                writeln!(out, "用户配置（{} 尚未创建，均为默认值）：", path.display()).unwrap()
+            }
+            None => writeln!(out, "用户配置（未设置 HOME，均为默认值）：").unwrap(),
+        }
+        for key in Config::KEYS {
+            let value = self.get(key).expect("KEYS 中的键均可读取");
+            writeln!(out, "  {:<18}{}", key, value).unwrap();
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_file: None,
+            count_dead: false,
+            confirm: true,
+            archive_dir: None,
+            language: LANGUAGES[0].to_string(),
+            autosave: None,
+            pager: true,
+            color: true,
+            columns: ColumnWidths::default(),
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 配置文件：`$XDG_CONFIG_HOME/zz-sim/config.toml`，未设置时为 `$HOME/.config/zz-sim/config.toml`
+pub fn config_path() -> Option<PathBuf> {
+    let non_empty = |key| env::var_os(key).filter(|v| !v.is_empty());
+    non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("zz-sim").join("config.toml"))
+}
+
+/// `~/` 开头的路径展开为主目录
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn check_language(language: &str) -> Result<(), String> {
+    if LANGUAGES.contains(&language) {
+        Ok(())
+    } else {
+        Err(format!(
+            "不支持的语言【{}】，目前只支持：{}",
+            language,
+            LANGUAGES.join(", ")
+        ))
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn unknown_key(key: &str) -> String {
+    format!("未知配置项【{}】，可用：{}", key, Config::KEYS.join(", "))
+}
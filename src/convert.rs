@@ -0,0 +1,55 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use zz_sim_data_tool::document::{FamilyDocument, FileFormat};
+
+/// 在 JSON、YAML 与 MessagePack 之间转换数据文件
+///
+/// 两端的格式均由扩展名决定。不加载会话，也不依赖 ZZ_SIM_FAMILY_DATA；
+/// 输出文件已存在时拒绝覆盖。
+///
+/// # Returns
+/// 转换成功时返回 `true`。
+pub fn run(input: &Path, output: &Path) -> bool {
+    if output.exists() {
+        statusln!("❌ {} 已存在，请先移走或换一个输出路径", output.display());
+        return false;
+    }
+
+    let start = Instant::now();
+    let doc = match FamilyDocument::load(input) {
+        Ok(doc) => doc,
+        Err(e) => {
+            statusln!("❌ {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = doc.save(output) {
+        statusln!("❌ 写入 {} 失败：{}", output.display(), e);
+        return false;
+    }
+
+    let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    statusln!(
+        "✅ {}（{}，{}）→ {}（{}，{}），共 {} 人，用时 {:.2?}",
+        input.display(),
+        FileFormat::of(input),
+        format_size(size(input)),
+        output.display(),
+        FileFormat::of(output),
+        format_size(size(output)),
+        doc.tree.total_count(),
+        start.elapsed()
+    );
+    true
+}
+
+/// 文件大小，如 `12.3 MB`
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::model::FamilyMember;
+use crate::render;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 同一成员在两份数据间的变化
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub name: String,
+    /// 变化的字段，如 `已故`、`职位：无 → 太守`
+    pub fields: Vec<String>,
+}
+
+/// 两份家族树的差异，成员按姓名对应
+///
+/// 由 [`compare`] 给出，各列表按先序排列。
+#[derive(Debug, Clone, Default)]
+pub struct TreeDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<Change>,
+}
+
+/// 差异图中节点的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Same,
+    Added,
+    Removed,
+    Changed,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// 单行汇总，如"新增 3 人，移除 1 人，变更 2 人"
+    pub fn summary(&self) -> String {
+        format!(
+            "新增 {} 人，移除 {} 人，变更 {} 人",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.is_empty() {
+            out.push_str("两份数据的成员没有差异\n");
+            return out;
+        }
+        writeln!(out, "{}", self.summary()).unwrap();
+        for name in &self.added {
+            writeln!(out, "  + {}", name).unwrap();
+        }
+        for name in &self.removed {
+            writeln!(out, "  - {}", name).unwrap();
+        }
+        for change in &self.changed {
+            writeln!(out, "  ~ {}：{}", change.name, change.fields.join("；")).unwrap();
+        }
+        out
+    }
+
+    fn status(&self, name: &str) -> Status {
+        if self.added.iter().any(|n| n == name) {
+            Status::Added
+        } else if self.removed.iter().any(|n| n == name) {
+            Status::Removed
+        } else if self.changed.iter().any(|c| c.name == name) {
+            Status::Changed
+        } else {
+            Status::Same
+        }
+    }
+
+    /// Graphviz DOT 差异图
+    ///
+    /// 以新数据的家族树为主体，移除的成员挂在原父辈下。新增为绿色，移除为红色，
+    /// 变更为黄色并在标签中列出变化，未变的成员为白色。
+    pub fn to_dot(&self, old: &FamilyMember, new: &FamilyMember, title: &str) -> String {
+        let mut out = String::from("digraph family_diff {\n");
+        out.push_str("    rankdir=TB;\n");
+        writeln!(
+            out,
+            "    label=\"{}\\n{}\";",
+            render::dot_escape(title),
+            self.summary()
+        )
+        .unwrap();
+        out.push_str("    labelloc=t;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"sans-serif\"];\n");
+        out.push_str("    edge [arrowhead=none];\n\n");
+
+        // 新树的成员在前，移除的成员在后；节点编号取此顺序的下标
+        let mut rows: Vec<(&FamilyMember, Option<&FamilyMember>, Status)> = render::rows(new)
+            .into_iter()
+            .map(|r| (r.member, r.parent, self.status(&r.member.name)))
+            .collect();
+        rows.extend(
+            render::rows(old)
+                .into_iter()
+                .filter(|r| self.status(&r.member.name) == Status::Removed)
+                .map(|r| (r.member, r.parent, Status::Removed)),
+        );
+        let index_of: HashMap<&str, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, (m, _, _))| (m.name.as_str(), i))
+            .collect();
+
+        for (i, (m, _, status)) in rows.iter().enumerate() {
+            let mut label = format!(
+                "{}\\n{} {}",
+                render::dot_escape(&m.name),
+                m.birth_year,
+                m.member_type
+            );
+            if let Some(position) = &m.position {
+                write!(label, "\\n{}", render::dot_escape(position)).unwrap();
+            }
+            if let Some(change) = self.changed.iter().find(|c| c.name == m.name) {
+                for field in &change.fields {
+                    write!(label, "\\n{}", render::dot_escape(field)).unwrap();
+                }
+            }
+            let (fill, border) = match status {
+                Status::Added => ("#abebc6", "#1e8449"),
+                Status::Removed => ("#f5b7b1", "#b03a2e"),
+                Status::Changed => ("#f9e79f", "#b7950b"),
+                Status::Same => ("#ffffff", "#aab7b8"),
+            };
+            writeln!(
+                out,
+                "    n{} [label=\"{}\", fillcolor=\"{}\", color=\"{}\"{}];",
+                i,
+                label,
+                fill,
+                border,
+                if *status == Status::Same {
+                    ""
+                } else {
+                    ", penwidth=2"
+                }
+            )
+            .unwrap();
+        }
+        out.push('\n');
+
+        for (i, (_, parent, status)) in rows.iter().enumerate() {
+            let Some(parent) = parent else { continue };
+            // 移除成员的父辈也被移除时，两者都在图中
+            let Some(p) = index_of.get(parent.name.as_str()) else {
+                continue;
+            };
+            let style = if *status == Status::Removed {
+                " [style=dashed, color=\"#b03a2e\"]"
+            } else {
+                ""
+            };
+            writeln!(out, "    n{} -> n{}{};", p, i, style).unwrap();
+        }
+
+        out.push_str("\n    subgraph cluster_legend {\n");
+        out.push_str("        label=\"图例\";\n");
+        out.push_str("        legend_added [label=\"新增\", fillcolor=\"#abebc6\"];\n");
+        out.push_str("        legend_removed [label=\"移除\", fillcolor=\"#f5b7b1\"];\n");
+        out.push_str("        legend_changed [label=\"变更\", fillcolor=\"#f9e79f\"];\n");
+        out.push_str("    }\n");
+        out.push_str("}\n");
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 比较两份家族树，成员按姓名对应
+///
+/// 比较出生年、称谓、威望加成、职位、是否在世与父辈（即是否被移动）。
+pub fn compare(old: &FamilyMember, new: &FamilyMember) -> TreeDiff {
+    let old_rows = render::rows(old);
+    let new_rows = render::rows(new);
+    let old_by_name: HashMap<&str, &render::Row> = old_rows
+        .iter()
+        .map(|r| (r.member.name.as_str(), r))
+        .collect();
+    let new_names: HashSet<&str> = new_rows.iter().map(|r| r.member.name.as_str()).collect();
+
+    let mut diff = TreeDiff::default();
+    for row in &new_rows {
+        match old_by_name.get(row.member.name.as_str()) {
+            None => diff.added.push(row.member.name.clone()),
+            Some(before) => {
+                let fields = changed_fields(before, row);
+                if !fields.is_empty() {
+                    diff.changed.push(Change {
+                        name: row.member.name.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed = old_rows
+        .iter()
+        .filter(|r| !new_names.contains(r.member.name.as_str()))
+        .map(|r| r.member.name.clone())
+        .collect();
+    diff
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn changed_fields(before: &render::Row, after: &render::Row) -> Vec<String> {
+    let (old, new) = (before.member, after.member);
+    let mut fields = Vec::new();
+    if old.birth_year != new.birth_year {
+        fields.push(format!("出生：{} → {}", old.birth_year, new.birth_year));
+    }
+    if old.member_type != new.member_type {
+        fields.push(format!("称谓：{} → {}", old.member_type, new.member_type));
+    }
+    if old.is_dead != new.is_dead {
+        fields.push(if new.is_dead { "已故" } else { "复生" }.to_string());
+    }
+    if old.missing_since != new.missing_since && !new.is_dead {
+        fields.push(match new.missing_since {
+            Some(year) => format!("自 {} 年起下落不明", year),
+            None => "寻回".to_string(),
+        });
+    }
+    if old.position != new.position {
+        let show = |p: &Option<String>| p.clone().unwrap_or_else(|| "无".to_string());
+        fields.push(format!(
+            "职位：{} → {}",
+            show(&old.position),
+            show(&new.position)
+        ));
+    }
+    if old.hoser_power_add != new.hoser_power_add {
+        fields.push(format!(
+            "威望：{} → {}",
+            old.hoser_power_add, new.hoser_power_add
+        ));
+    }
+    let parent = |r: &render::Row| r.parent.map(|p| p.name.clone());
+    if parent(before) != parent(after) {
+        let show = |p: Option<String>| p.unwrap_or_else(|| "（家主）".to_string());
+        fields.push(format!(
+            "父辈：{} → {}",
+            show(parent(before)),
+            show(parent(after))
+        ));
+    }
+    fields
+}
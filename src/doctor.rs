@@ -0,0 +1,163 @@
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+use zz_sim_data_tool::check::{self, Severity};
+use zz_sim_data_tool::document::{FamilyDocument, FileFormat, SCHEMA_VERSION};
+
+use crate::cli::DataSource;
+
+/// 环境与数据诊断
+///
+/// 依次检查数据文件路径（`source` 为其来源，会话中执行时为 `None`）、读写权限、归档目录 `archive_dir` 以及数据内容，
+/// 对每一项失败给出修复建议。不依赖已加载的家族树，
+/// 因此在数据文件损坏或环境未配置时同样可用。
+///
+/// # Returns
+/// 全部检查通过时返回 `true`。
+pub fn run(data_file: &str, source: Option<&DataSource>, archive_dir: &Path) -> bool {
+    statusln!("🩺 环境诊断");
+
+    // 1. 数据文件路径
+    match source {
+        Some(source) => pass(&format!("数据文件 {}（来自{}）", data_file, source)),
+        None => pass(&format!("数据文件 {}（当前会话）", data_file)),
+    }
+
+    let mut ok = true;
+
+    // 2. 数据文件可读
+    let data = match fs::read(data_file) {
+        Ok(data) => {
+            pass("数据文件可读");
+            Some(data)
+        }
+        Err(e) => {
+            fail(
+                &format!("无法读取数据文件：{}", e),
+                "确认路径正确且文件存在，或用 --file 指定已有的家族数据文件；首次使用可运行 `zz-sim-data-tool init`",
+            );
+            ok = false;
+            None
+        }
+    };
+
+    // 3. 数据文件可写
+    if data.is_some() {
+        match OpenOptions::new().append(true).open(data_file) {
+            Ok(_) => pass("数据文件可写"),
+            Err(e) => {
+                fail(
+                    &format!("数据文件不可写：{}", e),
+                    &format!("chmod u+w {}", data_file),
+                );
+                ok = false;
+            }
+        }
+    }
+
+    // 4. 归档目录
+    ok &= check_archive_dir(archive_dir);
+
+    // 5. 数据解析与校验
+    if let Some(data) = data {
+        match FamilyDocument::parse(&data, Path::new(&data_file)) {
+            Ok(mut doc) => {
+                pass(&format!("数据解析成功，共 {} 名在世成员", doc.tree.size()));
+
+                let version = doc.schema_version;
+                match doc.migrate() {
+                    Ok(()) if version < SCHEMA_VERSION => warn(&format!(
+                        "数据文件格式版本为 {}，下次保存时将升级到 {}",
+                        version, SCHEMA_VERSION
+                    )),
+                    Ok(()) => pass(&format!("数据文件格式版本 {}", version)),
+                    Err(e) => {
+                        fail(&e.to_string(), "升级本程序后再打开该数据文件");
+                        ok = false;
+                    }
+                }
+
+                let report = check::check(&doc.tree, doc.settings.power_range);
+                if report.findings.is_empty() {
+                    pass("数据校验通过");
+                } else {
+                    warn(&format!("数据校验{}", report.summary()));
+                    for finding in &report.findings {
+                        outln!("      - {}", finding.describe());
+                    }
+                    if report.count(Severity::Error) > 0 {
+                        outln!("    修复：fix 修正称谓，rename 消除重名，其余手动修正数据文件");
+                    }
+                }
+            }
+            Err(e) => {
+                fail(
+                    &e.to_string(),
+                    &format!(
+                        "按提示位置修正 {}，或从 archives/ 中恢复最近的归档",
+                        FileFormat::of(Path::new(&data_file))
+                    ),
+                );
+                ok = false;
+            }
+        }
+    }
+
+    if ok {
+        statusln!("✅ 诊断完成，未发现阻断性问题");
+    } else {
+        statusln!("❌ 诊断完成，请按上述提示修复");
+    }
+
+    ok
+}
+
+/// 检查归档目录是否存在且可写
+fn check_archive_dir(dir: &Path) -> bool {
+    if !dir.exists() {
+        warn(&format!("归档目录 {} 不存在", dir.display()));
+        outln!(
+            "    修复：首次 inherit 时会自动创建，也可手动执行 mkdir -p {}",
+            dir.display()
+        );
+        return true;
+    }
+
+    if !dir.is_dir() {
+        fail(
+            &format!("{} 不是目录", dir.display()),
+            "移除同名文件，以便 inherit 创建归档目录",
+        );
+        return false;
+    }
+
+    // 以创建临时文件的方式探测写权限
+    let probe = dir.join(".zz_doctor_probe");
+    match fs::write(&probe, b"") {
+        Ok(_) => {
+            fs::remove_file(&probe).ok();
+            pass(&format!("归档目录 {} 可写", dir.display()));
+            true
+        }
+        Err(e) => {
+            fail(
+                &format!("归档目录不可写：{}", e),
+                &format!("chmod u+w {}", dir.display()),
+            );
+            false
+        }
+    }
+}
+
+fn pass(msg: &str) {
+    statusln!("  ✅ {}", msg);
+}
+
+fn warn(msg: &str) {
+    statusln!("  ⚠️  {}", msg);
+}
+
+fn fail(msg: &str, fix: &str) {
+    statusln!("  ❌ {}", msg);
+    outln!("    修复：{}", fix);
+}
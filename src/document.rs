@@ -0,0 +1,457 @@
+use std::env;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::PositionCatalog;
+use crate::error::ZzError;
+use crate::limits::SizeLimits;
+use crate::model::{parse_year, FamilyMember};
+use crate::protection::{BranchLocks, RootProtection};
+use crate::settings::DocumentSettings;
+use crate::store::FamilyStore;
+use crate::verify::{diff_values, Divergence};
+
+/// 当前数据文件格式版本
+///
+/// 迁移说明：
+/// - 1：初始格式，文件中没有 `schema_version` 字段；
+/// - 2：`hoser_power_add` 由 `u8` 改为 `i32`，可表示负数（惩罚）与更大的加成。
+///   旧文件中的数值原样读取，无需转换，保存时写入新版本号。
+/// - 3：年份由 `u16` 改为 `i32`，负数表示公元前；旧文件同样无需转换。
+/// - 4：每名成员都有 ID，另记下一个自动分配的 ID（`next_id`）。
+///   迁移时按先序为没有 ID 的成员分配自增整数，已有的 ID 保持不变。
+/// - 5：成员 ID 由字符串改为整数。数字字符串按数值读取，其他字符串无法保留，
+///   与没有 ID 的成员一样重新分配。锁定分支由顶端成员的姓名改记为其 ID，
+///   重名时取先序第一处。
+pub const SCHEMA_VERSION: u32 = 5;
+
+/// 指定数据文件格式的环境变量，仅对扩展名无法识别的文件生效
+pub const FORMAT_ENV: &str = "ZZ_SIM_FAMILY_FORMAT";
+
+/// 数据文件的存储格式，由扩展名决定
+///
+/// `.yaml`/`.yml` 为 YAML，便于手工编辑长中文姓名；`.msgpack`/`.mpk` 为
+/// MessagePack，体积小、读写快，适合成员很多的家族；`.json` 为 JSON。
+/// 其他扩展名按环境变量 `ZZ_SIM_FAMILY_FORMAT` 决定，未设置时为 JSON。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Yaml,
+    MessagePack,
+}
+
+/// 家族数据文件
+///
+/// 在家族树之外附带文档级数据（元信息、职位目录、规模限制、家主保护策略、锁定的分支、显示与规则设置、回收站）。
+/// 家主的字段平铺在顶层，因此只含家族树的旧数据文件可以直接读取。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FamilyDocument {
+    /// 格式版本，旧文件缺省为 1
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+
+    #[serde(flatten)]
+    pub tree: FamilyMember,
+
+    #[serde(default, skip_serializing_if = "FamilyMeta::is_empty")]
+    pub meta: FamilyMeta,
+
+    #[serde(default, skip_serializing_if = "PositionCatalog::is_empty")]
+    pub position_catalog: PositionCatalog,
+
+    #[serde(default, skip_serializing_if = "SizeLimits::is_default")]
+    pub limits: SizeLimits,
+
+    #[serde(default, skip_serializing_if = "RootProtection::is_default")]
+    pub root_protection: RootProtection,
+
+    #[serde(default, skip_serializing_if = "BranchLocks::is_empty")]
+    pub locked_branches: BranchLocks,
+
+    #[serde(default, skip_serializing_if = "DocumentSettings::is_default")]
+    pub settings: DocumentSettings,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashEntry>,
+
+    /// 下一个自动分配的成员 ID，只增不减，移除的成员的 ID 不会再分配给他人
+    #[serde(default)]
+    pub next_id: u64,
+}
+
+/// 家族元信息
+///
+/// 族名、家训、始建年份与备注，显示在 `summary` 及各类导出的标题处
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FamilyMeta {
+    #[serde(default)]
+    pub surname: Option<String>,
+    #[serde(default)]
+    pub motto: Option<String>,
+    #[serde(default)]
+    pub founding_year: Option<i32>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// 回收站条目
+///
+/// 保存被移出家族树的成员（连同其子孙）以及移出时的年份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub deleted_year: i32,
+    pub member: FamilyMember,
+}
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+impl FamilyMeta {
+    /// 可编辑的字段名
+    pub const FIELDS: [&'static str; 4] = ["surname", "motto", "founding_year", "notes"];
+
+    pub fn is_empty(&self) -> bool {
+        self.surname.is_none()
+            && self.motto.is_none()
+            && self.founding_year.is_none()
+            && self.notes.is_none()
+    }
+
+    /// 标题，如"张氏家族"；未设置族名时为"家族"
+    pub fn title(&self) -> String {
+        match &self.surname {
+            Some(surname) => format!("{}氏家族", surname.trim_end_matches('氏')),
+            None => "家族".to_string(),
+        }
+    }
+
+    /// 设置字段
+    ///
+    /// `value` 为 `None` 时清除该字段
+    pub fn set(&mut self, field: &str, value: Option<&str>) -> Result<(), String> {
+        let value = value.map(str::to_string);
+        match field {
+            "surname" => self.surname = value,
+            "motto" => self.motto = value,
+            "founding_year" => {
+                self.founding_year = match value {
+                    Some(v) => Some(parse_year(&v)?),
+                    None => None,
+                }
+            }
+            "notes" => self.notes = value,
+            _ => {
+                return Err(format!(
+                    "未知字段【{}】，可选：{}",
+                    field,
+                    Self::FIELDS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// 元信息的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "族名：{}", self.surname.as_deref().unwrap_or("-")).unwrap();
+        writeln!(out, "家训：{}", self.motto.as_deref().unwrap_or("-")).unwrap();
+        match self.founding_year {
+            Some(year) => writeln!(out, "始建：{} 年", year).unwrap(),
+            None => writeln!(out, "始建：-").unwrap(),
+        }
+        writeln!(out, "备注：{}", self.notes.as_deref().unwrap_or("-")).unwrap();
+        out
+    }
+}
+
+impl FamilyDocument {
+    /// 将旧版本数据迁移到当前格式
+    ///
+    /// # Returns
+    /// 文件版本比本程序新时返回错误，避免保存时丢失新字段。
+    pub fn migrate(&mut self) -> Result<(), ZzError> {
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(ZzError::UnsupportedSchema {
+                found: self.schema_version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
+        // 1 -> 2：威望加成改为 i32；2 -> 3：年份改为 i32。数值均无需转换
+        // 3 -> 4：补上成员 ID；4 -> 5：ID 改为整数，读取时已转换，非数字 ID 在此重新分配；
+        // 锁定分支由姓名换成 ID
+        self.assign_ids();
+        self.locked_branches.resolve_names(&self.tree);
+        self.schema_version = SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// 为没有 ID 的成员（旧数据或新添加的成员）分配自增整数 ID
+    ///
+    /// 从 `next_id` 与家族树、回收站中已有的最大整数 ID 之后开始分配。
+    ///
+    /// # Returns
+    /// 新分配的个数。
+    pub fn assign_ids(&mut self) -> usize {
+        let taken = self
+            .trash
+            .iter()
+            .map(|e| e.member.max_id())
+            .fold(self.tree.max_id(), u64::max);
+        let mut next = self.next_id.max(taken + 1);
+        let assigned = self.tree.assign_ids(&mut next);
+        self.next_id = next;
+        assigned
+    }
+
+    /// 读取数据文件并迁移到当前格式，格式由扩展名决定
+    pub fn load(path: &Path) -> Result<Self, ZzError> {
+        let data = fs::read(path).map_err(|source| ZzError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut doc = FamilyDocument::parse(&data, path)?;
+        doc.migrate()?;
+        Ok(doc)
+    }
+
+    /// 按 `path` 的扩展名解析文件内容，不做迁移
+    pub fn parse(data: &[u8], path: &Path) -> Result<Self, ZzError> {
+        let format_error = |message: String| ZzError::Format {
+            path: path.to_path_buf(),
+            format: FileFormat::of(path).name(),
+            message,
+        };
+        match FileFormat::of(path) {
+            FileFormat::Json => serde_json::from_slice(data)
+                .map_err(|e| ZzError::json(Some(path.to_path_buf()), &e)),
+            FileFormat::Yaml => {
+                serde_yaml::from_slice(data).map_err(|e| format_error(e.to_string()))
+            }
+            FileFormat::MessagePack => {
+                rmp_serde::from_slice(data).map_err(|e| format_error(e.to_string()))
+            }
+        }
+    }
+
+    /// 写入文件，格式由扩展名决定（JSON 为格式化输出）
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = match FileFormat::of(path) {
+            FileFormat::Json => serde_json::to_vec_pretty(self).map_err(io::Error::other)?,
+            FileFormat::Yaml => serde_yaml::to_string(self)
+                .map_err(io::Error::other)?
+                .into_bytes(),
+            // 带字段名写入，省略默认值的字段读回时才能对上
+            FileFormat::MessagePack => rmp_serde::to_vec_named(self).map_err(io::Error::other)?,
+        };
+        fs::write(path, content)
+    }
+
+    /// 磁盘上的文件是否与内存中的数据一致
+    ///
+    /// 按内容比较：文件的格式、排版、字段顺序与格式版本不同不算不一致。
+    pub fn matches_file(&self, path: &Path) -> bool {
+        FamilyDocument::load(path).is_ok_and(|on_disk| self.same_content(&on_disk))
+    }
+
+    /// 两份数据内容是否相同（按序列化后的 JSON 比较）
+    pub fn same_content(&self, other: &FamilyDocument) -> bool {
+        match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// 从磁盘重新读取配置，家族树、元信息与回收站不变
+    ///
+    /// 配置指随数据文件保存的职位目录、规模限制、家主保护策略、锁定的分支与显示/规则设置，
+    /// 可由协作者或外部编辑修改后在不重启的情况下生效。
+    ///
+    /// # Returns
+    /// 发生变化的配置项，路径以旧值为"磁盘"侧、新值为"内存"侧。
+    pub fn reload_config(&mut self, store: &dyn FamilyStore) -> Result<Vec<Divergence>, ZzError> {
+        let on_disk = store.load()?;
+        let changes = diff_values(&self.config_value(), &on_disk.config_value());
+
+        self.position_catalog = on_disk.position_catalog;
+        self.limits = on_disk.limits;
+        self.root_protection = on_disk.root_protection;
+        self.locked_branches = on_disk.locked_branches;
+        self.settings = on_disk.settings;
+        Ok(changes)
+    }
+
+    fn config_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "position_catalog": self.position_catalog,
+            "limits": self.limits,
+            "root_protection": self.root_protection,
+            "locked_branches": self.locked_branches,
+            "settings": self.settings,
+        })
+    }
+
+    /// 家族概览：元信息、家主与规模
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let border = "━".repeat(80);
+        writeln!(out, "{border}").unwrap();
+
+        match &self.meta.motto {
+            Some(motto) => writeln!(out, "{}　——　{}", self.meta.title(), motto).unwrap(),
+            None => writeln!(out, "{}", self.meta.title()).unwrap(),
+        }
+        if let Some(year) = self.meta.founding_year {
+            writeln!(out, "始建于 {}", self.settings.format_year(year)).unwrap();
+        }
+
+        writeln!(out, "{border}").unwrap();
+        writeln!(
+            out,
+            "家主：{}（{} 年生）",
+            self.tree.name, self.tree.birth_year
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "成员：在世 {} 人，共 {} 人",
+            self.tree.size(),
+            self.tree.total_count()
+        )
+        .unwrap();
+        writeln!(out, "代数：{}", self.tree.depth()).unwrap();
+        writeln!(out, "回收站：{} 条", self.trash.len()).unwrap();
+
+        if let Some(notes) = &self.meta.notes {
+            writeln!(out, "备注：{}", notes).unwrap();
+        }
+
+        writeln!(out, "{border}").unwrap();
+        out
+    }
+
+    /// 将移出家族树的分支放入回收站
+    pub fn trash_members(&mut self, members: Vec<FamilyMember>, year: i32) {
+        self.trash
+            .extend(members.into_iter().map(|member| TrashEntry {
+                deleted_year: year,
+                member,
+            }));
+    }
+
+    /// 在回收站中查找成员
+    ///
+    /// # Returns
+    /// 包含该成员的条目（成员可能是被删除分支中的子孙）。
+    pub fn find_in_trash(&self, name: &str) -> Vec<&TrashEntry> {
+        self.trash
+            .iter()
+            .filter(|entry| entry.member.exists(name))
+            .collect()
+    }
+}
+
+impl FileFormat {
+    /// 按扩展名判断文件格式
+    pub fn of(path: &Path) -> Self {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| e.to_ascii_lowercase().parse().ok())
+            .or_else(|| env::var(FORMAT_ENV).ok()?.trim().parse().ok())
+            .unwrap_or(FileFormat::Json)
+    }
+
+    /// 保存时使用的扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Json => "json",
+            FileFormat::Yaml => "yaml",
+            FileFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// 显示名称
+    pub fn name(self) -> &'static str {
+        match self {
+            FileFormat::Json => "JSON",
+            FileFormat::Yaml => "YAML",
+            FileFormat::MessagePack => "MessagePack",
+        }
+    }
+}
+
+impl FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(FileFormat::Json),
+            "yaml" | "yml" => Ok(FileFormat::Yaml),
+            "msgpack" | "mpk" => Ok(FileFormat::MessagePack),
+            _ => Err(format!("未知格式【{}】，可选：json, yaml, msgpack", s)),
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION_4: &str = r##"
+{"schema_version":4,"id":"1","name":"张大","birth_year":1200,"hoser_power_add":5,"member_type":"家主","children":[
+ {"id":"#2","name":"张二","birth_year":1220,"hoser_power_add":3,"member_type":"儿"},
+ {"id":"外来-甲","name":"张丽","birth_year":1222,"hoser_power_add":2,"member_type":"女儿"}],
+ "trash":[{"deleted_year":1230,"member":{"id":"7","name":"张四","birth_year":1225,"hoser_power_add":0,"member_type":"儿"}}],
+ "next_id":3}"##;
+
+    fn ids(tree: &FamilyMember) -> Vec<Option<u64>> {
+        tree.members().iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn migrate_converts_string_ids() {
+        let mut doc = FamilyDocument::parse(VERSION_4.as_bytes(), Path::new("家族.json")).unwrap();
+        assert_eq!(ids(&doc.tree), [Some(1), Some(2), None]);
+
+        doc.migrate().unwrap();
+        assert_eq!(doc.schema_version, SCHEMA_VERSION);
+        // 非数字 ID 在回收站中已有的最大 ID 之后重新分配
+        assert_eq!(ids(&doc.tree), [Some(1), Some(2), Some(8)]);
+        assert_eq!(doc.trash[0].member.id, Some(7));
+        assert_eq!(doc.next_id, 9);
+    }
+
+    #[test]
+    fn migrated_ids_are_saved_as_integers() {
+        let mut doc = FamilyDocument::parse(VERSION_4.as_bytes(), Path::new("家族.json")).unwrap();
+        doc.migrate().unwrap();
+
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(value["id"], 1);
+        assert_eq!(value["children"][1]["id"], 8);
+
+        let saved = serde_json::to_vec(&doc).unwrap();
+        let reloaded = FamilyDocument::parse(&saved, Path::new("家族.json")).unwrap();
+        assert_eq!(ids(&reloaded.tree), ids(&doc.tree));
+    }
+
+    #[test]
+    fn string_ids_load_from_yaml() {
+        let yaml = "schema_version: 4\nid: '12'\nname: 张大\nbirth_year: 1200\nhoser_power_add: 5\nmember_type: 家主\n";
+        let doc = FamilyDocument::parse(yaml.as_bytes(), Path::new("家族.yaml")).unwrap();
+        assert_eq!(doc.tree.id, Some(12));
+    }
+}
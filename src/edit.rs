@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ZzError;
+use crate::model::{self, FamilyMember, Generation, MemberType};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 可用 `edit` 直接修改的成员字段
+///
+/// 姓名与职位另有 `rename`、`position` 命令，职位在这里只改当前值，不写任职记录。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberField {
+    BirthYear,
+    Power,
+    Position,
+    MemberType,
+    Dead,
+}
+
+/// 一次字段修改，由 [`set`] 给出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub name: String,
+    pub field: MemberField,
+    pub from: String,
+    pub to: String,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl MemberField {
+    pub const ALL: [MemberField; 5] = [
+        MemberField::BirthYear,
+        MemberField::Power,
+        MemberField::Position,
+        MemberField::MemberType,
+        MemberField::Dead,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            MemberField::BirthYear => "birth_year",
+            MemberField::Power => "power",
+            MemberField::Position => "position",
+            MemberField::MemberType => "member_type",
+            MemberField::Dead => "dead",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MemberField::BirthYear => "出生年",
+            MemberField::Power => "威望加成",
+            MemberField::Position => "职位",
+            MemberField::MemberType => "称谓",
+            MemberField::Dead => "是否已故",
+        }
+    }
+
+    /// 输入格式提示
+    pub fn hint(self) -> &'static str {
+        match self {
+            MemberField::BirthYear => "年份",
+            MemberField::Power => "整数",
+            MemberField::Position => "职位名，- 为无职位",
+            MemberField::MemberType => "如 孙女、外曾孙，代际须与所在位置一致",
+            MemberField::Dead => "true/false",
+        }
+    }
+
+    /// 成员的当前取值，格式与输入相同
+    pub fn value(self, m: &FamilyMember) -> String {
+        match self {
+            MemberField::BirthYear => m.birth_year.to_string(),
+            MemberField::Power => m.hoser_power_add.to_string(),
+            MemberField::Position => m.position.clone().unwrap_or_else(|| "-".to_string()),
+            MemberField::MemberType => m.member_type.to_string(),
+            MemberField::Dead => m.is_dead.to_string(),
+        }
+    }
+}
+
+impl Edit {
+    pub fn describe(&self) -> String {
+        format!(
+            "【{}】的{}：{} → {}",
+            self.name,
+            self.field.label(),
+            self.from,
+            self.to
+        )
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FromStr for MemberField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MemberField::ALL
+            .into_iter()
+            .find(|f| f.key() == s || f.label() == s)
+            .ok_or_else(|| {
+                let keys: Vec<&str> = MemberField::ALL.iter().map(|f| f.key()).collect();
+                format!("未知字段【{}】，可选：{}", s, keys.join(", "))
+            })
+    }
+}
+
+impl fmt::Display for MemberField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 校验并修改成员的一个字段
+///
+/// - 出生年不得早于父辈，也不得晚于任何子嗣或失踪年份；
+/// - 称谓的代际须与成员在树中的位置一致（移动位置请用 `move`），
+///   改动后按新称谓重算后代的称谓；
+/// - 标记已故时清除下落不明的标记。
+///
+/// 取值与原值相同时报错，不做改动。
+pub fn set(
+    tree: &mut FamilyMember,
+    name: &str,
+    field: MemberField,
+    value: &str,
+) -> Result<Edit, ZzError> {
+    let path = tree
+        .path_to(name)
+        .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+    let depth = path.len() - 1;
+    let parent_birth = depth.checked_sub(1).map(|i| path[i].birth_year);
+    let value = value.trim();
+
+    let member = tree
+        .find_member_by_name_mut(name)
+        .expect("路径存在时成员存在");
+    let from = field.value(member);
+    match field {
+        MemberField::BirthYear => {
+            let year = model::parse_year(value)?;
+            if let Some(parent) = parent_birth.filter(|p| year < *p) {
+                return Err(ZzError::InvalidYear(format!(
+                    "出生年 {} 早于父辈的出生年 {}",
+                    year, parent
+                )));
+            }
+            if let Some(child) = member.children.iter().find(|c| c.birth_year < year) {
+                return Err(ZzError::InvalidYear(format!(
+                    "出生年 {} 晚于子嗣【{}】的出生年 {}",
+                    year, child.name, child.birth_year
+                )));
+            }
+            if let Some(since) = member.missing_since.filter(|s| *s < year) {
+                return Err(ZzError::InvalidYear(format!(
+                    "出生年 {} 晚于失踪年份 {}",
+                    year, since
+                )));
+            }
+            member.birth_year = year;
+        }
+        MemberField::Power => {
+            member.hoser_power_add = value
+                .parse()
+                .map_err(|_| ZzError::Invalid(format!("威望加成应为整数：{}", value)))?;
+        }
+        MemberField::Position => {
+            member.position = match value {
+                "" => return Err(ZzError::Invalid("职位不能为空，无职位请用 -".to_string())),
+                "-" => None,
+                p => Some(p.to_string()),
+            };
+        }
+        MemberField::MemberType => {
+            let member_type: MemberType = value.parse().map_err(ZzError::Invalid)?;
+            let expected = Generation::from_u8(u8::try_from(depth).unwrap_or(u8::MAX));
+            if member_type.generation != expected {
+                return Err(ZzError::Invalid(format!(
+                    "按所在位置，【{}】的称谓应属{:?}一代，调整位置请用 move",
+                    name, expected
+                )));
+            }
+            member.member_type = member_type;
+            member.relabel();
+        }
+        MemberField::Dead => {
+            member.is_dead = match value {
+                "true" | "是" => true,
+                "false" | "否" => false,
+                _ => {
+                    return Err(ZzError::Invalid(format!(
+                        "无效的取值【{}】，可选：true, false",
+                        value
+                    )))
+                }
+            };
+            if member.is_dead {
+                member.missing_since = None;
+            }
+        }
+    }
+
+    let to = field.value(member);
+    if to == from {
+        return Err(ZzError::Invalid(format!(
+            "【{}】的{}已是 {}",
+            name,
+            field.label(),
+            from
+        )));
+    }
+    Ok(Edit {
+        name: name.to_string(),
+        field,
+        from,
+        to,
+    })
+}
@@ -0,0 +1,172 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use zz_sim_data_tool::document::{FamilyDocument, FileFormat};
+use zz_sim_data_tool::verify;
+use zz_sim_data_tool::{check, diff};
+
+use crate::session::Confirm;
+
+/// 最多列出的非成员字段差异条数
+const MAX_CHANGES: usize = 30;
+
+/// 在外部编辑器中编辑整份数据
+///
+/// 将内存中的数据写入临时文件（格式同数据文件，MessagePack 改用 JSON），
+/// 用 `$VISUAL` 或 `$EDITOR` 打开。编辑器退出后重新解析并校验：
+/// 无法解析或有重名时说明原因，可重新打开继续修改；
+/// 通过后列出成员与其他字段的变化及新出现的校验问题，确认后替换内存中的数据。
+/// 替换可用 undo 撤销，save 后才写入数据文件。
+///
+/// # Returns
+/// 是否成功完成（包括未做改动、放弃改动）。
+pub fn run(doc: &mut FamilyDocument, data_file: &str, confirm: Confirm) -> bool {
+    let editor = match find_editor() {
+        Ok(editor) => editor,
+        Err(e) => {
+            statusln!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let path = temp_path(data_file);
+    if let Err(e) = doc.save(&path) {
+        statusln!("❌ 写入临时文件 {} 失败：{}", path.display(), e);
+        return false;
+    }
+
+    let edited = loop {
+        statusln!("📝 正在用 {} 编辑 {}", editor, path.display());
+        if let Err(e) = launch(&editor, &path) {
+            statusln!("❌ {}", e);
+            outln!("   临时文件保留在 {}", path.display());
+            return false;
+        }
+        match check(&path) {
+            Ok(edited) => break edited,
+            Err(e) => {
+                statusln!("❌ {}", e);
+                // 非交互时重新打开也无法修改，直接放弃
+                if !confirm.interactive() || !confirm.ask("重新打开编辑器修改？") {
+                    statusln!("ℹ️ 已放弃改动，编辑过的文件保留在 {}", path.display());
+                    return false;
+                }
+            }
+        }
+    };
+    fs::remove_file(&path).ok();
+
+    if edited.same_content(doc) {
+        statusln!("ℹ️ 未做任何改动");
+        return true;
+    }
+
+    let members = diff::compare(&doc.tree, &edited.tree);
+    outln!("成员变化：{}", members.describe().trim_end());
+
+    // 家主的字段平铺在顶层，换成原来的家族树后再比较，只剩成员以外的变化
+    let to_value = |d: &FamilyDocument| serde_json::to_value(d).unwrap_or_default();
+    let mut rest = edited.clone();
+    rest.tree = doc.tree.clone();
+    let others = verify::diff_values(&to_value(doc), &to_value(&rest));
+    if !others.is_empty() {
+        outln!("其他字段的变化（{} 处）：", others.len());
+        for change in others.iter().take(MAX_CHANGES) {
+            outln!("  {}", change.describe_change());
+        }
+        if others.len() > MAX_CHANGES {
+            outln!("  … 其余 {} 处", others.len() - MAX_CHANGES);
+        }
+    }
+
+    // 与 check 命令同一套规则，只列出编辑后新出现的问题
+    let before = check::check(&doc.tree, doc.settings.power_range);
+    let after = check::check(&edited.tree, edited.settings.power_range);
+    let introduced = after.introduced(&before);
+    if !introduced.is_empty() {
+        statusln!("⚠️  编辑后新出现 {} 个校验问题：", introduced.len());
+        for finding in &introduced {
+            outln!("   - {}", finding.describe());
+        }
+    }
+
+    if confirm.ask("接受这些改动？") {
+        *doc = edited;
+        statusln!("✅ 已接受外部编辑（可 undo 撤销），save 后写入数据文件");
+    } else {
+        statusln!("ℹ️ 已放弃改动");
+    }
+    true
+}
+
+/// 在外部编辑器中编辑一段文本，返回保存后的内容
+///
+/// 文本写入扩展名为 `extension` 的临时文件（便于编辑器识别语法），
+/// 编辑器退出后读回并删除临时文件。
+pub fn edit_text(text: &str, extension: &str) -> Result<String, String> {
+    let editor = find_editor()?;
+    let path = env::temp_dir().join(format!("zz-sim-text-{}.{}", process::id(), extension));
+    fs::write(&path, text).map_err(|e| format!("写入临时文件 {} 失败：{}", path.display(), e))?;
+    statusln!("📝 正在用 {} 编辑 {}", editor, path.display());
+    let edited = launch(&editor, &path).and_then(|()| {
+        fs::read_to_string(&path).map_err(|e| format!("读取 {} 失败：{}", path.display(), e))
+    });
+    fs::remove_file(&path).ok();
+    edited
+}
+
+/// `$VISUAL` 或 `$EDITOR` 中的编辑器命令
+fn find_editor() -> Result<String, String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .find_map(|key| env::var(key).ok().filter(|v| !v.trim().is_empty()))
+        .ok_or_else(|| "未设置环境变量 EDITOR（或 VISUAL），如 export EDITOR=vim".to_string())
+}
+
+/// 临时文件路径，扩展名决定写入与解析的格式
+fn temp_path(data_file: &str) -> PathBuf {
+    let format = match FileFormat::of(Path::new(data_file)) {
+        FileFormat::MessagePack => FileFormat::Json,
+        format => format,
+    };
+    env::temp_dir().join(format!(
+        "zz-sim-edit-{}.{}",
+        process::id(),
+        format.extension()
+    ))
+}
+
+/// 启动编辑器并等待退出，编辑器命令可带参数，如 `code --wait`
+fn launch(editor: &str, path: &Path) -> Result<(), String> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("无法启动编辑器 {}：{}", editor, e))?;
+    if !status.success() {
+        return Err(format!("编辑器 {} 异常退出（{}）", editor, status));
+    }
+    Ok(())
+}
+
+/// 解析并迁移编辑后的文件，拒绝重名
+fn check(path: &Path) -> Result<FamilyDocument, String> {
+    let edited = FamilyDocument::load(path).map_err(|e| format!("编辑后的数据无法解析：{}", e))?;
+    let mut names = edited.tree.names();
+    names.sort_unstable();
+    let mut duplicates: Vec<&str> = names
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0])
+        .collect();
+    duplicates.dedup();
+    if !duplicates.is_empty() {
+        let listed: String = duplicates.iter().map(|n| format!("【{}】", n)).collect();
+        return Err(format!("编辑后的数据有重名：{}，成员须按姓名区分", listed));
+    }
+    Ok(edited)
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// 库的错误类型
+///
+/// `Display` 给出面向用户的中文提示，CLI 直接打印即可；
+/// 库调用方可按变体分别处理。
+#[derive(Debug)]
+pub enum ZzError {
+    /// 环境变量未设置或为空
+    MissingEnv(&'static str),
+    /// 文件读写失败
+    Io { path: PathBuf, source: io::Error },
+    /// JSON 解析失败
+    ///
+    /// `path` 为 `None` 时表示命令行输入的 JSON。
+    Json {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// JSON 以外格式（YAML、MessagePack）的数据文件解析失败，`message` 含出错说明
+    Format {
+        path: PathBuf,
+        format: &'static str,
+        message: String,
+    },
+    /// 数据文件格式版本比本程序新
+    UnsupportedSchema { found: u32, supported: u32 },
+    /// 找不到成员
+    MemberNotFound(String),
+    /// 姓名在家族树中重复
+    DuplicateName(String),
+    /// 成员不符合继承规则，逐条列出原因
+    InvalidInheritance { name: String, reasons: Vec<String> },
+    /// 年份无效
+    InvalidYear(String),
+    /// 其他输入或操作不合法
+    Invalid(String),
+}
+
+impl ZzError {
+    /// 由 serde_json 的错误构造，保留出错位置
+    pub fn json(path: Option<PathBuf>, e: &serde_json::Error) -> Self {
+        // serde_json 的错误信息末尾自带位置，位置已单独保存，去掉以免重复
+        let message = e.to_string();
+        let suffix = format!(" at line {} column {}", e.line(), e.column());
+        ZzError::Json {
+            path,
+            line: e.line(),
+            column: e.column(),
+            message: message
+                .strip_suffix(&suffix)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ZzError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ZzError::MissingEnv(var) => write!(
+                f,
+                "环境变量 {var} 未设置，请使用 export {var}=/path/to/offspring_tree.json（可运行 `zz-sim-data-tool doctor` 诊断，或用 `zz-sim-data-tool example` 生成示例数据）"
+            ),
+            ZzError::Io { path, source } => write!(f, "读写 {} 失败：{}", path.display(), source),
+            ZzError::Json {
+                path: Some(path),
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{} 解析失败（第 {} 行第 {} 列）：{}",
+                path.display(),
+                line,
+                column,
+                message
+            ),
+            ZzError::Json {
+                path: None,
+                column,
+                message,
+                ..
+            } => write!(f, "JSON 格式不正确（第 {} 列）：{}", column, message),
+            ZzError::Format {
+                path,
+                format,
+                message,
+            } => write!(f, "{} 解析失败（{}）：{}", path.display(), format, message),
+            ZzError::UnsupportedSchema { found, supported } => write!(
+                f,
+                "数据文件格式版本为 {}，本程序仅支持到 {}，请升级程序",
+                found, supported
+            ),
+            ZzError::MemberNotFound(name) => write!(f, "未找到成员【{}】", name),
+            ZzError::DuplicateName(name) => write!(f, "【{}】在当前家族树中重名", name),
+            ZzError::InvalidInheritance { name, reasons } => {
+                write!(f, "【{}】不符合继承规则：{}", name, reasons.join("；"))
+            }
+            ZzError::InvalidYear(reason) => write!(f, "{}", reason),
+            ZzError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ZzError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZzError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 仍以 `String` 报错的模块可直接用 `?` 传递
+impl From<ZzError> for String {
+    fn from(e: ZzError) -> Self {
+        e.to_string()
+    }
+}
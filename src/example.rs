@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use zz_sim_data_tool::document::{FamilyDocument, FamilyMeta, SCHEMA_VERSION};
+use zz_sim_data_tool::model::{FamilyMember, Gender, Generation, Lineage, MemberType};
+
+/// 示例家族的默认人数
+pub const DEFAULT_SIZE: usize = 20;
+/// 示例家族人数上限（四代之内可容纳的规模）
+pub const MAX_SIZE: usize = 100;
+
+const SURNAME: &str = "张";
+const ROOT_BIRTH_YEAR: i32 = 1200;
+/// 各代内系男丁的字辈
+const GENERATION_CHARS: [&str; 4] = ["", "文", "承", "德"];
+const MALE_CHARS: &str = "明远瑞康宁安泰和谦礼仁义信智勇毅恒昌";
+const FEMALE_CHARS: &str = "婉清秀兰芳玉静淑慧雅蓉月";
+/// 女儿出嫁后子嗣随夫姓
+const FOREIGN_SURNAMES: [&str; 6] = ["李", "王", "赵", "陈", "刘", "周"];
+const POSITIONS: [&str; 5] = ["太守", "县令", "主簿", "校尉", "郎中"];
+
+/// 生成示例家族数据
+///
+/// 以 1200 年出生的家主为根，按广度优先生成至多四代成员，
+/// 包含女儿一支的外系子孙、部分已故成员与部分任职成员。
+/// 相同人数得到的数据总是相同的。
+pub fn generate(size: usize) -> FamilyDocument {
+    let size = size.clamp(1, MAX_SIZE);
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15 ^ size as u64);
+    let mut used = HashSet::new();
+
+    let root = FamilyMember::builder()
+        .name(format!("{}{}", SURNAME, "公"))
+        .birth_year(ROOT_BIRTH_YEAR)
+        .hoser_power_add(5)
+        .member_type(MemberType::new(
+            Generation::家主,
+            Gender::Male,
+            Lineage::Direct,
+        ))
+        .position("太守")
+        .build()
+        .expect("示例家主数据合法");
+    used.insert(root.name.clone());
+
+    // 以扁平数组按广度优先生成，最后再组装成树
+    let mut nodes: Vec<Option<FamilyMember>> = vec![Some(root)];
+    let mut parents: Vec<usize> = vec![0];
+    let mut cursor = 0;
+    // 规模较大时每人多生几个，保证四代之内能容纳目标人数
+    let extra = u64::from(size > 30);
+
+    while cursor < nodes.len() && nodes.len() < size {
+        let parent = nodes[cursor].as_ref().expect("尚未组装").clone();
+        let depth = u8::from(parent.member_type.generation);
+        if depth >= 3 {
+            break;
+        }
+
+        let count = if depth < 2 {
+            rng.range(2 + extra, 5 + extra)
+        } else {
+            rng.range(1 + extra, 4 + extra)
+        };
+        let mut birth_year = parent.birth_year + rng.range(18, 24) as i32;
+
+        for _ in 0..count {
+            if nodes.len() >= size {
+                break;
+            }
+
+            let gender = if rng.range(0, 1) == 0 {
+                Gender::Male
+            } else {
+                Gender::Female
+            };
+            let member_type = MemberType::child_of(&parent.member_type, gender);
+            let name = unique_name(&mut rng, &mut used, &parent, &member_type);
+
+            let is_dead = match member_type.generation {
+                Generation::儿 => rng.range(0, 99) < 35,
+                Generation::孙 => rng.range(0, 99) < 10,
+                _ => false,
+            };
+            let position = (gender == Gender::Male
+                && member_type.generation <= Generation::孙
+                && rng.range(0, 99) < 30)
+                .then(|| POSITIONS[rng.range(0, POSITIONS.len() as u64 - 1) as usize]);
+
+            let mut member = FamilyMember::builder()
+                .name(name)
+                .birth_year(birth_year)
+                .member_type(member_type)
+                .hoser_power_add(rng.range(0, 8) as i32)
+                .dead(is_dead);
+            if let Some(position) = position {
+                member = member.position(position);
+            }
+            nodes.push(Some(member.build().expect("示例成员数据合法")));
+            parents.push(cursor);
+
+            birth_year += rng.range(1, 4) as i32;
+        }
+
+        cursor += 1;
+    }
+
+    // 自后向前挂到父节点下，保持兄弟间的出生顺序
+    for index in (1..nodes.len()).rev() {
+        let member = nodes[index].take().expect("每个节点只挂一次");
+        nodes[parents[index]]
+            .as_mut()
+            .expect("父节点下标小于子节点")
+            .children
+            .insert(0, member);
+    }
+
+    let mut doc = FamilyDocument {
+        schema_version: SCHEMA_VERSION,
+        tree: nodes[0].take().expect("根节点"),
+        meta: FamilyMeta {
+            surname: Some(SURNAME.to_string()),
+            motto: Some("耕读传家".to_string()),
+            founding_year: Some(ROOT_BIRTH_YEAR),
+            notes: Some("由 example 命令生成的示例数据".to_string()),
+        },
+        position_catalog: Default::default(),
+        limits: Default::default(),
+        root_protection: Default::default(),
+        locked_branches: Default::default(),
+        settings: Default::default(),
+        trash: Vec::new(),
+        next_id: 0,
+    };
+    doc.assign_ids();
+    doc
+}
+
+/// 生成 `size` 人的示例家族并写入 `path`
+pub fn run(path: &Path, size: usize) {
+    if size > MAX_SIZE {
+        statusln!("⚠️  示例家族最多 {} 人，已按 {} 人生成", MAX_SIZE, MAX_SIZE);
+    }
+
+    if let Err(e) = write(path, size) {
+        statusln!("❌ {}", e);
+    }
+}
+
+/// 生成示例数据并写入文件
+///
+/// 目标文件已存在时拒绝覆盖。
+fn write(path: &Path, size: usize) -> Result<(), String> {
+    if path.exists() {
+        return Err(format!("{} 已存在，请换一个路径", path.display()));
+    }
+
+    let doc = generate(size);
+    doc.save(path)
+        .map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+
+    let shown = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    statusln!(
+        "✅ 已生成示例家族（{} 人，{} 代）：{}",
+        doc.tree.total_count(),
+        doc.tree.depth(),
+        shown.display()
+    );
+    outln!("执行以下命令后即可开始体验：");
+    outln!("    zz-sim-data-tool --file {}", shown.display());
+
+    Ok(())
+}
+
+/// 按血统与性别取一个不重复的名字
+///
+/// 内系男丁按字辈取名；外系子孙随父姓（女儿的子女随夫姓）。
+fn unique_name(
+    rng: &mut Rng,
+    used: &mut HashSet<String>,
+    parent: &FamilyMember,
+    member_type: &MemberType,
+) -> String {
+    let surname = match member_type.lineage {
+        Lineage::Direct => SURNAME.to_string(),
+        Lineage::Foreign if parent.member_type.lineage == Lineage::Foreign => parent
+            .name
+            .chars()
+            .next()
+            .map(String::from)
+            .unwrap_or_default(),
+        Lineage::Foreign => {
+            FOREIGN_SURNAMES[rng.range(0, FOREIGN_SURNAMES.len() as u64 - 1) as usize].to_string()
+        }
+    };
+
+    let pool: Vec<char> = match member_type.gender {
+        Gender::Male => MALE_CHARS.chars().collect(),
+        Gender::Female => FEMALE_CHARS.chars().collect(),
+    };
+    let generation_char = match (member_type.gender, member_type.lineage) {
+        (Gender::Male, Lineage::Direct) => {
+            GENERATION_CHARS[usize::from(u8::from(member_type.generation)).min(3)]
+        }
+        _ => "",
+    };
+
+    for attempt in 0.. {
+        let mut name = format!("{}{}", surname, generation_char);
+        name.push(pool[rng.range(0, pool.len() as u64 - 1) as usize]);
+        if generation_char.is_empty() {
+            name.push(pool[rng.range(0, pool.len() as u64 - 1) as usize]);
+        }
+        // 多次重名后追加序号，保证一定能取到名字
+        if attempt >= 50 {
+            name.push_str(&attempt.to_string());
+        }
+
+        if used.insert(name.clone()) {
+            return name;
+        }
+    }
+
+    unreachable!()
+}
+
+/// 简单的 xorshift 伪随机数生成器，保证示例数据可复现
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// `[lo, hi]` 闭区间内的随机数
+    fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.next() % (hi - lo + 1)
+    }
+}
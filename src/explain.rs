@@ -0,0 +1,464 @@
+use std::fmt::Write;
+
+use crate::document::FamilyDocument;
+use crate::error::ZzError;
+use crate::model::FamilyMember;
+use crate::protection::{Guard, RootAction};
+use crate::verify::{self, Divergence};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 支持 `explain` 的命令
+pub const COMMANDS: [&str; 9] = [
+    "add",
+    "position",
+    "prune",
+    "delete",
+    "move",
+    "merge-members",
+    "rename",
+    "die",
+    "inherit",
+];
+
+/// 一条改动命令的执行计划，由 `explain` 输出
+///
+/// 命令在文档副本上试执行，不改动原数据、不写文件、不询问。
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// 原命令
+    pub command: String,
+    /// 涉及的成员，以家主到该成员的路径表示
+    pub targets: Vec<String>,
+    /// 适用的规则与策略
+    pub checks: Vec<PolicyCheck>,
+    /// 执行后将改变的字段，`disk` 侧为执行前的值
+    pub changes: Vec<Divergence>,
+    /// 数据以外的影响：确认、归档、后续询问等
+    pub effects: Vec<String>,
+    /// 命令会改动的锁定分支
+    pub locked: Vec<String>,
+}
+
+/// 一条规则或策略的判定
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyCheck {
+    pub rule: String,
+    pub verdict: Verdict,
+    pub detail: String,
+}
+
+/// 判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// 满足
+    Pass,
+    /// 可以执行，但需留意
+    Warn,
+    /// 命令会被拒绝
+    Block,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Plan {
+    fn new(command: &str) -> Self {
+        Plan {
+            command: command.to_string(),
+            targets: Vec::new(),
+            checks: Vec::new(),
+            changes: Vec::new(),
+            effects: Vec::new(),
+            locked: Vec::new(),
+        }
+    }
+
+    /// 命令是否会被规则拒绝
+    pub fn blocked(&self) -> bool {
+        self.checks.iter().any(|c| c.verdict == Verdict::Block)
+    }
+
+    fn check(&mut self, rule: &str, verdict: Verdict, detail: impl Into<String>) {
+        self.checks.push(PolicyCheck {
+            rule: rule.to_string(),
+            verdict,
+            detail: detail.into(),
+        });
+    }
+
+    /// 记录成员路径，找不到时返回错误
+    fn target(&mut self, tree: &FamilyMember, name: &str) -> Result<(), ZzError> {
+        let path = tree
+            .path_to(name)
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+        let names: Vec<&str> = path.iter().map(|m| m.name.as_str()).collect();
+        self.targets.push(names.join(" → "));
+        Ok(())
+    }
+
+    /// 计划的文字描述
+    pub fn describe(&self) -> String {
+        // 继位等命令会改动大量称谓，只列出前若干处
+        const MAX_CHANGES: usize = 30;
+
+        let mut out = String::new();
+        writeln!(out, "命令：{}", self.command).unwrap();
+
+        writeln!(out, "涉及成员：").unwrap();
+        for target in &self.targets {
+            writeln!(out, "  {}", target).unwrap();
+        }
+
+        if !self.checks.is_empty() {
+            writeln!(out, "规则与策略：").unwrap();
+            for c in &self.checks {
+                let mark = match c.verdict {
+                    Verdict::Pass => "✅",
+                    Verdict::Warn => "⚠️ ",
+                    Verdict::Block => "⛔",
+                };
+                writeln!(out, "  {} {}：{}", mark, c.rule, c.detail).unwrap();
+            }
+        }
+
+        if self.blocked() {
+            writeln!(out, "结论：命令会被拒绝，不会改动数据").unwrap();
+            return out;
+        }
+
+        if self.changes.is_empty() {
+            writeln!(out, "将改变的字段：无").unwrap();
+        } else {
+            writeln!(out, "将改变的字段（{} 处）：", self.changes.len()).unwrap();
+            for change in self.changes.iter().take(MAX_CHANGES) {
+                writeln!(out, "  {}", change.describe_change()).unwrap();
+            }
+            if self.changes.len() > MAX_CHANGES {
+                writeln!(out, "  … 其余 {} 处", self.changes.len() - MAX_CHANGES).unwrap();
+            }
+        }
+        if !self.effects.is_empty() {
+            writeln!(out, "其他影响：").unwrap();
+            for effect in &self.effects {
+                writeln!(out, "  - {}", effect).unwrap();
+            }
+        }
+        writeln!(out, "结论：可以执行").unwrap();
+        out
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 试执行一条改动命令，给出执行计划
+///
+/// # param
+/// * `args` - 已分词的命令，第一个为命令名
+/// * `current_year` - 会话年份，`prune`、`delete`、`inherit` 需要
+///
+/// # Returns
+/// 执行计划；命令不支持、参数不完整或成员不存在时返回错误。
+/// 规则拒绝、数据校验失败不算错误，记入计划的 [`Verdict::Block`]。
+pub fn plan(
+    doc: &FamilyDocument,
+    args: &[&str],
+    current_year: Option<i32>,
+) -> Result<Plan, ZzError> {
+    let Some((command, args)) = args.split_first() else {
+        return Err(ZzError::Invalid("用法：explain <命令>".to_string()));
+    };
+    let unlock = args.contains(&"--unlock");
+    let args: &[&str] = &args
+        .iter()
+        .copied()
+        .filter(|a| *a != "--unlock")
+        .collect::<Vec<_>>();
+    let mut plan = Plan::new(&args_line(command, args));
+    if unlock {
+        plan.command.push_str(" --unlock");
+    }
+    let mut after = doc.clone();
+    let tree = &doc.tree;
+
+    let usage = |u: &str| ZzError::Invalid(format!("用法：explain {}", u));
+    let need_year = |plan: &mut Plan| match current_year {
+        Some(year) => Some(year),
+        None => {
+            plan.check("年份", Verdict::Block, "未设置年份，请先执行 year <年份>");
+            None
+        }
+    };
+
+    match (*command, args) {
+        ("add", [parent, json]) => {
+            plan.target(tree, parent)?;
+            if let Err(e) = after.tree.add_children(parent, json) {
+                plan.check("数据校验", Verdict::Block, e.to_string());
+            }
+        }
+        ("add", _) => return Err(usage("add <姓名> '<JSON 数组>'")),
+
+        ("position", args) => {
+            let force = args.contains(&"--force");
+            let [name, position] = args
+                .iter()
+                .copied()
+                .filter(|a| *a != "--force")
+                .collect::<Vec<_>>()[..]
+            else {
+                return Err(usage("position <姓名> <职位> [--force]"));
+            };
+            plan.target(tree, name)?;
+            let member = tree.find_member_by_name(name).expect("已确认存在");
+            match doc.position_catalog.check(member, position, current_year) {
+                Ok(warnings) if warnings.is_empty() => {
+                    plan.check("任职条件", Verdict::Pass, "满足或该职位未设条件")
+                }
+                Ok(warnings) => {
+                    for w in warnings {
+                        plan.check("任职条件", Verdict::Warn, w);
+                    }
+                }
+                Err(violations) => {
+                    let verdict = if force { Verdict::Warn } else { Verdict::Block };
+                    for v in violations {
+                        plan.check("任职条件", verdict, v);
+                    }
+                    if force {
+                        plan.effects
+                            .push("--force：忽略不满足的任职条件".to_string());
+                    }
+                }
+            }
+            if let Err(e) = after.tree.add_position(name, position, current_year) {
+                plan.check("数据校验", Verdict::Block, e.to_string());
+            }
+        }
+
+        ("prune", []) => {
+            if let Some(year) = need_year(&mut plan) {
+                match after.tree.prune_future_births(year) {
+                    Ok(removed) => {
+                        for m in &removed {
+                            plan.target(tree, &m.name)?;
+                        }
+                        if !removed.is_empty() {
+                            let count: usize = removed.iter().map(|m| m.total_count()).sum();
+                            plan.effects.push("执行前需确认".to_string());
+                            plan.effects
+                                .push(format!("{} 年后出生的 {} 人移入回收站", year, count));
+                            after.trash_members(removed, year);
+                        }
+                    }
+                    Err(e) => plan.check("年份", Verdict::Block, e.to_string()),
+                }
+            }
+        }
+        ("prune", _) => return Err(usage("prune")),
+
+        ("delete", [name]) => {
+            plan.target(tree, name)?;
+            if tree.is_root(name) {
+                plan.check("家主保护（delete）", Verdict::Block, "家主不可删除");
+            } else if let Some(year) = need_year(&mut plan) {
+                let removed = after.tree.remove(name).expect("已确认存在");
+                plan.effects.push("执行前需确认".to_string());
+                plan.effects.push(format!(
+                    "【{}】一支共 {} 人移入回收站",
+                    name,
+                    removed.total_count()
+                ));
+                after.trash_members(vec![removed], year);
+            }
+        }
+        ("delete", _) => return Err(usage("delete <姓名>")),
+
+        ("move", [name, new_parent]) => {
+            plan.target(tree, name)?;
+            plan.target(tree, new_parent)?;
+            match after.tree.reparent(name, new_parent) {
+                Ok(()) => {
+                    let member = tree.find_member_by_name(name).expect("已确认存在");
+                    let parent = tree.find_member_by_name(new_parent).expect("已确认存在");
+                    if member.birth_year < parent.birth_year {
+                        plan.check(
+                            "出生顺序",
+                            Verdict::Warn,
+                            format!(
+                                "【{}】({}) 早于新父辈【{}】({}) 出生",
+                                name, member.birth_year, new_parent, parent.birth_year
+                            ),
+                        );
+                    }
+                }
+                Err(e) => plan.check("数据校验", Verdict::Block, e.to_string()),
+            }
+        }
+        ("move", _) => return Err(usage("move <姓名> <新父辈>")),
+
+        ("merge-members", [keep, duplicate]) => {
+            plan.target(tree, keep)?;
+            plan.target(tree, duplicate)?;
+            match after.tree.merge_members(keep, duplicate) {
+                Ok(moved) => {
+                    plan.effects.push("执行前需确认".to_string());
+                    plan.effects.push(format!(
+                        "【{}】的 {} 名子女与任职记录移到【{}】名下，随后移除【{}】",
+                        duplicate, moved, keep, duplicate
+                    ));
+                }
+                Err(e) => plan.check("数据校验", Verdict::Block, e.to_string()),
+            }
+        }
+        ("merge-members", _) => return Err(usage("merge-members <保留> <移除>")),
+
+        ("rename", [old_name, new_name]) => {
+            plan.target(tree, old_name)?;
+            let guard = doc
+                .root_protection
+                .guard(RootAction::Rename, tree, old_name);
+            plan.check(
+                "家主保护（rename）",
+                guard_verdict(guard),
+                guard_detail(guard, tree.is_root(old_name)),
+            );
+            if let Err(e) = after.tree.rename(old_name, new_name) {
+                plan.check("数据校验", Verdict::Block, e.to_string());
+            } else if guard == Guard::Prompt {
+                if let Some(surname) = &doc.meta.surname {
+                    if !new_name.starts_with(surname.trim_end_matches('氏')) {
+                        plan.effects
+                            .push("执行后询问是否随新名字更新族名".to_string());
+                    }
+                }
+            }
+        }
+        ("rename", _) => return Err(usage("rename <旧名> <新名>")),
+
+        ("die", [name]) => {
+            plan.target(tree, name)?;
+            let guard = doc.root_protection.guard(RootAction::Die, tree, name);
+            plan.check(
+                "家主保护（die）",
+                guard_verdict(guard),
+                guard_detail(guard, tree.is_root(name)),
+            );
+            if let Err(e) = after.tree.mark_dead(name) {
+                plan.check("数据校验", Verdict::Block, e.to_string());
+            } else if guard == Guard::Prompt {
+                plan.effects.push("执行后询问继承人".to_string());
+            }
+        }
+        ("die", _) => return Err(usage("die <姓名>")),
+
+        ("inherit", [name]) => {
+            plan.target(tree, name)?;
+            if let Some(year) = need_year(&mut plan) {
+                for c in doc.settings.succession.explain(tree, name, Some(year))? {
+                    let verdict = match c.passed {
+                        Some(false) => Verdict::Block,
+                        _ => Verdict::Pass,
+                    };
+                    plan.check(&format!("继承规则：{}", c.rule), verdict, c.detail);
+                }
+                if !plan.blocked() {
+                    after.tree = tree.inherit(name)?;
+                    plan.effects.push(format!(
+                        "家主由【{}】改为【{}】，只保留其一支，称谓随之调整",
+                        tree.name, name
+                    ));
+                    plan.effects.push("执行前需确认".to_string());
+                    plan.effects
+                        .push(format!("继位前将 {} 年的数据写入归档", year));
+                }
+            }
+        }
+        ("inherit", _) => return Err(usage("inherit <姓名>")),
+
+        (other, _) => {
+            return Err(ZzError::Invalid(format!(
+                "explain 不支持【{}】，可用于：{}",
+                other,
+                COMMANDS.join("、")
+            )))
+        }
+    }
+
+    let locked = doc.locked_branches.violations(tree, &after.tree);
+    if !locked.is_empty() {
+        let names = locked
+            .iter()
+            .map(|n| format!("【{}】", n))
+            .collect::<Vec<_>>()
+            .join("、");
+        if unlock {
+            plan.check(
+                "分支锁定",
+                Verdict::Warn,
+                format!("{}一支已锁定，--unlock 放行", names),
+            );
+        } else {
+            plan.check(
+                "分支锁定",
+                Verdict::Block,
+                format!("{}一支已锁定，加 --unlock 才能改动", names),
+            );
+        }
+        plan.locked = locked;
+    }
+
+    if plan.blocked() {
+        return Ok(plan);
+    }
+
+    // 执行后新出现的规模警告
+    let before_warnings: Vec<String> = doc.limits.check(tree).iter().map(|e| e.key()).collect();
+    for e in after.limits.check(&after.tree) {
+        if !before_warnings.contains(&e.key()) {
+            plan.check("规模上限", Verdict::Warn, e.describe());
+        }
+    }
+
+    let to_value = |d: &FamilyDocument| {
+        serde_json::to_value(d).map_err(|e| ZzError::Invalid(format!("序列化失败：{}", e)))
+    };
+    plan.changes = verify::diff_values(&to_value(doc)?, &to_value(&after)?);
+    Ok(plan)
+}
+
+/// 还原命令行，含空白的参数加引号
+fn args_line(command: &str, args: &[&str]) -> String {
+    let mut line = command.to_string();
+    for arg in args {
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+            write!(line, " '{}'", arg).unwrap();
+        } else {
+            write!(line, " {}", arg).unwrap();
+        }
+    }
+    line
+}
+
+fn guard_verdict(guard: Guard) -> Verdict {
+    match guard {
+        Guard::Allow | Guard::Prompt => Verdict::Pass,
+        Guard::Refuse => Verdict::Block,
+    }
+}
+
+fn guard_detail(guard: Guard, is_root: bool) -> String {
+    if !is_root {
+        return "不是家主，不受限制".to_string();
+    }
+    match guard {
+        Guard::Allow => "allow，直接执行".to_string(),
+        Guard::Prompt => "prompt，执行后提示后续操作".to_string(),
+        Guard::Refuse => "refuse，策略禁止（protect 可修改）".to_string(),
+    }
+}
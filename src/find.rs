@@ -0,0 +1,272 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::{FamilyMember, Gender, Generation, Lineage, MemberType, VitalStatus};
+use crate::render::{self, Row};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// `find` 的一个条件，写作 `<字段><运算符><值>`，如 `power>3`、`gen=孙`
+///
+/// 字段：
+/// - `gen` 代际（`家主`、`儿`、`孙`、`曾孙`……，可比较远近，如 `gen>=孙`）；
+/// - `gender` 性别（`男`/`女`，或 `male`/`female`）；
+/// - `lineage` 血统（`内系`/`外系`，或 `direct`/`foreign`）；
+/// - `dead` 是否已故、`missing` 是否下落不明（`true`/`false`）；
+/// - `position` 职位，`*` 表示有任意职位，`-` 表示无职位；
+/// - `power` 威望加成、`birth` 出生年、`children` 子嗣数、`age` 年龄（需设置年份）。
+///
+/// 运算符为 `= != > >= < <=`，大小比较只适用于数值字段与 `gen`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    field: Field,
+    op: Op,
+    value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Generation,
+    Gender,
+    Lineage,
+    Dead,
+    Missing,
+    Position,
+    Power,
+    Birth,
+    Children,
+    Age,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Generation(Generation),
+    Gender(Gender),
+    Lineage(Lineage),
+    Bool(bool),
+    /// 职位，`None` 为 `*`（任意职位）
+    Position(Option<String>),
+    /// 无职位（`-`）
+    NoPosition,
+    Number(i64),
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Filter {
+    /// 该条件是否需要当前年份
+    pub fn needs_year(&self) -> bool {
+        self.field == Field::Age
+    }
+
+    fn matches(&self, row: &Row, year: Option<i32>) -> bool {
+        let m = row.member;
+        let ordering = match &self.value {
+            Value::Generation(g) => m.member_type.generation.cmp(g),
+            Value::Gender(g) => return self.test_eq(m.member_type.gender == *g),
+            Value::Lineage(l) => return self.test_eq(m.member_type.lineage == *l),
+            Value::Bool(b) => {
+                let actual = match self.field {
+                    Field::Missing => matches!(m.status(), VitalStatus::Missing { .. }),
+                    _ => m.is_dead,
+                };
+                return self.test_eq(actual == *b);
+            }
+            Value::Position(None) => return self.test_eq(m.position.is_some()),
+            Value::Position(Some(p)) => return self.test_eq(m.position.as_ref() == Some(p)),
+            Value::NoPosition => return self.test_eq(m.position.is_none()),
+            Value::Number(n) => {
+                let actual = match self.field {
+                    Field::Power => i64::from(m.hoser_power_add),
+                    Field::Birth => i64::from(m.birth_year),
+                    Field::Children => m.children.len() as i64,
+                    // 没有年份或已故时没有年龄，任何比较都不成立
+                    _ => match year {
+                        Some(y) if !m.is_dead => i64::from(y - m.birth_year),
+                        _ => return false,
+                    },
+                };
+                actual.cmp(n)
+            }
+        };
+        match self.op {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+        }
+    }
+
+    /// 只支持 `=`、`!=` 的字段
+    fn test_eq(&self, equal: bool) -> bool {
+        equal == (self.op == Op::Eq)
+    }
+}
+
+impl Field {
+    const ALL: [Field; 10] = [
+        Field::Generation,
+        Field::Gender,
+        Field::Lineage,
+        Field::Dead,
+        Field::Missing,
+        Field::Position,
+        Field::Power,
+        Field::Birth,
+        Field::Children,
+        Field::Age,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Field::Generation => "gen",
+            Field::Gender => "gender",
+            Field::Lineage => "lineage",
+            Field::Dead => "dead",
+            Field::Missing => "missing",
+            Field::Position => "position",
+            Field::Power => "power",
+            Field::Birth => "birth",
+            Field::Children => "children",
+            Field::Age => "age",
+        }
+    }
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+        }
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(['=', '!', '>', '<']).ok_or_else(|| {
+            format!(
+                "条件【{}】缺少运算符，应写作 <字段><运算符><值>，如 power>3",
+                s
+            )
+        })?;
+        let (key, rest) = s.split_at(split);
+        let (op, value) = [
+            ("!=", Op::Ne),
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            ("=", Op::Eq),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+        ]
+        .into_iter()
+        .find_map(|(symbol, op)| rest.strip_prefix(symbol).map(|v| (op, v.trim())))
+        .ok_or_else(|| format!("条件【{}】的运算符无法识别，可用：= != > >= < <=", s))?;
+        let field = Field::ALL
+            .into_iter()
+            .find(|f| f.key() == key.trim())
+            .ok_or_else(|| {
+                let keys: Vec<&str> = Field::ALL.iter().map(|f| f.key()).collect();
+                format!("未知字段【{}】，可选：{}", key, keys.join(", "))
+            })?;
+        if value.is_empty() {
+            return Err(format!("条件【{}】缺少值", s));
+        }
+
+        let value = match field {
+            Field::Generation => {
+                let generation = value.parse::<MemberType>()?.generation;
+                if generation == Generation::其他 {
+                    return Err(format!("无法识别的代际【{}】，如 gen=儿、gen=孙", value));
+                }
+                Value::Generation(generation)
+            }
+            Field::Gender => Value::Gender(match value {
+                "男" | "male" => Gender::Male,
+                "女" | "female" => Gender::Female,
+                _ => return Err(format!("无效的性别【{}】，可选：男, 女", value)),
+            }),
+            Field::Lineage => Value::Lineage(match value {
+                "内系" | "direct" => Lineage::Direct,
+                "外系" | "foreign" => Lineage::Foreign,
+                _ => return Err(format!("无效的血统【{}】，可选：内系, 外系", value)),
+            }),
+            Field::Dead | Field::Missing => Value::Bool(match value {
+                "true" | "是" => true,
+                "false" | "否" => false,
+                _ => return Err(format!("无效的取值【{}】，可选：true, false", value)),
+            }),
+            Field::Position => match value {
+                "*" => Value::Position(None),
+                "-" => Value::NoPosition,
+                p => Value::Position(Some(p.to_string())),
+            },
+            Field::Power | Field::Birth | Field::Children | Field::Age => Value::Number(
+                value
+                    .parse()
+                    .map_err(|_| format!("条件【{}】的值应为整数", s))?,
+            ),
+        };
+        let ordered = matches!(value, Value::Number(_) | Value::Generation(_));
+        if !ordered && !matches!(op, Op::Eq | Op::Ne) {
+            return Err(format!("字段 {} 只能用 = 或 != 比较", field.key()));
+        }
+
+        Ok(Filter { field, op, value })
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = match &self.value {
+            Value::Generation(g) => format!("{:?}", g),
+            Value::Gender(g) => g.to_string(),
+            Value::Lineage(l) => l.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Position(None) => "*".to_string(),
+            Value::Position(Some(p)) => p.clone(),
+            Value::NoPosition => "-".to_string(),
+            Value::Number(n) => n.to_string(),
+        };
+        write!(f, "{}{}{}", self.field.key(), self.op.symbol(), value)
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 按先序列出满足全部条件的成员
+pub fn find<'a>(tree: &'a FamilyMember, filters: &[Filter], year: Option<i32>) -> Vec<Row<'a>> {
+    render::rows(tree)
+        .into_iter()
+        .filter(|row| filters.iter().all(|f| f.matches(row, year)))
+        .collect()
+}
@@ -0,0 +1,84 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+use crate::record;
+use crate::session::{Outcome, Session};
+
+/// 前端模式下不支持的命令：录制与回放自身要捕获输出，其余会占住终端
+const UNSUPPORTED: &[&str] = &["record", "replay", "edit-file", "watch-game"];
+
+/// 前端协议模式（`--frontend`）
+///
+/// 供图形界面等前端经管道调用：不打印提示符与欢迎语，每行读入一条命令，
+/// 与 REPL 经同一个 [`Session`] 执行（锁定分支、家主保护等检查相同），
+/// 命令的输出不写到终端，而是收进回复。每条命令回复一帧：
+///
+/// ```text
+/// <JSON 的 UTF-8 字节数>\n<JSON>\n
+/// ```
+///
+/// JSON 为 `{"ok":true,"data":...}` 或 `{"ok":false,"error":"..."}`。
+/// `data` 为命令的输出：输出本身是 JSON（会话的输出格式为 json，如 show、status --json）
+/// 时原样嵌入，否则为文本，没有输出时为 `null`；`error` 为失败时的输出文本。
+/// 需要确认的命令（delete、inherit 等）视为已确认，由前端负责确认。
+pub fn run(mut session: Session) {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let command = line.split_whitespace().next().unwrap_or_default();
+        if UNSUPPORTED.contains(&command.to_lowercase().as_str()) {
+            reply(
+                &mut stdout,
+                Err(format!("前端模式不支持命令【{}】", command)),
+            );
+            continue;
+        }
+
+        record::begin(true);
+        let outcome = session.execute(line);
+        session.report();
+        let (output, _) = record::finish();
+        match outcome {
+            Outcome::Done => reply(&mut stdout, Ok(data(&output))),
+            Outcome::Failed => reply(&mut stdout, Err(output.trim_end().to_string())),
+            Outcome::Exit => {
+                reply(&mut stdout, Ok(Value::Null));
+                break;
+            }
+        }
+    }
+
+    // 等待后台归档写完，其提示不属于任何回复
+    record::begin(true);
+    session.finish();
+    record::finish();
+}
+
+/// 命令输出作为回复的 `data`
+fn data(output: &str) -> Value {
+    let output = output.trim_end();
+    if output.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_str(output).unwrap_or_else(|_| json!(output))
+}
+
+/// 写出一帧回复
+fn reply(out: &mut impl Write, result: Result<Value, String>) {
+    let body = match result {
+        Ok(data) => json!({ "ok": true, "data": data }),
+        Err(error) => json!({ "ok": false, "error": error }),
+    };
+    let body = body.to_string();
+    // 管道已关闭时前端已退出，无需处理
+    write!(out, "{}\n{}\n", body.len(), body).ok();
+    out.flush().ok();
+}
@@ -0,0 +1,305 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::FamilyMember;
+use crate::render;
+use crate::succession::SuccessionPolicy;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 继承人达到此人数记满分
+const FULL_HEIRS: usize = 5;
+
+/// 在世成员平均威望达到此值记满分
+const FULL_POWER: f64 = 10.0;
+
+/// 计为壮年的年龄范围（含两端）
+const PRIME_AGE: (i32, i32) = (16, 60);
+
+/// 家族健康度各项的权重
+///
+/// 健康度为各项得分（0–100）的加权平均，权重只看相对大小。
+/// 随数据文件保存，可通过 `health-weights` 命令调整。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthWeights {
+    /// 在世比例
+    pub living: u32,
+    /// 符合继承规则的在世人数
+    pub heirs: u32,
+    /// 在世成员的平均威望
+    pub power: u32,
+    /// 在世成员中壮年的比例
+    pub age: u32,
+}
+
+/// 健康度的一项
+#[derive(Debug, Clone)]
+pub struct Component {
+    /// 权重键名，如 `living`
+    pub key: &'static str,
+    pub label: &'static str,
+    pub weight: u32,
+    /// 0–100，无法计算时为 `None`（不计入总分）
+    pub score: Option<f64>,
+    pub detail: String,
+}
+
+/// 某一年的家族健康度
+///
+/// 由 [`assess`] 计算。
+#[derive(Debug, Clone)]
+pub struct Health {
+    pub year: Option<i32>,
+    pub components: Vec<Component>,
+    /// 可计算各项的加权平均，全部无法计算或权重均为 0 时为 `None`
+    pub score: Option<f64>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        HealthWeights {
+            living: 25,
+            heirs: 25,
+            power: 25,
+            age: 25,
+        }
+    }
+}
+
+impl HealthWeights {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 设置一项权重
+    ///
+    /// # param
+    /// * `key` - `living`、`heirs`、`power` 或 `age`
+    /// * `value` - 非负整数，各项不能全为 0
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let weight: u32 = value
+            .parse()
+            .map_err(|_| format!("无效的权重【{}】，应为非负整数", value))?;
+        let mut updated = self.clone();
+        match key {
+            "living" => updated.living = weight,
+            "heirs" => updated.heirs = weight,
+            "power" => updated.power = weight,
+            "age" => updated.age = weight,
+            _ => {
+                return Err(format!(
+                    "未知项【{}】，可选：living, heirs, power, age",
+                    key
+                ))
+            }
+        }
+        if updated.living + updated.heirs + updated.power + updated.age == 0 {
+            return Err("各项权重不能全为 0".to_string());
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// 当前权重的文字描述
+    pub fn describe(&self) -> String {
+        let total = f64::from(self.living + self.heirs + self.power + self.age);
+        let mut out = String::new();
+        for (label, key, weight) in [
+            ("在世比例", "living", self.living),
+            ("继承人", "heirs", self.heirs),
+            ("威望", "power", self.power),
+            ("年龄结构", "age", self.age),
+        ] {
+            writeln!(
+                out,
+                "{}（{}）：{}（占 {:.0}%）",
+                label,
+                key,
+                weight,
+                f64::from(weight) * 100.0 / total
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+impl Health {
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let year = self
+            .year
+            .map(|y| format!("（{} 年）", y))
+            .unwrap_or_default();
+        match self.score {
+            Some(score) => writeln!(out, "家族健康度：{:.1} / 100{}", score, year).unwrap(),
+            None => writeln!(out, "家族健康度：无法计算{}", year).unwrap(),
+        }
+        for c in &self.components {
+            writeln!(
+                out,
+                "  {}{}{}",
+                render::pad(&format!("{}（{}）", c.label, c.key), 20),
+                render::pad(&format!("权重 {}", c.weight), 10),
+                c.score.map_or("—".to_string(), |s| format!("{:.1}", s)),
+            )
+            .unwrap();
+            writeln!(out, "      {}", c.detail).unwrap();
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 计算家族健康度
+///
+/// 各项得分：
+/// - 在世比例：在世人数占全部成员的比例；
+/// - 继承人：家主以外符合继承规则的在世人数，[`FULL_HEIRS`] 人及以上满分；
+/// - 威望：在世成员的平均威望加成，[`FULL_POWER`] 及以上满分，负数记 0；
+/// - 年龄结构：在世成员中 16–60 岁者的比例，需给出 `year`。
+pub fn assess(
+    tree: &FamilyMember,
+    policy: &SuccessionPolicy,
+    weights: &HealthWeights,
+    year: Option<i32>,
+) -> Health {
+    let members = tree.members();
+    let living: Vec<&FamilyMember> = members.iter().copied().filter(|m| !m.is_dead).collect();
+
+    let living_score = Component {
+        key: "living",
+        label: "在世比例",
+        weight: weights.living,
+        score: Some(living.len() as f64 * 100.0 / members.len() as f64),
+        detail: format!("在世 {} 人，共 {} 人", living.len(), members.len()),
+    };
+
+    let heirs = living
+        .iter()
+        .filter(|m| m.name != tree.name && policy.check(tree, &m.name, year).is_ok())
+        .count();
+    let heirs_score = Component {
+        key: "heirs",
+        label: "继承人",
+        weight: weights.heirs,
+        score: Some(heirs.min(FULL_HEIRS) as f64 * 100.0 / FULL_HEIRS as f64),
+        detail: format!(
+            "符合继承规则的在世成员 {} 人（{} 人及以上满分）",
+            heirs, FULL_HEIRS
+        ),
+    };
+
+    let power_score = if living.is_empty() {
+        Component {
+            key: "power",
+            label: "威望",
+            weight: weights.power,
+            score: None,
+            detail: "没有在世成员".to_string(),
+        }
+    } else {
+        let total: i64 = living.iter().map(|m| i64::from(m.hoser_power_add)).sum();
+        let average = total as f64 / living.len() as f64;
+        Component {
+            key: "power",
+            label: "威望",
+            weight: weights.power,
+            score: Some((average / FULL_POWER).clamp(0.0, 1.0) * 100.0),
+            detail: format!(
+                "在世成员威望合计 {}，人均 {:.1}（{} 及以上满分）",
+                total, average, FULL_POWER
+            ),
+        }
+    };
+
+    let age_score = match year {
+        None => Component {
+            key: "age",
+            label: "年龄结构",
+            weight: weights.age,
+            score: None,
+            detail: "需先设置年份：year <年份>".to_string(),
+        },
+        Some(_) if living.is_empty() => Component {
+            key: "age",
+            label: "年龄结构",
+            weight: weights.age,
+            score: None,
+            detail: "没有在世成员".to_string(),
+        },
+        Some(year) => {
+            let (low, high) = PRIME_AGE;
+            let ages: Vec<i32> = living.iter().map(|m| year - m.birth_year).collect();
+            let prime = ages.iter().filter(|a| (low..=high).contains(*a)).count();
+            let young = ages.iter().filter(|a| **a < low).count();
+            Component {
+                key: "age",
+                label: "年龄结构",
+                weight: weights.age,
+                score: Some(prime as f64 * 100.0 / living.len() as f64),
+                detail: format!(
+                    "{}–{} 岁 {} 人，未满 {} 岁 {} 人，{} 岁以上 {} 人",
+                    low,
+                    high,
+                    prime,
+                    low,
+                    young,
+                    high,
+                    living.len() - prime - young
+                ),
+            }
+        }
+    };
+
+    let components = vec![living_score, heirs_score, power_score, age_score];
+    let (sum, weight) = components
+        .iter()
+        .filter_map(|c| c.score.map(|s| (s * f64::from(c.weight), c.weight)))
+        .fold((0.0, 0), |(sum, weight), (s, w)| (sum + s, weight + w));
+    Health {
+        year,
+        score: (weight > 0).then(|| sum / f64::from(weight)),
+        components,
+    }
+}
+
+/// 各年份健康度的走势表，每行附一条按分数缩放的横条
+pub fn describe_trend(rows: &[(String, Health)]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{}{}{}{}{}年龄",
+        render::pad("年份", 14),
+        render::pad("健康度", 10),
+        render::pad("在世", 8),
+        render::pad("继承人", 8),
+        render::pad("威望", 8),
+    )
+    .unwrap();
+    let score = |s: Option<f64>| s.map_or("—".to_string(), |s| format!("{:.1}", s));
+    for (label, health) in rows {
+        let mut line = format!(
+            "{}{}",
+            render::pad(label, 14),
+            render::pad(&score(health.score), 10)
+        );
+        for c in &health.components {
+            line.push_str(&render::pad(&score(c.score), 8));
+        }
+        let bar = health.score.map_or(0, |s| (s / 5.0).round() as usize);
+        writeln!(out, "{}{}", line, "█".repeat(bar)).unwrap();
+    }
+    out
+}
@@ -0,0 +1,222 @@
+use std::fmt::Write;
+
+use crate::document::FamilyDocument;
+use crate::model::{FamilyMember, Lineage, VitalStatus};
+use crate::render::{self, Row};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 页面内嵌的样式，生成的文件不依赖任何外部资源
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em auto; max-width: 1100px; padding: 0 1em; color: #2c3e50; }
+h1 { margin-bottom: 0.2em; }
+.motto { color: #7f8c8d; font-style: italic; margin-top: 0; }
+.stats { display: flex; flex-wrap: wrap; gap: 0.8em; margin: 1em 0; }
+.stat { background: #f4f6f7; border-radius: 6px; padding: 0.5em 1em; }
+.stat b { display: block; font-size: 1.4em; }
+.tree ul { list-style: none; margin: 0; padding-left: 1.4em; border-left: 1px solid #d5dbdb; }
+.tree > ul { border-left: none; padding-left: 0; }
+.tree li { margin: 0.2em 0; }
+.tree summary { cursor: pointer; }
+.tree .leaf { padding-left: 1.1em; }
+.dead, .dead a { color: #95a5a6; }
+.foreign { font-style: italic; }
+.tree .root > a, .tree .root > details > summary > a { font-weight: bold; }
+.cards { display: grid; grid-template-columns: repeat(auto-fill, minmax(220px, 1fr)); gap: 0.8em; }
+.card { border: 1px solid #d5dbdb; border-radius: 6px; padding: 0.6em 0.9em; }
+.card h3 { margin: 0 0 0.3em; }
+.card dl { display: grid; grid-template-columns: auto 1fr; gap: 0.1em 0.8em; margin: 0; }
+.card dt { color: #7f8c8d; }
+.card dd { margin: 0; }
+.card.root { border: 2px solid #f1c40f; background: #fef9e7; }
+.card.dead { background: #f4f6f7; }
+.card.foreign { border-style: dashed; }
+.card:target { outline: 3px solid #5dade2; }
+button { margin-right: 0.5em; }
+"#;
+
+/// 全部展开、全部折叠按钮的脚本
+const SCRIPT: &str = r#"
+function toggleAll(open) {
+  document.querySelectorAll('.tree details').forEach(function (d) { d.open = open; });
+}
+"#;
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 生成可独立打开的 HTML 家族报告
+///
+/// 包含汇总统计、可折叠的家族树（`<details>`，无需脚本即可展开折叠）
+/// 与每位成员的资料卡（出生年、称谓、职位、威望、状态、父辈与子嗣）。
+/// 点击树中的姓名跳到对应资料卡。`year` 为当前年份，设置时标出在世成员的年龄。
+pub fn report(doc: &FamilyDocument, year: Option<i32>) -> String {
+    let title = doc.meta.title();
+    let rows = render::rows(&doc.tree);
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html lang=\"zh\">\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(out, "<title>{}</title>", escape(&title)).unwrap();
+    writeln!(out, "<style>{}</style>", STYLE).unwrap();
+    writeln!(out, "<script>{}</script>", SCRIPT).unwrap();
+    out.push_str("</head>\n<body>\n");
+
+    writeln!(out, "<h1>{}</h1>", escape(&title)).unwrap();
+    if let Some(motto) = &doc.meta.motto {
+        writeln!(out, "<p class=\"motto\">{}</p>", escape(motto)).unwrap();
+    }
+    if let Some(year) = year {
+        writeln!(out, "<p>截至 {} 年</p>", year).unwrap();
+    }
+
+    out.push_str("<h2>概况</h2>\n");
+    write_stats(&mut out, &doc.tree);
+
+    out.push_str("<h2>家族树</h2>\n");
+    out.push_str("<p><button onclick=\"toggleAll(true)\">全部展开</button>");
+    out.push_str("<button onclick=\"toggleAll(false)\">全部折叠</button></p>\n");
+    out.push_str("<div class=\"tree\">\n<ul>\n");
+    let mut next = 0;
+    write_branch(&mut out, &doc.tree, &mut next);
+    out.push_str("</ul>\n</div>\n");
+
+    out.push_str("<h2>成员</h2>\n<div class=\"cards\">\n");
+    for (i, row) in rows.iter().enumerate() {
+        write_card(&mut out, i, row, year);
+    }
+    out.push_str("</div>\n</body>\n</html>\n");
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn write_stats(out: &mut String, root: &FamilyMember) {
+    let stats = root.stats();
+    let mut items = vec![
+        ("成员", stats.total.to_string()),
+        ("在世", stats.living.to_string()),
+        ("已故", stats.dead.to_string()),
+    ];
+    if stats.missing > 0 {
+        items.push(("下落不明", stats.missing.to_string()));
+    }
+    items.extend([
+        ("男 / 女", format!("{} / {}", stats.male, stats.female)),
+        (
+            "内系 / 外系",
+            format!("{} / {}", stats.direct, stats.foreign),
+        ),
+        ("威望加成合计", stats.total_power.to_string()),
+    ]);
+
+    out.push_str("<div class=\"stats\">\n");
+    for (label, value) in items {
+        writeln!(out, "<div class=\"stat\"><b>{}</b>{}</div>", value, label).unwrap();
+    }
+    out.push_str("</div>\n");
+
+    let generations: Vec<String> = stats
+        .by_generation
+        .iter()
+        .map(|(g, n)| format!("{} {} 人", escape(g), n))
+        .collect();
+    writeln!(out, "<p>各代人数：{}</p>", generations.join("，")).unwrap();
+}
+
+/// 先序写出一支，`next` 为下一个成员的编号，与资料卡的编号一致
+fn write_branch(out: &mut String, member: &FamilyMember, next: &mut usize) {
+    let id = *next;
+    *next += 1;
+    let link = format!(
+        "<a href=\"#m{}\">{}</a> <small>{}</small>",
+        id,
+        escape(&member.name),
+        member.member_type
+    );
+    let class = classes(member, id == 0);
+    if member.children.is_empty() {
+        writeln!(out, "<li class=\"leaf {}\">{}</li>", class, link).unwrap();
+        return;
+    }
+    writeln!(
+        out,
+        "<li class=\"{}\"><details open><summary>{}</summary>\n<ul>",
+        class, link
+    )
+    .unwrap();
+    for child in &member.children {
+        write_branch(out, child, next);
+    }
+    out.push_str("</ul></details></li>\n");
+}
+
+fn write_card(out: &mut String, id: usize, row: &Row, year: Option<i32>) {
+    let m = row.member;
+    writeln!(
+        out,
+        "<div class=\"card {}\" id=\"m{}\">",
+        classes(m, row.depth == 0),
+        id
+    )
+    .unwrap();
+    writeln!(out, "<h3>{}</h3>\n<dl>", escape(&m.name)).unwrap();
+
+    let mut birth = m.birth_year.to_string();
+    if let (Some(year), VitalStatus::Alive | VitalStatus::Missing { .. }) = (year, m.status()) {
+        write!(birth, "（{} 岁）", year - m.birth_year).unwrap();
+    }
+    let mut fields = vec![
+        ("称谓", m.member_type.to_string()),
+        ("出生", birth),
+        (
+            "状态",
+            match m.status() {
+                VitalStatus::Missing { since_year } => format!("失踪（{} 年起）", since_year),
+                status => status.to_string(),
+            },
+        ),
+        (
+            "职位",
+            m.position.clone().unwrap_or_else(|| "-".to_string()),
+        ),
+        ("威望+", m.hoser_power_add.to_string()),
+    ];
+    if let Some(parent) = row.parent {
+        fields.push(("父辈", parent.name.clone()));
+    }
+    if !m.children.is_empty() {
+        let names: Vec<&str> = m.children.iter().map(|c| c.name.as_str()).collect();
+        fields.push(("子嗣", names.join("、")));
+    }
+    for (label, value) in fields {
+        writeln!(out, "<dt>{}</dt><dd>{}</dd>", label, escape(&value)).unwrap();
+    }
+    out.push_str("</dl>\n</div>\n");
+}
+
+/// 家主、已故、外系成员的样式类
+fn classes(m: &FamilyMember, root: bool) -> String {
+    let mut classes = Vec::new();
+    if root {
+        classes.push("root");
+    }
+    if m.is_dead {
+        classes.push("dead");
+    }
+    if m.member_type.lineage == Lineage::Foreign {
+        classes.push("foreign");
+    }
+    classes.join(" ")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,44 @@
+use crate::model::FamilyMember;
+
+/// 由内容推导的成员 ID
+///
+/// 对 (祖先姓名链, 姓名, 出生年) 做 FNV-1a 64 位哈希，结果与平台和版本无关，
+/// 同一成员在多次导出中得到相同的 ID。
+///
+/// # param
+/// * `ancestors` - 从家主到父辈的姓名链
+pub fn content_id(ancestors: &[&str], name: &str, birth_year: i32) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    let mut feed = |bytes: &[u8]| {
+        for &b in bytes {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(PRIME);
+        }
+        // 分隔符，避免 "张三"+"李" 与 "张"+"三李" 相撞
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(PRIME);
+    };
+
+    for ancestor in ancestors {
+        feed(ancestor.as_bytes());
+    }
+    feed(name.as_bytes());
+    feed(&birth_year.to_le_bytes());
+
+    format!("{:016x}", hash)
+}
+
+/// 判断两个位于同一祖先链下的成员是否为同一人
+///
+/// ID 相同且出生年相同时视为同一人，改名后仍能对上；否则比较由内容推导的 ID。
+/// 成员 ID 是各数据文件自行分配的自增整数，不同文件中相同的 ID 未必是同一人，
+/// 因此还要求出生年一致，ID 不同时也不据此断定是不同的人。
+pub fn same_member(ancestors: &[&str], a: &FamilyMember, b: &FamilyMember) -> bool {
+    let same_id = a.id.is_some() && a.id == b.id && a.birth_year == b.birth_year;
+    same_id
+        || content_id(ancestors, &a.name, a.birth_year)
+            == content_id(ancestors, &b.name, b.birth_year)
+}
@@ -0,0 +1,478 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::document::{FamilyDocument, FileFormat};
+use crate::error::ZzError;
+use crate::model::{parse_year, validate_year, FamilyMember, Gender};
+
+/// 隔离模式下未导入记录的文件名，写在被导入文件的同一目录下
+pub const REJECTS_FILE: &str = "import_rejects.json";
+
+/// 隔离模式下未导入的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct Reject {
+    /// 所在行号（CSV）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 家主到该成员的路径（JSON 成员）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// 原始记录：CSV 为整行文本，JSON 为成员（含子孙）
+    pub record: Value,
+    pub reason: String,
+}
+
+impl Reject {
+    /// 报告中的一行，如 `第 3 行：找不到【x】的父辈【y】`
+    pub fn describe(&self) -> String {
+        match (&self.line, &self.path) {
+            (Some(line), _) => format!("第 {} 行：{}", line, self.reason),
+            (None, Some(path)) => format!("{}：{}", path, self.reason),
+            (None, None) => self.reason.clone(),
+        }
+    }
+}
+
+/// 将未导入的记录写入被导入文件同目录下的 [`REJECTS_FILE`]（已存在则覆盖）
+///
+/// # Returns
+/// 写入的文件路径。
+pub fn write_rejects(source: &str, rejects: &[Reject]) -> io::Result<PathBuf> {
+    let path = Path::new(source)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(REJECTS_FILE);
+    let json = serde_json::to_string_pretty(rejects).map_err(io::Error::other)?;
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// 按行号排好的错误转为报告：隔离模式下为未导入的记录，否则为整批失败的错误列表
+fn finish_rejects(
+    mut errors: Vec<(usize, String)>,
+    lines: &[&str],
+    quarantine: bool,
+) -> Result<Vec<Reject>, Vec<String>> {
+    errors.sort_by_key(|(line_no, _)| *line_no);
+    if !quarantine && !errors.is_empty() {
+        return Err(errors
+            .into_iter()
+            .map(|(line_no, e)| format!("第 {} 行：{}", line_no, e))
+            .collect());
+    }
+    Ok(errors
+        .into_iter()
+        .map(|(line_no, reason)| Reject {
+            line: Some(line_no),
+            path: None,
+            record: Value::String(lines[line_no - 1].trim().to_string()),
+            reason,
+        })
+        .collect())
+}
+
+/// 从 CSV 批量导入任职记录
+///
+/// 每行格式为 `year,name,title`，首行若不是数字年份则视为表头跳过。
+/// 先校验全部行（年份格式、成员存在、不早于出生、与已有记录的时间顺序），
+/// 任一行出错则整批不导入；`quarantine` 时只导入校验通过的行。
+///
+/// # Returns
+/// * `Ok((n, rejects))` - 成功导入的记录数与未导入的行（仅隔离模式下可能非空）
+/// * `Err(errors)` - 每个错误一条描述（含行号）
+pub fn import_positions(
+    tree: &mut FamilyMember,
+    path: &str,
+    quarantine: bool,
+) -> Result<(usize, Vec<Reject>), Vec<String>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| vec![format!("读取 {} 失败：{}", path, e)])?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut records: Vec<(i32, String, String)> = Vec::new();
+    // (行号, 原因)
+    let mut errors: Vec<(usize, String)> = Vec::new();
+    // 每个成员最近一次任职的年份，用于校验时间顺序
+    let mut last_year: HashMap<String, i32> = HashMap::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.splitn(3, ',').map(str::trim).collect();
+        let [year, name, title] = fields[..] else {
+            errors.push((line_no, "应为 year,name,title 三列".to_string()));
+            continue;
+        };
+
+        let year = match parse_year(year) {
+            Ok(year) => year,
+            Err(_) if index == 0 => continue, // 表头
+            Err(e) => {
+                errors.push((line_no, e.to_string()));
+                continue;
+            }
+        };
+
+        if title.is_empty() {
+            errors.push((line_no, "职位为空".to_string()));
+            continue;
+        }
+
+        let Some(member) = tree.find_member_by_name(name) else {
+            errors.push((line_no, format!("未找到成员【{}】", name)));
+            continue;
+        };
+
+        if year < member.birth_year {
+            errors.push((
+                line_no,
+                format!(
+                    "【{}】{} 年出生，不能在 {} 年任职",
+                    name, member.birth_year, year
+                ),
+            ));
+            continue;
+        }
+
+        let previous = last_year
+            .get(name)
+            .copied()
+            .or_else(|| member.position_history.last().map(|r| r.year));
+        if let Some(previous) = previous {
+            if year < previous {
+                errors.push((
+                    line_no,
+                    format!(
+                        "【{}】已有 {} 年的任职记录，{} 年的记录顺序颠倒",
+                        name, previous, year
+                    ),
+                ));
+                continue;
+            }
+        }
+
+        last_year.insert(name.to_string(), year);
+        records.push((year, name.to_string(), title.to_string()));
+    }
+
+    let rejects = finish_rejects(errors, &lines, quarantine)?;
+
+    for (year, name, title) in &records {
+        // 已在校验阶段确认成员存在
+        tree.add_position(name, title, Some(*year)).ok();
+    }
+
+    Ok((records.len(), rejects))
+}
+
+/// 从 CSV 导入一整支成员
+///
+/// 每行格式为 `name,birth_year,parent,gender,power`，首行若出生年不是数字则视为表头跳过。
+/// 父辈可以是家族树中已有的成员，也可以是文件中的另一行，行的先后顺序不限：
+/// 按父子关系依次插入，称谓由父辈推算。性别为 `男`/`女`（或 `m`/`f`），
+/// 威望可省略（默认 0）。
+///
+/// 先校验全部行（格式、重名、父辈能否找到、不早于父辈出生），
+/// 任一行出错则整批不导入；父辈找不到或互为父辈成环的行逐条报告，不会被静默丢弃。
+/// `quarantine` 时导入其余的行，出错的行（及因此无法导入的子孙）作为未导入记录返回。
+///
+/// # Returns
+/// * `Ok((names, rejects))` - 按插入顺序排列的新成员姓名与未导入的行
+/// * `Err(errors)` - 每个错误一条描述（含行号），按行号排序
+pub fn import_branch(
+    tree: &mut FamilyMember,
+    path: &str,
+    quarantine: bool,
+) -> Result<(Vec<String>, Vec<Reject>), Vec<String>> {
+    struct Row {
+        line_no: usize,
+        name: String,
+        birth_year: i32,
+        parent: String,
+        gender: Gender,
+        power: i32,
+    }
+
+    let content =
+        fs::read_to_string(path).map_err(|e| vec![format!("读取 {} 失败：{}", path, e)])?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    // (行号, 原因)，最后按行号排序
+    let mut errors: Vec<(usize, String)> = Vec::new();
+    let mut reject = |line_no: usize, message: String| {
+        errors.push((line_no, message));
+    };
+
+    let mut rows: Vec<Row> = Vec::new();
+    // 文件中出现过的姓名，含校验失败的行
+    let mut seen: HashSet<String> = HashSet::new();
+    // 校验失败的行，其子嗣随之无法导入
+    let mut rejected: HashSet<String> = HashSet::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (name, birth_year, parent, gender, power) = match fields[..] {
+            [name, birth_year, parent, gender] => (name, birth_year, parent, gender, ""),
+            [name, birth_year, parent, gender, power] => (name, birth_year, parent, gender, power),
+            _ => {
+                reject(
+                    line_no,
+                    "应为 name,birth_year,parent,gender,power 五列".to_string(),
+                );
+                continue;
+            }
+        };
+
+        let birth_year = match parse_year(birth_year) {
+            Ok(year) => year,
+            Err(_) if index == 0 => continue, // 表头
+            Err(e) => {
+                reject(line_no, e.to_string());
+                rejected.insert(name.to_string());
+                continue;
+            }
+        };
+
+        if name.is_empty() {
+            reject(line_no, "姓名为空".to_string());
+            continue;
+        }
+        if tree.exists(name) || !seen.insert(name.to_string()) {
+            reject(line_no, format!("姓名【{}】已存在", name));
+            continue;
+        }
+
+        let gender = match gender.to_lowercase().as_str() {
+            "男" | "m" | "male" => Gender::Male,
+            "女" | "f" | "female" => Gender::Female,
+            _ => {
+                reject(
+                    line_no,
+                    format!("无法识别的性别【{}】，应为 男 或 女", gender),
+                );
+                rejected.insert(name.to_string());
+                continue;
+            }
+        };
+
+        let power = match power {
+            "" => 0,
+            _ => match power.parse() {
+                Ok(power) => power,
+                Err(_) => {
+                    reject(line_no, format!("威望【{}】不是整数", power));
+                    rejected.insert(name.to_string());
+                    continue;
+                }
+            },
+        };
+
+        rows.push(Row {
+            line_no,
+            name: name.to_string(),
+            birth_year,
+            parent: parent.to_string(),
+            gender,
+            power,
+        });
+    }
+
+    // 按父子关系逐轮插入：每轮插入父辈已在树中的行，直到没有进展
+    let mut staged = tree.clone();
+    let mut added = Vec::new();
+    let mut pending = rows;
+    while !pending.is_empty() {
+        let before = pending.len();
+        let mut waiting = Vec::new();
+        for row in pending {
+            let Some(parent) = staged.find_member_by_name(&row.parent) else {
+                waiting.push(row);
+                continue;
+            };
+
+            if row.birth_year < parent.birth_year {
+                reject(
+                    row.line_no,
+                    format!(
+                        "【{}】({}) 早于父辈【{}】({}) 出生",
+                        row.name, row.birth_year, parent.name, parent.birth_year
+                    ),
+                );
+                rejected.insert(row.name);
+                continue;
+            }
+
+            let member = FamilyMember::builder()
+                .name(row.name.as_str())
+                .birth_year(row.birth_year)
+                .gender(row.gender)
+                .child_of(&parent.member_type)
+                .hoser_power_add(row.power)
+                .build();
+            match member {
+                Ok(member) => {
+                    staged
+                        .find_member_by_name_mut(&row.parent)
+                        .expect("刚找到的父辈")
+                        .children
+                        .push(member);
+                    added.push(row.name);
+                }
+                Err(e) => {
+                    reject(row.line_no, e.to_string());
+                    rejected.insert(row.name);
+                }
+            }
+        }
+
+        if waiting.len() == before {
+            // 父辈既不在家族树中也不在文件中，或所在的行有误，或互为父辈成环
+            let parent_of = |name: &str| {
+                waiting
+                    .iter()
+                    .find(|w| w.name == name)
+                    .map(|w| w.parent.as_str())
+            };
+            for row in &waiting {
+                // 沿父辈向上找到第一个不在待插入行中的祖先，步数超过行数即成环
+                let mut ancestor = row.parent.as_str();
+                let mut cyclic = false;
+                let mut steps = 0;
+                while let Some(next) = parent_of(ancestor) {
+                    steps += 1;
+                    if steps > waiting.len() {
+                        cyclic = true;
+                        break;
+                    }
+                    ancestor = next;
+                }
+
+                let message = if cyclic {
+                    format!("【{}】的祖先关系成环，无法导入", row.name)
+                } else if ancestor != row.parent {
+                    format!(
+                        "父辈【{}】无法导入，【{}】随之无法导入",
+                        row.parent, row.name
+                    )
+                } else if rejected.contains(ancestor) {
+                    format!(
+                        "父辈【{}】所在的行有误，【{}】无法导入",
+                        row.parent, row.name
+                    )
+                } else {
+                    format!("找不到【{}】的父辈【{}】", row.name, row.parent)
+                };
+                reject(row.line_no, message);
+            }
+            break;
+        }
+        pending = waiting;
+    }
+
+    let rejects = finish_rejects(errors, &lines, quarantine)?;
+
+    *tree = staged;
+    Ok((added, rejects))
+}
+
+/// 读取成员数据文件，隔离其中无法导入的成员
+///
+/// 逐个成员校验（字段能否解析、出生年是否有效、是否早于父辈出生），
+/// 有误的成员连同其子孙一起移出，作为未导入记录返回；其余成员照常保留。
+/// 家主本身有误时无法隔离，返回错误。文件格式由扩展名决定。
+pub fn load_members_quarantined(path: &str) -> Result<(FamilyDocument, Vec<Reject>), ZzError> {
+    let file = Path::new(path);
+    let data = fs::read(file).map_err(|source| ZzError::Io {
+        path: file.to_path_buf(),
+        source,
+    })?;
+    let format_error = |message: String| ZzError::Format {
+        path: file.to_path_buf(),
+        format: FileFormat::of(file).name(),
+        message,
+    };
+    let mut value: Value = match FileFormat::of(file) {
+        FileFormat::Json => serde_json::from_slice(&data)
+            .map_err(|e| ZzError::json(Some(file.to_path_buf()), &e))?,
+        FileFormat::Yaml => {
+            serde_yaml::from_slice(&data).map_err(|e| format_error(e.to_string()))?
+        }
+        FileFormat::MessagePack => {
+            rmp_serde::from_slice(&data).map_err(|e| format_error(e.to_string()))?
+        }
+    };
+
+    let root =
+        check_member(&value, None).map_err(|reason| format_error(format!("家主{}", reason)))?;
+    let mut rejects = Vec::new();
+    quarantine_children(&mut value, &root, &mut rejects);
+
+    let mut doc: FamilyDocument =
+        serde_json::from_value(value).map_err(|e| format_error(e.to_string()))?;
+    doc.migrate()?;
+    Ok((doc, rejects))
+}
+
+/// 校验单个成员（不含子女），返回解析出的成员
+fn check_member(value: &Value, parent: Option<&FamilyMember>) -> Result<FamilyMember, String> {
+    let mut own = value.clone();
+    if let Some(fields) = own.as_object_mut() {
+        fields.remove("children");
+    }
+    let member: FamilyMember =
+        serde_json::from_value(own).map_err(|e| format!("无法解析：{}", e))?;
+    validate_year(member.birth_year).map_err(|e| e.to_string())?;
+    if let Some(parent) = parent {
+        if member.birth_year < parent.birth_year {
+            return Err(format!(
+                "{} 年出生，早于父辈【{}】（{} 年）",
+                member.birth_year, parent.name, parent.birth_year
+            ));
+        }
+    }
+    Ok(member)
+}
+
+/// 移出 `value` 子女中有误的成员（连同子孙），逐层向下检查
+fn quarantine_children(value: &mut Value, parent: &FamilyMember, rejects: &mut Vec<Reject>) {
+    let Some(Value::Array(children)) = value.get_mut("children") else {
+        return;
+    };
+    let mut kept = Vec::with_capacity(children.len());
+    for (index, mut child) in children.drain(..).enumerate() {
+        match check_member(&child, Some(parent)) {
+            Ok(member) => {
+                quarantine_children(&mut child, &member, rejects);
+                kept.push(child);
+            }
+            Err(reason) => {
+                let name = child.get("name").and_then(Value::as_str).map_or_else(
+                    || format!("第 {} 个子女", index + 1),
+                    |n| format!("【{}】", n),
+                );
+                rejects.push(Reject {
+                    line: None,
+                    path: Some(format!("{} → {}", parent.name, name)),
+                    record: child,
+                    reason,
+                });
+            }
+        }
+    }
+    *children = kept;
+}
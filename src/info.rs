@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use crate::error::ZzError;
+use crate::kinship;
+use crate::model::{FamilyMember, VitalStatus, ID_PREFIX};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 一名成员的详细资料
+///
+/// 由 [`lookup`] 给出，汇集 `show`、`path`、`age`、`relationship`
+/// 等命令分别给出的信息。
+#[derive(Debug, Clone)]
+pub struct MemberInfo<'a> {
+    pub member: &'a FamilyMember,
+    /// 从家主到本人的路径，含两端
+    pub path: Vec<&'a FamilyMember>,
+    /// 本人对家主的称呼（见 [`kinship::relate`]），本人即家主或没有常用称呼时为 `None`
+    pub title: Option<String>,
+    /// 当前年份，设置时给出在世成员的年龄
+    pub year: Option<i32>,
+}
+
+/// 子嗣最多列出的姓名数
+const CHILDREN_LISTED: usize = 10;
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl MemberInfo<'_> {
+    pub fn describe(&self) -> String {
+        let m = self.member;
+        let t = &m.member_type;
+        let mut out = String::new();
+        writeln!(out, "【{}】{} · {} · {}", m.name, t, t.gender, t.lineage).unwrap();
+        if let Some(id) = m.id {
+            writeln!(out, "  ID：{}{}", ID_PREFIX, id).unwrap();
+        }
+
+        let head = self.path[0];
+        match &self.title {
+            _ if self.path.len() == 1 => writeln!(out, "  亲属：本人即家主").unwrap(),
+            Some(title) => writeln!(out, "  亲属：家主【{}】的{}", head.name, title).unwrap(),
+            None => writeln!(
+                out,
+                "  亲属：家主【{}】下第 {} 代",
+                head.name,
+                self.path.len() - 1
+            )
+            .unwrap(),
+        }
+
+        let mut birth = format!("{} 年", m.birth_year);
+        if let Some(year) = self.year.filter(|_| !m.is_dead) {
+            write!(birth, "（{} 年时 {} 岁）", year, year - m.birth_year).unwrap();
+        }
+        writeln!(out, "  出生：{}", birth).unwrap();
+
+        let status = match m.status() {
+            VitalStatus::Missing { since_year } => format!("下落不明（{} 年起）", since_year),
+            status => status.to_string(),
+        };
+        writeln!(out, "  状态：{}", status).unwrap();
+
+        writeln!(out, "  职位：{}", m.position.as_deref().unwrap_or("无")).unwrap();
+        if !m.position_history.is_empty() {
+            let history: Vec<String> = m
+                .position_history
+                .iter()
+                .map(|r| format!("{} 年 {}", r.year, r.title))
+                .collect();
+            writeln!(out, "  任职记录：{}", history.join("；")).unwrap();
+        }
+        writeln!(out, "  威望加成：{:+}", m.hoser_power_add).unwrap();
+
+        match self.path.iter().rev().nth(1) {
+            Some(parent) => {
+                writeln!(out, "  父辈：{}（{}）", parent.name, parent.member_type).unwrap()
+            }
+            None => writeln!(out, "  父辈：无").unwrap(),
+        }
+
+        if m.children.is_empty() {
+            writeln!(out, "  子嗣：无").unwrap();
+        } else {
+            let mut names: Vec<&str> = m
+                .children
+                .iter()
+                .take(CHILDREN_LISTED)
+                .map(|c| c.name.as_str())
+                .collect();
+            if m.children.len() > CHILDREN_LISTED {
+                names.push("…");
+            }
+            writeln!(
+                out,
+                "  子嗣：{} 人（{}），后代共 {} 人",
+                m.children.len(),
+                names.join("、"),
+                m.total_count() - 1
+            )
+            .unwrap();
+        }
+
+        let names: Vec<&str> = self.path.iter().map(|m| m.name.as_str()).collect();
+        writeln!(out, "  世系：{}", names.join(" → ")).unwrap();
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 查找成员并汇集其详细资料，`year` 为当前年份
+pub fn lookup<'a>(
+    tree: &'a FamilyMember,
+    name: &str,
+    year: Option<i32>,
+) -> Result<MemberInfo<'a>, ZzError> {
+    let path = tree
+        .path_to(name)
+        .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+    let title = if path.len() > 1 {
+        kinship::relate(tree, &tree.name, name)?.term
+    } else {
+        None
+    };
+    Ok(MemberInfo {
+        member: path[path.len() - 1],
+        path,
+        title,
+        year,
+    })
+}
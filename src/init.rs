@@ -0,0 +1,88 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use zz_sim_data_tool::document::{FamilyDocument, FamilyMeta, SCHEMA_VERSION};
+use zz_sim_data_tool::model::{parse_year, FamilyMember, Gender, Generation, Lineage, MemberType};
+
+/// 交互式新建家族数据文件
+///
+/// 依次询问家主姓名与出生年，输入有误时重新询问；族名取家主姓名的首字，
+/// 开族年份取家主出生年。目标文件已存在时拒绝覆盖，输入结束（Ctrl+D）时放弃新建。
+///
+/// # Returns
+/// 新建并已写入 `path` 的数据。
+pub fn run(path: &Path) -> Result<FamilyDocument, String> {
+    if path.exists() {
+        return Err(format!("{} 已存在，请换一个路径", path.display()));
+    }
+
+    statusln!("📝 新建家族数据：{}", path.display());
+    let name = ask("家主姓名", |input| {
+        if input.is_empty() {
+            Err("姓名不能为空".to_string())
+        } else {
+            Ok(input.to_string())
+        }
+    })?;
+    let birth_year = ask("家主出生年", |input| {
+        parse_year(input).map_err(|e| e.to_string())
+    })?;
+
+    let root = FamilyMember::builder()
+        .name(name.as_str())
+        .birth_year(birth_year)
+        .member_type(MemberType::new(
+            Generation::家主,
+            Gender::Male,
+            Lineage::Direct,
+        ))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut doc = FamilyDocument {
+        schema_version: SCHEMA_VERSION,
+        tree: root,
+        meta: FamilyMeta {
+            surname: name.chars().next().map(String::from),
+            founding_year: Some(birth_year),
+            ..Default::default()
+        },
+        position_catalog: Default::default(),
+        limits: Default::default(),
+        root_protection: Default::default(),
+        locked_branches: Default::default(),
+        settings: Default::default(),
+        trash: Vec::new(),
+        next_id: 0,
+    };
+    doc.assign_ids();
+    doc.save(path)
+        .map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+
+    statusln!(
+        "✅ 已新建家族数据，家主【{}】（{} 年生）：{}",
+        name,
+        birth_year,
+        path.display()
+    );
+    Ok(doc)
+}
+
+/// 询问一项输入，`parse` 失败时提示原因并重新询问
+fn ask<T>(question: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, String> {
+    loop {
+        out!("{}：", question);
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => {
+                outln!();
+                return Err("输入已结束，未新建数据文件".to_string());
+            }
+            Ok(_) => match parse(input.trim()) {
+                Ok(value) => return Ok(value),
+                Err(e) => statusln!("❌ {}", e),
+            },
+        }
+    }
+}
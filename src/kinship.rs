@@ -0,0 +1,200 @@
+use std::fmt::Write;
+
+use crate::error::ZzError;
+use crate::model::{FamilyMember, Gender};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 两名成员的亲属关系
+///
+/// 由 [`relate`] 给出。家谱只记录血亲一方的父辈（不含配偶），
+/// 因此"母亲"等称呼指树中的女性成员，`外` 表示关系经由女性成员传下。
+#[derive(Debug, Clone)]
+pub struct Kinship {
+    pub a: String,
+    pub b: String,
+    /// 最近共同祖先，可以是甲或乙本人
+    pub ancestor: String,
+    /// 共同祖先到甲、乙各自的代数
+    pub a_depth: usize,
+    pub b_depth: usize,
+    /// 乙对甲的称呼，如 `堂兄`、`外甥女`；关系较远、没有常用称呼时为 `None`
+    pub term: Option<String>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Kinship {
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        match &self.term {
+            Some(term) => writeln!(out, "【{}】是【{}】的{}", self.b, self.a, term).unwrap(),
+            None => writeln!(out, "【{}】与【{}】没有常用的称呼", self.b, self.a).unwrap(),
+        }
+
+        let depth = |name: &str, depth: usize| {
+            if depth == 0 {
+                format!("【{}】即其本人", name)
+            } else {
+                format!("【{}】是其下第 {} 代", name, depth)
+            }
+        };
+        writeln!(
+            out,
+            "共同祖先【{}】：{}，{}",
+            self.ancestor,
+            depth(&self.a, self.a_depth),
+            depth(&self.b, self.b_depth)
+        )
+        .unwrap();
+
+        let gap = self.a_depth.abs_diff(self.b_depth);
+        if self.a_depth == self.b_depth {
+            writeln!(out, "【{}】与【{}】同辈", self.b, self.a).unwrap();
+        } else {
+            let relative = if self.b_depth < self.a_depth {
+                "长"
+            } else {
+                "晚"
+            };
+            writeln!(out, "【{}】比【{}】{} {} 辈", self.b, self.a, relative, gap).unwrap();
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 推算乙（`b`）对甲（`a`）的亲属关系
+pub fn relate(tree: &FamilyMember, a: &str, b: &str) -> Result<Kinship, ZzError> {
+    if a == b {
+        return Err(ZzError::Invalid("两个姓名相同，是同一人".to_string()));
+    }
+    let (ancestor, a_depth, b_depth) = tree
+        .common_ancestor(a, b)
+        .ok_or_else(|| ZzError::MemberNotFound(if tree.exists(a) { b } else { a }.to_string()))?;
+
+    if a_depth == 0 && b_depth == 0 {
+        return Err(ZzError::Invalid(format!("【{}】与【{}】是同一人", a, b)));
+    }
+
+    // 姓名可能是路径写法，从家主起取路径再截去共同祖先之上的部分
+    let path_a = tree.path_to(a).expect("已确认存在");
+    let path_b = tree.path_to(b).expect("已确认存在");
+    let path_a = &path_a[path_a.len() - a_depth - 1..];
+    let path_b = &path_b[path_b.len() - b_depth - 1..];
+    Ok(Kinship {
+        a: a.to_string(),
+        b: b.to_string(),
+        ancestor: ancestor.name.clone(),
+        a_depth,
+        b_depth,
+        term: term(path_a, path_b),
+    })
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 乙对甲的称呼
+///
+/// 两条路径都从共同祖先开始，分别到甲、乙为止。
+fn term(path_a: &[&FamilyMember], path_b: &[&FamilyMember]) -> Option<String> {
+    let a = path_a[path_a.len() - 1];
+    let b = path_b[path_b.len() - 1];
+    let female = |m: &FamilyMember| m.member_type.gender == Gender::Female;
+    // 路径中间（不含两端）都是男性时为父系，称堂、侄；否则称表、外
+    let paternal = |path: &[&FamilyMember]| !path[1..path.len() - 1].iter().any(|m| female(m));
+    let by_gender = |male: &str, female_term: &str| {
+        if female(b) {
+            female_term.to_string()
+        } else {
+            male.to_string()
+        }
+    };
+    // 同辈按出生年份分长幼，同年时不分
+    let elder = |other: &FamilyMember, older: &str, younger: &str, same: &str| match b
+        .birth_year
+        .cmp(&other.birth_year)
+    {
+        std::cmp::Ordering::Less => older.to_string(),
+        std::cmp::Ordering::Greater => younger.to_string(),
+        std::cmp::Ordering::Equal => same.to_string(),
+    };
+    let sibling = |prefix: &str| {
+        let (older, younger, same) = if female(b) {
+            ("姐", "妹", "姐妹")
+        } else {
+            ("兄", "弟", "兄弟")
+        };
+        format!("{}{}", prefix, elder(a, older, younger, same))
+    };
+
+    let outer = |path: &[&FamilyMember]| if paternal(path) { "" } else { "外" };
+    let term = match (path_a.len() - 1, path_b.len() - 1) {
+        // 乙是甲的后代
+        (0, n @ 1..=4) => {
+            let base = ["子", "孙", "曾孙", "玄孙"][n - 1];
+            let base = if n == 1 {
+                by_gender("儿子", "女儿")
+            } else {
+                by_gender(base, &format!("{}女", base))
+            };
+            format!("{}{}", outer(path_b), base)
+        }
+        // 乙是甲的祖先
+        (1, 0) => by_gender("父亲", "母亲"),
+        (n @ 2..=4, 0) => {
+            let base = ["祖", "曾祖", "高祖"][n - 2];
+            format!(
+                "{}{}",
+                outer(path_a),
+                by_gender(&format!("{}父", base), &format!("{}母", base))
+            )
+        }
+        (1, 1) => {
+            let (older, younger, same) = if female(b) {
+                ("姐姐", "妹妹", "姐妹")
+            } else {
+                ("哥哥", "弟弟", "兄弟")
+            };
+            elder(a, older, younger, same)
+        }
+        // 兄弟姐妹的子女、孙辈
+        (1, 2) if female(path_b[1]) => by_gender("外甥", "外甥女"),
+        (1, 2) => by_gender("侄子", "侄女"),
+        (1, 3) if female(path_b[1]) => by_gender("外甥孙", "外甥孙女"),
+        (1, 3) if female(path_b[2]) => by_gender("侄外孙", "侄外孙女"),
+        (1, 3) => by_gender("侄孙", "侄孙女"),
+        // 父母的兄弟姐妹
+        (2, 1) if female(path_a[1]) => by_gender("舅父", "姨母"),
+        (2, 1) if female(b) => "姑母".to_string(),
+        (2, 1) => elder(path_a[1], "伯父", "叔父", "叔伯"),
+        // 祖父的兄弟姐妹（父系）
+        (3, 1) if paternal(path_a) && female(b) => "姑祖母".to_string(),
+        (3, 1) if paternal(path_a) => elder(path_a[1], "伯祖父", "叔祖父", "叔伯祖父"),
+        // 同辈的堂表亲
+        (2, 2) if paternal(path_a) && paternal(path_b) => sibling("堂"),
+        (2, 2) => sibling("表"),
+        (3, 3) if paternal(path_a) && paternal(path_b) => sibling("再从"),
+        (n, m) if n == m && n >= 4 && paternal(path_a) && paternal(path_b) => sibling("族"),
+        (n, m) if n == m && n >= 3 => "远房表亲".to_string(),
+        // 堂表兄弟姐妹的子女
+        (2, 3) if paternal(path_a) && paternal(path_b) => by_gender("堂侄", "堂侄女"),
+        (2, 3) => by_gender("表侄", "表侄女"),
+        // 父母的堂表兄弟姐妹
+        (3, 2) if paternal(path_a) && paternal(path_b) && female(b) => "堂姑".to_string(),
+        (3, 2) if paternal(path_a) && paternal(path_b) => {
+            elder(path_a[2], "堂伯", "堂叔", "堂叔伯")
+        }
+        _ => return None,
+    };
+    Some(term)
+}
@@ -0,0 +1,46 @@
+//! 祖宗模拟器家族数据引擎
+//!
+//! 家族树的数据模型、遍历、修改与查询，以及数据文件的读写、归档与合并。
+//! 所有接口都返回数据而不直接打印，命令行工具（`src/main.rs`）只负责交互与输出，
+//! 其他工具或脚本可以直接复用。
+
+pub mod archive;
+pub mod calc;
+pub mod catalog;
+pub mod check;
+pub mod columns;
+pub mod diff;
+pub mod document;
+pub mod edit;
+pub mod error;
+pub mod explain;
+pub mod find;
+pub mod health;
+pub mod html;
+pub mod identity;
+pub mod import;
+pub mod info;
+pub mod kinship;
+pub mod limits;
+pub mod merge;
+pub mod metrics;
+pub mod model;
+pub mod naming;
+pub mod parser;
+pub mod pick;
+pub mod protection;
+pub mod query;
+pub mod render;
+pub mod search;
+pub mod settings;
+pub mod store;
+pub mod style;
+pub mod succession;
+pub mod template;
+#[cfg(test)]
+mod testing;
+pub mod undo;
+pub mod verify;
+
+pub use error::ZzError;
+pub use model::{FamilyMember, Gender, Generation, Lineage, MemberType};
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::FamilyMember;
+
+/// 家族规模软限制
+///
+/// 游戏引擎在家族过大时会变慢，超过阈值时只给出警告，不阻止操作。
+/// 阈值为 `None` 表示关闭该项检查。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeLimits {
+    /// 在世成员数上限
+    #[serde(default = "default_max_living")]
+    pub max_living: Option<usize>,
+    /// 单个成员的子嗣数上限
+    #[serde(default = "default_max_children")]
+    pub max_children: Option<usize>,
+}
+
+fn default_max_living() -> Option<usize> {
+    Some(500)
+}
+
+fn default_max_children() -> Option<usize> {
+    Some(20)
+}
+
+/// 超过阈值的一项，由 [`SizeLimits::check`] 给出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exceeded {
+    /// 在世成员数超过上限
+    Living { count: usize, max: usize },
+    /// 某成员的子嗣数超过上限
+    Children {
+        name: String,
+        count: usize,
+        max: usize,
+    },
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        SizeLimits {
+            max_living: default_max_living(),
+            max_children: default_max_children(),
+        }
+    }
+}
+
+impl SizeLimits {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 设置阈值
+    ///
+    /// # param
+    /// * `key` - `living` 或 `children`
+    /// * `value` - 正整数，或 `off` 关闭该项检查
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let limit = match value {
+            "off" => None,
+            v => match v.parse::<usize>() {
+                Ok(n) if n > 0 => Some(n),
+                _ => return Err(format!("无效的阈值：{}", v)),
+            },
+        };
+
+        match key {
+            "living" => self.max_living = limit,
+            "children" => self.max_children = limit,
+            _ => return Err(format!("未知项【{}】，可选：living, children", key)),
+        }
+        Ok(())
+    }
+
+    /// 检查家族树是否超过阈值
+    ///
+    /// # Returns
+    /// 每个超限项一条。
+    pub fn check(&self, tree: &FamilyMember) -> Vec<Exceeded> {
+        let mut exceeded = Vec::new();
+
+        if let Some(max) = self.max_living {
+            let count = tree.size();
+            if count > max {
+                exceeded.push(Exceeded::Living { count, max });
+            }
+        }
+
+        if let Some(max) = self.max_children {
+            check_children(tree, max, &mut exceeded);
+        }
+
+        exceeded
+    }
+
+    /// 当前阈值的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let fmt = |limit: Option<usize>| match limit {
+            Some(n) => n.to_string(),
+            None => "关闭".to_string(),
+        };
+        writeln!(out, "在世成员上限（living）：{}", fmt(self.max_living)).unwrap();
+        writeln!(out, "单人子嗣上限（children）：{}", fmt(self.max_children)).unwrap();
+        out
+    }
+}
+
+impl Exceeded {
+    /// 超限项的标识，人数变化时不变，供只提示一次时去重
+    pub fn key(&self) -> String {
+        match self {
+            Exceeded::Living { .. } => "living".to_string(),
+            Exceeded::Children { name, .. } => format!("children:{}", name),
+        }
+    }
+
+    /// 警告文字，如 `在世成员 520 人，超过上限 500 人`
+    pub fn describe(&self) -> String {
+        match self {
+            Exceeded::Living { count, max } => {
+                format!("在世成员 {} 人，超过上限 {} 人", count, max)
+            }
+            Exceeded::Children { name, count, max } => {
+                format!("【{}】有 {} 名子嗣，超过上限 {} 名", name, count, max)
+            }
+        }
+    }
+}
+
+fn check_children(member: &FamilyMember, max: usize, exceeded: &mut Vec<Exceeded>) {
+    if member.children.len() > max {
+        exceeded.push(Exceeded::Children {
+            name: member.name.clone(),
+            count: member.children.len(),
+            max,
+        });
+    }
+
+    for child in &member.children {
+        check_children(child, max, exceeded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+
+    #[test]
+    fn check_reports_each_exceeded_limit() {
+        let limits = SizeLimits {
+            max_living: Some(3),
+            max_children: Some(1),
+        };
+        let exceeded = limits.check(&sample());
+        let keys: Vec<String> = exceeded.iter().map(Exceeded::key).collect();
+        assert_eq!(keys, ["living", "children:张大", "children:张二"]);
+    }
+
+    #[test]
+    fn key_ignores_counts() {
+        let limits = SizeLimits {
+            max_living: Some(3),
+            max_children: None,
+        };
+        let mut tree = sample();
+        let before = limits.check(&tree);
+        tree.mark_dead("张三").unwrap();
+        let after = limits.check(&tree);
+        assert_ne!(before[0].describe(), after[0].describe());
+        assert_eq!(before[0].key(), after[0].key());
+    }
+}
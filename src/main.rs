@@ -1,330 +1,432 @@
-mod model;
-use model::FamilyMember;
-use serde_json;
-use std::io::{self, Write};
-use std::{env, fs, path::Path};
-
-const HELP_TEXT: &str = r#"================== 祖宗模拟器帮助 ==================
-命令列表:
-    help
-      显示此帮助信息
-
-    exit | quit
-      退出程序
-
-    count
-      显示家族成员总数（忽略已标记死亡者）
-
-    exists <姓名>
-      检查某个家族成员是否存在
-
-    show [<姓名>]
-      不带参数显示整个家族树，或展示指定成员的所有后代
-
-    add
-      交互式为指定成员添加子嗣，按提示粘贴 JSON 数组
-
-      JSON 格式示例:
-      [{"name":"张小明","birth_year":2000,"hoser_power_add":5,"children":[]}]
-
-    save
-      将当前内存中的家族数据保存到 ZZ_SIM_FAMILY_DATA 指定文件
-
-    position <姓名> <职位>
-      为成员设置职位称谓
-
-    year [<年份>]
-      不带参数时显示当前年份，带参数时更新年份状态
-
-    stats
-      统计信息占位命令，当前尚未实现
-
-    path <姓名>
-      显示家主到指定成员的路径
-
-    prune
-      删除当前年份之后出生的成员（需先设置 year，操作会二次确认）
-
-    rename <旧名> <新名>
-      重命名成员
-
-    die <姓名>
-      将成员标记为死亡
-
-    clear
-      清空终端显示
-
-    inherit <姓名>
-      在 archives/offspring_tree_<年份>.json 归档后，让成员继承家主。
-      需先执行 year 设置年份，仅支持两代以内的继承人。
-
-提示:
-  - 输入命令时不区分大小写
-  - 输入 exit 或按 Ctrl+D 可以退出
-===================================================="#;
-
-fn get_data_file() -> String {
-    match env::var("ZZ_SIM_FAMILY_DATA") {
-        Ok(path) => path,
-        Err(_) => panic!("❌ 环境变量 ZZ_SIM_FAMILY_DATA 未设置，请使用 export ZZ_SIM_FAMILY_DATA=/path/to/offspring_tree.json"),
-    }
+/// 输出一行，不做任何替换
+///
+/// 命令的数据与导出内容（JSON、CSV、DOT 等）都经由以下宏输出，原样写出；
+/// 录制会话时原文同时交给 [`record::tee`]；前端模式下输出经它收进回复。
+macro_rules! outln {
+    () => {
+        if $crate::record::tee("", "\n") {
+            ::std::println!()
+        }
+    };
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "\n") {
+            ::std::println!("{}", text)
+        }
+    }};
 }
 
-fn main() {
-    println!("祖宗模拟器数据处理 CLI 已启动");
-    println!("输入 `help` 查看命令；输入 `exit`/`quit` 或按 Ctrl+D 退出。\n");
-
-    let data_file = get_data_file();
-    let data = fs::read_to_string(&data_file).expect("读取数据文件失败");
-    let mut tree = serde_json::from_str::<FamilyMember>(&data).expect("解析数据失败");
-
-    let mut current_year: Option<u16> = None;
+/// 同 [`outln!`]，不换行
+macro_rules! out {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "") {
+            ::std::print!("{}", text)
+        }
+    }};
+}
 
-    loop {
-        print!("zz> ");
-        io::stdout().flush().unwrap();
+/// 同 [`outln!`]，写到 stderr
+macro_rules! errln {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "\n") {
+            ::std::eprintln!("{}", text)
+        }
+    }};
+}
 
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
-            // EOF (Ctrl+D)
-            break;
+/// 输出带状态符号的一行，符号按当前主题替换（见 [`theme::apply`]）
+///
+/// 只用于提示、警告与错误等状态消息，书写时照常使用 emoji。
+macro_rules! statusln {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "\n") {
+            ::std::println!("{}", $crate::theme::apply(&text))
         }
+    }};
+}
 
-        let line = input.trim();
-        if line.is_empty() {
-            continue;
+/// 同 [`statusln!`]，不换行
+macro_rules! status {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "") {
+            ::std::print!("{}", $crate::theme::apply(&text))
         }
+    }};
+}
 
-        let mut parts = line.split_whitespace();
-        let command = parts.next().unwrap().to_lowercase();
-        let args: Vec<&str> = parts.collect();
+/// 同 [`statusln!`]，写到 stderr
+macro_rules! estatusln {
+    ($($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        if $crate::record::tee(&text, "\n") {
+            ::std::eprintln!("{}", $crate::theme::apply(&text))
+        }
+    }};
+}
 
-        match command.as_str() {
-            "help" => {
-                println!("{HELP_TEXT}");
-            }
-            "exit" | "quit" => {
-                break;
-            }
+mod cli;
+mod completion;
+mod config;
+mod convert;
+mod doctor;
+mod editor;
+mod example;
+mod frontend;
+mod init;
+mod pager;
+mod record;
+mod recover;
+mod sandbox;
+mod session;
+mod stats;
+mod status;
+mod theme;
+mod tutorial;
+mod watch;
+use cli::{BatchSource, Command};
+use completion::ReplHelper;
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use rustyline::{CompletionType, Config, Editor};
+use session::{Confirm, Outcome, Session};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use zz_sim_data_tool::parser;
+use zz_sim_data_tool::render::OutputFormat;
+use zz_sim_data_tool::store::FamilyStore;
+use zz_sim_data_tool::ZzError;
+
+/// 环境变量 ZZ_SIM_METRICS 为 1 或 on 时开启指标记录
+fn metrics_enabled() -> bool {
+    matches!(env::var("ZZ_SIM_METRICS").as_deref(), Ok("1" | "on"))
+}
 
-            "count" => {
-                println!("总共的家族人数：{}.", tree.size())
-            }
+/// 命令历史文件名，位于用户主目录
+const HISTORY_FILE_NAME: &str = ".zz-sim-data-tool_history";
+/// 保留的命令历史条数
+const HISTORY_SIZE: usize = 1000;
+
+/// 命令历史文件：`$HOME/.zz-sim-data-tool_history`
+///
+/// 未设置 HOME 时不保存历史。
+fn history_file() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
+}
 
-            "exists" => {
-                if args.len() != 1 {
-                    println!("用法: exists <name>");
-                } else {
-                    let name = args[0];
-                    if tree.exists(name) {
-                        println!("【{name}】存在于家族中。");
-                    } else {
-                        println!("【{name}】不存在于家族中。");
-                    }
-                }
-            }
+/// 一次性子命令：执行一条会话命令，带 `--save` 时成功后保存
+///
+/// 参数已由 shell 拆分，重新加引号后交给会话，保证会话拆出的参数与命令行一致。
+fn run_once(session: &mut Session, args: &[String]) -> bool {
+    let save = args.iter().any(|a| a == "--save");
+    let line: Vec<String> = args
+        .iter()
+        .filter(|a| *a != "--save")
+        .map(|a| parser::quote(a))
+        .collect();
+    let outcome = session.execute(&line.join(" "));
+    session.report();
+    match outcome {
+        Outcome::Failed => false,
+        Outcome::Done if save => session.execute("save") == Outcome::Done,
+        _ => true,
+    }
+}
 
-            "show" => {
-                if args.len() > 1 {
-                    println!("用法: show [<name>]");
-                } else if args.len() == 1 {
-                    let name = args[0];
-                    tree.show(Some(name));
+/// 批处理：依次执行命令，遇到失败即停止
+///
+/// 空行与 `#` 开头的注释行跳过。全部成功（或遇到 exit）时返回 true。
+fn run_batch(session: &mut Session, sources: &[BatchSource]) -> bool {
+    for source in sources {
+        let commands: Vec<(String, String)> = match source {
+            BatchSource::Exec(command) => vec![("--exec".to_string(), command.clone())],
+            BatchSource::Script(path) => {
+                let content = if path == "-" {
+                    io::stdin()
+                        .lock()
+                        .lines()
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|lines| lines.join("\n"))
                 } else {
-                    tree.show(None);
-                }
-            }
-
-            "add" => {
-                println!("📝 添加子嗣模式");
-
-                // 1. 获取父节点
-                let parent_name = loop {
-                    print!("请输入成员姓名：");
-                    io::stdout().flush().unwrap();
-
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input).ok();
-                    let name = input.trim();
-
-                    if name.is_empty() {
-                        continue;
-                    }
-
-                    if tree.exists(name) {
-                        break Some(name.to_string());
-                    } else {
-                        println!("【{name}】不存在，请重新输入");
+                    fs::read_to_string(path)
+                };
+                let content = match content {
+                    Ok(content) => content,
+                    Err(e) => {
+                        estatusln!("❌ 读取脚本 {} 失败：{}", path, e);
+                        return false;
                     }
                 };
-
-                let Some(parent) = parent_name else { continue };
-
-                // 2. 获取 JSON array 插入子嗣
-                println!("✅ 找到【{parent}】");
-                print!("> ");
-                io::stdout().flush().unwrap();
-
-                let mut json_input = String::new();
-                if io::stdin().read_line(&mut json_input).is_ok() {
-                    tree.add_children(&parent, json_input.trim());
-                }
-            }
-
-            "save" => {
-                let json = serde_json::to_string_pretty(&tree).unwrap();
-                if let Err(e) = fs::write(&data_file, json) {
-                    eprintln!("❌ 保存失败: {}", e);
-                }
+                content
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| (format!("{}:{}", path, i + 1), line.to_string()))
+                    .collect()
             }
+        };
 
-            "position" => {
-                if args.len() != 2 {
-                    println!("用法: position <姓名> <职位>");
-                    continue;
-                }
-
-                let name = args[0];
-                let position = args[1];
-
-                match tree.add_position(name, position) {
-                    Ok(_) => println!("✅ 已为【{}】设置职位：{}", name, position),
-                    Err(e) => eprintln!("❌ {}", e),
-                }
+        for (location, command) in commands {
+            let command = command.trim();
+            if command.is_empty() || command.starts_with('#') {
+                continue;
             }
 
-            "year" => {
-                if args.is_empty() {
-                    match current_year {
-                        Some(y) => println!("当前年份：{}", y),
-                        None => println!("⚠️  尚未设置当前年份"),
-                    }
-                } else {
-                    match args[0].parse::<u16>() {
-                        Ok(year) => {
-                            current_year = Some(year);
-                            println!("✅ 当前年份设置为 {}", year);
-                        }
-                        Err(_) => println!("❌ 无效的年份"),
-                    }
+            let outcome = session.execute(command);
+            session.report();
+            match outcome {
+                Outcome::Done => {}
+                Outcome::Exit => return true,
+                Outcome::Failed => {
+                    estatusln!("❌ {}：命令失败，已停止：{}", location, command);
+                    return false;
                 }
             }
+        }
+    }
+    true
+}
 
-            "stats" => {
-                println!("统计功能待实现");
+fn main() {
+    match env::var(theme::THEME_ENV) {
+        Ok(name) if !name.trim().is_empty() => match name.trim().parse() {
+            Ok(chosen) => theme::set(chosen),
+            Err(e) => {
+                theme::set(theme::detect());
+                estatusln!("⚠️  {}：{}，已按终端自动选择", theme::THEME_ENV, e);
             }
+        },
+        _ => theme::set(theme::detect()),
+    }
 
-            "path" => {
-                if args.len() != 1 {
-                    println!("用法: path <姓名>");
-                } else {
-                    tree.path(args[0]);
+    let cli = cli::parse();
+    theme::set_color(!cli.no_color && theme::detect_color());
+    // 诊断、示例生成与格式转换不依赖数据加载，须在读取数据前处理
+    let mut init_path = None;
+    let mut once = None;
+    match &cli.command {
+        Some(Command::Doctor) => {
+            let ok = match config::Config::load() {
+                Ok(config) => {
+                    let data_file = cli::resolve_data_file(cli.file.as_ref(), &config);
+                    let store = session::file_store(&config, &data_file.path, false);
+                    doctor::run(
+                        &data_file.path,
+                        Some(&data_file.source),
+                        store.archive_dir(),
+                    )
                 }
-            }
-
-            "prune" => match current_year {
-                None => {
-                    println!("❌ 请先设置年份：year <年份>");
+                Err(e) => {
+                    estatusln!("❌ {}", e);
+                    false
                 }
-                Some(year) => {
-                    println!("⚠️  即将删除 {} 年后出生的所有成员（用于退档）", year);
-                    print!("确认删除？(y/n): ");
-                    io::stdout().flush().unwrap();
-
-                    let mut confirm = String::new();
-                    io::stdin().read_line(&mut confirm).ok();
-
-                    match confirm.trim() {
-                        "y" => tree.prune_future_births(year),
-
-                        "n" => {
-                            println!("❌ 已取消");
-                        }
+            };
+            std::process::exit(if ok { 0 } else { 1 });
+        }
+        Some(Command::Example { path, size }) => {
+            example::run(path, *size);
+            return;
+        }
+        Some(Command::Convert { input, output }) => {
+            let ok = convert::run(input, output);
+            std::process::exit(if ok { 0 } else { 1 });
+        }
+        // init 新建数据文件后直接进入交互会话
+        Some(Command::Init { path }) => init_path = Some(path.clone()),
+        Some(Command::Once(args)) => once = Some(args.clone()),
+        None => {}
+    }
+    if init_path.is_some() && (cli.frontend || !cli.batch.is_empty()) {
+        estatusln!("❌ init 需要交互输入，不能与 --frontend、--exec、--script 同时使用");
+        std::process::exit(2);
+    }
+    if once.is_some() && (cli.frontend || !cli.batch.is_empty()) {
+        estatusln!("❌ 一次性子命令不能与 --frontend、--exec、--script 同时使用");
+        std::process::exit(2);
+    }
 
-                        _ => {}
-                    }
-                }
-            },
+    let frontend = cli.frontend;
+    let batch = cli.batch;
+    let assume_yes = cli.yes;
+    let interactive = !frontend && batch.is_empty() && once.is_none();
+    if interactive {
+        outln!("祖宗模拟器数据处理 CLI 已启动");
+        outln!("输入 `help` 查看命令；输入 `exit`/`quit` 或按 Ctrl+D 退出。\n");
+    }
 
-            "rename" => {
-                if args.len() != 2 {
-                    println!("用法：rename <旧名> <新名>");
-                } else {
-                    let old_name = args[0];
-                    let new_name = args[1];
-                    match tree.rename(old_name, new_name) {
-                        Ok(_) => println!("✅ 已将【{}】改名为【{}】", old_name, new_name),
-                        Err(e) => println!("❌ {}", e),
-                    }
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            estatusln!("❌ {}", e);
+            errln!("   请修正或删除配置文件");
+            std::process::exit(1);
+        }
+    };
+    let data_file = cli::resolve_data_file(
+        init_path.clone().flatten().as_ref().or(cli.file.as_ref()),
+        &config,
+    );
+    let store = session::file_store(&config, &data_file.path, false);
+    let loaded = if init_path.is_some() {
+        init::run(store.path()).map_err(ZzError::Invalid)
+    } else {
+        match store.load() {
+            // 首次运行时数据文件还不存在，交互模式下询问是否新建
+            Err(ZzError::Io { source, .. })
+                if interactive && source.kind() == io::ErrorKind::NotFound =>
+            {
+                statusln!(
+                    "⚠️  数据文件 {} 不存在（来自{}）",
+                    data_file.path,
+                    data_file.source
+                );
+                if !Confirm::Ask.ask("是否现在新建？") {
+                    outln!("可稍后运行 `zz-sim-data-tool init` 新建，或用 `zz-sim-data-tool example` 生成示例数据");
+                    std::process::exit(1);
                 }
+                init::run(store.path()).map_err(ZzError::Invalid)
             }
-
-            "die" => {
-                if args.len() != 1 {
-                    println!("用法：die <姓名>");
-                } else {
-                    let name = args[0];
-                    match tree.mark_dead(name) {
-                        Ok(_) => println!("✅ 已将【{}】标记为死亡。", name),
-                        Err(e) => println!("❌ {}", e),
-                    }
+            loaded => loaded,
+        }
+    };
+    let doc = match loaded {
+        Ok(doc) => doc,
+        Err(e) => {
+            estatusln!("❌ {}", e);
+            match &e {
+                // 新建数据文件时的输入或写入错误，无需诊断
+                ZzError::Invalid(_) => {}
+                ZzError::Io { source, .. } if source.kind() == io::ErrorKind::NotFound => {
+                    errln!("   数据文件来自{}", data_file.source);
+                    errln!(
+                        "   可用 --file 指定已有的数据文件，或运行 `zz-sim-data-tool init` 新建"
+                    );
                 }
+                _ => errln!("   可运行 `zz-sim-data-tool doctor` 诊断"),
             }
+            std::process::exit(1);
+        }
+    };
+
+    if frontend {
+        // 需要确认的操作由前端确认；表格输出不便解析，默认输出 JSON
+        let mut session = Session::new(doc, Box::new(store), Confirm::Yes);
+        session.output_format = OutputFormat::Json;
+        session.configure(config);
+        frontend::run(session);
+        return;
+    }
 
-            "clear" => {
-                print!("\x1B[2J\x1B[1;1H");
-                io::stdout().flush().unwrap();
+    if !interactive {
+        let confirm = if assume_yes {
+            Confirm::Yes
+        } else {
+            Confirm::No
+        };
+        let mut session = Session::new(doc, Box::new(store), confirm);
+        session.metrics = metrics_enabled();
+        session.configure(config);
+        let ok = match &once {
+            Some(args) => run_once(&mut session, args),
+            None => run_batch(&mut session, &batch),
+        };
+        if !session.store.matches(&session.doc) {
+            if once.is_some() {
+                estatusln!("⚠️  有未保存的改动，未写入数据文件（可在命令末尾加 --save）");
+            } else {
+                estatusln!("⚠️  有未保存的改动，未写入数据文件（可在命令末尾加 save）");
             }
+        }
+        session.finish();
+        std::process::exit(if ok { 0 } else { 1 });
+    }
 
-            "inherit" => {
-                if args.len() != 1 {
-                    println!("用法：inherit <姓名>");
-                }
-
-                let Some(year) = current_year else {
-                    println!("❌ 请先执行 year <年份>");
-                    continue;
-                };
-
-                // 确认
-                print!("当前年份 {}，是否归档并继承？(y/n): ", year);
-                io::stdout().flush().unwrap();
-
-                let mut input = String::new();
-                io::stdin().read_line(&mut input).ok();
+    let mut session = Session::new(doc, Box::new(store), Confirm::Ask);
+    session.metrics = metrics_enabled();
+    session.configure(config);
+
+    let mut editor = match Editor::<ReplHelper, FileHistory>::with_config(
+        Config::builder()
+            .max_history_size(HISTORY_SIZE)
+            .expect("历史条数为正数")
+            .history_ignore_dups(true)
+            .expect("固定配置")
+            .history_ignore_space(true)
+            .completion_type(CompletionType::List)
+            .build(),
+    ) {
+        Ok(editor) => editor,
+        Err(e) => {
+            estatusln!("❌ 初始化命令行编辑失败：{}", e);
+            std::process::exit(1);
+        }
+    };
+    editor.set_helper(Some(ReplHelper::default()));
+    let history = history_file();
+    if let Some(path) = &history {
+        // 首次运行时历史文件不存在，忽略
+        editor.load_history(path).ok();
+    }
 
-                if input.trim().to_lowercase() != "y" {
-                    println!("ℹ️ 已取消");
-                    continue;
-                }
+    loop {
+        session.report();
+
+        let mode = match session.mode() {
+            "tutorial" => "(tutorial)",
+            "sandbox" => "(sandbox)",
+            _ => "",
+        };
+        // 有未保存的改动时显示 *
+        let dirty = if session.is_dirty() { "*" } else { "" };
+        let prompt = format!("zz{}{}> ", mode, dirty);
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_names(
+                session
+                    .doc
+                    .tree
+                    .names()
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+        let input = match editor.readline(&prompt) {
+            Ok(input) => input,
+            // Ctrl+C 放弃当前输入
+            Err(ReadlineError::Interrupted) => continue,
+            // EOF (Ctrl+D)
+            Err(ReadlineError::Eof) if session.confirm_exit() => break,
+            Err(ReadlineError::Eof) => continue,
+            Err(e) => {
+                estatusln!("❌ 读取输入失败：{}", e);
+                break;
+            }
+        };
 
-                // 归档
-                let archive_path = Path::new(&get_data_file())
-                    .parent()
-                    .unwrap_or(Path::new("."))
-                    .join("archives")
-                    .join(format!("offspring_tree_{}.json", year));
-                if let Ok(json) = serde_json::to_string_pretty(&tree) {
-                    fs::create_dir_all(archive_path.parent().unwrap()).ok();
-                    if fs::write(&archive_path, json).is_ok() {
-                        println!("🗃️ 已归档到 {}", archive_path.display());
-                    }
-                }
+        let line = input.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // 传入原始输入：以空格开头的命令按 history_ignore_space 不记入历史
+        editor.add_history_entry(input.as_str()).ok();
 
-                // 继承
-                let name = args[0];
-                match tree.inherit(name) {
-                    Ok(new_tree) => {
-                        tree = new_tree;
-                        println!("✅ 【{}】已继位", args[0]);
-                    }
-                    Err(e) => eprintln!("❌ {}", e),
-                }
-            }
+        if session.execute(line) == Outcome::Exit && session.confirm_exit() {
+            break;
+        }
+    }
 
-            _ => {
-                println!("未知命令: '{line}'. 输入 'help' 查看可用命令。");
-            }
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            estatusln!("⚠️  保存命令历史失败：{}", e);
         }
     }
+
+    session.finish();
 }
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::identity;
+use crate::model::FamilyMember;
+
+/// 导入数据中的成员已存在且字段不同时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnConflict {
+    /// 保留现有数据，记为已存在
+    Skip,
+    /// 以导入数据更新死亡状态、职位与威望
+    #[default]
+    Update,
+    /// 视为冲突，整批不导入
+    Error,
+}
+
+/// 合并结果汇总
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// 新增的成员
+    pub added: Vec<String>,
+    /// 字段有变化的成员
+    pub updated: Vec<String>,
+    /// 已存在而跳过的成员（含字段无变化的成员）
+    pub skipped: Vec<String>,
+    /// 因冲突被跳过的成员及原因
+    pub conflicts: Vec<String>,
+}
+
+impl FromStr for OnConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(OnConflict::Skip),
+            "update" => Ok(OnConflict::Update),
+            "error" => Ok(OnConflict::Error),
+            _ => Err(format!(
+                "未知冲突处理方式【{}】，可选：skip, update, error",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OnConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            OnConflict::Skip => "skip",
+            OnConflict::Update => "update",
+            OnConflict::Error => "error",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl MergeReport {
+    /// 单行汇总，如"新增 3 人，更新 1 人，已存在 20 人，冲突 0 处"
+    pub fn summary(&self) -> String {
+        format!(
+            "新增 {} 人，更新 {} 人，已存在 {} 人，冲突 {} 处",
+            self.added.len(),
+            self.updated.len(),
+            self.skipped.len(),
+            self.conflicts.len()
+        )
+    }
+
+    /// 逐人列出的去重报告
+    ///
+    /// `+` 新增，`~` 更新，`=` 已存在而跳过，`⚠️` 冲突。
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        for name in &self.added {
+            writeln!(out, "    + {}", name).unwrap();
+        }
+        for name in &self.updated {
+            writeln!(out, "    ~ {}", name).unwrap();
+        }
+        for name in &self.skipped {
+            writeln!(out, "    = {}", name).unwrap();
+        }
+        for conflict in &self.conflicts {
+            writeln!(out, "    ⚠️  {}", conflict).unwrap();
+        }
+        out
+    }
+}
+
+/// 将导入的家族树合并进现有家族树
+///
+/// 以导入树的根为锚点，在现有树中按姓名定位后逐层对比子嗣：
+/// - 同一父辈下身份相同的成员视为同一人，更新死亡状态、职位与威望。
+///   身份见 [`identity::same_member`]；
+/// - 不存在的成员连同其子孙一起新增，其中已被家族树占用的 ID 被清除，另行分配；
+/// - 新成员（或其子孙）与已有成员重名时视为冲突，跳过该分支。
+///
+/// 出生年与称谓以现有数据为准，不会被覆盖。已存在成员的字段不同时按
+/// `on_conflict` 处理。
+///
+/// # Returns
+/// * `Ok(report)` - 合并结果
+/// * `Err(conflicts)` - `on_conflict` 为 [`OnConflict::Error`] 且存在冲突，
+///   家族树保持不变
+pub fn merge(
+    live: &mut FamilyMember,
+    incoming: &FamilyMember,
+    on_conflict: OnConflict,
+) -> Result<MergeReport, Vec<String>> {
+    if on_conflict == OnConflict::Error {
+        // 先在副本上合并，无冲突才写回
+        let mut staged = live.clone();
+        let report = merge_into(&mut staged, incoming, on_conflict);
+        if !report.conflicts.is_empty() {
+            return Err(report.conflicts);
+        }
+        *live = staged;
+        return Ok(report);
+    }
+
+    Ok(merge_into(live, incoming, on_conflict))
+}
+
+fn merge_into(
+    live: &mut FamilyMember,
+    incoming: &FamilyMember,
+    on_conflict: OnConflict,
+) -> MergeReport {
+    let mut report = MergeReport::default();
+
+    if !live.exists(&incoming.name) {
+        report
+            .conflicts
+            .push(format!("导入数据的根【{}】不在家族树中", incoming.name));
+        return report;
+    }
+
+    // 预先收集冲突检查所需的全部姓名及锚点的祖先链，避免在可变借用期间遍历整棵树
+    let existing: Vec<String> = live.names().into_iter().map(String::from).collect();
+    let mut taken = HashSet::new();
+    collect_ids(live, &mut taken);
+    let mut ancestors: Vec<String> = live
+        .path_to(&incoming.name)
+        .unwrap_or_default()
+        .iter()
+        .map(|m| m.name.clone())
+        .collect();
+    ancestors.pop();
+
+    let anchor = live
+        .find_member_by_name_mut(&incoming.name)
+        .expect("已确认存在");
+    merge_node(
+        anchor,
+        incoming,
+        on_conflict,
+        &mut ancestors,
+        &existing,
+        &taken,
+        &mut report,
+    );
+    report
+}
+
+/// 递归合并单个成员
+///
+/// # param
+/// * `ancestors` - 从家主到 `live` 父辈的姓名链
+fn merge_node(
+    live: &mut FamilyMember,
+    incoming: &FamilyMember,
+    on_conflict: OnConflict,
+    ancestors: &mut Vec<String>,
+    existing: &[String],
+    taken: &HashSet<u64>,
+    report: &mut MergeReport,
+) {
+    match (differs(live, incoming), on_conflict) {
+        (false, _) | (true, OnConflict::Skip) => report.skipped.push(live.name.clone()),
+        (true, OnConflict::Update) => {
+            update_fields(live, incoming);
+            report.updated.push(live.name.clone());
+        }
+        (true, OnConflict::Error) => report
+            .conflicts
+            .push(format!("【{}】已存在且数据不同", live.name)),
+    }
+
+    ancestors.push(live.name.clone());
+    for child in &incoming.children {
+        let chain: Vec<&str> = ancestors.iter().map(String::as_str).collect();
+        let matched = live
+            .children
+            .iter_mut()
+            .find(|c| identity::same_member(&chain, c, child));
+
+        match matched {
+            Some(live_child) => merge_node(
+                live_child,
+                child,
+                on_conflict,
+                ancestors,
+                existing,
+                taken,
+                report,
+            ),
+            None => {
+                let names = child.names();
+                if let Some(dup) = names.iter().find(|n| existing.iter().any(|e| e == *n)) {
+                    report.conflicts.push(format!(
+                        "【{}】与家族树中已有成员重名，跳过【{}】下的【{}】一支",
+                        dup, live.name, child.name
+                    ));
+                    continue;
+                }
+
+                report.added.extend(names.into_iter().map(String::from));
+                let mut branch = child.clone();
+                release_taken_ids(&mut branch, taken);
+                live.children.push(branch);
+            }
+        }
+    }
+    ancestors.pop();
+}
+
+/// 导入数据是否会改变成员字段
+fn differs(live: &FamilyMember, incoming: &FamilyMember) -> bool {
+    (incoming.is_dead && !live.is_dead)
+        || (incoming.position.is_some() && incoming.position != live.position)
+        || incoming.hoser_power_add != live.hoser_power_add
+}
+
+/// 用导入数据更新成员字段
+fn update_fields(live: &mut FamilyMember, incoming: &FamilyMember) {
+    if incoming.is_dead {
+        live.is_dead = true;
+    }
+
+    if incoming.position.is_some() {
+        live.position = incoming.position.clone();
+    }
+
+    live.hoser_power_add = incoming.hoser_power_add;
+}
+
+/// 收集家族树中已有的 ID
+fn collect_ids(member: &FamilyMember, ids: &mut HashSet<u64>) {
+    ids.extend(member.id);
+    for child in &member.children {
+        collect_ids(child, ids);
+    }
+}
+
+/// 清除新增分支中已被占用的 ID，之后由 [`FamilyDocument::assign_ids`] 重新分配
+///
+/// [`FamilyDocument::assign_ids`]: crate::document::FamilyDocument::assign_ids
+fn release_taken_ids(member: &mut FamilyMember, taken: &HashSet<u64>) {
+    if member.id.is_some_and(|id| taken.contains(&id)) {
+        member.id = None;
+    }
+    for child in &mut member.children {
+        release_taken_ids(child, taken);
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZzError;
+use crate::render::pad;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 指标日志中的一条记录（JSON Lines，每行一条）
+///
+/// 只记录命令关键字，不记录参数，日志中不会出现成员姓名。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Record {
+    /// 执行一条命令
+    Command {
+        /// Unix 时间戳（秒）
+        at: u64,
+        command: String,
+        duration_ms: f64,
+        /// 执行后的在世成员数
+        living: usize,
+        /// 执行后的成员总数（含已故）
+        total: usize,
+        ok: bool,
+    },
+    /// 一次会话结束
+    Session {
+        at: u64,
+        duration_ms: f64,
+        commands: usize,
+    },
+}
+
+/// 指标汇总，由 `perf report` 输出
+#[derive(Debug, Clone)]
+pub struct PerfReport {
+    pub sessions: usize,
+    pub session_ms: f64,
+    /// 按命令汇总，按最长耗时降序
+    pub commands: Vec<CommandStats>,
+    /// 耗时最长的单次执行
+    pub slowest: Vec<Record>,
+    /// 无法解析而跳过的行数
+    pub skipped: usize,
+}
+
+/// 单个命令的耗时汇总
+#[derive(Debug, Clone)]
+pub struct CommandStats {
+    pub command: String,
+    pub count: usize,
+    pub failed: usize,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Record {
+    pub fn command(
+        command: &str,
+        duration: Duration,
+        living: usize,
+        total: usize,
+        ok: bool,
+    ) -> Self {
+        Record::Command {
+            at: now(),
+            command: command.to_string(),
+            duration_ms: millis(duration),
+            living,
+            total,
+            ok,
+        }
+    }
+
+    pub fn session(duration: Duration, commands: usize) -> Self {
+        Record::Session {
+            at: now(),
+            duration_ms: millis(duration),
+            commands,
+        }
+    }
+}
+
+impl PerfReport {
+    /// 汇总指标日志
+    ///
+    /// # param
+    /// * `top` - 列出耗时最长的单次执行条数
+    pub fn load(path: &Path, top: usize) -> Result<Self, ZzError> {
+        let content = fs::read_to_string(path).map_err(|source| ZzError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut skipped = 0;
+        let records: Vec<Record> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                let record = serde_json::from_str(line).ok();
+                if record.is_none() {
+                    skipped += 1;
+                }
+                record
+            })
+            .collect();
+
+        let mut report = Self::new(&records, top);
+        report.skipped = skipped;
+        Ok(report)
+    }
+
+    pub fn new(records: &[Record], top: usize) -> Self {
+        let mut sessions = 0;
+        let mut session_ms = 0.0;
+        let mut by_command: BTreeMap<&str, Vec<(f64, bool)>> = BTreeMap::new();
+
+        for record in records {
+            match record {
+                Record::Command {
+                    command,
+                    duration_ms,
+                    ok,
+                    ..
+                } => by_command
+                    .entry(command)
+                    .or_default()
+                    .push((*duration_ms, *ok)),
+                Record::Session { duration_ms, .. } => {
+                    sessions += 1;
+                    session_ms += duration_ms;
+                }
+            }
+        }
+
+        let mut commands: Vec<CommandStats> = by_command
+            .into_iter()
+            .map(|(command, runs)| CommandStats {
+                command: command.to_string(),
+                count: runs.len(),
+                failed: runs.iter().filter(|(_, ok)| !ok).count(),
+                avg_ms: runs.iter().map(|(ms, _)| ms).sum::<f64>() / runs.len() as f64,
+                max_ms: runs.iter().map(|(ms, _)| *ms).fold(0.0, f64::max),
+            })
+            .collect();
+        commands.sort_by(|a, b| b.max_ms.total_cmp(&a.max_ms));
+
+        let mut slowest: Vec<Record> = records
+            .iter()
+            .filter(|r| matches!(r, Record::Command { .. }))
+            .cloned()
+            .collect();
+        slowest.sort_by(|a, b| duration_of(b).total_cmp(&duration_of(a)));
+        slowest.truncate(top);
+
+        PerfReport {
+            sessions,
+            session_ms,
+            commands,
+            slowest,
+            skipped: 0,
+        }
+    }
+
+    /// 汇总的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+
+        if self.commands.is_empty() {
+            writeln!(out, "指标日志中还没有命令记录").unwrap();
+            return out;
+        }
+
+        let runs: usize = self.commands.iter().map(|c| c.count).sum();
+        write!(out, "共 {} 次会话、{} 条命令", self.sessions, runs).unwrap();
+        if self.sessions > 0 {
+            write!(
+                out,
+                "，会话平均 {}",
+                format_ms(self.session_ms / self.sessions as f64)
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+        if self.skipped > 0 {
+            writeln!(out, "⚠️  跳过 {} 行无法解析的记录", self.skipped).unwrap();
+        }
+
+        writeln!(out, "\n按命令（按最长耗时排序）：").unwrap();
+        writeln!(
+            out,
+            "{}{}{}{}{}",
+            pad("命令", 20),
+            pad("次数", 8),
+            pad("失败", 8),
+            pad("平均", 12),
+            pad("最长", 12)
+        )
+        .unwrap();
+        for c in &self.commands {
+            writeln!(
+                out,
+                "{}{}{}{}{}",
+                pad(&c.command, 20),
+                pad(&c.count.to_string(), 8),
+                pad(&c.failed.to_string(), 8),
+                pad(&format_ms(c.avg_ms), 12),
+                pad(&format_ms(c.max_ms), 12)
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "\n最慢的 {} 次执行：", self.slowest.len()).unwrap();
+        for record in &self.slowest {
+            if let Record::Command {
+                at,
+                command,
+                duration_ms,
+                living,
+                total,
+                ..
+            } = record
+            {
+                writeln!(
+                    out,
+                    "  {}  {}（当时在世 {} 人，共 {} 人，时间戳 {}）",
+                    pad(&format_ms(*duration_ms), 12),
+                    command,
+                    living,
+                    total,
+                    at
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            out,
+            "注：交互式命令（add、prune、inherit 等）的耗时含等待输入的时间"
+        )
+        .unwrap();
+        out
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 指标日志路径：数据文件同级，如 `offspring_tree.metrics.jsonl`
+pub fn log_path(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("metrics.jsonl")
+}
+
+/// 追加一条记录
+pub fn append(path: &Path, record: &Record) -> io::Result<()> {
+    let line = serde_json::to_string(record).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn duration_of(record: &Record) -> f64 {
+    match record {
+        Record::Command { duration_ms, .. } | Record::Session { duration_ms, .. } => *duration_ms,
+    }
+}
+
+fn format_ms(ms: f64) -> String {
+    if ms >= 1000.0 {
+        format!("{:.2} s", ms / 1000.0)
+    } else {
+        format!("{:.1} ms", ms)
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use unicode_width::UnicodeWidthStr;
+
+use crate::error::ZzError;
 
 // ============================================================================
 // Type Definitions
@@ -14,9 +16,18 @@ use unicode_width::UnicodeWidthStr;
 /// 以及子女（`children`）。构成一棵多叉树。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FamilyMember {
+    /// 成员 ID（自增整数），合并导入时优先用于识别同一成员；新添加的成员在分配前为 `None`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<u64>,
+
     pub name: String,
-    pub birth_year: u16,
-    pub hoser_power_add: u8,
+    pub birth_year: i32,
+    /// 威望加成，可为负数（惩罚）
+    pub hoser_power_add: i32,
     pub member_type: MemberType,
 
     #[serde(default)]
@@ -26,15 +37,119 @@ pub struct FamilyMember {
 
     #[serde(default)]
     pub is_dead: bool,
+
+    /// 下落不明的起始年份，在世状态由 [`FamilyMember::status`] 综合给出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing_since: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub position_history: Vec<PositionRecord>,
+}
+
+/// 任职记录
+///
+/// 记录成员在某一年获得的职位，按时间顺序追加
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionRecord {
+    pub year: i32,
+    pub title: String,
+}
+
+/// 一名成员的称谓变化
+///
+/// 由 [`FamilyMember::label_changes`] 比较结构改动前后的家族树得出，
+/// 汇总文字见 [`describe_relabels`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relabel {
+    pub name: String,
+    pub from: MemberType,
+    pub to: MemberType,
+}
+
+/// 重名的一组成员
+///
+/// 由 [`FamilyMember::duplicates`] 给出，先序排列。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    pub name: String,
+    /// 每一处可唯一指定它的写法（见 [`FamilyMember::address_of`]），如 `张二/张三`、`#12`；
+    /// 无法区分时为 `None`
+    pub addresses: Vec<Option<String>>,
+}
+
+/// 成员构造器
+///
+/// 由 [`FamilyMember::builder`] 创建，`build` 时统一校验。
+/// 称谓可直接指定（[`member_type`](Self::member_type)），
+/// 也可由父辈推算（[`child_of`](Self::child_of)）；二者都未给出时按性别取家主，
+/// 性别也未给出时报错。
+#[derive(Debug, Clone, Default)]
+pub struct FamilyMemberBuilder {
+    id: Option<u64>,
+    name: Option<String>,
+    birth_year: Option<i32>,
+    gender: Option<Gender>,
+    member_type: Option<MemberType>,
+    parent_type: Option<MemberType>,
+    hoser_power_add: i32,
+    position: Option<String>,
+    is_dead: bool,
+}
+
+/// 家族统计
+///
+/// 由 [`FamilyMember::stats`] 汇总，统计范围为以该成员为根的整棵子树（含已故成员）。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FamilyStats {
+    pub total: usize,
+    /// 未故成员数，含下落不明者
+    pub living: usize,
+    pub dead: usize,
+    /// 下落不明的成员数（已计入 `living`）
+    pub missing: usize,
+    /// 各代人数，按代际由近到远排列，如 `("孙", 12)`
+    pub by_generation: Vec<(String, usize)>,
+    pub male: usize,
+    pub female: usize,
+    pub direct: usize,
+    pub foreign: usize,
+    /// 有子嗣的成员数
+    pub parents: usize,
+    /// 子嗣总数（即除根以外的成员数）
+    pub children: usize,
+    /// 威望加成合计
+    pub total_power: i64,
+}
+
+/// 同一代际成员的汇总（队列）
+///
+/// 由 [`FamilyMember::cohorts`] 按代际由近到远给出，比率由调用方按需计算。
+/// 数据中没有卒年，无法计算寿命；年龄只能对在世成员按当前年份计算。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Cohort {
+    /// 代际名称，如 `孙`
+    pub generation: String,
+    pub members: usize,
+    pub living: usize,
+    pub foreign: usize,
+    /// 本代成员的子嗣总数
+    pub children: usize,
+    pub total_power: i64,
+    /// 在世成员出生年份之和，配合当前年份计算平均年龄
+    pub living_birth_years: i64,
 }
 
 /// 代际关系枚举
 ///
-/// 表示家族成员与家主的代际距离，从家主（0代）到耳孙（9代）。
+/// 表示家族成员与家主的代际距离，从家主（0代）到耳孙（9代），
+/// 超出耳孙或称谓无法识别时为 `其他`。
 /// 使用 `#[repr(u8)]` 以支持代际升降计算。
+///
+/// 今后可能补充更远的代际，库外匹配时需保留通配分支。
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd)]
 #[repr(u8)]
-pub(crate) enum Generation {
+#[non_exhaustive]
+pub enum Generation {
     家主 = 0,
     儿 = 1,
     孙 = 2,
@@ -49,25 +164,40 @@ pub(crate) enum Generation {
 }
 
 /// 性别枚举
+///
+/// 决定称谓中是否带"女"，女性成员的子嗣为外系。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Gender {
+pub enum Gender {
     Male,
     Female,
 }
 
 /// 血统枚举
 ///
-/// 区分内系（直系血亲）和外系（通过女儿延续的血脉）
+/// 区分内系（直系血亲）和外系（通过女儿延续的血脉）。
+/// 家主的子女总是内系，外系成员的子嗣仍为外系。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Lineage {
+pub enum Lineage {
     Direct,  // 内系
     Foreign, // 外系
 }
 
+/// 成员的生死状态
+///
+/// 由 [`FamilyMember::status`] 给出。下落不明的成员不算已故，
+/// 但不能继承家主位，可之后用 `die` 或 `found` 了结。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VitalStatus {
+    Alive,
+    Dead,
+    Missing { since_year: i32 },
+}
+
 /// 成员类型
 ///
-/// 组合代际、性别、血统三个维度，用于生成成员称谓（如"孙女"、"外曾孙"等）
-#[derive(Debug, Clone, Copy)]
+/// 组合代际、性别、血统三个维度，用于生成成员称谓（如"孙女"、"外曾孙"等）。
+/// 与称谓字符串互相转换：`"外曾孙".parse::<MemberType>()`、`to_string()`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MemberType {
     pub generation: Generation,
     pub gender: Gender,
@@ -213,19 +343,59 @@ impl fmt::Display for MemberType {
     }
 }
 
+impl FromStr for Gender {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "男" | "male" => Ok(Gender::Male),
+            "女" | "female" => Ok(Gender::Female),
+            _ => Err(format!("无效的性别【{}】，可选：男, 女", s)),
+        }
+    }
+}
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Gender::Male => "男",
+            Gender::Female => "女",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for VitalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VitalStatus::Alive => write!(f, "在世"),
+            VitalStatus::Dead => write!(f, "已故"),
+            VitalStatus::Missing { .. } => write!(f, "失踪"),
+        }
+    }
+}
+
+impl fmt::Display for Lineage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Lineage::Direct => "内系",
+            Lineage::Foreign => "外系",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 // ============================================================================
 // Method Implementations
 // ============================================================================
 
 impl FamilyMember {
-    // 表格列宽常量
-    const TREE_COLUMN_WIDTH: usize = 30; // 树形符号+姓名的总宽度
-    const BIRTH_WIDTH: usize = 8;
-    const TYPE_WIDTH: usize = 12;
-    const STATUS_WIDTH: usize = 8;
-    const POSITION_WIDTH: usize = 18;
-    const ATTR_WIDTH: usize = 8;
-    const CHILD_WIDTH: usize = 8;
+    /// 以构造器创建成员，如
+    /// `FamilyMember::builder().name("张三").birth_year(1990).gender(Gender::Male).build()`
+    /// 创建一名家主，加 `.child_of(&parent.member_type)` 则按父辈推算称谓
+    pub fn builder() -> FamilyMemberBuilder {
+        FamilyMemberBuilder::default()
+    }
 
     /// 计算以当前成员为根的家族树规模（包含所有子孙）。
     ///
@@ -240,86 +410,191 @@ impl FamilyMember {
             .sum::<usize>()
     }
 
-    /// 检查指定姓名的成员是否存在
-    pub fn exists(&self, name: &str) -> bool {
-        if self.name == name {
-            return true;
+    /// 家族树中的成员总数（包括已故成员及其子孙）
+    pub fn total_count(&self) -> usize {
+        1 + self.children.iter().map(|c| c.total_count()).sum::<usize>()
+    }
+
+    /// 本成员及所有后代的威望加成之和
+    pub fn subtree_power(&self) -> i64 {
+        i64::from(self.hoser_power_add)
+            + self.children.iter().map(|c| c.subtree_power()).sum::<i64>()
+    }
+
+    /// 家族树的代数（仅有根节点时为 1）
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(|c| c.depth()).max().unwrap_or(0)
+    }
+
+    /// 以当前成员为根的所有成员（先序遍历）
+    pub fn members(&self) -> Vec<&FamilyMember> {
+        let mut members = vec![self];
+        for child in &self.children {
+            members.extend(child.members());
         }
+        members
+    }
+
+    /// 汇总统计
+    pub fn stats(&self) -> FamilyStats {
+        let mut stats = FamilyStats::default();
+        let mut generations: Vec<(Generation, usize)> = Vec::new();
+
+        for m in self.members() {
+            stats.total += 1;
+            match m.status() {
+                VitalStatus::Dead => stats.dead += 1,
+                VitalStatus::Missing { .. } => {
+                    stats.living += 1;
+                    stats.missing += 1;
+                }
+                VitalStatus::Alive => stats.living += 1,
+            }
+
+            let generation = m.member_type.generation;
+            match generations.iter_mut().find(|(g, _)| *g == generation) {
+                Some((_, n)) => *n += 1,
+                None => generations.push((generation, 1)),
+            }
+
+            match m.member_type.gender {
+                Gender::Male => stats.male += 1,
+                Gender::Female => stats.female += 1,
+            }
+            match m.member_type.lineage {
+                Lineage::Direct => stats.direct += 1,
+                Lineage::Foreign => stats.foreign += 1,
+            }
 
-        self.children.iter().any(|c| c.exists(name))
+            if !m.children.is_empty() {
+                stats.parents += 1;
+            }
+            stats.children += m.children.len();
+            stats.total_power += i64::from(m.hoser_power_add);
+        }
+
+        generations.sort();
+        stats.by_generation = generations
+            .into_iter()
+            .map(|(g, n)| (format!("{:?}", g), n))
+            .collect();
+        stats
     }
 
-    /// 打印家族树。
-    ///
-    /// - 若 `name` 为 `None`，则显示以当前成员为根的整棵家族树。
-    /// - 若指定 `name`，则仅显示该成员及其子孙。
-    pub fn show(&self, name: Option<&str>) {
-        let border = "━".repeat(80);
+    /// 以当前成员为根的所有成员姓名（先序遍历）
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_names(&mut names);
+        names
+    }
 
-        println!("{border}");
+    /// 检查指定姓名的成员是否存在
+    pub fn exists(&self, name: &str) -> bool {
+        self.locate(name).is_some()
+    }
 
-        // 表头 - 手动填充每一列
-        let header_name = format!(
-            "{}{}",
-            "姓名",
-            " ".repeat(Self::TREE_COLUMN_WIDTH.saturating_sub("姓名".width()))
-        );
-        let header_birth = format!(
-            "{}{}",
-            "出生",
-            " ".repeat(Self::BIRTH_WIDTH.saturating_sub("出生".width()))
-        );
-        let header_type = format!(
-            "{}{}",
-            "类别",
-            " ".repeat(Self::TYPE_WIDTH.saturating_sub("类别".width()))
-        );
-        let header_status = format!(
-            "{}{}",
-            "状态",
-            " ".repeat(Self::STATUS_WIDTH.saturating_sub("状态".width()))
-        );
-        let header_position = format!(
-            "{}{}",
-            "职位",
-            " ".repeat(Self::POSITION_WIDTH.saturating_sub("职位".width()))
-        );
-        let header_attr = format!(
-            "{}{}",
-            "威望+",
-            " ".repeat(Self::ATTR_WIDTH.saturating_sub("威望+".width()))
-        );
-        let header_child = format!(
-            "{}{}",
-            "子嗣",
-            " ".repeat(Self::CHILD_WIDTH.saturating_sub("子嗣".width()))
-        );
+    /// `name`（姓名、路径或 `#ID`）指的是否为本成员，即家族树的根
+    pub fn is_root(&self, name: &str) -> bool {
+        self.locate(name).is_some_and(|at| at.is_empty())
+    }
 
-        println!(
-            "{}{}{}{}{}{}{}",
-            header_name,
-            header_birth,
-            header_type,
-            header_status,
-            header_position,
-            header_attr,
-            header_child
+    /// 家族树中的重名成员，按姓名首次出现的先后排列
+    pub fn duplicates(&self) -> Vec<Duplicate> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for name in self.names() {
+            *counts.entry(name).or_default() += 1;
+        }
+        let mut chains = Vec::new();
+        self.collect_chains(
+            &|m| counts[m.name.as_str()] > 1,
+            &mut Vec::new(),
+            &mut chains,
         );
 
-        println!("{border}");
-
-        match name {
-            None => self.show_with_descendants(0),
-            Some(target) => {
-                if let Some(p) = self.find_member_by_name(target) {
-                    p.show_with_descendants(0);
-                } else {
-                    println!("未找到【{}】", target);
-                }
+        let mut duplicates: Vec<Duplicate> = Vec::new();
+        for chain in &chains {
+            let address = self.address_of(chain);
+            let name = &chain[chain.len() - 1].name;
+            match duplicates.iter_mut().find(|d| d.name == *name) {
+                Some(duplicate) => duplicate.addresses.push(address),
+                None => duplicates.push(Duplicate {
+                    name: name.clone(),
+                    addresses: vec![address],
+                }),
             }
         }
+        duplicates
+    }
+
+    /// 只写姓名且该姓名重名时报错，列出各处可用的写法
+    ///
+    /// 查找类方法重名时取先序第一处；修改类命令先经此检查，以免改错人。
+    /// 路径与 `#ID` 写法不受影响。
+    pub fn require_unique(&self, name: &str) -> Result<(), ZzError> {
+        match self.duplicates().into_iter().find(|d| d.name == name) {
+            Some(duplicate) => Err(ZzError::Invalid(format!(
+                "{}，请改用其中一种写法指定",
+                duplicate.describe()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// 可唯一指定某成员的最短路径写法，至少含父辈一级（家主除外）
+    ///
+    /// `chain` 为从本成员到目标成员的路径（见 [`path_to`](Self::path_to)）。
+    /// 同一父辈下有同名子嗣时后出现的无法按路径指定，改用 ID（如 `#12`）；
+    /// 没有 ID 或 ID 也重复时返回 `None`。
+    pub fn address_of(&self, chain: &[&FamilyMember]) -> Option<String> {
+        let target = *chain.last()?;
+        let separator = PATH_SEPARATOR.to_string();
+        (chain.len().min(2)..=chain.len())
+            .map(|k| {
+                let names: Vec<&str> = chain[chain.len() - k..]
+                    .iter()
+                    .map(|m| m.name.as_str())
+                    .collect();
+                names.join(&separator)
+            })
+            .chain(target.id.iter().map(|id| format!("{}{}", ID_PREFIX, id)))
+            .find(|address| {
+                self.find_member_by_name(address)
+                    .is_some_and(|m| std::ptr::eq(m, target))
+            })
+    }
+
+    /// 按 ID 查找成员，ID 重复时取先序第一处
+    pub fn find_member_by_id(&self, id: u64) -> Option<&FamilyMember> {
+        let mut at = Vec::new();
+        self.locate_where(&|m| m.id == Some(id), &mut at)
+            .then(|| self.member_at(&at))
+    }
+
+    /// 为没有 ID 的成员按先序依次分配自增整数 ID
+    ///
+    /// `next` 为下一个可用的 ID，分配后随之递增。已有的 ID 保持不变。
+    ///
+    /// # Returns
+    /// 新分配的个数。
+    pub fn assign_ids(&mut self, next: &mut u64) -> usize {
+        let mut assigned = 0;
+        if self.id.is_none() {
+            self.id = Some(*next);
+            *next += 1;
+            assigned += 1;
+        }
+        for child in &mut self.children {
+            assigned += child.assign_ids(next);
+        }
+        assigned
+    }
 
-        println!(); // 空行结尾
+    /// 本支已有 ID 中的最大值，没有时为 0
+    pub fn max_id(&self) -> u64 {
+        self.children
+            .iter()
+            .map(|c| c.max_id())
+            .fold(self.id.unwrap_or(0), u64::max)
     }
 
     /// 添加子嗣
@@ -327,324 +602,951 @@ impl FamilyMember {
     /// 需要指定是谁的子嗣，可以一次添加多个。
     /// 并且实现了事务保证。
     ///
+    /// 称谓按父辈推算（见 [`MemberType::child_of`]），每人只需给出性别：
+    /// `gender` 字段（`男`/`女`），或沿用 `member_type` 中的性别，都没有时为男。
+    /// 给出的 `member_type` 与推算结果不符时以推算为准，嵌套的子嗣同样处理。
+    ///
     /// # param
     /// * `parent_name` - 父辈成员的姓名
     /// * `child_json` - 子嗣信息的 JSON 数组字符串
-    pub fn add_children(&mut self, parent_name: &str, child_json: &str) {
-        let Ok(children_vec) = serde_json::from_str::<Vec<FamilyMember>>(child_json) else {
-            eprintln!("添加的子代格式不正确。");
-            return;
-        };
+    ///
+    /// # Returns
+    /// 被更正的称谓，`from` 为输入中给出的称谓。
+    pub fn add_children(
+        &mut self,
+        parent_name: &str,
+        child_json: &str,
+    ) -> Result<Vec<Relabel>, ZzError> {
+        let parent_type = self
+            .find_member_by_name(parent_name)
+            .ok_or_else(|| ZzError::MemberNotFound(parent_name.to_string()))?
+            .member_type;
+        let mut values = serde_json::from_str::<Vec<serde_json::Value>>(child_json)
+            .map_err(|e| ZzError::json(None, &e))?;
+        let mut corrected = Vec::new();
+        for value in &mut values {
+            fill_member_type(value, &parent_type, &mut corrected)?;
+        }
+        let children_vec = serde_json::from_value::<Vec<FamilyMember>>(values.into())
+            .map_err(|e| ZzError::Invalid(format!("子嗣信息不正确：{}", e)))?;
 
         // 提前检查，保证一次添加原子化
         for node in &children_vec {
+            validate_name(&node.name)?;
             if self.exists(&node.name) {
-                println!("【{}】在当前家族树中重名，请重新命名。", node.name);
-                return;
+                return Err(ZzError::DuplicateName(node.name.clone()));
             }
+            self.check_id_free(node)?;
+            node.validate_years()?;
         }
 
         for node in &children_vec {
             self.add_child_entity(parent_name, node)
         }
+
+        Ok(corrected)
+    }
+
+    /// 添加一名已构建的子嗣（见 [`FamilyMember::builder`]）
+    ///
+    /// 与 [`add_children`](Self::add_children) 相同地检查重名与年份。
+    pub fn add_child(&mut self, parent_name: &str, child: FamilyMember) -> Result<(), ZzError> {
+        if !self.exists(parent_name) {
+            return Err(ZzError::MemberNotFound(parent_name.to_string()));
+        }
+        validate_name(&child.name)?;
+        if self.exists(&child.name) {
+            return Err(ZzError::DuplicateName(child.name));
+        }
+        self.check_id_free(&child)?;
+        child.validate_years()?;
+        self.add_child_entity(parent_name, &child);
+        Ok(())
     }
 
     /// 添加职位
     ///
+    /// 指定年份时同时追加一条任职记录
+    ///
     /// # param
     /// - name: 姓名
     /// - position: 职位
-    pub fn add_position(&mut self, name: &str, position: &str) -> Result<(), String> {
-        self.find_member_by_name_mut(name)
-            .map(|member| member.position = Some(position.to_string()))
-            .ok_or_else(|| format!("未找到成员【{}】", name))
+    /// - year: 任职年份
+    pub fn add_position(
+        &mut self,
+        name: &str,
+        position: &str,
+        year: Option<i32>,
+    ) -> Result<(), ZzError> {
+        let member = self
+            .find_member_by_name_mut(name)
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+
+        member.position = Some(position.to_string());
+        if let Some(year) = year {
+            member.position_history.push(PositionRecord {
+                year,
+                title: position.to_string(),
+            });
+        }
+
+        Ok(())
     }
 
-    /// 显示从根到指定成员的路径
-    pub fn path(&self, name: &str) {
-        let mut path = Vec::new();
+    /// 按代际分组汇总，统计范围与 [`stats`](Self::stats) 相同
+    pub fn cohorts(&self) -> Vec<Cohort> {
+        let mut cohorts: Vec<(Generation, Cohort)> = Vec::new();
+
+        for m in self.members() {
+            let generation = m.member_type.generation;
+            let index = match cohorts.iter().position(|(g, _)| *g == generation) {
+                Some(i) => i,
+                None => {
+                    let cohort = Cohort {
+                        generation: format!("{:?}", generation),
+                        ..Cohort::default()
+                    };
+                    cohorts.push((generation, cohort));
+                    cohorts.len() - 1
+                }
+            };
+            let cohort = &mut cohorts[index].1;
 
-        if self.find_path_recursive(name, &mut path) {
-            let names: Vec<&str> = path.iter().map(|m| m.name.as_str()).collect();
-            println!("{}", names.join(" → "));
-        } else {
-            println!("❌ 未找到【{}】", name);
+            cohort.members += 1;
+            if !m.is_dead {
+                cohort.living += 1;
+                cohort.living_birth_years += i64::from(m.birth_year);
+            }
+            if m.member_type.lineage == Lineage::Foreign {
+                cohort.foreign += 1;
+            }
+            cohort.children += m.children.len();
+            cohort.total_power += i64::from(m.hoser_power_add);
+        }
+
+        cohorts.sort_by_key(|(g, _)| *g);
+        cohorts.into_iter().map(|(_, c)| c).collect()
+    }
+
+    /// 从当前成员到指定成员的路径（含两端）
+    pub fn path_to(&self, name: &str) -> Option<Vec<&FamilyMember>> {
+        let at = self.locate(name)?;
+        let mut member = self;
+        let mut path = vec![member];
+        for &i in &at {
+            member = &member.children[i];
+            path.push(member);
+        }
+        Some(path)
+    }
+
+    /// 两名成员的最近共同祖先，可以是其中一人
+    ///
+    /// # Returns
+    /// 共同祖先及其到 `a`、`b` 各自的代数；任一成员不存在时为 `None`。
+    pub fn common_ancestor(&self, a: &str, b: &str) -> Option<(&FamilyMember, usize, usize)> {
+        let path_a = self.path_to(a)?;
+        let path_b = self.path_to(b)?;
+        // 两条路径都从本成员开始，至少有一个公共节点
+        let shared = path_a
+            .iter()
+            .zip(&path_b)
+            .take_while(|(x, y)| std::ptr::eq(**x, **y))
+            .count();
+        Some((
+            path_a[shared - 1],
+            path_a.len() - shared,
+            path_b.len() - shared,
+        ))
+    }
+
+    /// 指定成员的父辈；成员不存在或为本成员时为 `None`
+    pub fn parent_of(&self, name: &str) -> Option<&FamilyMember> {
+        let path = self.path_to(name)?;
+        path.len().checked_sub(2).map(|i| path[i])
+    }
+
+    /// 指定成员的兄弟姐妹（同一父辈的其他子嗣），按出生年排序
+    ///
+    /// # Returns
+    /// 成员不存在时为 `None`；本成员没有父辈，返回空列表。
+    pub fn siblings(&self, name: &str) -> Option<Vec<&FamilyMember>> {
+        let member = self.find_member_by_name(name)?;
+        let Some(parent) = self.parent_of(name) else {
+            return Some(Vec::new());
+        };
+        let mut siblings: Vec<&FamilyMember> = parent
+            .children
+            .iter()
+            .filter(|c| !std::ptr::eq(*c, member))
+            .collect();
+        siblings.sort_by_key(|m| m.birth_year);
+        Some(siblings)
+    }
+
+    /// 指定成员的堂表兄弟姐妹（祖父辈其他子嗣的子嗣），按出生年排序
+    ///
+    /// # Returns
+    /// 成员不存在时为 `None`；不足两代祖辈时返回空列表。
+    pub fn cousins(&self, name: &str) -> Option<Vec<&FamilyMember>> {
+        let path = self.path_to(name)?;
+        if path.len() < 3 {
+            return Some(Vec::new());
         }
+        let (grandparent, parent) = (path[path.len() - 3], path[path.len() - 2]);
+        let mut cousins: Vec<&FamilyMember> = grandparent
+            .children
+            .iter()
+            .filter(|uncle| !std::ptr::eq(*uncle, parent))
+            .flat_map(|uncle| &uncle.children)
+            .collect();
+        cousins.sort_by_key(|m| m.birth_year);
+        Some(cousins)
     }
 
     /// 清理未来出生的成员
     ///
     /// 用于处理读档后，删除当前年份之后出生的成员（通常因回档导致）
-    pub fn prune_future_births(&mut self, year: u16) {
-        self.children.retain(|child| child.birth_year <= year);
+    ///
+    /// # Returns
+    /// 被移除的分支（连同其子孙）；年份早于本成员出生时返回错误，
+    /// 因为此时整棵树都应被清理，通常是年份输错了。
+    pub fn prune_future_births(&mut self, year: i32) -> Result<Vec<FamilyMember>, ZzError> {
+        if year < self.birth_year {
+            return Err(ZzError::Invalid(format!(
+                "{} 年早于【{}】的出生年 {}，清理会清空整棵家族树",
+                year, self.name, self.birth_year
+            )));
+        }
 
-        for item in &mut self.children {
-            item.prune_future_births(year)
+        Ok(self.prune_born_after(year))
+    }
+
+    /// 推演到指定年份的家族树副本
+    ///
+    /// 此年之后出生的成员视为不存在，年满 [`LIFESPAN`] 岁的成员视为已故，
+    /// 本树不受影响。年份早于本成员出生时返回错误。
+    pub fn as_of(&self, year: i32) -> Result<FamilyMember, ZzError> {
+        if year < self.birth_year {
+            return Err(ZzError::Invalid(format!(
+                "{} 年时【{}】尚未出生（{} 年生）",
+                year, self.name, self.birth_year
+            )));
         }
+
+        let mut tree = self.clone();
+        tree.prune_born_after(year);
+        tree.expire(year);
+        Ok(tree)
+    }
+
+    /// 指定年份时担任的职位
+    ///
+    /// 按任职记录取该年及以前最近的一条；年份不早于最后一条记录时即为当前职位。
+    /// 没有任职记录时无从得知任职年份，按当前职位算。
+    pub fn position_at(&self, year: i32) -> Option<&str> {
+        let Some(last) = self.position_history.iter().map(|r| r.year).max() else {
+            return self.position.as_deref();
+        };
+        if year >= last {
+            return self.position.as_deref();
+        }
+        self.position_history
+            .iter()
+            .filter(|r| r.year <= year)
+            .max_by_key(|r| r.year)
+            .map(|r| r.title.as_str())
+    }
+
+    /// 家族树副本，每人的职位换成指定年份时担任的职位（见 [`position_at`](Self::position_at)）
+    pub fn positions_at(&self, year: i32) -> FamilyMember {
+        let mut tree = self.clone();
+        tree.set_positions_at(year);
+        tree
+    }
+
+    /// 移除成员及其子孙
+    ///
+    /// 家主（根节点）无法移除。
+    ///
+    /// # Returns
+    /// 被移除的子树，未找到时返回 `None`。
+    pub fn remove(&mut self, name: &str) -> Option<FamilyMember> {
+        let at = self.locate(name)?;
+        self.remove_at(at)
+    }
+
+    /// 按 ID 移除成员及其子孙，不受重名影响
+    ///
+    /// # Returns
+    /// 被移除的子树，未找到或为家主时返回 `None`。
+    pub fn remove_by_id(&mut self, id: u64) -> Option<FamilyMember> {
+        let mut at = Vec::new();
+        if !self.locate_where(&|m| m.id == Some(id), &mut at) {
+            return None;
+        }
+        self.remove_at(at)
+    }
+
+    /// 按 [`locate`](Self::locate) 给出的下标移除成员，家主无法移除
+    fn remove_at(&mut self, mut at: Vec<usize>) -> Option<FamilyMember> {
+        let index = at.pop()?;
+        let mut parent = self;
+        for &i in &at {
+            parent = &mut parent.children[i];
+        }
+        Some(parent.children.remove(index))
+    }
+
+    /// 将成员（连同其子孙）移到另一成员名下
+    ///
+    /// 按新父辈重新推算整支的称谓（代际与内外系）。家主不可移动，
+    /// 也不能移到自己或自己的后代名下。
+    pub fn reparent(&mut self, name: &str, new_parent: &str) -> Result<(), ZzError> {
+        let at = self
+            .locate(name)
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+        if at.is_empty() {
+            return Err(ZzError::Invalid("家主不可移动".to_string()));
+        }
+        let parent_at = self
+            .locate(new_parent)
+            .ok_or_else(|| ZzError::MemberNotFound(new_parent.to_string()))?;
+        if parent_at.starts_with(&at) {
+            return Err(ZzError::Invalid(format!(
+                "【{}】是【{}】本人或其后代，不能移到其名下",
+                new_parent, name
+            )));
+        }
+        if parent_at == at[..at.len() - 1] {
+            return Err(ZzError::Invalid(format!(
+                "【{}】已在【{}】名下",
+                name, new_parent
+            )));
+        }
+
+        let mut branch = self.remove(name).expect("已确认存在且不是家主");
+        let parent = self
+            .find_member_by_name_mut(new_parent)
+            .expect("已确认存在且不在被移动的分支内");
+        branch.retype(&parent.member_type);
+        parent.children.push(branch);
+        Ok(())
+    }
+
+    /// 将重复录入的成员 `duplicate` 并入 `keep`
+    ///
+    /// `duplicate` 的子女（按 `keep` 重新推算称谓）与任职记录移到 `keep` 名下，
+    /// `keep` 没有的职位与 ID 由 `duplicate` 补上，随后移除 `duplicate`。
+    /// 家主不能被并入他人，`keep` 也不能是 `duplicate` 的后代。
+    ///
+    /// # Returns
+    /// 移过来的子女人数。
+    pub fn merge_members(&mut self, keep: &str, duplicate: &str) -> Result<usize, ZzError> {
+        let keep_at = self
+            .locate(keep)
+            .ok_or_else(|| ZzError::MemberNotFound(keep.to_string()))?;
+        let duplicate_at = self
+            .locate(duplicate)
+            .ok_or_else(|| ZzError::MemberNotFound(duplicate.to_string()))?;
+        if keep_at == duplicate_at {
+            return Err(ZzError::Invalid("保留与移除的是同一成员".to_string()));
+        }
+        if duplicate_at.is_empty() {
+            return Err(ZzError::Invalid(
+                "家主不能被并入他人，可将他人并入家主".to_string(),
+            ));
+        }
+        if keep_at.starts_with(&duplicate_at) {
+            return Err(ZzError::Invalid(format!(
+                "【{}】是【{}】的后代，不能并入",
+                keep, duplicate
+            )));
+        }
+
+        let removed = self.remove(duplicate).expect("已确认存在且不是家主");
+        let kept = self
+            .find_member_by_name_mut(keep)
+            .expect("已确认存在且不在被移除的分支内");
+        let moved = removed.children.len();
+        for mut child in removed.children {
+            child.retype(&kept.member_type);
+            kept.children.push(child);
+        }
+        kept.position_history.extend(removed.position_history);
+        kept.position_history.sort_by_key(|r| r.year);
+        if kept.position.is_none() {
+            kept.position = removed.position;
+        }
+        if kept.id.is_none() {
+            kept.id = removed.id;
+        }
+        Ok(moved)
+    }
+
+    /// 查找全部成员均已故的分支
+    ///
+    /// 只返回最大的分支（不会同时返回某分支及其内部的子分支），不含家主。
+    pub fn dead_branches(&self) -> Vec<&FamilyMember> {
+        let mut branches = Vec::new();
+        for child in &self.children {
+            if child.all_dead() {
+                branches.push(child);
+            } else {
+                branches.extend(child.dead_branches());
+            }
+        }
+        branches
+    }
+
+    /// 以当前成员为根的分支是否全部已故
+    pub fn all_dead(&self) -> bool {
+        self.is_dead && self.children.iter().all(|c| c.all_dead())
+    }
+
+    /// 分支中最晚的出生年份
+    pub fn latest_birth_year(&self) -> i32 {
+        self.children
+            .iter()
+            .map(|c| c.latest_birth_year())
+            .fold(self.birth_year, i32::max)
     }
 
     /// 重命名成员
     ///
     /// 确保新名称在家族树中不重复
-    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Result<(), ZzError> {
+        if old_name == new_name {
+            return Err(ZzError::Invalid("新旧名称相同，无需重命名".to_string()));
+        }
+        if new_name.trim().is_empty() {
+            return Err(ZzError::Invalid("新名称不能为空".to_string()));
+        }
+        validate_name(new_name)?;
         if self.exists(new_name) {
-            return Err(format!("⚠️ 名称【{}】已存在，无法重命名。", new_name));
+            return Err(ZzError::DuplicateName(new_name.to_string()));
         }
 
         if let Some(member) = self.find_member_by_name_mut(old_name) {
             member.name = new_name.to_string();
             Ok(())
         } else {
-            Err(format!("未找到成员【{}】", old_name))
+            Err(ZzError::MemberNotFound(old_name.to_string()))
+        }
+    }
+
+    /// 生死状态，已故优先于下落不明
+    pub fn status(&self) -> VitalStatus {
+        match (self.is_dead, self.missing_since) {
+            (true, _) => VitalStatus::Dead,
+            (false, Some(since_year)) => VitalStatus::Missing { since_year },
+            (false, None) => VitalStatus::Alive,
         }
     }
 
     /// 标记成员死亡
     ///
-    /// 死亡成员不再计入家族规模统计
-    pub fn mark_dead(&mut self, name: &str) -> Result<(), String> {
+    /// 死亡成员不再计入家族规模统计，下落不明的标记随之清除
+    pub fn mark_dead(&mut self, name: &str) -> Result<(), ZzError> {
         if let Some(member) = self.find_member_by_name_mut(name) {
             if member.is_dead {
-                return Err(format!("⚠️ 成员【{}】已被标记为死亡。", name));
+                return Err(ZzError::Invalid(format!("成员【{}】已被标记为死亡", name)));
             }
 
             member.is_dead = true;
+            member.missing_since = None;
             Ok(())
         } else {
-            Err(format!("未找到成员【{}】", name))
+            Err(ZzError::MemberNotFound(name.to_string()))
+        }
+    }
+
+    /// 标记成员自某年起下落不明
+    ///
+    /// 下落不明的成员仍计为在世，但不能继承家主位
+    pub fn mark_missing(&mut self, name: &str, year: i32) -> Result<(), ZzError> {
+        let member = self
+            .find_member_by_name_mut(name)
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+        match member.status() {
+            VitalStatus::Dead => Err(ZzError::Invalid(format!("成员【{}】已故", name))),
+            VitalStatus::Missing { since_year } => Err(ZzError::Invalid(format!(
+                "成员【{}】自 {} 年起已下落不明",
+                name, since_year
+            ))),
+            VitalStatus::Alive if year < member.birth_year => Err(ZzError::Invalid(format!(
+                "失踪年份 {} 早于【{}】的出生年 {}",
+                year, name, member.birth_year
+            ))),
+            VitalStatus::Alive => {
+                member.missing_since = Some(year);
+                Ok(())
+            }
+        }
+    }
+
+    /// 寻回下落不明的成员
+    ///
+    /// # Returns
+    /// 失踪的起始年份。
+    pub fn mark_found(&mut self, name: &str) -> Result<i32, ZzError> {
+        if let Some(member) = self.find_member_by_name_mut(name) {
+            member
+                .missing_since
+                .take()
+                .ok_or_else(|| ZzError::Invalid(format!("成员【{}】并未下落不明", name)))
+        } else {
+            Err(ZzError::MemberNotFound(name.to_string()))
         }
     }
 
     /// 继承家主位
     ///
-    /// 将指定成员提升为新家主，并自动调整其后代的代际关系
-    pub fn inherit(&self, name: &str) -> Result<FamilyMember, String> {
+    /// 将指定成员提升为新家主，并自动调整其后代的代际关系。
+    /// 继承资格（代际、在世等）由 [`crate::succession::SuccessionPolicy`] 判定。
+    pub fn inherit(&self, name: &str) -> Result<FamilyMember, ZzError> {
         let successor = self
             .find_member_by_name(name)
-            .ok_or_else(|| format!("找不到【{}】", name))?;
-
-        let generation = successor.member_type.generation;
-        if generation > Generation::孙 {
-            return Err(format!(
-                "只能两代以内的成员继承家主. 当前的【{}】位于第{}代",
-                name,
-                u8::from(generation)
-            ));
-        }
-
-        let levels = u8::from(successor.member_type.generation);
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
 
         let mut new_head = successor.clone();
-        let head_gender = new_head.member_type.gender;
-        new_head.member_type = MemberType {
-            generation: Generation::家主,
-            gender: new_head.member_type.gender,
-            lineage: new_head.member_type.lineage,
-        };
-
-        for child in new_head.children.iter_mut() {
-            child.promote_descendants(levels);
-        }
+        new_head.member_type = MemberType::new(
+            Generation::家主,
+            new_head.member_type.gender,
+            Lineage::Direct,
+        );
+        new_head.relabel();
 
-        if matches!(head_gender, Gender::Female) {
-            for child in new_head.children.iter_mut() {
-                child.member_type.lineage = Lineage::Direct;
+        Ok(new_head)
+    }
 
-                if matches!(child.member_type.gender, Gender::Male) {
-                    child.set_lineage_for_descendants(Lineage::Direct);
-                }
-            }
+    /// 按本成员的称谓重算全部后代的称谓
+    ///
+    /// 结构改动（继位、移动分支、合并成员）后统一由此保持称谓一致，
+    /// 规则见 [`MemberType::child_of`]。目前没有 merge-family、split、replace
+    /// 这类拆并家族的命令，日后新增时也应经由此处并用 [`Self::label_changes`] 报告变化。
+    pub fn relabel(&mut self) {
+        let member_type = self.member_type;
+        for child in &mut self.children {
+            child.retype(&member_type);
         }
+    }
 
-        Ok(new_head)
+    /// 以本成员为家主，按树深度与经由的性别重算全部称谓
+    ///
+    /// 只保留各人称谓中的性别，代际与内外系一律重新推算，用于修正手工编辑后
+    /// 不一致的称谓。
+    ///
+    /// # Returns
+    /// 称谓有变的成员（先序）。
+    pub fn fix_labels(&mut self) -> Vec<Relabel> {
+        let before = self.clone();
+        self.member_type =
+            MemberType::new(Generation::家主, self.member_type.gender, Lineage::Direct);
+        self.relabel();
+        // 结构不变，按先序逐一对应，重名时也不会错配
+        before
+            .members()
+            .into_iter()
+            .zip(self.members())
+            .filter(|(b, a)| b.member_type != a.member_type)
+            .map(|(b, a)| Relabel {
+                name: a.name.clone(),
+                from: b.member_type,
+                to: a.member_type,
+            })
+            .collect()
+    }
+
+    /// 与改动后的家族树比较，列出仍在树中、称谓有变的成员（先序）
+    ///
+    /// 按 ID 对应改动前后的成员，重名时也不会错配；没有 ID 的按姓名对应。
+    pub fn label_changes(&self, after: &FamilyMember) -> Vec<Relabel> {
+        after
+            .members()
+            .into_iter()
+            .filter_map(|m| {
+                let before = match m.id {
+                    Some(id) => self.find_member_by_id(id)?,
+                    None => self.find_member_by_name(&m.name)?,
+                };
+                (before.member_type != m.member_type).then(|| Relabel {
+                    name: m.name.clone(),
+                    from: before.member_type,
+                    to: m.member_type,
+                })
+            })
+            .collect()
     }
 
     // ------------------------------------------------------------------------
     // 私有辅助方法 (Private Helper Methods)
     // ------------------------------------------------------------------------
 
-    /// 递归查找并添加单个子节点到指定父节点
-    fn add_child_entity(&mut self, parent_name: &str, child: &FamilyMember) {
-        if self.name == parent_name {
-            self.children.push(child.to_owned());
-            return;
-        }
+    /// 递归移除指定年份之后出生的子孙
+    fn prune_born_after(&mut self, year: i32) -> Vec<FamilyMember> {
+        let (kept, mut removed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.children)
+            .into_iter()
+            .partition(|child| child.birth_year <= year);
+        self.children = kept;
 
-        for node in self.children.iter_mut() {
-            node.add_child_entity(parent_name, child);
+        for item in &mut self.children {
+            removed.extend(item.prune_born_after(year));
         }
-    }
 
-    /// 按树形结构打印成员及其所有子代
-    fn show_with_descendants(&self, level: usize) {
-        // 根节点调用辅助方法，不使用树形符号
-        self.show_with_descendants_helper(level, true, Vec::new());
+        removed
     }
 
-    /// 递归打印家族树，支持树形分支符号
-    ///
-    /// # param
-    /// * `level` - 当前层级（0为根节点）
-    /// * `is_last` - 当前节点是否是父节点的最后一个子节点
-    /// * `parent_markers` - 记录每一层的父节点是否是最后一个（用于决定是否画竖线）
-    fn show_with_descendants_helper(&self, level: usize, is_last: bool, parent_markers: Vec<bool>) {
-        // 构建树形前缀
-        let mut tree_prefix = String::new();
-
-        // 为每一层父节点添加竖线或空格
-        for &parent_is_last in &parent_markers {
-            if parent_is_last {
-                tree_prefix.push_str("   "); // 父节点是最后一个，不画竖线
-            } else {
-                tree_prefix.push_str("│  "); // 父节点不是最后一个，画竖线
-            }
+    /// 递归将年满 [`LIFESPAN`] 岁的成员标记为已故
+    fn expire(&mut self, year: i32) {
+        if year - self.birth_year >= LIFESPAN {
+            self.is_dead = true;
+            self.missing_since = None;
         }
+        for child in &mut self.children {
+            child.expire(year);
+        }
+    }
 
-        // 当前节点的分支符号（根节点除外）
-        let branch_symbol = if level > 0 {
-            if is_last {
-                "└─ " // 最后一个子节点
-            } else {
-                "├─ " // 中间子节点
-            }
-        } else {
-            "" // 根节点无符号
-        };
-
-        tree_prefix.push_str(branch_symbol);
-
-        // 组合树形前缀和姓名
-        let name_with_tree = format!("{}{}", tree_prefix, self.name);
-
-        // 填充到固定总宽度
-        let total_display_width = name_with_tree.width();
-        let padding = Self::TREE_COLUMN_WIDTH.saturating_sub(total_display_width);
-        let name_column = format!("{}{}", name_with_tree, " ".repeat(padding));
-
-        // 出生年 - 手动填充
-        let birth_str = self.birth_year.to_string();
-        let birth_padding = Self::BIRTH_WIDTH.saturating_sub(birth_str.width());
-        let birth_padded = format!("{}{}", birth_str, " ".repeat(birth_padding));
-
-        // 类别 - 手动填充
-        let type_padding = Self::TYPE_WIDTH.saturating_sub(self.member_type.to_string().width());
-        let type_padded = format!("{}{}", self.member_type, " ".repeat(type_padding));
-
-        // 状态 - 手动填充
-        let status_str = if self.is_dead { "已故" } else { "" };
-        let status_padding = Self::STATUS_WIDTH.saturating_sub(status_str.width());
-        let status_padded = format!("{}{}", status_str, " ".repeat(status_padding));
-
-        // 职位 - 手动填充
-        let position_str = self.position.as_deref().unwrap_or("-");
-        let position_padding = Self::POSITION_WIDTH.saturating_sub(position_str.width());
-        let position_padded = format!("{}{}", position_str, " ".repeat(position_padding));
-
-        // 属性+ - 手动填充
-        let attr_str = self.hoser_power_add.to_string();
-        let attr_padding = Self::ATTR_WIDTH.saturating_sub(attr_str.width());
-        let attr_padded = format!("{}{}", attr_str, " ".repeat(attr_padding));
-
-        // 子嗣 - 手动填充
-        let child_str = self.children.len().to_string();
-        let child_padding = Self::CHILD_WIDTH.saturating_sub(child_str.width());
-        let child_padded = format!("{}{}", child_str, " ".repeat(child_padding));
-
-        // 直接拼接输出
-        println!(
-            "{}{}{}{}{}{}{}",
-            name_column,
-            birth_padded,
-            type_padded,
-            status_padded,
-            position_padded,
-            attr_padded,
-            child_padded
-        );
-
-        // 递归处理子节点
-        let child_count = self.children.len();
-        for (index, child) in self.children.iter().enumerate() {
-            let child_is_last = index == child_count - 1;
-
-            // 更新 parent_markers：添加当前节点的状态
-            let mut new_markers = parent_markers.clone();
-            new_markers.push(is_last);
-
-            child.show_with_descendants_helper(level + 1, child_is_last, new_markers);
+    /// 添加单个子节点到指定父节点
+    fn add_child_entity(&mut self, parent_name: &str, child: &FamilyMember) {
+        if let Some(parent) = self.find_member_by_name_mut(parent_name) {
+            parent.children.push(child.to_owned());
         }
     }
 
-    /// 在当前家族树中递归查找指定姓名的成员。
+    /// 在当前家族树中查找指定姓名的成员，支持路径写法（见 [`locate`](Self::locate)）。
     ///
     /// # Returns
     /// 若找到则返回 `Some(&FamilyMember)`，否则返回 `None`。
-    fn find_member_by_name(&self, name: &str) -> Option<&FamilyMember> {
-        if self.name == name {
-            return Some(self);
-        }
-        self.children
-            .iter()
-            .find_map(|c| c.find_member_by_name(name))
+    pub fn find_member_by_name(&self, name: &str) -> Option<&FamilyMember> {
+        self.locate(name).map(|at| self.member_at(&at))
     }
 
-    /// 在当前家族树中递归查找指定姓名的成员（可变引用版本）。
+    /// 在当前家族树中查找指定姓名的成员（可变引用版本）。
     ///
     /// # Returns
     /// 若找到则返回 `Some(&mut FamilyMember)`，否则返回 `None`。
-    fn find_member_by_name_mut(&mut self, name: &str) -> Option<&mut FamilyMember> {
-        if self.name == name {
-            return Some(self);
+    pub fn find_member_by_name_mut(&mut self, name: &str) -> Option<&mut FamilyMember> {
+        let at = self.locate(name)?;
+        let mut member = self;
+        for &i in &at {
+            member = &mut member.children[i];
         }
-        self.children
-            .iter_mut()
-            .find_map(|c| c.find_member_by_name_mut(name))
+        Some(member)
     }
 
-    /// 递归查找路径（回溯法）
-    fn find_path_recursive<'a>(
-        &'a self,
-        target_name: &str,
-        path: &mut Vec<&'a FamilyMember>,
-    ) -> bool {
-        path.push(self);
+    /// 按姓名定位成员，给出从本成员起逐级的子嗣下标
+    ///
+    /// 以 [`ID_PREFIX`] 开头的按 ID 查找（如 `#12`）；其余先按完整姓名先序查找，重名时取
+    /// 第一处，找不到时含 [`PATH_SEPARATOR`] 的按路径查找：`张三/李四` 为任一【张三】名下的
+    /// 【李四】，可写多级，首段不必是本成员。
+    fn locate(&self, name: &str) -> Option<Vec<usize>> {
+        let mut at = Vec::new();
+        if let Some(id) = name.strip_prefix(ID_PREFIX) {
+            let id: u64 = id.trim().parse().ok()?;
+            return self
+                .locate_where(&|m| m.id == Some(id), &mut at)
+                .then_some(at);
+        }
+        if self.locate_where(&|m| m.name == name, &mut at) {
+            return Some(at);
+        }
+        let segments: Vec<&str> = name.split(PATH_SEPARATOR).map(str::trim).collect();
+        if segments.len() < 2 || segments.iter().any(|s| s.is_empty()) {
+            return None;
+        }
+        self.locate_path(&segments, &mut at).then_some(at)
+    }
 
-        if self.name == target_name {
+    /// 先序查找第一个满足条件的成员（回溯法）
+    fn locate_where(&self, wanted: &dyn Fn(&FamilyMember) -> bool, at: &mut Vec<usize>) -> bool {
+        if wanted(self) {
             return true;
         }
+        for (i, child) in self.children.iter().enumerate() {
+            at.push(i);
+            if child.locate_where(wanted, at) {
+                return true;
+            }
+            at.pop();
+        }
+        false
+    }
 
-        for child in &self.children {
-            if child.find_path_recursive(target_name, path) {
+    /// 先序查找路径首段，再逐级沿子嗣匹配其余各段（回溯法）
+    fn locate_path(&self, segments: &[&str], at: &mut Vec<usize>) -> bool {
+        if self.name == segments[0] && self.follow_path(&segments[1..], at) {
+            return true;
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            at.push(i);
+            if child.locate_path(segments, at) {
                 return true;
             }
+            at.pop();
         }
+        false
+    }
 
-        path.pop();
+    fn follow_path(&self, segments: &[&str], at: &mut Vec<usize>) -> bool {
+        let Some((first, rest)) = segments.split_first() else {
+            return true;
+        };
+        for (i, child) in self.children.iter().enumerate() {
+            if child.name != *first {
+                continue;
+            }
+            at.push(i);
+            if child.follow_path(rest, at) {
+                return true;
+            }
+            at.pop();
+        }
         false
     }
 
-    /// 递归提升后代的代际
-    ///
-    /// 在继承时调用，将所有子孙的代际向上提升指定层级
-    fn promote_descendants(&mut self, levels: u8) {
-        self.member_type.generation = self.member_type.generation.promote(levels);
-        for child in self.children.iter_mut() {
-            child.promote_descendants(levels);
+    /// 按 [`locate`](Self::locate) 给出的下标取成员
+    fn member_at(&self, at: &[usize]) -> &FamilyMember {
+        at.iter().fold(self, |member, &i| &member.children[i])
+    }
+
+    /// 将本成员及所有后代的职位改写为指定年份时担任的职位
+    fn set_positions_at(&mut self, year: i32) {
+        self.position = self.position_at(year).map(String::from);
+        for child in &mut self.children {
+            child.set_positions_at(year);
+        }
+    }
+
+    /// 新成员自带的 ID 不能与已有成员相同
+    fn check_id_free(&self, member: &FamilyMember) -> Result<(), ZzError> {
+        let Some(id) = member.id else {
+            return Ok(());
+        };
+        match self.find_member_by_id(id) {
+            Some(holder) => Err(ZzError::Invalid(format!(
+                "【{}】的 ID {}{} 已被【{}】使用",
+                member.name, ID_PREFIX, id, holder.name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// 先序收集满足条件的成员从本成员起的路径
+    fn collect_chains<'a>(
+        &'a self,
+        wanted: &dyn Fn(&FamilyMember) -> bool,
+        path: &mut Vec<&'a FamilyMember>,
+        out: &mut Vec<Vec<&'a FamilyMember>>,
+    ) {
+        path.push(self);
+        if wanted(self) {
+            out.push(path.clone());
+        }
+        for child in &self.children {
+            child.collect_chains(wanted, path, out);
         }
+        path.pop();
     }
 
-    /// 递归设置所有后代的血统
-    fn set_lineage_for_descendants(&mut self, lineage: Lineage) {
-        for child in self.children.iter_mut() {
-            child.member_type.lineage = lineage;
-            child.set_lineage_for_descendants(lineage);
+    /// 收集所有成员姓名
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a str>) {
+        names.push(&self.name);
+        for child in &self.children {
+            child.collect_names(names);
         }
     }
+
+    /// 校验本成员及所有后代的出生年与任职年份
+    fn validate_years(&self) -> Result<(), ZzError> {
+        validate_year(self.birth_year)
+            .map_err(|e| ZzError::InvalidYear(format!("【{}】的出生年无效：{}", self.name, e)))?;
+        for record in &self.position_history {
+            validate_year(record.year).map_err(|e| {
+                ZzError::InvalidYear(format!("【{}】的任职年份无效：{}", self.name, e))
+            })?;
+        }
+        self.children.iter().try_for_each(|c| c.validate_years())
+    }
+
+    /// 按父辈的成员类型递归重算本成员及其后代的成员类型
+    fn retype(&mut self, parent: &MemberType) {
+        self.member_type = MemberType::child_of(parent, self.member_type.gender);
+        self.relabel();
+    }
+}
+
+impl Duplicate {
+    /// 如 `【张三】出现 2 次：张二/张三、张丽/张三`
+    pub fn describe(&self) -> String {
+        let addresses: Vec<&str> = self
+            .addresses
+            .iter()
+            .map(|a| a.as_deref().unwrap_or("（无法区分）"))
+            .collect();
+        format!(
+            "【{}】出现 {} 次：{}",
+            self.name,
+            self.addresses.len(),
+            addresses.join("、")
+        )
+    }
+}
+
+impl FamilyMemberBuilder {
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn birth_year(mut self, year: i32) -> Self {
+        self.birth_year = Some(year);
+        self
+    }
+
+    /// 性别，未指定时取称谓中的性别，由父辈推算时默认为男；
+    /// 称谓与父辈都未指定时据此取家主
+    pub fn gender(mut self, gender: Gender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    /// 直接指定称谓
+    pub fn member_type(mut self, member_type: MemberType) -> Self {
+        self.member_type = Some(member_type);
+        self
+    }
+
+    /// 由父辈的称谓推算称谓（见 [`MemberType::child_of`]）
+    pub fn child_of(mut self, parent: &MemberType) -> Self {
+        self.parent_type = Some(*parent);
+        self
+    }
+
+    pub fn hoser_power_add(mut self, power: i32) -> Self {
+        self.hoser_power_add = power;
+        self
+    }
+
+    pub fn position(mut self, position: impl Into<String>) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    pub fn dead(mut self, is_dead: bool) -> Self {
+        self.is_dead = is_dead;
+        self
+    }
+
+    /// 校验并创建成员
+    ///
+    /// 姓名不能为空，出生年须为有效年份，须能确定称谓。
+    pub fn build(self) -> Result<FamilyMember, ZzError> {
+        let name = self
+            .name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| ZzError::Invalid("姓名不能为空".to_string()))?;
+        validate_name(&name)?;
+        let birth_year = self
+            .birth_year
+            .ok_or_else(|| ZzError::InvalidYear(format!("【{}】缺少出生年", name)))?;
+        validate_year(birth_year)
+            .map_err(|e| ZzError::InvalidYear(format!("【{}】的出生年无效：{}", name, e)))?;
+
+        let member_type = match (self.member_type, self.parent_type) {
+            (Some(mut member_type), _) => {
+                if let Some(gender) = self.gender {
+                    member_type.gender = gender;
+                }
+                member_type
+            }
+            (None, Some(parent)) => {
+                MemberType::child_of(&parent, self.gender.unwrap_or(Gender::Male))
+            }
+            // 没有父辈即为一族之首
+            (None, None) => match self.gender {
+                Some(gender) => MemberType {
+                    generation: Generation::家主,
+                    gender,
+                    lineage: Lineage::Direct,
+                },
+                None => {
+                    return Err(ZzError::Invalid(format!(
+                        "【{}】缺少称谓：请指定称谓、性别或父辈",
+                        name
+                    )))
+                }
+            },
+        };
+
+        Ok(FamilyMember {
+            id: self.id,
+            name,
+            birth_year,
+            hoser_power_add: self.hoser_power_add,
+            member_type,
+            position: self.position,
+            children: Vec::new(),
+            is_dead: self.is_dead,
+            missing_since: None,
+            position_history: Vec::new(),
+        })
+    }
+}
+
+impl MemberType {
+    pub fn new(generation: Generation, gender: Gender, lineage: Lineage) -> Self {
+        MemberType {
+            generation,
+            gender,
+            lineage,
+        }
+    }
+
+    /// 是否为内系
+    pub fn is_direct(&self) -> bool {
+        self.lineage == Lineage::Direct
+    }
+
+    /// 距家主的代数，家主为 0
+    ///
+    /// 代际为 `其他` 时返回 `None`。
+    pub fn generation_depth(&self) -> Option<u8> {
+        self.generation.depth()
+    }
+
+    /// 根据父辈的成员类型推算子嗣的成员类型
+    ///
+    /// 代际为父辈的下一代；家主的子女为内系，
+    /// 经由女儿或外系成员延续的子嗣为外系。
+    pub fn child_of(parent: &MemberType, gender: Gender) -> MemberType {
+        let lineage = match parent.generation {
+            Generation::家主 => Lineage::Direct,
+            _ if parent.gender == Gender::Female => Lineage::Foreign,
+            _ => parent.lineage,
+        };
+
+        MemberType::new(parent.generation.next(), gender, lineage)
+    }
 }
 
 impl Generation {
+    /// 距家主的代数，`其他` 时返回 `None`
+    pub fn depth(self) -> Option<u8> {
+        (self != Generation::其他).then_some(self as u8)
+    }
+
     /// 从数值转换为代际
-    fn from_u8(n: u8) -> Self {
+    ///
+    /// 超出耳孙（9）时为 `其他`。
+    pub fn from_u8(n: u8) -> Self {
         match n {
             0 => Self::家主,
             1 => Self::儿,
@@ -660,6 +1562,14 @@ impl Generation {
         }
     }
 
+    /// 下一代
+    ///
+    /// 超出耳孙时为 `其他`
+    pub fn next(self) -> Self {
+        let current: u8 = self.into();
+        Self::from_u8(current.saturating_add(1))
+    }
+
     /// 代际提升
     ///
     /// 将当前代际向上提升指定层级（数值减少）
@@ -669,3 +1579,529 @@ impl Generation {
         Self::from_u8(new_level)
     }
 }
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 允许的最早年份（负数表示公元前，不存在 0 年）
+pub const MIN_YEAR: i32 = -1_000_000;
+/// 允许的最晚年份
+pub const MAX_YEAR: i32 = 1_000_000;
+
+/// 推演年份时视为寿终的年龄
+pub const LIFESPAN: i32 = 100;
+
+/// 称谓变化汇总中每组最多列出的姓名数
+const RELABEL_LISTED: usize = 5;
+
+/// 按路径指定成员时的分隔符，如 `张三/李四`，姓名中不能含有
+pub const PATH_SEPARATOR: char = '/';
+
+/// 按 ID 指定成员时的前缀，如 `#12`，姓名不能以它开头
+pub const ID_PREFIX: char = '#';
+
+/// 校验新成员的姓名：不能为空，不能含有 [`PATH_SEPARATOR`]，也不能以 [`ID_PREFIX`] 开头
+fn validate_name(name: &str) -> Result<(), ZzError> {
+    if name.trim().is_empty() {
+        return Err(ZzError::Invalid("姓名不能为空".to_string()));
+    }
+    if name.contains(PATH_SEPARATOR) {
+        return Err(ZzError::Invalid(format!(
+            "姓名【{}】不能含有 {}，它用于按路径指定成员（如 张三{}李四）",
+            name, PATH_SEPARATOR, PATH_SEPARATOR
+        )));
+    }
+    if name.starts_with(ID_PREFIX) {
+        return Err(ZzError::Invalid(format!(
+            "姓名【{}】不能以 {} 开头，它用于按 ID 指定成员（如 {}12）",
+            name, ID_PREFIX, ID_PREFIX
+        )));
+    }
+    Ok(())
+}
+
+/// 校验年份：不能为 0，且须在 [`MIN_YEAR`, `MAX_YEAR`] 范围内
+pub fn validate_year(year: i32) -> Result<i32, ZzError> {
+    if year == 0 {
+        Err(ZzError::InvalidYear(
+            "不存在 0 年（公元前 1 年记为 -1）".to_string(),
+        ))
+    } else if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        Err(ZzError::InvalidYear(format!(
+            "年份 {} 超出范围（{} ~ {}）",
+            year, MIN_YEAR, MAX_YEAR
+        )))
+    } else {
+        Ok(year)
+    }
+}
+
+/// 解析并校验用户输入的年份
+pub fn parse_year(s: &str) -> Result<i32, ZzError> {
+    let year = s
+        .parse::<i32>()
+        .map_err(|_| ZzError::InvalidYear(format!("无效的年份：{}", s)))?;
+    validate_year(year)
+}
+
+/// 读取成员 ID：整数，或格式 4 保存的字符串
+///
+/// 字符串为整数（可带 [`ID_PREFIX`]）时按数值读取；其他字符串（如导入时带来的非数字 ID）
+/// 无法保留，读为 `None`，迁移时重新分配。
+fn deserialize_id<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredId {
+        Number(u64),
+        Text(String),
+    }
+
+    Ok(match Option::<StoredId>::deserialize(deserializer)? {
+        Some(StoredId::Number(id)) => Some(id),
+        Some(StoredId::Text(id)) => id.trim().trim_start_matches(ID_PREFIX).parse().ok(),
+        None => None,
+    })
+}
+
+/// 按父辈推算 JSON 中一名子嗣及其后代的称谓，写回 `member_type` 并去掉 `gender`
+fn fill_member_type(
+    value: &mut serde_json::Value,
+    parent: &MemberType,
+    corrected: &mut Vec<Relabel>,
+) -> Result<(), ZzError> {
+    let Some(object) = value.as_object_mut() else {
+        return Ok(());
+    };
+    let name = object
+        .get("name")
+        .and_then(|n| n.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let given = match object.get("member_type").and_then(|t| t.as_str()) {
+        Some(label) => Some(
+            label
+                .parse::<MemberType>()
+                .map_err(|e| ZzError::Invalid(format!("【{}】的称谓无效：{}", name, e)))?,
+        ),
+        None => None,
+    };
+    let gender = match object.remove("gender") {
+        Some(gender) => gender
+            .as_str()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e| ZzError::Invalid(format!("【{}】：{}", name, e)))?,
+        None => given.map_or(Gender::Male, |t| t.gender),
+    };
+
+    let member_type = MemberType::child_of(parent, gender);
+    if let Some(from) = given.filter(|t| *t != member_type) {
+        corrected.push(Relabel {
+            name,
+            from,
+            to: member_type,
+        });
+    }
+    object.insert("member_type".to_string(), member_type.to_string().into());
+    if let Some(children) = object.get_mut("children").and_then(|c| c.as_array_mut()) {
+        for child in children {
+            fill_member_type(child, &member_type, corrected)?;
+        }
+    }
+    Ok(())
+}
+
+/// 称谓变化的汇总，按变化方式分组，每组一行，如 `12 名成员由 外孙 → 孙：【甲】【乙】…等`
+///
+/// 每组最多列出 [`RELABEL_LISTED`] 个姓名，没有变化时为空字符串。
+pub fn describe_relabels(changes: &[Relabel]) -> String {
+    let mut groups: Vec<(MemberType, MemberType, Vec<&str>)> = Vec::new();
+    for change in changes {
+        match groups
+            .iter_mut()
+            .find(|(from, to, _)| *from == change.from && *to == change.to)
+        {
+            Some((_, _, names)) => names.push(&change.name),
+            None => groups.push((change.from, change.to, vec![&change.name])),
+        }
+    }
+
+    let mut out = String::new();
+    for (from, to, names) in groups {
+        let mut listed: String = names
+            .iter()
+            .take(RELABEL_LISTED)
+            .map(|n| format!("【{}】", n))
+            .collect();
+        if names.len() > RELABEL_LISTED {
+            listed.push('等');
+        }
+        out.push_str(&format!(
+            "{} 名成员由 {} → {}：{}\n",
+            names.len(),
+            from,
+            to,
+            listed
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{names, sample};
+
+    #[test]
+    fn prune_refuses_year_before_root_birth() {
+        let mut tree = sample();
+        assert!(tree.prune_future_births(1199).is_err());
+        assert_eq!(tree.total_count(), 7);
+    }
+
+    #[test]
+    fn prune_removes_later_births() {
+        let mut tree = sample();
+        let removed = tree.prune_future_births(1241).unwrap();
+        let removed: Vec<&str> = removed.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(removed, ["张小红", "王五"]);
+        assert_eq!(names(&tree), ["张大", "张二", "张三", "张丽"]);
+    }
+
+    #[test]
+    fn rename_refuses_same_or_empty_name() {
+        let mut tree = sample();
+        assert!(tree.rename("张三", "张三").is_err());
+        assert!(tree.rename("张三", "  ").is_err());
+        assert!(matches!(
+            tree.rename("张三", "张二"),
+            Err(ZzError::DuplicateName(_))
+        ));
+        assert!(tree.exists("张三"));
+    }
+
+    #[test]
+    fn rename_allows_new_name() {
+        let mut tree = sample();
+        tree.rename("张三", "张叁").unwrap();
+        assert!(tree.exists("张叁"));
+        assert!(!tree.exists("张三"));
+    }
+
+    #[test]
+    fn reparent_refuses_root_and_own_descendant() {
+        let mut tree = sample();
+        assert!(tree.reparent("张大", "张二").is_err());
+        assert!(tree.reparent("张二", "李四").is_err());
+        assert!(tree.reparent("张二", "张二").is_err());
+        assert_eq!(names(&tree), names(&sample()));
+    }
+
+    #[test]
+    fn reparent_moves_branch() {
+        let mut tree = sample();
+        tree.reparent("张小红", "张丽").unwrap();
+        assert_eq!(tree.parent_of("李四").unwrap().name, "张小红");
+        assert_eq!(tree.parent_of("张小红").unwrap().name, "张丽");
+    }
+
+    /// 示例树中【张丽】名下再添一名【张三】
+    fn sample_with_twin() -> FamilyMember {
+        let mut tree = sample();
+        let mut twin = tree.find_member_by_name("张三").unwrap().clone();
+        twin.birth_year = 1246;
+        tree.find_member_by_name_mut("张丽")
+            .unwrap()
+            .children
+            .push(twin);
+        tree
+    }
+
+    #[test]
+    fn locate_by_name_takes_first_match() {
+        let tree = sample_with_twin();
+        assert_eq!(tree.locate("张三"), Some(vec![0, 0]));
+        assert_eq!(tree.find_member_by_name("张三").unwrap().birth_year, 1240);
+        assert_eq!(tree.locate("张大"), Some(vec![]));
+        assert_eq!(tree.locate("赵六"), None);
+    }
+
+    #[test]
+    fn locate_path_follows_each_segment() {
+        let tree = sample_with_twin();
+        assert_eq!(tree.locate("张丽/张三"), Some(vec![1, 1]));
+        assert_eq!(tree.locate("张大/张丽/张三"), Some(vec![1, 1]));
+        assert_eq!(tree.locate(" 张二 / 张小红 / 李四 "), Some(vec![0, 1, 0]));
+        assert_eq!(tree.locate("张二/王五"), None);
+        assert_eq!(tree.locate("张二/"), None);
+        assert_eq!(tree.locate("张二//张三"), None);
+    }
+
+    #[test]
+    fn locate_tries_id_before_name() {
+        let mut tree = sample();
+        tree.id = Some(1);
+        tree.find_member_by_name_mut("王五").unwrap().id = Some(5);
+        // 旧数据中可能有以 # 开头的姓名，#ID 写法仍指 ID
+        tree.find_member_by_name_mut("张三").unwrap().name = "#5".to_string();
+        assert_eq!(tree.find_member_by_name("#5").unwrap().name, "王五");
+        assert_eq!(tree.find_member_by_name("#1").unwrap().name, "张大");
+        assert!(tree.find_member_by_name("#9").is_none());
+        assert!(tree.find_member_by_name("#甲").is_none());
+    }
+
+    #[test]
+    fn remove_by_id_skips_living_namesake() {
+        let mut tree = sample();
+        tree.id = Some(1);
+        // 已故的【张丽】一支中有与在世的【张三】同名的成员
+        let dead = tree.find_member_by_name_mut("张丽").unwrap();
+        dead.id = Some(2);
+        dead.children[0].name = "张三".to_string();
+        dead.children[0].is_dead = true;
+
+        let branches: Vec<u64> = tree.dead_branches().iter().filter_map(|b| b.id).collect();
+        assert_eq!(branches, [2]);
+        let removed = tree.remove_by_id(2).unwrap();
+        assert_eq!(removed.name, "张丽");
+        assert!(tree.find_member_by_name("张三").is_some_and(|m| !m.is_dead));
+        assert!(tree.remove_by_id(1).is_none());
+        assert!(tree.remove_by_id(2).is_none());
+    }
+
+    #[test]
+    fn cousins_keep_uncle_sharing_parent_name() {
+        let mut tree = sample();
+        // 【张丽】改名为与【张二】同名，其子【王五】仍是【张三】的堂表亲
+        tree.find_member_by_name_mut("张丽").unwrap().name = "张二".to_string();
+        let cousins: Vec<&str> = tree
+            .cousins("张三")
+            .unwrap()
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(cousins, ["王五"]);
+    }
+
+    #[test]
+    fn duplicates_list_unique_addresses() {
+        let tree = sample_with_twin();
+        let duplicates = tree.duplicates();
+        assert_eq!(
+            duplicates,
+            [Duplicate {
+                name: "张三".to_string(),
+                addresses: vec![Some("张二/张三".to_string()), Some("张丽/张三".to_string())],
+            }]
+        );
+        assert!(sample().duplicates().is_empty());
+    }
+
+    #[test]
+    fn require_unique_refuses_bare_duplicate() {
+        let tree = sample_with_twin();
+        let err = tree.require_unique("张三").unwrap_err().to_string();
+        assert!(err.contains("张二/张三、张丽/张三"), "{}", err);
+        assert!(tree.require_unique("张丽/张三").is_ok());
+        assert!(tree.require_unique("张二").is_ok());
+    }
+
+    fn member_type(s: &str) -> MemberType {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn fix_labels_keeps_consistent_tree() {
+        let mut tree = sample();
+        assert!(tree.fix_labels().is_empty());
+    }
+
+    #[test]
+    fn fix_labels_recomputes_generation_and_lineage() {
+        let mut tree = sample();
+        tree.find_member_by_name_mut("王五").unwrap().member_type = member_type("孙");
+        tree.find_member_by_name_mut("张小红").unwrap().member_type = member_type("外曾孙女");
+
+        let changes = tree.fix_labels();
+        assert_eq!(
+            changes,
+            [
+                Relabel {
+                    name: "张小红".to_string(),
+                    from: member_type("外曾孙女"),
+                    to: member_type("孙女"),
+                },
+                Relabel {
+                    name: "王五".to_string(),
+                    from: member_type("孙"),
+                    to: member_type("外孙"),
+                },
+            ]
+        );
+        assert_eq!(
+            tree.find_member_by_name("李四").unwrap().member_type,
+            member_type("外曾孙")
+        );
+    }
+
+    #[test]
+    fn fix_labels_matches_duplicates_by_position() {
+        let mut tree = sample();
+        let mut twin = tree.find_member_by_name("张三").unwrap().clone();
+        twin.member_type = member_type("孙");
+        tree.find_member_by_name_mut("张丽")
+            .unwrap()
+            .children
+            .push(twin);
+
+        let changes = tree.fix_labels();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].from, member_type("孙"));
+        assert_eq!(changes[0].to, member_type("外孙"));
+        assert_eq!(
+            tree.find_member_by_name("张三").unwrap().member_type,
+            member_type("孙")
+        );
+    }
+
+    #[test]
+    fn child_of_follows_head_and_daughters() {
+        let male = Gender::Male;
+        assert_eq!(
+            MemberType::child_of(&member_type("家主"), male),
+            member_type("儿")
+        );
+        assert_eq!(
+            MemberType::child_of(&member_type("女儿"), male),
+            member_type("外孙")
+        );
+        assert_eq!(
+            MemberType::child_of(&member_type("儿"), male),
+            member_type("孙")
+        );
+        assert_eq!(
+            MemberType::child_of(&member_type("外孙"), male),
+            member_type("外曾孙")
+        );
+        assert_eq!(
+            MemberType::child_of(&member_type("儿"), Gender::Female),
+            member_type("孙女")
+        );
+    }
+
+    #[test]
+    fn inherit_through_female_head_keeps_previous_labels() {
+        // 以下为移除前 promote_descendants / set_lineage_for_descendants 的结果：
+        // 代际各提升一级，女家主的子女及儿子一支改为内系，女儿一支保持外系
+        let mut tree = sample();
+        let daughter = tree.find_member_by_name_mut("张丽").unwrap();
+        daughter.children.push(
+            serde_json::from_str(
+                r#"{"name":"王霞","birth_year":1247,"hoser_power_add":1,"member_type":"外孙女","children":[
+                     {"name":"赵七","birth_year":1265,"hoser_power_add":1,"member_type":"外曾孙"}]}"#,
+            )
+            .unwrap(),
+        );
+        daughter.children[0].children.push(
+            serde_json::from_str(
+                r#"{"name":"王六","birth_year":1266,"hoser_power_add":1,"member_type":"外曾孙"}"#,
+            )
+            .unwrap(),
+        );
+
+        let head = tree.inherit("张丽").unwrap();
+        assert_eq!(
+            head.member_type,
+            MemberType::new(Generation::家主, Gender::Female, Lineage::Direct)
+        );
+        let labels: Vec<(&str, MemberType)> = head
+            .members()
+            .into_iter()
+            .skip(1)
+            .map(|m| (m.name.as_str(), m.member_type))
+            .collect();
+        let expected = [
+            ("王五", "儿"),
+            ("王六", "孙"),
+            ("王霞", "女儿"),
+            ("赵七", "外孙"),
+        ]
+        .map(|(name, label)| (name, member_type(label)));
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn label_changes_match_namesakes_by_id() {
+        let mut before = sample_with_twin();
+        before.fix_labels();
+        before.assign_ids(&mut 1);
+        let mut after = before.clone();
+        after.reparent("张丽/张三", "张二").unwrap();
+
+        let changes = before.label_changes(&after);
+        assert_eq!(
+            changes,
+            [Relabel {
+                name: "张三".to_string(),
+                from: member_type("外孙"),
+                to: member_type("孙"),
+            }]
+        );
+    }
+
+    #[test]
+    fn describe_relabels_groups_by_change() {
+        let change = |name: &str, from: &str, to: &str| Relabel {
+            name: name.to_string(),
+            from: member_type(from),
+            to: member_type(to),
+        };
+        assert_eq!(describe_relabels(&[]), "");
+
+        let mut changes = vec![change("王五", "外孙", "孙"), change("张三", "孙", "儿")];
+        changes.extend((0..RELABEL_LISTED).map(|i| change(&format!("甲{}", i), "外孙", "孙")));
+        let text = describe_relabels(&changes);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!(
+            "{} 名成员由 外孙 → 孙：【王五】",
+            RELABEL_LISTED + 1
+        )));
+        assert!(lines[0].ends_with('等'));
+        assert_eq!(lines[1], "1 名成员由 孙 → 儿：【张三】");
+    }
+
+    #[test]
+    fn builder_defaults_to_head_from_gender() {
+        let member = FamilyMember::builder()
+            .name("张三")
+            .birth_year(1990)
+            .gender(Gender::Male)
+            .build()
+            .unwrap();
+        assert_eq!(member.member_type, member_type("家主"));
+        assert_eq!(member.member_type.gender, Gender::Male);
+
+        let missing = FamilyMember::builder()
+            .name("张三")
+            .birth_year(1990)
+            .build();
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn builder_derives_type_from_parent() {
+        let member = FamilyMember::builder()
+            .name("张丽")
+            .birth_year(1990)
+            .gender(Gender::Female)
+            .child_of(&member_type("儿"))
+            .build()
+            .unwrap();
+        assert_eq!(member.member_type, member_type("孙女"));
+    }
+}
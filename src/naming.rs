@@ -0,0 +1,222 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::model::{FamilyMember, Generation};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 每代列出的常用字个数
+const TOP_CHARS: usize = 5;
+
+/// 疑似重复的两人出生年份最多相差的年数
+const MAX_BIRTH_GAP: i32 = 1;
+
+/// 取名统计
+///
+/// 由 [`analyze`] 汇总：各代名字（去掉首字的姓）中最常用的字，
+/// 以及只差一个字、出生年份相近、性别相同的疑似重复录入。
+/// 按字辈取名时同辈兄弟本就只差一字，因此还要求出生年份相近。
+#[derive(Debug, Clone, Default)]
+pub struct NameReport {
+    /// 按代际由近到远排列
+    pub generations: Vec<GenerationNames>,
+    pub near_duplicates: Vec<NearDuplicate>,
+}
+
+/// 一代成员的常用字
+#[derive(Debug, Clone)]
+pub struct GenerationNames {
+    /// 代际名称，如 `孙`
+    pub generation: String,
+    pub members: usize,
+    /// 常用字及出现次数，按次数降序，至多 [`TOP_CHARS`] 个
+    pub top_chars: Vec<(char, usize)>,
+}
+
+/// 疑似重复录入的两名成员
+#[derive(Debug, Clone)]
+pub struct NearDuplicate {
+    /// 建议保留的成员：子孙与任职记录较多者，相同时取先录入（先序在前）者
+    pub keep: String,
+    /// 建议并入 `keep` 后移除的成员
+    pub duplicate: String,
+    /// 两人的出生年份，顺序同上
+    pub birth_years: (i32, i32),
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl NameReport {
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "各代常用字（名字去掉首字的姓）：").unwrap();
+        for generation in &self.generations {
+            let chars: Vec<String> = generation
+                .top_chars
+                .iter()
+                .map(|(c, n)| format!("{}×{}", c, n))
+                .collect();
+            writeln!(
+                out,
+                "  {}（{} 人）：{}",
+                generation.generation,
+                generation.members,
+                if chars.is_empty() {
+                    "-".to_string()
+                } else {
+                    chars.join("  ")
+                }
+            )
+            .unwrap();
+        }
+
+        if self.near_duplicates.is_empty() {
+            writeln!(out, "✅ 未发现只差一字的疑似重复").unwrap();
+            return out;
+        }
+        writeln!(
+            out,
+            "⚠️  {} 对疑似重复（只差一字、出生年份相近、性别相同），可能是录入错误：",
+            self.near_duplicates.len()
+        )
+        .unwrap();
+        for pair in &self.near_duplicates {
+            writeln!(
+                out,
+                "  【{}】({}) ≈ 【{}】({})  确认重复可执行：merge-members {} {}",
+                pair.keep,
+                pair.birth_years.0,
+                pair.duplicate,
+                pair.birth_years.1,
+                pair.keep,
+                pair.duplicate
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 汇总以 `tree` 为根的整棵子树的取名情况
+pub fn analyze(tree: &FamilyMember) -> NameReport {
+    let members = tree.members();
+
+    let mut generations: Vec<(Generation, usize, HashMap<char, usize>)> = Vec::new();
+    for m in &members {
+        let generation = m.member_type.generation;
+        let index = match generations.iter().position(|(g, _, _)| *g == generation) {
+            Some(i) => i,
+            None => {
+                generations.push((generation, 0, HashMap::new()));
+                generations.len() - 1
+            }
+        };
+        let (_, count, chars) = &mut generations[index];
+        *count += 1;
+        for c in m.name.chars().skip(1) {
+            *chars.entry(c).or_insert(0) += 1;
+        }
+    }
+    generations.sort_by_key(|(g, _, _)| *g);
+
+    let generations = generations
+        .into_iter()
+        .map(|(generation, members, chars)| {
+            let mut top_chars: Vec<(char, usize)> = chars.into_iter().collect();
+            top_chars.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            top_chars.truncate(TOP_CHARS);
+            GenerationNames {
+                generation: format!("{:?}", generation),
+                members,
+                top_chars,
+            }
+        })
+        .collect();
+
+    NameReport {
+        generations,
+        near_duplicates: near_duplicates(&members),
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 找出只差一个字（替换、多一字或少一字）的疑似重复
+///
+/// 以"把某一位换成占位符"和"删去某一位"得到的变体分桶，
+/// 只比较同一桶内的成员，避免两两比较。
+fn near_duplicates(members: &[&FamilyMember]) -> Vec<NearDuplicate> {
+    const WILDCARD: char = '\u{0}';
+
+    let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+    // 完整姓名按"删去一位"的桶登记，与长一字的姓名配对
+    let mut deletions: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, m) in members.iter().enumerate() {
+        let chars: Vec<char> = m.name.chars().collect();
+        for i in 0..chars.len() {
+            let mut replaced = chars.clone();
+            replaced[i] = WILDCARD;
+            buckets
+                .entry(replaced.into_iter().collect())
+                .or_default()
+                .push(index);
+
+            let mut removed = chars.clone();
+            removed.remove(i);
+            deletions
+                .entry(removed.into_iter().collect())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut pairs: BTreeSet<(usize, usize)> = BTreeSet::new();
+    for group in buckets.values() {
+        for (i, &a) in group.iter().enumerate() {
+            for &b in &group[i + 1..] {
+                pairs.insert((a.min(b), a.max(b)));
+            }
+        }
+    }
+    for (index, m) in members.iter().enumerate() {
+        for &longer in deletions.get(&m.name).into_iter().flatten() {
+            pairs.insert((index.min(longer), index.max(longer)));
+        }
+    }
+
+    pairs
+        .into_iter()
+        .filter(|&(a, b)| {
+            let (a, b) = (members[a], members[b]);
+            (a.birth_year - b.birth_year).abs() <= MAX_BIRTH_GAP
+                && a.member_type.gender == b.member_type.gender
+                // 一方是另一方的祖先时不可能是同一人
+                && !a.exists(&b.name)
+                && !b.exists(&a.name)
+        })
+        .map(|(a, b)| {
+            let weight = |m: &FamilyMember| (m.total_count(), m.position_history.len());
+            // 先序在前者（a）权重不低于 b 时保留 a
+            let (keep, duplicate) = if weight(members[b]) > weight(members[a]) {
+                (members[b], members[a])
+            } else {
+                (members[a], members[b])
+            };
+            NearDuplicate {
+                keep: keep.name.clone(),
+                duplicate: duplicate.name.clone(),
+                birth_years: (keep.birth_year, duplicate.birth_year),
+            }
+        })
+        .collect()
+}
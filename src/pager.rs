@@ -0,0 +1,144 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::record;
+
+/// 输出长文本，超过一屏时分页
+///
+/// 仅在 `enabled`（交互模式且 `config pager` 开启）、标准输出是终端、
+/// 且行数超过终端高度时分页：设置了 `$PAGER` 时交给它显示，
+/// 否则（或启动失败时）用内置的逐屏显示。其余情况与 `print!` 相同。
+/// 文本须已渲染为字符串，原样输出（不做主题替换），会话录制照常生效。
+pub fn page(text: &str, enabled: bool) {
+    if !record::tee(text, "") {
+        return;
+    }
+    let height = match terminal_height() {
+        Some(height) if enabled && io::stdout().is_terminal() => height,
+        _ => {
+            print_raw(text);
+            return;
+        }
+    };
+    if text.lines().count() < height {
+        print_raw(text);
+        return;
+    }
+
+    let pager = env::var("PAGER").ok().filter(|p| !p.trim().is_empty());
+    if let Some(pager) = pager {
+        match external(&pager, text) {
+            Ok(()) => return,
+            Err(e) => statusln!("⚠️  {}，改用内置分页", e),
+        }
+    }
+    builtin(text, height);
+}
+
+/// 输出到终端时的终端宽度（优先取环境变量 `COLUMNS`），供表格收窄；
+/// 标准输出不是终端时为 `None`
+pub fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size("COLUMNS", 1)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn print_raw(text: &str) {
+    std::print!("{}", text);
+    io::stdout().flush().ok();
+}
+
+/// 终端行数：优先取环境变量 `LINES`，否则询问 `stty size`
+fn terminal_height() -> Option<usize> {
+    terminal_size("LINES", 0)
+}
+
+/// 环境变量给出的尺寸，或 `stty size` 输出（`行 列`）中的第 `field` 项
+fn terminal_size(key: &str, field: usize) -> Option<usize> {
+    if let Some(n) = env::var(key).ok().and_then(|v| v.trim().parse().ok()) {
+        return Some(n).filter(|n| *n > 0);
+    }
+    let tty = File::open("/dev/tty").ok()?;
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(tty)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(field)?
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+}
+
+/// 交给外部分页程序显示，命令可带参数，如 `less -R`
+fn external(pager: &str, text: &str) -> Result<(), String> {
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut command = Command::new(program);
+    // 与 git 相同，未设置 LESS 时让 less 保留颜色
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+    let mut child = command
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法启动分页程序 {}：{}", pager, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 用户提前退出分页程序时管道断开，不算出错
+        stdin.write_all(text.as_bytes()).ok();
+    }
+    child
+        .wait()
+        .map_err(|e| format!("分页程序 {} 异常退出：{}", pager, e))?;
+    Ok(())
+}
+
+/// 内置分页：每次显示一屏，回车显示下一屏，a 显示其余全部，q 结束
+fn builtin(text: &str, height: usize) {
+    let lines: Vec<&str> = text.lines().collect();
+    // 留一行给提示
+    let screen = height.saturating_sub(1).max(1);
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + screen).min(lines.len());
+        for line in &lines[start..end] {
+            std::println!("{}", line);
+        }
+        if end == lines.len() {
+            break;
+        }
+        std::print!(
+            "-- 第 {}–{} 行，共 {} 行（回车下一屏，a 其余全部，q 结束）-- ",
+            start + 1,
+            end,
+            lines.len()
+        );
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            std::println!();
+            break;
+        }
+        match input.trim() {
+            "q" => break,
+            "a" => {
+                for line in &lines[end..] {
+                    std::println!("{}", line);
+                }
+                break;
+            }
+            _ => start = end,
+        }
+    }
+}
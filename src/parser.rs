@@ -0,0 +1,152 @@
+/// 将命令行拆分为参数
+///
+/// 规则与常见 shell 一致：
+/// - 空白（含全角空格）分隔参数；
+/// - 单引号内的内容按原样保留；
+/// - 双引号（或中文引号 “”）内可用 `\"`、`\\` 转义；
+/// - 引号外的 `\` 转义下一个字符（如 `\ ` 表示空格）。
+///
+/// 引号紧贴其他字符时合并为同一参数，如 `a"b c"` 得到 `ab c`。
+///
+/// # Returns
+/// 引号未闭合或行尾为单独的 `\` 时返回错误。
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    // 当前参数是否已开始（用于保留 "" 这样的空参数）
+    let mut in_token = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("单引号未闭合".to_string()),
+                    }
+                }
+            }
+            '"' | '“' => {
+                in_token = true;
+                let close = if c == '"' { '"' } else { '”' };
+                loop {
+                    match chars.next() {
+                        Some(c) if c == close => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '”')) => current.push(escaped),
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err("双引号未闭合".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("双引号未闭合".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_token = true;
+                match chars.next() {
+                    Some(escaped) => current.push(escaped),
+                    None => return Err("行尾的 \\ 后缺少字符".to_string()),
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_token {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+/// 将参数加上必要的引号，使 [`tokenize`] 还原出同一个参数
+///
+/// 不含空白、引号与 `\` 的参数原样返回；其余用单引号包裹，
+/// 参数中的单引号写作 `'\''`（闭合、转义、重新打开）。
+pub fn quote(token: &str) -> String {
+    let special = |c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '“' | '\\');
+    if !token.is_empty() && !token.chars().any(special) {
+        return token.to_string();
+    }
+    format!("'{}'", token.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &str) -> Vec<String> {
+        tokenize(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_ascii_and_fullwidth_spaces() {
+        assert_eq!(tokens("position 张三  将军"), ["position", "张三", "将军"]);
+        assert_eq!(tokens("show\u{3000}张三"), ["show", "张三"]);
+    }
+
+    #[test]
+    fn keeps_quoted_cjk_names_together() {
+        assert_eq!(
+            tokens(r#"position 张三 "镇西 大将军""#),
+            ["position", "张三", "镇西 大将军"]
+        );
+        assert_eq!(
+            tokens("rename '欧阳 小明' 欧阳明"),
+            ["rename", "欧阳 小明", "欧阳明"]
+        );
+        assert_eq!(
+            tokens("position 张三 “镇西 大将军”"),
+            ["position", "张三", "镇西 大将军"]
+        );
+        assert_eq!(tokens(r#"a"b c"d"#), ["ab cd"]);
+        assert_eq!(tokens(r#"meta notes """#), ["meta", "notes", ""]);
+    }
+
+    #[test]
+    fn handles_escapes() {
+        assert_eq!(tokens(r#""说\"书\"人""#), [r#"说"书"人"#]);
+        assert_eq!(tokens(r#""a\\b""#), [r"a\b"]);
+        assert_eq!(tokens(r#""a\nb""#), [r"a\nb"]);
+        assert_eq!(tokens(r"镇西\ 将军"), ["镇西 将军"]);
+        assert_eq!(tokens(r"'a\b'"), [r"a\b"]);
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(tokenize("rename '张三").is_err());
+        assert!(tokenize(r#"rename "张三"#).is_err());
+        assert!(tokenize("rename “张三").is_err());
+        assert!(tokenize(r#""张三\"#).is_err());
+        assert!(tokenize(r"张三\").is_err());
+    }
+
+    #[test]
+    fn empty_input_has_no_tokens() {
+        assert!(tokens("").is_empty());
+        assert!(tokens("   \t ").is_empty());
+    }
+
+    #[test]
+    fn quote_round_trips() {
+        for token in ["张三", "镇西 大将军", "", "it's", r#"a"b\c"#, "“引”"] {
+            assert_eq!(tokens(&quote(token)), [token]);
+        }
+    }
+}
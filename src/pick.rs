@@ -0,0 +1,339 @@
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::model::{FamilyMember, Gender, Generation, Lineage, MemberType};
+use crate::render;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 抽签的权重方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weight {
+    /// 人人相同
+    #[default]
+    Uniform,
+    /// 按威望加成，不大于 0 者不参与
+    Power,
+    /// 按当前年份的年龄，需设置年份
+    Age,
+}
+
+/// 候选成员须满足的一个条件，多个条件同时满足
+///
+/// 写法：`alive`、`dead`、`male`、`female`、`direct`、`foreign`、
+/// `gen=<代际>`（如 `gen=孙`）、`under=<姓名>`（该成员的后代，不含本人）、
+/// `age>=<岁>`、`age<=<岁>`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Alive(bool),
+    Gender(Gender),
+    Lineage(Lineage),
+    Generation(Generation),
+    Under(String),
+    MinAge(i32),
+    MaxAge(i32),
+}
+
+/// 一名候选成员及其权重
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub name: String,
+    pub weight: u64,
+}
+
+/// 抽签结果
+///
+/// 由 [`pick`] 给出，列出全部候选、各自的权重与抽中的点数，便于核对。
+#[derive(Debug, Clone)]
+pub struct Pick {
+    /// 实际生效的条件（含默认的"在世"）
+    pub conditions: Vec<Condition>,
+    pub weight: Weight,
+    pub seed: u64,
+    /// 权重大于 0 的候选，按先序排列
+    pub candidates: Vec<Candidate>,
+    /// 满足条件但权重为 0、不参与抽签的成员
+    pub excluded: Vec<String>,
+    /// 抽中的点数，落在 `[0, 总权重)` 内
+    pub roll: u64,
+    /// 抽中的候选在 `candidates` 中的下标
+    pub chosen: usize,
+}
+
+/// xorshift 伪随机数，相同种子得到相同序列
+struct Rng(u64);
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Condition {
+    fn matches(&self, tree: &FamilyMember, member: &FamilyMember, year: Option<i32>) -> bool {
+        let age = || year.map(|y| y - member.birth_year);
+        match self {
+            Condition::Alive(alive) => member.is_dead != *alive,
+            Condition::Gender(gender) => member.member_type.gender == *gender,
+            Condition::Lineage(lineage) => member.member_type.lineage == *lineage,
+            Condition::Generation(generation) => member.member_type.generation == *generation,
+            Condition::Under(name) => tree
+                .find_member_by_name(name)
+                .is_some_and(|m| m.name != member.name && m.exists(&member.name)),
+            Condition::MinAge(min) => age().is_some_and(|a| a >= *min),
+            Condition::MaxAge(max) => age().is_some_and(|a| a <= *max),
+        }
+    }
+}
+
+impl Pick {
+    pub fn total_weight(&self) -> u64 {
+        self.candidates.iter().map(|c| c.weight).sum()
+    }
+
+    pub fn chosen(&self) -> &Candidate {
+        &self.candidates[self.chosen]
+    }
+
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let total = self.total_weight();
+        let conditions: Vec<String> = self.conditions.iter().map(|c| c.to_string()).collect();
+        writeln!(
+            out,
+            "候选 {} 人（条件：{}；权重：{}）：",
+            self.candidates.len(),
+            conditions.join("、"),
+            self.weight
+        )
+        .unwrap();
+
+        let mut start = 0;
+        for (i, c) in self.candidates.iter().enumerate() {
+            writeln!(
+                out,
+                "  {} {}权重 {:<6}点数 [{}, {})  概率 {:.1}%",
+                if i == self.chosen { "▶" } else { " " },
+                render::pad(&c.name, 12),
+                c.weight,
+                start,
+                start + c.weight,
+                c.weight as f64 * 100.0 / total as f64
+            )
+            .unwrap();
+            start += c.weight;
+        }
+        if !self.excluded.is_empty() {
+            writeln!(
+                out,
+                "  权重为 0、不参与：{}",
+                self.excluded
+                    .iter()
+                    .map(|n| format!("【{}】", n))
+                    .collect::<String>()
+            )
+            .unwrap();
+        }
+
+        let chosen = self.chosen();
+        writeln!(
+            out,
+            "🎲 种子 {}，点数 {}（共 {}）→ 抽中【{}】，中签概率 {:.1}%",
+            self.seed,
+            self.roll,
+            total,
+            chosen.name,
+            chosen.weight as f64 * 100.0 / total as f64
+        )
+        .unwrap();
+        out
+    }
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+        // xorshift 的状态不能为 0
+        match seed ^ MIX {
+            0 => Rng(MIX),
+            state => Rng(state),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FromStr for Weight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Weight::Uniform),
+            "power" => Ok(Weight::Power),
+            "age" => Ok(Weight::Age),
+            _ => Err(format!("未知权重【{}】，可选：power, age, uniform", s)),
+        }
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Weight::Uniform => write!(f, "均等"),
+            Weight::Power => write!(f, "威望"),
+            Weight::Age => write!(f, "年龄"),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let age = |v: &str| {
+            v.trim()
+                .parse::<i32>()
+                .map_err(|_| format!("条件【{}】中的年龄应为整数", s))
+        };
+        match s {
+            "alive" => return Ok(Condition::Alive(true)),
+            "dead" => return Ok(Condition::Alive(false)),
+            "male" => return Ok(Condition::Gender(Gender::Male)),
+            "female" => return Ok(Condition::Gender(Gender::Female)),
+            "direct" => return Ok(Condition::Lineage(Lineage::Direct)),
+            "foreign" => return Ok(Condition::Lineage(Lineage::Foreign)),
+            _ => {}
+        }
+        if let Some(v) = s.strip_prefix("age>=") {
+            return Ok(Condition::MinAge(age(v)?));
+        }
+        if let Some(v) = s.strip_prefix("age<=") {
+            return Ok(Condition::MaxAge(age(v)?));
+        }
+        if let Some(name) = s.strip_prefix("under=").filter(|n| !n.is_empty()) {
+            return Ok(Condition::Under(name.to_string()));
+        }
+        if let Some(v) = s.strip_prefix("gen=") {
+            let generation = v.parse::<MemberType>()?.generation;
+            if generation == Generation::其他 {
+                return Err(format!("无法识别的代际【{}】，如 gen=儿、gen=孙", v));
+            }
+            return Ok(Condition::Generation(generation));
+        }
+        Err(format!(
+            "无法识别的条件【{}】，可用：alive, dead, male, female, direct, foreign, \
+             gen=<代际>, under=<姓名>, age>=<岁>, age<=<岁>",
+            s
+        ))
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Alive(true) => write!(f, "在世"),
+            Condition::Alive(false) => write!(f, "已故"),
+            Condition::Gender(Gender::Male) => write!(f, "男"),
+            Condition::Gender(Gender::Female) => write!(f, "女"),
+            Condition::Lineage(Lineage::Direct) => write!(f, "内系"),
+            Condition::Lineage(Lineage::Foreign) => write!(f, "外系"),
+            Condition::Generation(generation) => write!(f, "{:?}辈", generation),
+            Condition::Under(name) => write!(f, "【{}】的后代", name),
+            Condition::MinAge(min) => write!(f, "年满 {} 岁", min),
+            Condition::MaxAge(max) => write!(f, "不超过 {} 岁", max),
+        }
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 从满足全部条件的成员中按权重随机抽取一人
+///
+/// 条件中未指定在世或已故时只抽在世成员。按年龄加权或按年龄筛选时需给出 `year`。
+/// 相同的数据、条件、权重与种子总是抽中同一人。
+pub fn pick(
+    tree: &FamilyMember,
+    conditions: &[Condition],
+    weight: Weight,
+    year: Option<i32>,
+    seed: u64,
+) -> Result<Pick, String> {
+    let mut conditions = conditions.to_vec();
+    if !conditions.iter().any(|c| matches!(c, Condition::Alive(_))) {
+        conditions.insert(0, Condition::Alive(true));
+    }
+    let needs_year = weight == Weight::Age
+        || conditions
+            .iter()
+            .any(|c| matches!(c, Condition::MinAge(_) | Condition::MaxAge(_)));
+    if needs_year && year.is_none() {
+        return Err("按年龄加权或筛选需先设置年份：year <年份>".to_string());
+    }
+    for c in &conditions {
+        if let Condition::Under(name) = c {
+            if !tree.exists(name) {
+                return Err(format!("成员【{}】不存在", name));
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let mut excluded = Vec::new();
+    for member in tree.members() {
+        if !conditions.iter().all(|c| c.matches(tree, member, year)) {
+            continue;
+        }
+        let w = match weight {
+            Weight::Uniform => 1,
+            Weight::Power => member.hoser_power_add.max(0) as u64,
+            Weight::Age => year.map_or(0, |y| (y - member.birth_year).max(0) as u64),
+        };
+        if w == 0 {
+            excluded.push(member.name.clone());
+        } else {
+            candidates.push(Candidate {
+                name: member.name.clone(),
+                weight: w,
+            });
+        }
+    }
+    if candidates.is_empty() {
+        return Err(if excluded.is_empty() {
+            "没有满足条件的成员".to_string()
+        } else {
+            format!("满足条件的 {} 人权重均为 0，无法抽签", excluded.len())
+        });
+    }
+
+    let total: u64 = candidates.iter().map(|c| c.weight).sum();
+    let roll = Rng::new(seed).next() % total;
+    let mut start = 0;
+    let chosen = candidates
+        .iter()
+        .position(|c| {
+            start += c.weight;
+            roll < start
+        })
+        .expect("点数小于总权重");
+
+    Ok(Pick {
+        conditions,
+        weight,
+        seed,
+        candidates,
+        excluded,
+        roll,
+        chosen,
+    })
+}
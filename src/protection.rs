@@ -0,0 +1,402 @@
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::model::{FamilyMember, ID_PREFIX};
+
+/// 对家主执行某项操作时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Guard {
+    /// 直接执行
+    Allow,
+    /// 执行并提示后续操作（死亡后继承、改名后更新族名）
+    Prompt,
+    /// 拒绝执行
+    Refuse,
+}
+
+/// 受保护的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootAction {
+    Die,
+    Rename,
+}
+
+/// 家主保护策略
+///
+/// 规定 `die`、`rename` 作用在当前家主身上时的处理方式，对其他成员不生效。
+/// 家主不可删除（家族树必须有家主，更换家主应使用 `inherit`），不在此配置。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootProtection {
+    #[serde(default = "default_guard")]
+    pub die: Guard,
+    #[serde(default = "default_guard")]
+    pub rename: Guard,
+}
+
+fn default_guard() -> Guard {
+    Guard::Prompt
+}
+
+impl Default for RootProtection {
+    fn default() -> Self {
+        RootProtection {
+            die: default_guard(),
+            rename: default_guard(),
+        }
+    }
+}
+
+/// 锁定的分支
+///
+/// 以分支顶端成员的 ID 记录，不受重名与改名影响；整支（含子孙）只读：改动其中任何成员、
+/// 把该支移走或删除的命令都会被拒绝，命令加 `--unlock` 时放行。
+/// 称谓随家主变化推算，继位时改动称谓不算改动分支。
+///
+/// 格式 4 及以前按姓名记录，读取后由 [`resolve_names`](Self::resolve_names) 换成 ID。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct BranchLocks {
+    ids: BTreeSet<u64>,
+    /// 旧数据文件中按姓名记录、尚未换成 ID 的分支
+    #[serde(skip)]
+    names: Vec<String>,
+}
+
+impl fmt::Display for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Guard::Allow => "allow",
+            Guard::Prompt => "prompt",
+            Guard::Refuse => "refuse",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl RootProtection {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 对 `name` 执行 `action` 时的处理方式
+    ///
+    /// `name` 不是家主（`tree` 的根）时总是 [`Guard::Allow`]。
+    pub fn guard(&self, action: RootAction, tree: &FamilyMember, name: &str) -> Guard {
+        if !tree.is_root(name) {
+            return Guard::Allow;
+        }
+
+        match action {
+            RootAction::Die => self.die,
+            RootAction::Rename => self.rename,
+        }
+    }
+
+    /// 设置策略
+    ///
+    /// # param
+    /// * `key` - `die` 或 `rename`
+    /// * `value` - `allow`、`prompt` 或 `refuse`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let guard = match value {
+            "allow" => Guard::Allow,
+            "prompt" => Guard::Prompt,
+            "refuse" => Guard::Refuse,
+            _ => {
+                return Err(format!(
+                    "未知策略【{}】，可选：allow, prompt, refuse",
+                    value
+                ))
+            }
+        };
+
+        match key {
+            "die" => self.die = guard,
+            "rename" => self.rename = guard,
+            "delete" => return Err("家主不可删除，该项不可配置".to_string()),
+            _ => return Err(format!("未知项【{}】，可选：die, rename", key)),
+        }
+        Ok(())
+    }
+
+    /// 当前策略的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "家主死亡（die）：{}", self.die).unwrap();
+        writeln!(out, "家主改名（rename）：{}", self.rename).unwrap();
+        writeln!(out, "删除家主（delete）：refuse（不可配置）").unwrap();
+        out
+    }
+}
+
+impl<'de> Deserialize<'de> for BranchLocks {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoredLock {
+            Id(u64),
+            Name(String),
+        }
+
+        let mut locks = BranchLocks::default();
+        for lock in Vec::<StoredLock>::deserialize(deserializer)? {
+            match lock {
+                StoredLock::Id(id) => {
+                    locks.ids.insert(id);
+                }
+                StoredLock::Name(name) => locks.names.push(name),
+            }
+        }
+        Ok(locks)
+    }
+}
+
+impl BranchLocks {
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.names.is_empty()
+    }
+
+    /// 锁定以该 ID 的成员为顶端的分支，已锁定时返回 `false`
+    pub fn lock(&mut self, id: u64) -> bool {
+        self.ids.insert(id)
+    }
+
+    /// 解除锁定，未锁定时返回 `false`
+    pub fn unlock(&mut self, id: u64) -> bool {
+        self.ids.remove(&id)
+    }
+
+    /// 将旧数据文件中按姓名记录的分支换成顶端成员的 ID
+    ///
+    /// 重名时与旧版一样取先序第一处；已不在家族树中的丢弃。
+    pub fn resolve_names(&mut self, tree: &FamilyMember) {
+        for name in std::mem::take(&mut self.names) {
+            if let Some(id) = tree.find_member_by_name(&name).and_then(|m| m.id) {
+                self.ids.insert(id);
+            }
+        }
+    }
+
+    /// 去掉已不在家族树中的分支
+    pub fn retain_existing(&mut self, tree: &FamilyMember) {
+        self.ids.retain(|&id| tree.find_member_by_id(id).is_some());
+    }
+
+    /// `name` 所在的锁定分支的顶端成员（离该成员最近的一支）
+    pub fn covering<'a>(&self, tree: &'a FamilyMember, name: &str) -> Option<&'a FamilyMember> {
+        tree.path_to(name)?
+            .into_iter()
+            .rev()
+            .find(|m| m.id.is_some_and(|id| self.ids.contains(&id)))
+    }
+
+    /// 从 `before` 到 `after` 被改动的锁定分支，给出其顶端成员改动前的姓名
+    ///
+    /// 分支内容（除称谓外）、所在位置有任何变化，或分支已不在树中，都算改动。
+    pub fn violations(&self, before: &FamilyMember, after: &FamilyMember) -> Vec<String> {
+        self.ids
+            .iter()
+            .filter(|&&id| match snapshot(before, id) {
+                Some(old) => snapshot(after, id) != Some(old),
+                None => false,
+            })
+            .filter_map(|&id| before.find_member_by_id(id).map(|m| m.name.clone()))
+            .collect()
+    }
+
+    /// 命令因改动锁定分支被拒绝时的说明
+    pub fn refusal(names: &[String]) -> String {
+        let names = names
+            .iter()
+            .map(|n| format!("【{}】", n))
+            .collect::<Vec<_>>()
+            .join("、");
+        format!(
+            "{}一支已锁定，命令未执行；确需改动请在命令末尾加 --unlock，或先 unlock <姓名>",
+            names
+        )
+    }
+
+    /// 锁定分支的文字描述
+    pub fn describe(&self, tree: &FamilyMember) -> String {
+        if self.ids.is_empty() {
+            return "没有锁定的分支\n".to_string();
+        }
+        let mut out = "🔒 锁定的分支：\n".to_string();
+        for &id in &self.ids {
+            match tree.find_member_by_id(id) {
+                Some(member) => writeln!(
+                    out,
+                    "  【{}】（{}{}）一支，共 {} 人",
+                    member.name,
+                    ID_PREFIX,
+                    id,
+                    member.total_count()
+                )
+                .unwrap(),
+                None => writeln!(out, "  {}{}（已不在家族树中）", ID_PREFIX, id).unwrap(),
+            }
+        }
+        out
+    }
+}
+
+/// 分支的父辈与去掉称谓后的内容，用于比较分支是否被改动
+fn snapshot(tree: &FamilyMember, id: u64) -> Option<(Option<u64>, Value)> {
+    let path = tree.path_to(&format!("{}{}", ID_PREFIX, id))?;
+    let parent = path.len().checked_sub(2).and_then(|i| path[i].id);
+    let mut value = serde_json::to_value(path.last()?).ok()?;
+    strip_member_type(&mut value);
+    Some((parent, value))
+}
+
+fn strip_member_type(value: &mut Value) {
+    if let Value::Object(map) = value {
+        map.remove("member_type");
+        if let Some(Value::Array(children)) = map.get_mut("children") {
+            children.iter_mut().for_each(strip_member_type);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+
+    #[test]
+    fn root_guard_applies_configured_policy_to_root() {
+        let tree = sample();
+        let mut protection = RootProtection::default();
+        assert_eq!(
+            protection.guard(RootAction::Die, &tree, "张大"),
+            Guard::Prompt
+        );
+        protection.set("die", "refuse").unwrap();
+        protection.set("rename", "refuse").unwrap();
+        assert_eq!(
+            protection.guard(RootAction::Die, &tree, "张大"),
+            Guard::Refuse
+        );
+        assert_eq!(
+            protection.guard(RootAction::Rename, &tree, "张大"),
+            Guard::Refuse
+        );
+    }
+
+    #[test]
+    fn root_guard_allows_other_members() {
+        let tree = sample();
+        let mut protection = RootProtection::default();
+        protection.set("die", "refuse").unwrap();
+        assert_eq!(
+            protection.guard(RootAction::Die, &tree, "张二"),
+            Guard::Allow
+        );
+        protection.set("die", "allow").unwrap();
+        assert_eq!(
+            protection.guard(RootAction::Die, &tree, "张大"),
+            Guard::Allow
+        );
+    }
+
+    #[test]
+    fn root_delete_is_not_configurable() {
+        assert!(RootProtection::default().set("delete", "allow").is_err());
+    }
+
+    /// 示例树按先序分配 ID：张大 #1、张二 #2、张三 #3、张小红 #4、李四 #5、张丽 #6、王五 #7
+    fn sample_with_ids() -> FamilyMember {
+        let mut tree = sample();
+        tree.assign_ids(&mut 1);
+        tree
+    }
+
+    #[test]
+    fn lock_reports_changes_inside_branch() {
+        let before = sample_with_ids();
+        let mut locks = BranchLocks::default();
+        assert!(locks.lock(4));
+        assert!(!locks.lock(4));
+
+        let mut after = before.clone();
+        after.find_member_by_name_mut("李四").unwrap().is_dead = true;
+        assert_eq!(locks.violations(&before, &after), ["张小红"]);
+
+        let mut after = before.clone();
+        after.reparent("张小红", "张丽").unwrap();
+        assert_eq!(locks.violations(&before, &after), ["张小红"]);
+
+        let mut after = before.clone();
+        after.remove("张小红");
+        assert_eq!(locks.violations(&before, &after), ["张小红"]);
+    }
+
+    #[test]
+    fn lock_allows_changes_elsewhere_and_relabels() {
+        let before = sample_with_ids();
+        let mut locks = BranchLocks::default();
+        locks.lock(4);
+
+        let mut after = before.clone();
+        after.find_member_by_name_mut("王五").unwrap().is_dead = true;
+        after.find_member_by_name_mut("李四").unwrap().member_type = "外孙".parse().unwrap();
+        assert!(locks.violations(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn lock_covering_and_unlock() {
+        let mut tree = sample_with_ids();
+        let mut locks = BranchLocks::default();
+        locks.lock(2);
+        assert_eq!(locks.covering(&tree, "李四").map(|m| m.id), Some(Some(2)));
+        assert!(locks.covering(&tree, "王五").is_none());
+        assert!(!locks.unlock(5));
+
+        // 改名后锁定仍在
+        tree.rename("张二", "张贰").unwrap();
+        locks.retain_existing(&tree);
+        assert_eq!(locks.covering(&tree, "李四").map(|m| m.id), Some(Some(2)));
+        assert!(locks.unlock(2));
+        assert!(locks.is_empty());
+    }
+
+    #[test]
+    fn lock_guards_the_chosen_namesake() {
+        let mut before = sample_with_ids();
+        let mut twin = before.find_member_by_name("张三").unwrap().clone();
+        twin.id = Some(8);
+        before
+            .find_member_by_name_mut("张丽")
+            .unwrap()
+            .children
+            .push(twin);
+        let mut locks = BranchLocks::default();
+        locks.lock(8);
+
+        let mut after = before.clone();
+        after.find_member_by_name_mut("张二/张三").unwrap().is_dead = true;
+        assert!(locks.violations(&before, &after).is_empty());
+
+        let mut after = before.clone();
+        after.find_member_by_name_mut("#8").unwrap().is_dead = true;
+        assert_eq!(locks.violations(&before, &after), ["张三"]);
+    }
+
+    #[test]
+    fn legacy_locks_resolve_names_to_ids() {
+        let tree = sample_with_ids();
+        let mut locks: BranchLocks = serde_json::from_str(r#"["张小红", "赵六", 7]"#).unwrap();
+        locks.resolve_names(&tree);
+        assert_eq!(locks.ids, BTreeSet::from([4, 7]));
+        assert!(locks.names.is_empty());
+        assert_eq!(serde_json::to_string(&locks).unwrap(), "[4,7]");
+    }
+}
@@ -0,0 +1,470 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 编译后的 JSONPath 查询
+///
+/// 支持的语法：
+/// - `$` 根节点，`.字段` / `['字段']` 取字段，`..字段` 递归查找；
+/// - `[n]` 取下标（负数从末尾数），`*` / `[*]` 取全部；
+/// - `[?(@.字段 运算符 值)]` 过滤，运算符为 `== != < <= > >=`，
+///   值可以是数字、带引号的字符串、`true`/`false`/`null`；
+///   省略运算符时判断字段是否存在且不为 `false`/`null`；
+///   多个条件可用 `&&`、`||` 连接（`&&` 优先）。
+#[derive(Debug)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug)]
+enum Step {
+    Field(String),
+    Index(i64),
+    Wildcard,
+    /// 展开为当前节点及其全部后代，再由下一步筛选
+    Descend,
+    /// 析取范式：任一组内条件全部成立即保留
+    Filter(Vec<Vec<Condition>>),
+}
+
+#[derive(Debug)]
+struct Condition {
+    /// `@` 之后的字段链
+    path: Vec<String>,
+    test: Option<(Op, Value)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// 查询路径的逐字符解析器
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    source: &'a str,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: source.trim().chars().collect(),
+            pos: 0,
+            source,
+        };
+        parser.expect('$')?;
+
+        let mut steps = Vec::new();
+        while let Some(c) = parser.peek() {
+            match c {
+                '.' => {
+                    parser.pos += 1;
+                    if parser.eat('.') {
+                        steps.push(Step::Descend);
+                        if parser.peek() == Some('[') {
+                            continue;
+                        }
+                    }
+                    if parser.eat('*') {
+                        steps.push(Step::Wildcard);
+                    } else {
+                        steps.push(Step::Field(parser.identifier()?));
+                    }
+                }
+                '[' => {
+                    parser.pos += 1;
+                    steps.push(parser.bracket()?);
+                    parser.expect(']')?;
+                }
+                _ => return Err(parser.error("应为 . 或 [")),
+            }
+        }
+
+        Ok(Query { steps })
+    }
+
+    /// 在 JSON 上执行查询
+    ///
+    /// # Returns
+    /// 按文档顺序排列的匹配节点。
+    pub fn eval<'v>(&self, root: &'v Value) -> Vec<&'v Value> {
+        let mut current = vec![root];
+
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for value in current {
+                match step {
+                    Step::Field(name) => next.extend(value.get(name)),
+                    Step::Index(i) => {
+                        if let Value::Array(items) = value {
+                            let index = if *i < 0 { items.len() as i64 + i } else { *i };
+                            if index >= 0 {
+                                next.extend(items.get(index as usize));
+                            }
+                        }
+                    }
+                    Step::Wildcard => next.extend(children(value)),
+                    Step::Descend => collect_descendants(value, &mut next),
+                    Step::Filter(groups) => next.extend(
+                        children(value)
+                            .filter(|item| groups.iter().any(|g| g.iter().all(|c| c.test(item)))),
+                    ),
+                }
+            }
+            current = next;
+        }
+
+        current
+    }
+}
+
+impl Condition {
+    fn test(&self, item: &Value) -> bool {
+        let mut target = Some(item);
+        for field in &self.path {
+            target = target.and_then(|v| v.get(field));
+        }
+
+        match (&self.test, target) {
+            (None, Some(v)) => !matches!(v, Value::Null | Value::Bool(false)),
+            (None, None) => false,
+            (Some((op, expected)), Some(actual)) => {
+                let ordering = compare(actual, expected);
+                match op {
+                    Op::Eq => ordering == Some(Ordering::Equal),
+                    Op::Ne => ordering != Some(Ordering::Equal),
+                    Op::Lt => ordering == Some(Ordering::Less),
+                    Op::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Op::Gt => ordering == Some(Ordering::Greater),
+                    Op::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+            // 字段不存在时只有 != 成立
+            (Some((op, _)), None) => matches!(op, Op::Ne),
+        }
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let len = s.chars().count();
+        if self.chars[self.pos..]
+            .iter()
+            .take(len)
+            .copied()
+            .eq(s.chars())
+        {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("应为 {}", c)))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!(
+            "查询【{}】第 {} 个字符处{}",
+            self.source,
+            self.pos + 1,
+            message
+        )
+    }
+
+    /// 字段名：字母、数字、下划线或任意非 ASCII 字符
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || !c.is_ascii())
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("应为字段名"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// `'...'` 或 `"..."`
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or_else(|| self.error("应为引号"))?;
+        self.pos += 1;
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != quote) {
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().collect();
+        self.expect(quote)?;
+        Ok(text)
+    }
+
+    /// `[` 与 `]` 之间的内容
+    fn bracket(&mut self) -> Result<Step, String> {
+        self.skip_spaces();
+        let step = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Step::Wildcard
+            }
+            Some('\'' | '"') => Step::Field(self.quoted()?),
+            Some('?') => {
+                self.pos += 1;
+                self.expect('(')?;
+                let groups = self.filter()?;
+                self.expect(')')?;
+                Step::Filter(groups)
+            }
+            _ => {
+                let start = self.pos;
+                self.eat('-');
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                Step::Index(
+                    text.parse()
+                        .map_err(|_| self.error("应为下标、* 或过滤条件"))?,
+                )
+            }
+        };
+        self.skip_spaces();
+        Ok(step)
+    }
+
+    fn filter(&mut self) -> Result<Vec<Vec<Condition>>, String> {
+        let mut groups = vec![vec![self.condition()?]];
+        loop {
+            self.skip_spaces();
+            if self.eat_str("&&") {
+                groups.last_mut().unwrap().push(self.condition()?);
+            } else if self.eat_str("||") {
+                groups.push(vec![self.condition()?]);
+            } else {
+                return Ok(groups);
+            }
+        }
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        self.skip_spaces();
+        self.expect('@')?;
+
+        let mut path = Vec::new();
+        loop {
+            if self.eat('.') {
+                path.push(self.identifier()?);
+            } else if self.peek() == Some('[') {
+                self.pos += 1;
+                path.push(self.quoted()?);
+                self.expect(']')?;
+            } else {
+                break;
+            }
+        }
+
+        self.skip_spaces();
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .into_iter()
+        .find(|(s, _)| self.eat_str(s))
+        .map(|(_, op)| op);
+
+        let test = match op {
+            Some(op) => {
+                self.skip_spaces();
+                Some((op, self.literal()?))
+            }
+            None => None,
+        };
+
+        Ok(Condition { path, test })
+    }
+
+    fn literal(&mut self) -> Result<Value, String> {
+        if matches!(self.peek(), Some('\'' | '"')) {
+            return Ok(Value::String(self.quoted()?));
+        }
+        for (word, value) in [
+            ("true", Value::Bool(true)),
+            ("false", Value::Bool(false)),
+            ("null", Value::Null),
+        ] {
+            if self.eat_str(word) {
+                return Ok(value);
+            }
+        }
+
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | 'e' | 'E' | '+'))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        serde_json::from_str::<serde_json::Number>(&text)
+            .map(Value::Number)
+            .map_err(|_| self.error("应为数字、字符串、true、false 或 null"))
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 数组元素或对象的字段值
+fn children(value: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match value {
+        Value::Array(items) => Box::new(items.iter()),
+        Value::Object(map) => Box::new(map.values()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// 先序收集节点自身及全部后代
+fn collect_descendants<'v>(value: &'v Value, out: &mut Vec<&'v Value>) {
+    out.push(value);
+    for child in children(value) {
+        collect_descendants(child, out);
+    }
+}
+
+/// 比较两个 JSON 值：数字按数值、字符串按字典序，其余只判断相等
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// 将查询结果渲染为表格
+///
+/// 结果为对象时，以各对象中的标量字段为列（嵌套的数组与对象显示为摘要）；
+/// 否则每个结果一行。
+pub fn render_table(results: &[&Value]) -> String {
+    let mut columns: Vec<&str> = Vec::new();
+    for value in results {
+        if let Value::Object(map) = value {
+            for key in map.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+
+    // 字段按字母序排列，姓名放在最前便于辨认
+    if let Some(i) = columns.iter().position(|c| *c == "name") {
+        let name = columns.remove(i);
+        columns.insert(0, name);
+    }
+
+    let rows: Vec<Vec<String>> = if columns.is_empty() {
+        columns.push("值");
+        results.iter().map(|v| vec![cell(v)]).collect()
+    } else {
+        results
+            .iter()
+            .map(|v| {
+                columns
+                    .iter()
+                    .map(|c| v.get(c).map(cell).unwrap_or_default())
+                    .collect()
+            })
+            .collect()
+    };
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            rows.iter()
+                .map(|r| r[i].width())
+                .chain([c.width()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(s, w)| format!("{}{}", s, " ".repeat(w - s.width())))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    out.push_str(&line(columns.clone()));
+    out.push('\n');
+    out.push_str(&"─".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1)));
+    out.push('\n');
+    for row in &rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+        out.push('\n');
+    }
+    out
+}
+
+/// 单元格文本
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        Value::Array(items) => format!("[{} 项]", items.len()),
+        Value::Object(_) => "{…}".to_string(),
+        other => other.to_string(),
+    }
+}
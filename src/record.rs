@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use zz_sim_data_tool::document::FamilyDocument;
+
+/// 会话录制文件的格式版本
+const RECORD_VERSION: u32 = 1;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 录制文件的首行：录制开始时的完整数据与会话状态，回放时据此重建会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    /// 录制时的程序版本
+    pub tool: String,
+    pub data_file: String,
+    pub current_year: Option<i32>,
+    /// 确认方式：ask、auto、yes、no
+    pub confirm: String,
+    pub doc: FamilyDocument,
+}
+
+/// 录制的一条命令，每条一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub command: String,
+    /// 命令执行中读取的输入（确认、补充的 JSON 等），按读取顺序
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input: Vec<String>,
+    /// 命令的输出（替换主题符号之前的原文）
+    pub output: String,
+    /// done、failed 或 exit
+    pub outcome: String,
+    /// 执行前后数据的哈希，见 [`tree_hash`]
+    pub before: String,
+    pub after: String,
+}
+
+/// 正在写入的录制文件
+///
+/// 每条命令执行完立即追加一行并刷新，程序中途崩溃时已执行的命令仍在文件中。
+pub struct Recorder {
+    file: File,
+    path: PathBuf,
+    steps: usize,
+}
+
+/// 当前线程的输出捕获与输入回放状态
+#[derive(Default)]
+struct Capture {
+    /// 捕获中的输出，`None` 表示未在捕获
+    output: Option<String>,
+    /// 捕获时不再打印到终端
+    mute: bool,
+    /// 捕获期间读取的输入
+    input: Vec<String>,
+    /// 回放时代替标准输入的应答，`None` 表示读取标准输入
+    replay: Option<VecDeque<String>>,
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Capture> = RefCell::new(Capture::default());
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Recorder {
+    /// 新建录制文件并写入首行，已存在的文件会被覆盖
+    pub fn start(path: &Path, header: &Header) -> Result<Self, String> {
+        let mut file =
+            File::create(path).map_err(|e| format!("创建 {} 失败：{}", path.display(), e))?;
+        write_line(&mut file, header)
+            .map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+        Ok(Recorder {
+            file,
+            path: path.to_path_buf(),
+            steps: 0,
+        })
+    }
+
+    pub fn append(&mut self, step: &Step) -> Result<(), String> {
+        write_line(&mut self.file, step)
+            .map_err(|e| format!("写入 {} 失败：{}", self.path.display(), e))?;
+        self.steps += 1;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 已录制的命令条数
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+impl Header {
+    pub fn new(
+        data_file: &str,
+        current_year: Option<i32>,
+        confirm: &str,
+        doc: &FamilyDocument,
+    ) -> Self {
+        Header {
+            version: RECORD_VERSION,
+            tool: env!("CARGO_PKG_VERSION").to_string(),
+            data_file: data_file.to_string(),
+            current_year,
+            confirm: confirm.to_string(),
+            doc: doc.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 读取录制文件
+pub fn load(path: &Path) -> Result<(Header, Vec<Step>), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取 {} 失败：{}", path.display(), e))?;
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let (_, first) = lines
+        .next()
+        .ok_or_else(|| format!("{} 是空文件", path.display()))?;
+    let header: Header = serde_json::from_str(first)
+        .map_err(|e| format!("{} 不是会话录制文件：{}", path.display(), e))?;
+    if header.version > RECORD_VERSION {
+        return Err(format!(
+            "{} 由较新版本（{}）录制，格式版本 {} 暂不支持",
+            path.display(),
+            header.tool,
+            header.version
+        ));
+    }
+    let steps = lines
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("{} 第 {} 行格式不正确：{}", path.display(), i + 1, e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((header, steps))
+}
+
+/// 数据的哈希（FNV-1a，按 JSON 序列化计算），不同版本与平台上结果相同
+pub fn tree_hash(doc: &FamilyDocument) -> String {
+    let bytes = serde_json::to_vec(doc).unwrap_or_default();
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// 开始捕获输出；`mute` 为 true 时捕获期间不打印到终端
+pub fn begin(mute: bool) {
+    CAPTURE.with(|c| {
+        let mut c = c.borrow_mut();
+        c.output = Some(String::new());
+        c.mute = mute;
+        c.input.clear();
+    });
+}
+
+/// 结束捕获，同时结束输入回放
+///
+/// # Returns
+/// 捕获的输出与期间读取的输入。
+pub fn finish() -> (String, Vec<String>) {
+    CAPTURE.with(|c| {
+        let mut c = c.borrow_mut();
+        c.mute = false;
+        c.replay = None;
+        (
+            c.output.take().unwrap_or_default(),
+            std::mem::take(&mut c.input),
+        )
+    })
+}
+
+/// 以录制的应答代替标准输入，直到 [`finish`]；应答用完后视为输入结束
+pub fn feed(input: Vec<String>) {
+    CAPTURE.with(|c| c.borrow_mut().replay = Some(input.into()));
+}
+
+/// 是否正在捕获输出（录制或回放中），此时不应输出随终端而变的内容
+pub fn capturing() -> bool {
+    CAPTURE.with(|c| c.borrow().output.is_some())
+}
+
+/// 输出宏的钩子：捕获中时记下原文
+///
+/// # Returns
+/// 是否仍应打印到终端。
+pub fn tee(text: &str, end: &str) -> bool {
+    CAPTURE.with(|c| {
+        let mut c = c.borrow_mut();
+        let mute = c.mute;
+        if let Some(output) = &mut c.output {
+            output.push_str(text);
+            output.push_str(end);
+            return !mute;
+        }
+        true
+    })
+}
+
+/// 读取一行输入，回放时取录制的应答，捕获中时记下读到的内容
+pub fn read_line(buf: &mut String) -> io::Result<usize> {
+    let replayed = CAPTURE.with(|c| {
+        c.borrow_mut()
+            .replay
+            .as_mut()
+            .map(|queue| queue.pop_front())
+    });
+    let read = match replayed {
+        Some(Some(line)) => {
+            buf.push_str(&line);
+            line.len()
+        }
+        Some(None) => 0,
+        None => {
+            let before = buf.len();
+            let n = io::stdin().lock().read_line(buf)?;
+            let line = buf[before..].to_string();
+            CAPTURE.with(|c| {
+                let mut c = c.borrow_mut();
+                if c.output.is_some() {
+                    c.input.push(line);
+                }
+            });
+            n
+        }
+    };
+    Ok(read)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn write_line<T: Serialize>(file: &mut File, value: &T) -> io::Result<()> {
+    let line = serde_json::to_string(value).map_err(io::Error::other)?;
+    writeln!(file, "{}", line)?;
+    file.flush()
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use zz_sim_data_tool::document::{FamilyDocument, TrashEntry};
+use zz_sim_data_tool::store::FamilyStore;
+use zz_sim_data_tool::verify;
+
+use crate::session::Confirm;
+
+/// 最多列出的差异条数
+const MAX_CHANGES: usize = 30;
+
+/// 继位失败或中断后的恢复向导
+///
+/// 找到最近一份可读取的归档（继位前的快照），说明归档、数据文件与内存中的数据
+/// 各处于继位前还是继位后，列出归档与当前数据的差异，确认后以归档替换内存中的数据。
+/// 替换可用 undo 撤销，save 后才写入数据文件。
+///
+/// 回收站随归档一并保存，默认恢复为归档时的状态，与归档的家族树一致。
+/// `keep_trash` 时另保留归档之后删除、且不在归档家族树中的成员，仍可在回收站中找到。
+///
+/// # param
+/// * `year` - 指定归档年份，省略时取最近一份
+/// * `pending` - 尚未写完的后台归档数，不为 0 时不做判断
+///
+/// # Returns
+/// 是否成功完成（包括无需恢复、选择保留当前数据）。
+pub fn run(
+    doc: &mut FamilyDocument,
+    store: &dyn FamilyStore,
+    year: Option<i32>,
+    pending: usize,
+    keep_trash: bool,
+    confirm: Confirm,
+) -> bool {
+    if pending > 0 {
+        statusln!(
+            "❌ 还有 {} 个归档正在后台写入，请稍后再执行 recover",
+            pending
+        );
+        return false;
+    }
+
+    let Some((year, mut archived)) = find_archive(store, year) else {
+        return false;
+    };
+    let data_file = store.location();
+    let on_disk = store.load().ok();
+
+    statusln!("🔎 继位前的快照：{} 年归档", year);
+    let path = store.archive_location(year);
+    outln!(
+        "  归档      {}（{}）：家主【{}】，共 {} 人",
+        path,
+        modified_ago(Path::new(&path)),
+        archived.tree.name,
+        archived.tree.total_count()
+    );
+    match &on_disk {
+        Some(disk) => outln!(
+            "  数据文件  {}（{}）：{}",
+            data_file,
+            modified_ago(Path::new(&data_file)),
+            relation(&archived, disk)
+        ),
+        None => outln!("  数据文件  {}：无法读取", data_file),
+    }
+    let dirty = !on_disk.as_ref().is_some_and(|disk| disk.same_content(doc));
+    outln!(
+        "  内存      {}：{}",
+        if dirty {
+            "有未保存的改动"
+        } else {
+            "与数据文件一致"
+        },
+        relation(&archived, doc)
+    );
+
+    let later = later_trash(&archived, doc);
+    outln!(
+        "  回收站    归档 {} 条，当前 {} 条{}",
+        archived.trash.len(),
+        doc.trash.len(),
+        if later.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<String> = later
+                .iter()
+                .map(|e| format!("【{}】（{} 年）", e.member.name, e.deleted_year))
+                .collect();
+            format!("；归档之后删除、归档中也没有的：{}", names.join(""))
+        }
+    );
+    if keep_trash {
+        archived.trash.extend(later.iter().map(|&e| e.clone()));
+    } else if !later.is_empty() {
+        outln!("            恢复后这些成员不再在回收站中，加 --keep-trash 可保留");
+    }
+
+    if archived.same_content(doc) {
+        statusln!("✅ 当前数据与归档一致，无需恢复");
+        return true;
+    }
+
+    let to_value = |d: &FamilyDocument| serde_json::to_value(d).unwrap_or_default();
+    let changes = verify::diff_values(&to_value(&archived), &to_value(doc));
+    outln!("\n归档 → 当前数据的差异（{} 处）：", changes.len());
+    for change in changes.iter().take(MAX_CHANGES) {
+        outln!("  {}", change.describe_change());
+    }
+    if changes.len() > MAX_CHANGES {
+        outln!("  … 其余 {} 处", changes.len() - MAX_CHANGES);
+    }
+
+    outln!();
+    outln!(
+        "以归档恢复：回到 {} 年继位前的数据，之后可重新 inherit",
+        year
+    );
+    outln!("保留当前数据：若继位结果无误，save 即以当前数据为准");
+    if confirm.ask("用归档替换当前数据？") {
+        *doc = archived;
+        statusln!("✅ 已用 {} 年归档替换内存中的数据（可 undo 撤销）", year);
+        outln!("   确认无误后执行 save 写入数据文件");
+    } else {
+        statusln!("ℹ️ 保留当前数据");
+    }
+    true
+}
+
+/// 当前回收站中归档里没有、且不在归档家族树中的条目
+///
+/// 归档之后才删除的归档中成员在恢复后回到家族树，不再需要回收站中的副本。
+fn later_trash<'a>(archived: &FamilyDocument, doc: &'a FamilyDocument) -> Vec<&'a TrashEntry> {
+    doc.trash
+        .iter()
+        .filter(|e| {
+            !archived.tree.exists(&e.member.name)
+                && !archived
+                    .trash
+                    .iter()
+                    .any(|a| a.deleted_year == e.deleted_year && a.member.name == e.member.name)
+        })
+        .collect()
+}
+
+/// 指定年份或最近一份可读取的归档；最近的归档写入不完整时退回上一份
+fn find_archive(store: &dyn FamilyStore, year: Option<i32>) -> Option<(i32, FamilyDocument)> {
+    let archives = store.list_archives();
+    if archives.is_empty() {
+        statusln!("❌ {} 没有归档，无法恢复继位前的数据", store.location());
+        return None;
+    }
+
+    if let Some(year) = year {
+        if !archives.contains(&year) {
+            statusln!("❌ 没有 {} 年的归档", year);
+            return None;
+        }
+        return match store.load_archive(year) {
+            Ok(doc) => Some((year, doc)),
+            Err(e) => {
+                statusln!("❌ {}", e);
+                None
+            }
+        };
+    }
+
+    for year in archives.into_iter().rev() {
+        match store.load_archive(year) {
+            Ok(doc) => return Some((year, doc)),
+            // 进程在写归档时被终止，文件可能不完整
+            Err(e) => statusln!("⚠️  跳过无法读取的归档：{}", e),
+        }
+    }
+    statusln!("❌ 所有归档都无法读取");
+    None
+}
+
+/// 数据相对归档处于继位前还是继位后
+fn relation(archived: &FamilyDocument, doc: &FamilyDocument) -> String {
+    let head = &doc.tree.name;
+    if *head == archived.tree.name {
+        if archived.same_content(doc) {
+            format!("家主【{}】，与归档相同", head)
+        } else {
+            format!("家主【{}】，与归档同一家主，继位未生效", head)
+        }
+    } else if archived.tree.exists(head) {
+        format!("家主【{}】，继位已生效", head)
+    } else {
+        format!("家主【{}】，与归档中的家族无关", head)
+    }
+}
+
+/// 文件修改时间距今多久，如 `3 分钟前修改`
+fn modified_ago(path: &Path) -> String {
+    let Some(elapsed) = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+    else {
+        return "修改时间未知".to_string();
+    };
+
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => format!("{} 秒前修改", secs),
+        60..=3599 => format!("{} 分钟前修改", secs / 60),
+        3600..=86399 => format!("{} 小时前修改", secs / 3600),
+        _ => format!("{} 天前修改", secs / 86400),
+    }
+}
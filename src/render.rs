@@ -0,0 +1,1139 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::model::{FamilyMember, Gender, Generation, Lineage, VitalStatus};
+use crate::style::{Cell, Color, Style};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 家族树渲染器
+///
+/// 将以某成员为根的家族树渲染为字符串，由调用方决定输出到终端或文件。
+pub trait Renderer {
+    fn render_tree(&self, root: &FamilyMember) -> String;
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// 终端表格（带树形分支符号）
+///
+/// 列宽按实际内容计算（不超过 `widths`），总宽超过 `max_width` 时
+/// 依次收窄姓名、职位、称谓中最宽的一列，放不下的文字以 `…` 截断。
+#[derive(Debug, Clone, Default)]
+pub struct TerminalTable {
+    pub folding: Folding,
+    pub widths: ColumnWidths,
+    /// 是否用 ANSI 转义序列着色，见 [`crate::style`]
+    pub color: bool,
+    /// 表格总宽上限（通常为终端宽度），`None` 为不限
+    pub max_width: Option<usize>,
+    /// 框线与树形分支是否只用 ASCII 字符，供不能显示制表符的终端使用
+    pub ascii: bool,
+}
+
+/// 终端表格的一行，先收集再按内容统一计算列宽
+enum Line {
+    /// 成员行：树形前缀与各列
+    Member { prefix: String, cells: [Cell; 7] },
+    /// 仅为连接后代而显示的先辈，只有姓名
+    Skeleton { prefix: String, name: String },
+    /// 折叠提示，`text` 占姓名列，`hint` 接在其后
+    Note { text: String, hint: String },
+}
+
+/// 各列之间的最小空白
+const GAP: usize = 2;
+
+/// 终端表格的表头
+const TABLE_TITLES: [&str; 7] = ["姓名", "出生", "类别", "状态", "职位", "威望+", "子嗣"];
+
+/// 终端表格各列的显示宽度上限（中文字符按两格计算）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnWidths {
+    /// 树形符号与姓名的总宽度
+    pub name: usize,
+    pub birth: usize,
+    #[serde(rename = "type")]
+    pub member_type: usize,
+    pub status: usize,
+    pub position: usize,
+    pub power: usize,
+    pub children: usize,
+}
+
+/// 子嗣折叠
+///
+/// 子嗣多于 `limit` 人时只显示前 `limit` 人，其余折叠为一行 `… 其余 N 人`。
+/// `expand` 中的成员不折叠；被折叠的成员若在 `expand` 中或其后代在其中，
+/// 仍会显示，以便沿路径看到要展开的成员。
+/// `depth` 限制显示的代数，更深的后代合并为一行 `… 以下 N 人`。
+/// `living` 只显示在世（或已故）的成员，不符合但有符合条件的后代的先辈
+/// 只显示姓名，保留树形。
+#[derive(Debug, Clone, Default)]
+pub struct Folding {
+    /// 每个成员最多显示的子嗣数，`None` 为不折叠
+    pub limit: Option<usize>,
+    /// 不折叠子嗣的成员
+    pub expand: Vec<String>,
+    /// 起点以下最多显示的代数，`None` 为不限
+    pub depth: Option<usize>,
+    /// `Some(true)` 只显示未故成员（含下落不明），`Some(false)` 只显示已故成员
+    pub living: Option<bool>,
+}
+
+/// 格式化 JSON
+pub struct Json;
+
+/// CSV，每个成员一行
+pub struct Csv;
+
+/// GitHub 风格的 Markdown 表格
+pub struct Markdown;
+
+/// Graphviz DOT 家谱图
+///
+/// 节点按代际着色，同代对齐；家主加粗高亮，已故成员灰显，外系成员与其连线为虚线。
+pub struct Dot;
+
+/// Mermaid 流程图（`graph TD`），可嵌入 Markdown 笔记
+///
+/// 节点标注出生年、称谓与职位，已故成员加 † 并灰显，家主高亮。
+pub struct Mermaid;
+
+/// SVG 家谱图，不依赖 Graphviz
+///
+/// 按距起点的代数分层，每人一个方框，标注姓名、出生年与称谓、职位；
+/// 着色同 [`Dot`]，外系成员的方框与连线为虚线。
+pub struct Svg;
+
+/// SVG 方框的高度、层间距、同层间距与页边距（像素）
+const SVG_BOX_HEIGHT: usize = 62;
+const SVG_LAYER_GAP: usize = 48;
+const SVG_SIBLING_GAP: usize = 20;
+const SVG_MARGIN: usize = 20;
+
+/// SVG 中一个显示宽度单位（半个汉字）的像素数，按 14px 字号估算
+const SVG_CHAR_WIDTH: usize = 7;
+
+/// GEDCOM 5.5 家谱交换格式，可导入常见家谱软件
+///
+/// 每人一条 `INDI`：首字作姓（姓在前），性别取自称谓，出生年记为 `BIRT`，已故记 `DEAT Y`，
+/// 职位记为 `OCCU`，称谓写入 `NOTE`。每个有子嗣的成员一条 `FAM`，数据中没有配偶，
+/// 只填本人一方（按性别为 `HUSB` 或 `WIFE`）。字符集声明为 UTF-8。
+pub struct Gedcom;
+
+/// 按先序遍历展开的一行
+pub struct Row<'a> {
+    pub member: &'a FamilyMember,
+    pub parent: Option<&'a FamilyMember>,
+    pub depth: usize,
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(format!(
+                "未知格式【{}】，可选：table, json, csv, markdown",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Default for ColumnWidths {
+    fn default() -> Self {
+        ColumnWidths {
+            name: 30,
+            birth: 8,
+            member_type: 12,
+            status: 8,
+            position: 18,
+            power: 8,
+            children: 8,
+        }
+    }
+}
+
+impl Renderer for TerminalTable {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        // 根节点不使用树形符号
+        let mut lines = Vec::new();
+        self.render_rows(root, 0, true, Vec::new(), &mut lines);
+        let widths = self.layout(&lines);
+
+        let border = self.glyph("━", "=").repeat(widths.iter().sum());
+        let mut out = String::new();
+        writeln!(out, "{border}").unwrap();
+        let header: String = TABLE_TITLES
+            .iter()
+            .zip(widths)
+            .map(|(title, width)| pad(title, width))
+            .collect();
+        writeln!(out, "{header}").unwrap();
+        writeln!(out, "{border}").unwrap();
+
+        for line in lines {
+            match line {
+                Line::Member { prefix, cells } => {
+                    out.push_str(&prefix);
+                    let name_width = widths[0].saturating_sub(prefix.width());
+                    for (cell, width) in cells
+                        .iter()
+                        .zip([name_width].into_iter().chain(widths[1..].iter().copied()))
+                    {
+                        let fitted =
+                            Cell::new(truncate(&cell.text, width.saturating_sub(GAP)), cell.style);
+                        out.push_str(&fitted.render(width, self.color));
+                    }
+                    out.push('\n');
+                }
+                Line::Skeleton { prefix, name } => {
+                    let width = widths[0].saturating_sub(prefix.width() + GAP);
+                    writeln!(out, "{}{}", prefix, truncate(&name, width)).unwrap();
+                }
+                Line::Note { text, hint } => {
+                    writeln!(out, "{}{}", pad(&text, widths[0]), hint).unwrap();
+                }
+            }
+        }
+
+        writeln!(out).unwrap(); // 空行结尾
+        out
+    }
+}
+
+impl Renderer for Json {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let mut out = serde_json::to_string_pretty(root).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+}
+
+impl Renderer for Csv {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let mut out = String::from(
+            "name,birth_year,generation,gender,lineage,member_type,position,power,is_dead,parent,depth,children\n",
+        );
+
+        for row in rows(root) {
+            let m = row.member;
+            writeln!(
+                out,
+                "{},{},{:?},{},{},{},{},{},{},{},{},{}",
+                csv_field(&m.name),
+                m.birth_year,
+                m.member_type.generation,
+                m.member_type.gender,
+                m.member_type.lineage,
+                m.member_type,
+                csv_field(m.position.as_deref().unwrap_or("")),
+                m.hoser_power_add,
+                m.is_dead,
+                csv_field(row.parent.map(|p| p.name.as_str()).unwrap_or("")),
+                row.depth,
+                m.children.len(),
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+impl Renderer for Markdown {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let mut out = String::from("| 姓名 | 出生 | 类别 | 状态 | 职位 | 威望+ | 子嗣 |\n");
+        out.push_str("| --- | --- | --- | --- | --- | --- | --- |\n");
+
+        for row in rows(root) {
+            let m = row.member;
+            // 以全角空格与 └ 标示层级
+            let indent = if row.depth == 0 {
+                String::new()
+            } else {
+                format!("{}└ ", "　".repeat(row.depth - 1))
+            };
+            writeln!(
+                out,
+                "| {}{} | {} | {} | {} | {} | {} | {} |",
+                indent,
+                md_cell(&m.name),
+                m.birth_year,
+                m.member_type,
+                status_label(m),
+                md_cell(m.position.as_deref().unwrap_or("-")),
+                m.hoser_power_add,
+                m.children.len(),
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+impl Renderer for Dot {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let rows = rows(root);
+        let mut out = String::from("digraph family {\n");
+        out.push_str("    rankdir=TB;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"sans-serif\"];\n");
+        out.push_str("    edge [arrowhead=none];\n\n");
+
+        // 节点编号取先序下标，避免姓名中的特殊字符
+        let index_of: HashMap<*const FamilyMember, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.member as *const FamilyMember, i))
+            .collect();
+
+        for (i, row) in rows.iter().enumerate() {
+            let m = row.member;
+            let mut label = format!(
+                "{}\\n{} {}",
+                dot_escape(&m.name),
+                m.birth_year,
+                m.member_type
+            );
+            if let Some(position) = &m.position {
+                write!(label, "\\n{}", dot_escape(position)).unwrap();
+            }
+
+            let mut attrs = vec![format!("label=\"{}\"", label)];
+            let foreign = m.member_type.lineage == Lineage::Foreign;
+            if row.depth == 0 {
+                attrs.push("fillcolor=\"#f7dc6f\"".to_string());
+                attrs.push("penwidth=3".to_string());
+            } else if m.is_dead {
+                attrs.push("fillcolor=\"#e5e7e9\"".to_string());
+                attrs.push("fontcolor=\"#7f8c8d\"".to_string());
+                attrs.push("color=\"#aab7b8\"".to_string());
+            } else {
+                attrs.push(format!(
+                    "fillcolor=\"{}\"",
+                    generation_color(m.member_type.generation)
+                ));
+            }
+            if foreign {
+                attrs.push("style=\"rounded,filled,dashed\"".to_string());
+            }
+            writeln!(out, "    n{} [{}];", i, attrs.join(", ")).unwrap();
+        }
+        out.push('\n');
+
+        for (i, row) in rows.iter().enumerate() {
+            if let Some(parent) = row.parent {
+                let style = if row.member.member_type.lineage == Lineage::Foreign {
+                    " [style=dashed]"
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "    n{} -> n{}{};",
+                    index_of[&(parent as *const FamilyMember)],
+                    i,
+                    style
+                )
+                .unwrap();
+            }
+        }
+
+        // 同一层级对齐
+        let depth = rows.iter().map(|r| r.depth).max().unwrap_or_default();
+        for d in 1..=depth {
+            let ids: Vec<String> = rows
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.depth == d)
+                .map(|(i, _)| format!("n{}", i))
+                .collect();
+            writeln!(out, "    {{ rank=same; {}; }}", ids.join("; ")).unwrap();
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Renderer for Mermaid {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let rows = rows(root);
+        let index_of: HashMap<*const FamilyMember, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.member as *const FamilyMember, i))
+            .collect();
+
+        let mut out = String::from("graph TD\n");
+        for (i, row) in rows.iter().enumerate() {
+            let m = row.member;
+            let mut label = format!(
+                "{}{}<br/>{} {}",
+                if m.is_dead { "† " } else { "" },
+                mermaid_escape(&m.name),
+                m.birth_year,
+                m.member_type
+            );
+            if let Some(position) = &m.position {
+                write!(label, "<br/>{}", mermaid_escape(position)).unwrap();
+            }
+            let class = if row.depth == 0 {
+                ":::head"
+            } else if m.is_dead {
+                ":::dead"
+            } else {
+                ""
+            };
+            writeln!(out, "    n{}[\"{}\"]{}", i, label, class).unwrap();
+        }
+
+        for (i, row) in rows.iter().enumerate() {
+            if let Some(parent) = row.parent {
+                let arrow = if row.member.member_type.lineage == Lineage::Foreign {
+                    "-.->"
+                } else {
+                    "-->"
+                };
+                writeln!(
+                    out,
+                    "    n{} {} n{}",
+                    index_of[&(parent as *const FamilyMember)],
+                    arrow,
+                    i
+                )
+                .unwrap();
+            }
+        }
+
+        out.push_str("    classDef head fill:#f7dc6f,stroke:#b7950b,stroke-width:3px\n");
+        out.push_str("    classDef dead fill:#e5e7e9,color:#7f8c8d,stroke:#aab7b8\n");
+        out
+    }
+}
+
+impl Renderer for Svg {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let rows = rows(root);
+        let labels: Vec<[String; 3]> = rows
+            .iter()
+            .map(|r| {
+                let m = r.member;
+                [
+                    m.name.clone(),
+                    format!("{} {}", m.birth_year, m.member_type),
+                    m.position.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        let box_width = labels
+            .iter()
+            .flatten()
+            .map(|l| l.width() * SVG_CHAR_WIDTH + 16)
+            .max()
+            .unwrap_or_default()
+            .max(100);
+
+        // 叶子依次占一格，父辈居于首末子嗣之间；序号与先序下标一致
+        let slots = svg_slots(root);
+        let slot_count = slots.iter().fold(0.0_f64, |a, &b| a.max(b)) + 1.0;
+        let depth = rows.iter().map(|r| r.depth).max().unwrap_or_default();
+        let step = (box_width + SVG_SIBLING_GAP) as f64;
+        let width = (slot_count * step) as usize - SVG_SIBLING_GAP + 2 * SVG_MARGIN;
+        let height =
+            (depth + 1) * (SVG_BOX_HEIGHT + SVG_LAYER_GAP) - SVG_LAYER_GAP + 2 * SVG_MARGIN;
+        let left = |i: usize| SVG_MARGIN as f64 + slots[i] * step;
+        let top = |depth: usize| SVG_MARGIN + depth * (SVG_BOX_HEIGHT + SVG_LAYER_GAP);
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"14\">",
+            w = width,
+            h = height
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>"
+        )
+        .unwrap();
+
+        let index_of: HashMap<*const FamilyMember, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.member as *const FamilyMember, i))
+            .collect();
+        let center = box_width as f64 / 2.0;
+        for (i, row) in rows.iter().enumerate() {
+            let Some(parent) = row.parent else { continue };
+            let p = index_of[&(parent as *const FamilyMember)];
+            let (x1, y1) = (left(p) + center, top(row.depth - 1) + SVG_BOX_HEIGHT);
+            let (x2, y2) = (left(i) + center, top(row.depth));
+            let dash = if row.member.member_type.lineage == Lineage::Foreign {
+                " stroke-dasharray=\"6,4\""
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "  <path d=\"M{} {} V{} H{} V{}\" fill=\"none\" stroke=\"#7f8c8d\"{}/>",
+                x1,
+                y1,
+                y1 + SVG_LAYER_GAP / 2,
+                x2,
+                y2,
+                dash
+            )
+            .unwrap();
+        }
+
+        for (i, (row, label)) in rows.iter().zip(&labels).enumerate() {
+            let m = row.member;
+            let (fill, stroke, text, stroke_width) = if row.depth == 0 {
+                ("#f7dc6f", "#b7950b", "#000000", 3)
+            } else if m.is_dead {
+                ("#e5e7e9", "#aab7b8", "#7f8c8d", 1)
+            } else {
+                (
+                    generation_color(m.member_type.generation),
+                    "#566573",
+                    "#000000",
+                    1,
+                )
+            };
+            let dash = if m.member_type.lineage == Lineage::Foreign {
+                " stroke-dasharray=\"6,4\""
+            } else {
+                ""
+            };
+            let (x, y) = (left(i), top(row.depth));
+            writeln!(out, "  <g>").unwrap();
+            writeln!(
+                out,
+                "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+                x, y, box_width, SVG_BOX_HEIGHT, fill, stroke, stroke_width, dash
+            )
+            .unwrap();
+            for (line, content) in label.iter().enumerate() {
+                if content.is_empty() {
+                    continue;
+                }
+                let weight = if line == 0 {
+                    " font-weight=\"bold\""
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "    <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" fill=\"{}\"{}>{}</text>",
+                    x + center,
+                    y + 18 + line * 18,
+                    text,
+                    weight,
+                    xml_escape(content)
+                )
+                .unwrap();
+            }
+            writeln!(out, "  </g>").unwrap();
+        }
+
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+impl Renderer for Gedcom {
+    fn render_tree(&self, root: &FamilyMember) -> String {
+        let rows = rows(root);
+        let mut out = String::new();
+        out.push_str("0 HEAD\n");
+        out.push_str("1 SOUR ZZ-SIM-DATA-TOOL\n");
+        writeln!(out, "2 VERS {}", env!("CARGO_PKG_VERSION")).unwrap();
+        out.push_str("1 GEDC\n2 VERS 5.5\n2 FORM LINEAGE-LINKED\n");
+        out.push_str("1 CHAR UTF-8\n");
+
+        // 个人编号取先序下标 + 1，家庭编号按有子嗣的成员依次分配
+        let index_of: HashMap<*const FamilyMember, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.member as *const FamilyMember, i + 1))
+            .collect();
+        let family_of: HashMap<*const FamilyMember, usize> = rows
+            .iter()
+            .filter(|r| !r.member.children.is_empty())
+            .enumerate()
+            .map(|(i, r)| (r.member as *const FamilyMember, i + 1))
+            .collect();
+
+        for (i, row) in rows.iter().enumerate() {
+            let m = row.member;
+            let (surname, given) = split_name(&m.name);
+            writeln!(out, "0 @I{}@ INDI", i + 1).unwrap();
+            writeln!(out, "1 NAME /{}/{}", surname, given).unwrap();
+            writeln!(out, "2 GIVN {}", given).unwrap();
+            writeln!(out, "2 SURN {}", surname).unwrap();
+            let sex = match m.member_type.gender {
+                Gender::Male => "M",
+                Gender::Female => "F",
+            };
+            writeln!(out, "1 SEX {}", sex).unwrap();
+            out.push_str("1 BIRT\n");
+            writeln!(out, "2 DATE {}", gedcom_year(m.birth_year)).unwrap();
+            if m.is_dead {
+                // 数据中没有卒年，`Y` 表示已故、日期不详
+                out.push_str("1 DEAT Y\n");
+            }
+            if let Some(position) = &m.position {
+                writeln!(out, "1 OCCU {}", position).unwrap();
+            }
+            writeln!(out, "1 NOTE 称谓：{}", m.member_type).unwrap();
+            if let Some(parent) = row.parent {
+                writeln!(
+                    out,
+                    "1 FAMC @F{}@",
+                    family_of[&(parent as *const FamilyMember)]
+                )
+                .unwrap();
+            }
+            if let Some(f) = family_of.get(&(m as *const FamilyMember)) {
+                writeln!(out, "1 FAMS @F{}@", f).unwrap();
+            }
+        }
+
+        for row in rows.iter().filter(|r| !r.member.children.is_empty()) {
+            let m = row.member;
+            let key = m as *const FamilyMember;
+            writeln!(out, "0 @F{}@ FAM", family_of[&key]).unwrap();
+            let role = match m.member_type.gender {
+                Gender::Male => "HUSB",
+                Gender::Female => "WIFE",
+            };
+            writeln!(out, "1 {} @I{}@", role, index_of[&key]).unwrap();
+            for child in &m.children {
+                writeln!(
+                    out,
+                    "1 CHIL @I{}@",
+                    index_of[&(child as *const FamilyMember)]
+                )
+                .unwrap();
+            }
+        }
+
+        out.push_str("0 TRLR\n");
+        out
+    }
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl OutputFormat {
+    /// 对应格式的渲染器
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        self.renderer_folded(Folding::default())
+    }
+
+    /// 对应格式的渲染器，终端表格按 `folding` 折叠子嗣
+    ///
+    /// JSON、CSV、Markdown 用于导出，始终输出全部成员。
+    pub fn renderer_folded(self, folding: Folding) -> Box<dyn Renderer> {
+        self.renderer_with(TerminalTable {
+            folding,
+            ..TerminalTable::default()
+        })
+    }
+
+    /// 同 [`renderer_folded`](Self::renderer_folded)，终端表格按 `table` 的
+    /// 折叠、列宽、着色与总宽设置输出
+    pub fn renderer_with(self, table: TerminalTable) -> Box<dyn Renderer> {
+        match self {
+            OutputFormat::Table => Box::new(table),
+            OutputFormat::Json => Box::new(Json),
+            OutputFormat::Csv => Box::new(Csv),
+            OutputFormat::Markdown => Box::new(Markdown),
+        }
+    }
+}
+
+impl TerminalTable {
+    /// 递归渲染家族树，支持树形分支符号
+    ///
+    /// # param
+    /// * `level` - 当前层级（0为根节点）
+    /// * `is_last` - 当前节点是否是父节点的最后一个子节点
+    /// * `parent_markers` - 记录每一层的父节点是否是最后一个（用于决定是否画竖线）
+    fn render_rows(
+        &self,
+        member: &FamilyMember,
+        level: usize,
+        is_last: bool,
+        parent_markers: Vec<bool>,
+        lines: &mut Vec<Line>,
+    ) {
+        // 构建树形前缀
+        let mut tree_prefix = String::new();
+
+        // 为每一层父节点添加竖线或空格
+        for &parent_is_last in &parent_markers {
+            if parent_is_last {
+                tree_prefix.push_str("   "); // 父节点是最后一个，不画竖线
+            } else {
+                tree_prefix.push_str(self.glyph("│  ", "|  ")); // 父节点不是最后一个，画竖线
+            }
+        }
+
+        // 当前节点的分支符号（根节点除外）
+        let branch_symbol = if level > 0 {
+            if is_last {
+                self.glyph("└─ ", "`- ") // 最后一个子节点
+            } else {
+                self.glyph("├─ ", "+- ") // 中间子节点
+            }
+        } else {
+            "" // 根节点无符号
+        };
+
+        tree_prefix.push_str(branch_symbol);
+
+        // 树形前缀不着色，姓名等单元格按成员状态着色
+        if self.folding.matches(member) {
+            lines.push(Line::Member {
+                prefix: tree_prefix,
+                cells: self.cells(member),
+            });
+        } else {
+            lines.push(Line::Skeleton {
+                prefix: tree_prefix,
+                name: member.name.clone(),
+            });
+        }
+
+        // 更新 parent_markers：添加当前节点的状态
+        let mut new_markers = parent_markers.clone();
+        new_markers.push(is_last);
+
+        // 到达代数上限，其下的后代合并为一行
+        let below = self.folding.count_below(member);
+        if self.folding.depth == Some(level) && below > 0 {
+            let mut prefix = self.fold_prefix(&new_markers);
+            prefix.push_str(self.glyph("└─ ", "`- "));
+            lines.push(Line::Note {
+                text: format!("{}… 以下 {} 人", prefix, below),
+                hint: format!("（show {} 查看）", member.name),
+            });
+            return;
+        }
+
+        // 递归处理子节点，超出上限的折叠
+        let relevant: Vec<&FamilyMember> = member
+            .children
+            .iter()
+            .filter(|child| self.folding.relevant(child))
+            .collect();
+        let shown: Vec<&FamilyMember> = relevant
+            .iter()
+            .enumerate()
+            .filter(|(index, child)| self.folding.shows(member, *index, child))
+            .map(|(_, child)| *child)
+            .collect();
+        let folded = relevant.len() - shown.len();
+
+        for (index, child) in shown.iter().enumerate() {
+            let child_is_last = index == shown.len() - 1 && folded == 0;
+            self.render_rows(child, level + 1, child_is_last, new_markers.clone(), lines);
+        }
+
+        if folded > 0 {
+            let mut prefix = self.fold_prefix(&new_markers);
+            prefix.push_str(self.glyph("└─ ", "`- "));
+            lines.push(Line::Note {
+                text: format!("{}… 其余 {} 人", prefix, folded),
+                hint: format!("（show --expand {} 展开）", member.name),
+            });
+        }
+    }
+}
+
+impl TerminalTable {
+    /// 按 `ascii` 选用制表符或其 ASCII 替代（宽度相同）
+    fn glyph(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        if self.ascii {
+            ascii
+        } else {
+            unicode
+        }
+    }
+
+    /// 折叠行的树形前缀，与成员行的竖线对齐
+    fn fold_prefix(&self, markers: &[bool]) -> String {
+        markers
+            .iter()
+            .map(|&last| {
+                if last {
+                    "   "
+                } else {
+                    self.glyph("│  ", "|  ")
+                }
+            })
+            .collect()
+    }
+
+    /// 按内容计算各列宽度（含列间空白），再按 `widths` 与 `max_width` 收窄
+    fn layout(&self, lines: &[Line]) -> [usize; 7] {
+        let mut content = TABLE_TITLES.map(|t| t.width());
+        let mut prefix_width = 0;
+        for line in lines {
+            match line {
+                Line::Member { prefix, cells } => {
+                    prefix_width = prefix_width.max(prefix.width());
+                    content[0] = content[0].max(prefix.width() + cells[0].text.width());
+                    for (i, cell) in cells.iter().enumerate().skip(1) {
+                        content[i] = content[i].max(cell.text.width());
+                    }
+                }
+                Line::Skeleton { prefix, name } => {
+                    prefix_width = prefix_width.max(prefix.width());
+                    content[0] = content[0].max(prefix.width() + name.width());
+                }
+                Line::Note { .. } => {}
+            }
+        }
+
+        let w = &self.widths;
+        let limits = [
+            w.name,
+            w.birth,
+            w.member_type,
+            w.status,
+            w.position,
+            w.power,
+            w.children,
+        ];
+        // 姓名列至少保留一个字加省略号
+        let mut minimums = TABLE_TITLES.map(|t| t.width() + GAP);
+        minimums[0] = minimums[0].max(prefix_width + 3 + GAP);
+        let mut widths = [0; 7];
+        for i in 0..7 {
+            widths[i] = (content[i] + GAP).min(limits[i].max(minimums[i]));
+        }
+        if let Some(max_width) = self.max_width {
+            // 只收窄文字列，数字列保持完整
+            fit_widths(&mut widths, &minimums, &[0, 4, 2], max_width);
+        }
+        widths
+    }
+
+    /// 一名成员的各列：已故者整行暗淡，家主加粗，外系成员的姓名与称谓
+    /// 用青色，有职位的职位用黄色，下落不明的状态用品红
+    fn cells(&self, member: &FamilyMember) -> [Cell; 7] {
+        let row = if member.is_dead {
+            Style::default().dim()
+        } else {
+            Style::default()
+        };
+        let mut name = row;
+        if member.member_type.generation == Generation::家主 {
+            name = name.bold();
+        }
+        let mut member_type = row;
+        if member.member_type.lineage == Lineage::Foreign {
+            name = name.fg(Color::Cyan);
+            member_type = member_type.fg(Color::Cyan);
+        }
+        let status = match member.status() {
+            VitalStatus::Missing { .. } => row.fg(Color::Magenta),
+            _ => row,
+        };
+        let position = match &member.position {
+            Some(_) => row.fg(Color::Yellow),
+            None => row,
+        };
+        [
+            Cell::new(member.name.clone(), name),
+            Cell::new(member.birth_year.to_string(), row),
+            Cell::new(member.member_type.to_string(), member_type),
+            Cell::new(status_label(member), status),
+            Cell::new(member.position.as_deref().unwrap_or("-"), position),
+            Cell::new(member.hoser_power_add.to_string(), row),
+            Cell::new(member.children.len().to_string(), row),
+        ]
+    }
+}
+
+impl Folding {
+    /// 成员本身是否符合 `living` 条件
+    pub fn matches(&self, member: &FamilyMember) -> bool {
+        self.living.is_none_or(|living| member.is_dead != living)
+    }
+
+    /// 成员或其任一后代符合 `living` 条件
+    fn relevant(&self, member: &FamilyMember) -> bool {
+        self.matches(member) || member.children.iter().any(|c| self.relevant(c))
+    }
+
+    /// 后代中符合 `living` 条件的人数
+    fn count_below(&self, member: &FamilyMember) -> usize {
+        member
+            .children
+            .iter()
+            .map(|c| usize::from(self.matches(c)) + self.count_below(c))
+            .sum()
+    }
+
+    /// `parent` 的第 `index` 个子嗣是否显示
+    fn shows(&self, parent: &FamilyMember, index: usize, child: &FamilyMember) -> bool {
+        match self.limit {
+            None => true,
+            Some(limit) => {
+                index < limit
+                    || self.expand.contains(&parent.name)
+                    || self.expand.iter().any(|name| child.exists(name))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 按显示宽度右侧填充空格（中文字符按两格计算）
+pub fn pad(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(s.width())))
+}
+
+/// 将全部已故的远支折叠为汇总节点，用于分享的家谱图
+///
+/// 距家主不少于 `min_generation` 代、成员全部已故且不止一人的分支，替换为一个
+/// 无子嗣的已故节点，姓名如 `张丽一支（已故，共 12 人）`，出生年与称谓沿用分支的根。
+/// 返回折叠后的副本与折叠的分支数，原树不变。
+pub fn collapse_dead(root: &FamilyMember, min_generation: usize) -> (FamilyMember, usize) {
+    fn walk(
+        member: &FamilyMember,
+        depth: usize,
+        min_generation: usize,
+        count: &mut usize,
+    ) -> FamilyMember {
+        let total = member.total_count();
+        if depth >= min_generation && depth > 0 && total > 1 && member.all_dead() {
+            *count += 1;
+            return FamilyMember {
+                name: format!("{}一支（已故，共 {} 人）", member.name, total),
+                position: None,
+                children: Vec::new(),
+                position_history: Vec::new(),
+                ..member.clone()
+            };
+        }
+
+        FamilyMember {
+            children: member
+                .children
+                .iter()
+                .map(|c| walk(c, depth + 1, min_generation, count))
+                .collect(),
+            ..member.clone()
+        }
+    }
+
+    let mut count = 0;
+    let collapsed = walk(root, 0, min_generation, &mut count);
+    (collapsed, count)
+}
+
+/// 先序展开家族树
+pub fn rows(root: &FamilyMember) -> Vec<Row<'_>> {
+    fn walk<'a>(
+        member: &'a FamilyMember,
+        parent: Option<&'a FamilyMember>,
+        depth: usize,
+        out: &mut Vec<Row<'a>>,
+    ) {
+        out.push(Row {
+            member,
+            parent,
+            depth,
+        });
+        for child in &member.children {
+            walk(child, Some(member), depth + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(root, None, 0, &mut out);
+    out
+}
+
+/// CSV 字段转义：含逗号、引号或换行时加引号
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// DOT 字符串转义
+pub fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 各代节点的填充色，按代际轮换
+fn generation_color(generation: Generation) -> &'static str {
+    const PALETTE: [&str; 5] = ["#aed6f1", "#a9dfbf", "#f9e79f", "#f5cba7", "#d7bde2"];
+    match generation.depth() {
+        Some(depth) if depth > 0 => PALETTE[(usize::from(depth) - 1) % PALETTE.len()],
+        _ => "#ffffff",
+    }
+}
+
+/// SVG 布局中各成员所在的格（先序下标），叶子依次占一格，父辈居中
+fn svg_slots(root: &FamilyMember) -> Vec<f64> {
+    fn place(member: &FamilyMember, next_leaf: &mut f64, slots: &mut Vec<f64>) -> f64 {
+        let i = slots.len();
+        slots.push(0.0);
+        let slot = match member.children.as_slice() {
+            [] => {
+                *next_leaf += 1.0;
+                *next_leaf - 1.0
+            }
+            children => {
+                let placed: Vec<f64> = children
+                    .iter()
+                    .map(|c| place(c, next_leaf, slots))
+                    .collect();
+                (placed[0] + placed[placed.len() - 1]) / 2.0
+            }
+        };
+        slots[i] = slot;
+        slot
+    }
+
+    let mut slots = Vec::new();
+    place(root, &mut 0.0, &mut slots);
+    slots
+}
+
+/// XML 文本转义
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Mermaid 标签转义：引号与尖括号用实体表示
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// 总宽超过 `max_width` 时逐格收窄 `shrinkable` 中富余最多的一列，
+/// 不低于 `minimums`；都已到下限时不再收窄
+pub fn fit_widths(
+    widths: &mut [usize],
+    minimums: &[usize],
+    shrinkable: &[usize],
+    max_width: usize,
+) {
+    while widths.iter().sum::<usize>() > max_width {
+        let Some(&i) = shrinkable
+            .iter()
+            .filter(|&&i| widths[i] > minimums[i])
+            .max_by_key(|&&i| widths[i] - minimums[i])
+        else {
+            break;
+        };
+        widths[i] -= 1;
+    }
+}
+
+/// 按显示宽度截断，超出时以 `…` 结尾
+pub fn truncate(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// Markdown 单元格转义竖线
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+/// 表格中的状态列，在世留空
+fn status_label(m: &FamilyMember) -> &'static str {
+    match m.status() {
+        VitalStatus::Alive => "",
+        VitalStatus::Dead => "已故",
+        VitalStatus::Missing { .. } => "失踪",
+    }
+}
+
+/// 拆分姓与名：首字为姓，其余为名
+fn split_name(name: &str) -> (&str, &str) {
+    match name.char_indices().nth(1) {
+        Some((i, _)) => name.split_at(i),
+        None => (name, ""),
+    }
+}
+
+/// GEDCOM 日期中的年份，公元前记为 `B.C.`（没有公元 0 年，0 年即公元前 1 年）
+fn gedcom_year(year: i32) -> String {
+    if year > 0 {
+        year.to_string()
+    } else {
+        format!("{} B.C.", 1 - year)
+    }
+}
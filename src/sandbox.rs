@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::PathBuf;
+
+use zz_sim_data_tool::document::FamilyDocument;
+
+/// 沙盒会话
+///
+/// 进入时把当前数据复制到临时目录，并将会话的数据文件切换为副本，
+/// 此后 `save`、归档等写操作都只落在临时目录中。
+/// 真实数据暂存在沙盒里，提交或放弃时再切回；临时目录在沙盒结束时删除。
+pub struct Sandbox {
+    real_doc: FamilyDocument,
+    real_data_file: String,
+    real_year: Option<i32>,
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    /// 进入沙盒
+    ///
+    /// # param
+    /// * `label` - 临时目录名前缀，区分沙盒与教程等用途
+    pub fn enter(
+        label: &str,
+        doc: &FamilyDocument,
+        data_file: &mut String,
+        current_year: Option<i32>,
+    ) -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!("zz-sim-{}-{}", label, std::process::id()));
+        fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败：{}", e))?;
+
+        let sandbox_file = dir.join("offspring_tree.json");
+        doc.save(&sandbox_file)
+            .map_err(|e| format!("写入临时副本失败：{}", e))?;
+
+        Ok(Sandbox {
+            real_doc: doc.clone(),
+            real_data_file: std::mem::replace(data_file, sandbox_file.display().to_string()),
+            real_year: current_year,
+            dir,
+        })
+    }
+
+    /// 提交沙盒：保留当前内存中的数据，数据文件切回真实路径
+    ///
+    /// 数据并不会自动写盘，需要再执行 `save`。
+    pub fn commit(self, data_file: &mut String) {
+        *data_file = self.real_data_file.clone();
+    }
+
+    /// 放弃沙盒：恢复进入沙盒前的数据、数据文件与年份
+    pub fn discard(
+        self,
+        doc: &mut FamilyDocument,
+        data_file: &mut String,
+        current_year: &mut Option<i32>,
+    ) {
+        *doc = self.real_doc.clone();
+        *data_file = self.real_data_file.clone();
+        *current_year = self.real_year;
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
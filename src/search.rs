@@ -0,0 +1,159 @@
+use std::fmt::Write;
+
+use crate::model::{FamilyMember, VitalStatus};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 常用姓名用字的拼音首字母，多音字取作姓名时的读音
+///
+/// 只收录姓名中常见的字，不在表中的字不参与首字母匹配。
+const INITIALS: &[(char, &str)] = &[
+    ('a', "阿安爱艾奥傲昂"),
+    ('b', "白柏百包保宝鲍贝北毕碧边卞彬斌冰兵炳伯博薄步卜班邦滨标表别"),
+    ('c', "蔡曹岑柴常昌长陈晨成承程诚澄池迟崇楚褚春纯淳聪从翠才财采彩灿苍策蝉超朝潮辰琛橙驰充初川传创慈辞存"),
+    ('d', "达大代戴丹道德邓狄迪笛典殿丁定东董窦杜端段敦多朵当党岛得登帝第蝶栋冬斗都督度"),
+    ('e', "恩尔二娥鄂"),
+    ('f', "法樊范方芳房飞菲斐费丰风枫峰锋凤冯奉福富傅付甫复馥凡帆繁芬粉奋"),
+    ('g', "甘干刚高戈葛根耿弓公功宫龚巩古谷顾关官管光广贵桂郭国果冠歌革格庚更观归"),
+    ('h', "哈海韩寒汉翰杭浩昊皓郝何和河荷贺赫鹤恒衡弘红宏洪鸿侯后厚胡湖虎花华滑怀欢桓环焕黄晃辉徽惠慧会霍火豪好禾合"),
+    ('j', "姬吉纪季济继佳家嘉贾坚建剑健江姜将蒋交娇焦皎杰洁捷金锦进晋京经晶精景靖静敬九久玖居菊巨君俊峻骏军钧箭鉴菁竞炯"),
+    ('k', "卡开凯康柯珂可克科孔寇库匡奎坤昆阔宽魁"),
+    ('l', "拉来莱赖兰岚蓝澜郎朗浪劳老乐雷蕾磊冷黎李里理力立丽利励莉连莲廉良梁亮辽廖林霖琳麟凌玲灵铃零刘柳六龙隆娄楼卢鲁陆路璐禄露伦罗洛骆吕律绿列烈"),
+    ('m', "马玛麦满曼茂梅美孟梦米密绵苗妙民敏明鸣铭茗莫墨牧慕穆木沐毛卯冒眉媚门萌蒙淼渺闵名默母"),
+    ('n', "娜纳乃南楠男能尼倪年念宁凝牛农诺女"),
+    ('o', "欧鸥"),
+    ('p', "潘盘庞裴佩沛彭鹏朋蓬平萍屏坡珀蒲朴浦普攀培"),
+    ('q', "七戚齐奇祁琪旗启琦绮千迁钱乾潜谦倩强乔桥巧钦秦琴勤青清庆晴琼秋求裘屈曲全泉权群棋骞茜芹沁擎"),
+    ('r', "然冉让饶仁任荣容蓉融如儒汝阮瑞润若锐日柔"),
+    ('s', "萨赛三桑森沙山杉珊善尚绍邵深申沈慎盛圣诗施石时史士世仕市寿书舒淑树双霜水顺舜朔思斯司松宋苏素肃随岁孙笋索胜生声晟升莎少守叔蜀帅爽硕丝嵩颂"),
+    ('t', "塔台太泰谈覃谭汤唐棠陶滕天田甜铁亭庭婷廷通同彤桐童统图涂土团拓陀檀韬涛腾提添恬"),
+    ('w', "瓦万汪王望旺威微薇韦卫未伟玮尉魏温文闻稳翁沃乌吴武伍午舞悟务雾巫婉宛菀皖晚琬维惟唯为蔚渭炜"),
+    ('x', "西希奚溪熙曦习席喜夏仙先贤弦显宪献香湘翔向项萧晓小孝笑肖谢心辛欣新信星兴邢行幸雄熊修秀绣徐许旭宣轩玄薛学雪勋寻循逊夕霞峡纤鲜咸限线乡相祥享效协鑫馨杏性胥须序叙璇萱"),
+    ('y', "丫鸭牙雅亚烟延言岩炎研颜严阎燕扬羊阳杨洋仰养尧姚瑶耀也叶夜一伊依仪宜怡易奕弈益逸意毅翼因音殷银寅尹引隐英应莺樱鹰迎盈莹营颖影映永咏勇用优幽尤由游友有佑右于余鱼俞渔瑜榆虞宇羽雨语玉育郁昱钰裕毓遇御元园员原圆袁源远苑院愿月岳悦越跃云芸允运韵蕴雁妍彦艳宴焰遥野业烨晔冶"),
+    ('z', "杂载宰再在赞藏早枣曾增泽择则扎翟展湛张章彰昭招赵照哲浙贞真珍振镇震征正政郑之芝枝知直志至治智中忠钟仲周洲舟州朱珠竹祝庄壮卓子紫宗邹祖尊左佐作灼姿梓资滋自纵族最遵战"),
+];
+
+/// 一名匹配的成员
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub name: String,
+    pub member_type: String,
+    pub birth_year: i32,
+    pub status: VitalStatus,
+    /// 家主到该成员的路径（含两端）
+    pub path: Vec<String>,
+    /// 按拼音首字母而非姓名中的字匹配
+    pub by_initials: bool,
+}
+
+/// 搜索结果
+///
+/// 由 [`search`] 给出，按先序排列。
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub keyword: String,
+    pub matches: Vec<Match>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl SearchResult {
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.matches.is_empty() {
+            writeln!(out, "没有成员匹配「{}」", self.keyword).unwrap();
+            return out;
+        }
+        writeln!(
+            out,
+            "🔍 找到 {} 名成员匹配「{}」：",
+            self.matches.len(),
+            self.keyword
+        )
+        .unwrap();
+        for m in &self.matches {
+            writeln!(
+                out,
+                "  {}（{}，{} 年生，{}）{}",
+                m.name,
+                m.member_type,
+                m.birth_year,
+                m.status,
+                if m.by_initials {
+                    "  [拼音首字母]"
+                } else {
+                    ""
+                }
+            )
+            .unwrap();
+            writeln!(out, "     {}", m.path.join(" → ")).unwrap();
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 按姓名片段搜索成员
+///
+/// 姓名包含关键字即匹配；关键字全为英文字母时，另按拼音首字母匹配
+/// （不区分大小写，如 `zs`、`ZSm` 可找到"张三"、"张思明"）。
+pub fn search(tree: &FamilyMember, keyword: &str) -> SearchResult {
+    let keyword = keyword.trim();
+    let letters = !keyword.is_empty() && keyword.chars().all(|c| c.is_ascii_alphabetic());
+    let lower = keyword.to_ascii_lowercase();
+
+    let matches = tree
+        .members()
+        .into_iter()
+        .filter_map(|m| {
+            let by_initials = if m.name.contains(keyword) {
+                false
+            } else if letters && initials(&m.name).contains(&lower) {
+                true
+            } else {
+                return None;
+            };
+            let path = tree
+                .path_to(&m.name)
+                .unwrap_or_default()
+                .iter()
+                .map(|p| p.name.clone())
+                .collect();
+            Some(Match {
+                name: m.name.clone(),
+                member_type: m.member_type.to_string(),
+                birth_year: m.birth_year,
+                status: m.status(),
+                path,
+                by_initials,
+            })
+        })
+        .collect();
+
+    SearchResult {
+        keyword: keyword.to_string(),
+        matches,
+    }
+}
+
+/// 姓名的拼音首字母（小写），英文字母原样转小写，不在表中的字记为 `?`
+pub fn initials(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                return c.to_ascii_lowercase();
+            }
+            INITIALS
+                .iter()
+                .find(|(_, chars)| chars.contains(c))
+                .map_or('?', |(initial, _)| *initial)
+        })
+        .collect()
+}
@@ -0,0 +1,4794 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use zz_sim_data_tool::archive::ArchiveWriter;
+use zz_sim_data_tool::catalog::{self, PositionRule, RankBy};
+use zz_sim_data_tool::columns::Column;
+use zz_sim_data_tool::document::{FamilyDocument, FileFormat};
+use zz_sim_data_tool::merge::{self, OnConflict};
+use zz_sim_data_tool::metrics::{self, PerfReport, Record};
+use zz_sim_data_tool::model::{FamilyMember, Gender, Generation, Lineage, MemberType, Relabel};
+use zz_sim_data_tool::protection::{BranchLocks, Guard, RootAction};
+use zz_sim_data_tool::render::{self, Folding, OutputFormat, Renderer, TerminalTable};
+use zz_sim_data_tool::store::{FamilyStore, FileStore};
+use zz_sim_data_tool::undo::UndoHistory;
+use zz_sim_data_tool::{
+    calc, check, columns, diff, edit, explain, find, health, html, import, info, kinship, model,
+    naming, parser, pick, query, search, settings, template, verify, ZzError,
+};
+
+use crate::config::{self, Config};
+use crate::record::{self, Recorder, Step};
+use crate::sandbox::Sandbox;
+use crate::tutorial::Tutorial;
+use crate::{cli, doctor, editor, example, pager, recover, stats, status, theme, watch};
+
+/// 执行后不检查分支锁定的命令
+///
+/// undo、redo、recover 整体替换数据；watch-game 合并后立即写入数据文件，无法撤回。
+const LOCK_EXEMPT: &[&str] = &["undo", "redo", "recover", "watch-game"];
+
+/// 改动成员的命令及其前几个参数为成员
+///
+/// 这些参数只写姓名且重名时拒绝执行（见 [`FamilyMember::require_unique`]），
+/// 以免改到先序第一处的同名成员。
+const MEMBER_TARGETS: &[(&str, usize)] = &[
+    ("add", 1),
+    ("position", 1),
+    ("delete", 1),
+    ("move", 2),
+    ("merge-members", 2),
+    ("rename", 1),
+    ("edit", 1),
+    ("die", 1),
+    ("missing", 1),
+    ("found", 1),
+    ("inherit", 1),
+    ("lock", 1),
+    ("unlock", 1),
+];
+
+const HELP_TEXT: &str = r#"================== 祖宗模拟器帮助 ==================
+命令列表:
+    help
+      显示此帮助信息
+
+    exit | quit
+      退出程序
+
+    count
+      显示家族成员总数（忽略已标记死亡者）
+
+    exists <姓名> [--everywhere]
+      检查某个家族成员是否存在
+      加 --everywhere 时同时搜索回收站与归档，并报告所在位置
+
+    find <条件>... [--columns <列,列,...> | --profile <方案>] [--sort <列>] [--desc] [--as-of <年份>]
+      按条件筛选成员，以 list 的表格输出。条件写作 <字段><运算符><值>，多个条件须同时满足，
+      运算符为 = != > >= < <=。字段：gen（代际，可比远近，如 gen>=孙）、gender（男/女）、
+      lineage（内系/外系）、dead、missing（true/false）、position（职位，* 为任意职位，- 为无职位）、
+      power、birth、children、age（需设置年份）。
+      示例：find gen=孙 dead=false power>3 position=将军 birth<1250
+
+    search <关键字>
+      按姓名片段查找成员，列出全部匹配者及家主到其的路径。
+      关键字全为英文字母时另按拼音首字母匹配（如 zs 可找到张三；只收录常用姓名用字）
+
+    show [<姓名>] [--alive | --dead] [--depth <代数>] [--expand <姓名>]... [--all] [--positions-at <年份>]
+      不带参数显示整个家族树，或展示指定成员的所有后代，按 format 设置的格式输出。
+      --alive 只显示未故成员（含下落不明），--dead 只显示已故成员，其先辈只列姓名以保留树形。
+      --depth 只显示起点以下若干代，更深的后代在表格中合并为 `… 以下 N 人`。
+      终端表格着色：已故暗淡、家主加粗、外系青色、职位黄色、下落不明品红
+      （config set color off、--no-color 或环境变量 NO_COLOR 可关闭）。
+      设置了 fold 时，表格中子嗣过多的成员只显示前几人，其余折叠为 `… 其余 N 人`；
+      --expand 展开指定成员的全部子嗣（可重复），--all 不折叠。
+      设置了 year 时，有任职记录的成员显示该年担任的职位；--positions-at 显示
+      指定年份的职位并列出当年在任者。没有任职记录的成员按当前职位显示
+
+    fold [<人数> | off]
+      查看或设置 show 中每人最多显示的子嗣数（各层相同），随数据文件保存。
+      仅作用于终端表格，json、csv、markdown 始终输出全部成员
+
+    format [table|json|csv|markdown]
+      查看或设置输出格式，默认为终端表格 table
+
+    add [<姓名> ['<JSON 数组>' | --from <文件> | --edit]]
+      交互式为指定成员添加子嗣：逐项询问姓名、出生年、性别与威望加成，
+      称谓按父辈自动推算，确认后添加，可接着添加下一名；在姓名处输入 json
+      改为粘贴 JSON 数组。也可直接在参数中给出成员姓名与 JSON 数组（批处理模式须用此形式）。
+      --from 从文件读取 JSON 数组；--edit 用 $EDITOR 打开预填了称谓与出生年的模板，
+      保存退出后添加，出错时可重新打开修改，适合一次添加一大批新生儿
+
+      JSON 格式示例（称谓按父辈推算，只需给出性别，默认为男）:
+      [{"name":"张小明","gender":"男","birth_year":2000,"hoser_power_add":5,"children":[]}]
+
+    save [json|yaml|msgpack]
+      将当前内存中的家族数据保存到数据文件（--file 或 ZZ_SIM_FAMILY_DATA 等指定）。
+      文件格式由扩展名决定：.yaml/.yml 为 YAML，.msgpack/.mpk 为 MessagePack
+      （二进制，适合成员很多的家族），.json 为 JSON；其他扩展名按环境变量
+      ZZ_SIM_FAMILY_FORMAT 决定，未设置时为 JSON。
+      指定与当前不同的格式时另存为同名的新扩展名文件并改用新文件，原文件保留。
+      不启动会话转换文件：zz-sim-data-tool convert <输入文件> <输出文件>
+
+    verify-save
+      重新读取数据文件，与内存中的数据逐字段比较并列出差异（成员按姓名定位），
+      用于怀疑写入不完整或文件被外部修改时检查。不一致时批处理模式以失败退出
+
+    position <姓名> <职位> [--force]
+      为成员设置职位称谓。若职位目录中有任职限制（年龄、在世），
+      不满足时拒绝任命，加 --force 可强制任命。已设置年份时记入任职履历
+
+    positions vacancies
+      列出职位目录中没有在世成员担任的职位，以及已故的前任
+
+    position succeed <职位> [--by generation|age|power] [--assign]
+      列出出缺职位的继任人选：在世、未任职且满足任职条件的成员。
+      前任的后代优先，其余按代际（默认）、年龄或威望排序。
+      --assign 由第一位直接继任。任职者去世时会提示出缺
+
+    catalog [add <职位> [--min-age <岁>] [--alive-only] | remove <职位>]
+      查看或编辑职位目录中的任职限制，年龄按 year 设置的当前年份计算
+
+    year [<年份>]
+      不带参数时显示当前年份，带参数时更新年份状态
+
+    summary
+      显示家族概览：族名、家训、家主与成员规模
+
+    status [--json]
+      显示会话运行状态：数据文件、有无未保存改动、当前年份、会话模式、
+      输出格式、归档数与后台写入数。--json 输出单行 JSON，便于脚本解析
+
+    meta [<字段> <值> | clear <字段>]
+      查看或编辑家族元信息，字段：surname（族名）、motto（家训）、
+      founding_year（始建年份）、notes（备注）
+
+    check [power-range [<下限> <上限> | off]]
+      全面校验家族树：出生年无效、子嗣早于父辈出生、称谓代际与所在深度不符、内外系与经由的
+      性别不符、重名、ID 重复（错误）；已故父辈在其满 100 岁后才有子嗣、威望加成超出设定范围（警告）。
+      数据中没有卒年，已故父辈一项只是按年龄推断，100 岁前去世的查不出来。
+      每条结果标出严重程度与从家主起的路径。有错误时批处理模式以失败退出。
+      doctor 以及 edit-file、merge-members 之后的检查与此使用同一套规则。
+      称谓代际与内外系的问题可用 fix 自动修正。
+      power-range 查看或设置威望加成的合理范围，随数据文件保存；未设置时不检查威望
+
+    fix [--dry-run]
+      按树深度重算每名成员称谓的代际，按经由的性别重算内外系（只保留各人的性别），
+      修正手工编辑数据文件后不一致的称谓。先列出将更正的成员，确认后执行；
+      --dry-run 只预览不修改
+
+    stats [<姓名>] [--as-of <年份>] [--cohort [--csv [<文件>]] | --names | --health [--trend]]
+      统计全家族或指定成员一支：在世/已故人数、各代人数、男女比、
+      内系/外系构成、平均子嗣数与威望合计。
+      --cohort 按代际对比人均子嗣、已故比例、外系占比、平均威望与在世均龄，
+      并给出最早一代到最近完整一代的变化，用于判断家族是否衰落；
+      --csv 以 CSV 输出，可写入文件
+      --names 列出各代名字中最常用的字，并找出只差一字、出生年份相近的
+      疑似重复录入，附合并建议
+      --health 计算家族健康度（0–100）：在世比例、符合继承规则的在世人数、
+      在世成员平均威望与壮年（16–60 岁）比例的加权平均，各项得分一并列出；
+      --trend 对每个归档年份与当前数据分别计算，列出走势
+
+    health-weights [<项> <权重>]
+      查看或设置 stats --health 各项的权重（living、heirs、power、age，默认各 25），
+      随数据文件保存
+
+    age <姓名> [--as-of <年份>]
+      显示成员在当前年份（或指定年份）的年龄
+
+    calc <表达式> [--as-of <年份>]
+      计算年份与年龄，支持 + - * /（整除）、括号，以及 birth(姓名) 出生年、
+      age(姓名) 年龄、power(姓名) 威望加成、year 当前年份。例：
+        calc 1923 - birth(张三)
+        calc age(张三) + 20
+        calc birth(张三) + 16 - year
+
+    compare-branches <姓名> <姓名>
+      并列对比两支（如家主的两个儿子各自一房）：成员数、在世人数、
+      在世成员平均年龄（需先设置 year）、威望合计与在任职位。
+      两支不能相互包含
+
+    info <姓名>
+      显示一名成员的全部资料：称谓、性别与血统、对家主的称呼、出生年与年龄（需设置年份）、
+      状态、职位与任职记录、威望、父辈、子嗣与后代人数，以及从家主到本人的世系
+
+    path <姓名>
+      显示家主到指定成员的路径
+
+    ancestors <姓名>
+      自下而上列出成员的父辈、祖辈直至家主，含称谓、出生年与是否在世，
+      并指出属于家主哪一房；外系成员另指出经哪位女性成员传下
+
+    siblings <姓名>
+      按出生先后列出同一父辈的兄弟姐妹，标出长幼称呼与本人的排行
+
+    cousins <姓名>
+      按出生先后列出堂表兄弟姐妹（祖父辈其他子嗣的子嗣），标出称呼与所属的父辈
+
+    relationship <甲> <乙>
+      推算乙对甲的称呼（如 叔父、堂兄、外甥女），并列出最近共同祖先及两人各在其下第几代。
+      家谱只记录一方父辈，关系较远时只给出共同祖先与辈分差
+
+    list [--columns <列,列,...> | --profile <方案>] [--sort <列>] [--desc] [--as-of <年份>]
+      平铺列出全部成员。--columns 选择列（默认与 show 相同），可选：
+      name, birth, type, status, position, power, children, parent，
+      以及派生列 age（年龄，需设置 year）、descendants（后代人数）、
+      subtree_power（本支威望合计）、depth（层级）、id（成员 ID）。
+      --sort 按某列升序排序，加 --desc 降序。--profile 使用 profile 保存的列方案
+
+    list <代际> [...]
+      只列出某一代（如 list 曾孙，外系同代一并列出），默认按出生年排序并显示父辈，
+      表后列出其中在世的成员，便于挑选联姻、任职人选。其余参数同上
+
+    --as-of <年份>
+      stats、age、calc、list、why-not-heir 可加此参数，按假设的年份求值而不改变会话年份：
+      该年之后出生的成员视为不存在，年满 100 岁的成员视为已故
+
+    profile [save <方案> <列,列,...> | remove <方案>]
+      查看或编辑命名的列方案，随数据文件保存，所有协作者共用
+
+    calendar [<偏移> [<纪年名>] | off]
+      设置游戏内纪年：游戏内年份 = 数据年份 + 偏移，显示年份时一并给出。
+      随数据文件保存
+
+    query '<JSONPath>' [--json]
+      用 JSONPath 查询数据文件的 JSON 内容，结果以表格或 JSON（--json）输出。
+      支持 .字段、..字段（递归）、[n]、[*] 与过滤 [?(@.字段 运算符 值)]，
+      条件可用 && / || 组合，例如：
+        query '$.children[?(@.birth_year>1220)].name'
+        query '$..children[?(@.is_dead==false && @.hoser_power_add>=2)]'
+
+    prune
+      删除当前年份之后出生的成员（需先设置 year，操作会二次确认）
+      被删除的成员会移入回收站，随 save 一并保存
+
+    delete <姓名>
+      删除成员及其全部后代，删除前显示将移除的人数并二次确认（需先设置 year）。
+      被删除的分支移入回收站；家主不可删除
+
+    move <姓名> <新父辈>
+      将成员连同其后代移到另一成员名下，按新父辈重新推算整支的称谓并汇总变化。
+      家主不可移动，也不能移到自己的后代名下
+
+    merge-members <保留> <移除>
+      同一人被录入两次时合并：<移除> 的子女（称谓按 <保留> 重算）与任职记录
+      移到 <保留> 名下，<保留> 没有的职位与 ID 一并补上，随后移除 <移除>。
+      合并前重新校验家族树，列出合并后新出现的问题（如子女早于新父辈出生）。
+      执行前需确认，可用 undo 撤销
+
+    config [reload | watch <on|off> | get <键> | set <键> <值> | unset <键>]
+      reload 重新读取数据文件中的配置（职位目录、规模限制、家主保护策略、继承规则、纪年、
+      列方案与 fold 等显示设置）与下述用户配置文件并列出变化，家族树与未保存的成员改动不受影响。
+      数据文件中的设置为全族共享，用户配置不会覆盖它们。
+      默认在数据文件或用户配置文件被外部修改后自动重新加载，watch off 关闭。
+      不带参数时同时列出用户配置（~/.config/zz-sim/config.toml，
+      设置了 XDG_CONFIG_HOME 时在其下）：
+        data_file     默认数据文件（--file 与环境变量优先）
+        count_dead    count 是否计入已故成员，on|off
+        confirm       交互模式下是否询问确认，on|off
+        archive_dir   归档目录，相对路径相对于数据文件所在目录
+        language      界面语言，目前只有 zh
+        autosave      默认自动保存条数，或 off
+        pager         交互模式下超过一屏的 show、list、find 输出是否分页，on|off
+                      （设置了 PAGER 时交给它显示，否则逐屏显示）
+        color         终端支持时 show 表格是否着色，on|off（--no-color 或 NO_COLOR 也可关闭）
+        columns.<列>  show 表格列宽上限（按内容自动收窄，超出终端宽度时截断）：name birth type status position power children
+      get <键> 查看，set <键> <值> 修改并写回文件（文件中的注释不会保留），
+      unset <键> 恢复默认值。data_file 与 language 下次启动时生效
+
+    autosave [<条数> | off]
+      查看或设置自动保存：每累计指定条数改动数据的命令后自动 save，仅本次会话有效。
+      有未保存的改动时，提示符显示 *，exit 或 Ctrl+D 退出前会询问是否保存
+
+    theme [emoji|ascii|minimal]
+      查看或切换输出中状态符号（✅ ❌ ⚠️ 等）的主题，仅本次会话有效：
+      ascii 换成 [OK]、[ERROR] 等纯 ASCII 标记并用 ASCII 制表线，适合显示不了 emoji 的终端；
+      minimal 只保留 OK、ERROR 等成败标记，适合写入日志。
+      只影响状态消息与表格框线，成员数据与导出内容（JSON、CSV 等）原样输出。
+      启动时按环境变量 ZZ_SIM_THEME 选择，未设置时按终端自动判断
+
+    export csv [<文件>]
+      导出成员平铺表，每人一行：姓名、出生年、代际、性别、血统、称谓、职位、威望、
+      是否已故、父辈、深度与子嗣数。写入文件时带 UTF-8 BOM，可直接用 Excel 打开
+
+    export dot [<文件>]
+      导出 Graphviz DOT 家谱图，不指定文件时输出到终端。节点按代际着色、同代对齐，
+      家主加粗高亮，已故成员灰显，外系成员与其连线为虚线。
+      渲染示例：dot -Tsvg family.dot -o family.svg
+
+    export mermaid [<文件>]
+      导出 Mermaid 流程图（graph TD），含出生年、称谓与职位，已故成员标 † 并灰显，
+      外系连线为虚线。输出包在 ```mermaid 代码块中，可直接粘贴到 Obsidian、GitHub
+      等 Markdown 笔记；文件名以 .mmd 结尾时输出不带代码块的原文
+
+    export gedcom [<文件>]
+      导出 GEDCOM 5.5（UTF-8），可导入 Gramps 等家谱软件。出生年、是否已故、
+      性别（取自称谓）与职位（记为职业）对应到标准字段，称谓写入备注。
+      数据中没有配偶与卒年，导出时留空
+
+    export md [<姓名>] [<文件>]
+      导出 GitHub 风格的 Markdown 表格，列同 show 的终端表格，姓名前以全角空格与 └
+      标示层级，可直接粘贴到游戏日志。指定姓名时只导出此人一支，不指定文件时输出到终端
+
+    export html <目录>
+      在目录中生成可独立打开的 HTML 报告 index.html：汇总统计、可折叠的家族树，
+      以及每位成员的资料卡（出生年、称谓、状态、职位、威望、父辈与子嗣），
+      点击树中的姓名跳到资料卡。设置了年份时标出在世成员的年龄，便于分享给不用命令行的人
+
+    export svg [<文件>]
+      直接生成 SVG 家谱图，无需安装 Graphviz：按代分层，方框内为姓名、出生年、
+      称谓与职位，着色同 dot 导出，外系成员的方框与连线为虚线。可用浏览器打开
+
+    export <dot|mermaid|svg> ... --collapse-dead [--min-generation <代数>]
+      将全部已故的远支折叠为一个汇总节点（如"张丽一支（已故，共 12 人）"），
+      让分享的家谱图更易读；只影响导出，数据文件保留全部成员。
+      --min-generation 指定距家主至少几代的分支才折叠，默认 1
+
+    diff <年份A> <年份B> [--dot <文件>]
+      比较两个年份的归档，列出新增、移除与变更的成员（出生年、称谓、在世、职位、
+      威望与父辈）。加 --dot 时另导出 Graphviz DOT 差异图：新增为绿色，移除为红色
+      并以虚线挂在原父辈下，变更为黄色并标出变化，便于直观核对继承前后的变动
+
+    report <模板文件> [<输出文件>]
+      按自定义模板生成族谱、名册等报表，不指定输出文件时输出到终端。
+      模板语法为 Handlebars 的常用子集：{{路径}} 输出值，{{#each 路径}}…{{/each}}
+      遍历（块内有 @index、@first、@last），{{#if 路径}}…{{else}}…{{/if}}、
+      {{#unless 路径}}…{{/unless}} 条件，{{! 注释}}；输出不做 HTML 转义。
+      可用数据：meta（surname、motto、founding_year、notes）、stats（同 stats 命令，
+      by_generation 为 [代际, 人数]）、year、root（家主）、members（先序平铺的全部成员：
+      name、birth_year、type、generation、gender、lineage、status、is_dead、position、
+      power、age、depth、indent、parent、children、child_count、descendants、
+      position_history）。例：
+        {{meta.surname}}氏族谱（共 {{stats.total}} 人）
+        {{#each members}}
+        {{indent}}{{name}}（{{birth_year}}，{{type}}）{{#if is_dead}} †{{/if}}
+        {{/each}}
+
+    record [start <文件> | stop]
+      录制会话，用于报告难以重现的问题：start 时写入当前数据的完整副本，
+      此后每条命令连同输出、确认时的应答与执行前后的数据哈希逐条写入文件。
+      不带参数时显示录制状态
+
+    replay <文件> [--show]
+      在录制数据的临时副本上重新执行录制的命令（不影响当前数据与数据文件），
+      确认时使用录制的应答，逐条比较数据哈希、成败与输出，列出与录制不同的命令。
+      --show 同时显示各命令的输出
+
+    undo [--list] | redo
+      撤销或重做上一条改动数据的命令（add、prune、die、rename、inherit 等均可），
+      最多保留 50 步，仅在本次会话内有效，保存前可随时撤销。
+      --list 列出可撤销与可重做的命令。已写入的归档与数据文件不会随之撤销；
+      进出沙盒或教程时历史清空
+
+    suggest-prune [--older-than <年数>]
+      找出全部成员已故、且最晚出生者距当前年份已满指定年数（默认 60）的分支，
+      报告可缩减的人数，确认后移入回收站（需先设置 year）
+
+    rename <旧名> <新名>
+      重命名成员。对家主改名时按保护策略处理
+
+    edit <姓名> [<字段> <值>]
+      修改成员的字段：birth_year 出生年、power 威望加成、position 职位（- 为无职位）、
+      member_type 称谓、dead 是否已故（true/false），也可写中文字段名，如 edit 张三 出生年 1234。
+      出生年须不早于父辈、不晚于子嗣；称谓的代际须与所在位置一致，改动后重算后代称谓。
+      只给姓名时逐项询问新值，直接回车保留原值。标记家主已故时按保护策略处理
+
+    die <姓名>
+      将成员标记为死亡。对家主执行时按保护策略处理。也用于了结下落不明的成员
+
+    missing <姓名> [<年份>]
+      将成员标记为自某年（默认当前年份）起下落不明。下落不明者不算已故，
+      统计中计入在世，但不能继承家主位
+
+    found <姓名>
+      寻回下落不明的成员，恢复为在世
+
+    protect [die|rename <allow|prompt|refuse>]
+      查看或设置家主保护策略：allow 直接执行；refuse 拒绝执行；
+      prompt（默认）执行后提示——家主死亡时询问继承人，改名时询问是否更新族名。
+      家主不可删除
+
+    lock [<姓名>]
+      锁定以该成员为顶端的一支（含全部子孙，按成员 ID 记录并随数据文件保存，改名后仍然有效），不加参数列出锁定的分支。
+      改动锁定分支中成员、移走或删除该支的命令都会被拒绝，命令末尾加 --unlock 放行。
+      继位时称谓随之调整不算改动；undo、redo、recover、watch-game 不受锁定限制
+
+    unlock <姓名>
+      解除锁定
+
+    clear
+      清空终端显示
+
+    inherit <姓名>
+      在 archives/offspring_tree_<年份>.json 归档后，让成员继承家主。
+      归档在后台写入，完成后提示；内容与已有归档相同时跳过写入。
+      需先执行 year 设置年份，继承人须符合 succession 规则（默认两代以内）。
+      继位后按新家主重算全部称谓，并汇总变化（如 3 名成员由 孙 → 儿）。
+
+    explain <命令>
+      试执行一条改动命令而不改动数据：列出涉及成员的路径、将改变的字段，
+      以及适用的家主保护、继承规则、任职条件与规模上限，说明命令是否会被拒绝。
+      支持 add <姓名> '<JSON>'、position、prune、delete、move、rename、die、inherit
+
+    recover [<归档年份>] [--keep-trash]
+      继位失败或中途中断后的恢复向导：找到最近一份可读取的归档（继位前的快照），
+      说明归档、数据文件与内存中的数据各自处于继位前还是继位后，列出差异，
+      确认后以归档替换当前数据（可 undo 撤销，save 后写入数据文件）。
+      回收站随归档一并恢复为归档时的状态；--keep-trash 另保留归档之后删除、
+      归档中也没有的成员，以便之后仍能找回
+
+    edit-file
+      用 $VISUAL 或 $EDITOR 打开整份数据（写入临时文件，格式同数据文件，MessagePack 改用 JSON）。
+      编辑器退出后校验：无法解析或有重名时说明原因并可重新打开修改；
+      通过后列出成员与其他字段的变化及新出现的校验问题，确认后替换当前数据
+      （可 undo 撤销，save 后写入数据文件）
+
+    why-not-heir <姓名> [--as-of <年份>]
+      逐条列出成员是否符合各项继承规则（代际、在世、直系、年龄），说明能否继承
+
+    pick [--filter <条件>]... [--weight power|age|uniform] [--seed <种子>] [--as-of <年份>]
+      从满足全部条件的成员中随机抽取一人（分家产、抽签继承等家规），列出候选、
+      各自的权重与点数区间，以及抽中的点数。条件可重复：alive、dead、male、
+      female、direct、foreign、gen=<代际>、under=<姓名>（后代）、age>=<岁>、
+      age<=<岁>；未指定 alive/dead 时只抽在世成员。--weight power 按威望加成
+      （不大于 0 者不参与），age 按年龄（需设置 year），默认均等。
+      相同的数据、条件与 --seed 总是抽中同一人
+      例：pick --filter under=张大 --filter gen=孙 --weight power
+
+    succession [max-gen <代数> | alive-only|direct-only <on|off> | min-age <岁|off>]
+      查看或设置继承规则，随数据文件保存。默认只要求两代以内，其余规则关闭
+
+    import positions <CSV 文件> [--quarantine]
+      批量导入任职记录，每行格式为 year,name,title（可带表头）。
+      任一行校验失败（成员不存在、早于出生、时间顺序颠倒）则整批不导入
+
+    import branch <CSV 文件> [--quarantine]
+      从 CSV 导入一整支成员，每行格式为 name,birth_year,parent,gender,power
+      （可带表头，性别为 男/女，威望可省略）。父辈可以是已有成员或文件中的另一行，
+      行序不限，称谓由父辈推算。找不到父辈、重名、早于父辈出生的行逐条报告，
+      任一行出错则整批不导入
+
+    import members <JSON 文件> [--on-conflict skip|update|error] [--quarantine]
+      将数据文件或游戏导出合并进家族树，并列出去重报告：
+      + 新增、~ 更新、= 已存在而跳过、⚠️ 冲突（如重名）。
+      已存在成员的状态、职位或威望不同时：update 以导入数据为准（默认），
+      skip 保留现有数据，error 视为冲突并整批不导入
+
+    以上三种导入加 --quarantine 时进入隔离模式：有误的记录（年份无效、找不到父辈、
+      字段无法解析等）不再导致整批失败，其余记录照常导入，有误的记录连同原因
+      写入被导入文件同目录下的 import_rejects.json，修正后可再次导入
+
+    watch-game <目录> [--on-conflict skip|update|error]
+      持续监视游戏导出目录，新出现的 JSON 文件会自动合并进家族树：
+      同一父辈下的同一成员（优先按 id，否则按姓名+出生年+祖先链识别）
+      按 --on-conflict 处理（默认更新状态、职位与威望），新成员连同子孙加入。
+      每次导入前归档到 archives/offspring_tree_<年份>.json，导入后自动保存。
+      需先执行 year 设置年份，按回车结束监视
+
+    limits [living|children <数量|off>]
+      查看或设置家族规模软限制：在世成员上限（默认 500）、单人子嗣上限（默认 20）。
+      启动时及每次操作后检查，超限时给出警告
+
+    perf [on | off | report [--top <条数>]]
+      本地指标记录（默认关闭，也可设置环境变量 ZZ_SIM_METRICS=1 开启）：
+      每条命令的关键字、耗时与当时的家族规模追加到数据文件旁的
+      <数据文件名>.metrics.jsonl，不记录参数。report 按命令汇总耗时，
+      并列出最慢的若干次执行（默认 10 次）
+
+    tutorial | tutorial exit
+      交互式教程：在临时副本上依次练习 show → add → position → die → save → inherit，
+      每步完成后自动检查，全程不会改动真实数据文件
+
+    sandbox | sandbox commit | sandbox discard
+      进入沙盒：在临时副本上试验任意命令，save 只写入临时文件。
+      commit 保留改动（之后需 save 写入数据文件），discard 放弃全部改动
+
+    example [<路径>] [--size <人数>]
+      生成一份示例家族数据（3~4 代，含外系、已故与任职成员），默认 20 人，
+      写入指定路径（默认 ./offspring_tree.json）。
+      首次使用时可直接运行 `zz-sim-data-tool example`
+
+    doctor
+      诊断环境变量、数据文件、归档目录与数据内容，并给出修复建议。
+      环境未配置时可直接运行 `zz-sim-data-tool doctor`
+
+提示:
+  - 输入命令时不区分大小写
+  - 含空格的参数可用引号括起，如 position 张三 "镇西 大将军"；支持 '…'、"…"、“…” 与 \ 转义
+  - 有重名成员时查看类命令只写姓名指先序第一处；改动成员的命令（add、position、rename、
+    edit、die、missing、found、inherit、delete、move、merge-members、lock、unlock）
+    不接受重名的姓名，需用路径或 #ID 指定：张二/张三 为【张二】名下的【张三】，
+    可写多级，首段不必是家主；载入及之后出现重名时会提示各处的写法
+  - 每名成员有不变的 ID（自增整数，保存在数据文件中），凡是要求姓名处都可写 #ID，
+    如 show #12；改名后 ID 不变，外部工具可据此引用成员。ID 见 info 与 list --columns id
+  - Tab 补全命令，以及 exists、show、path、die、rename、position、inherit 后的成员姓名
+  - 上下方向键翻阅命令历史，Ctrl+R 搜索历史；历史保存在 ~/.zz-sim-data-tool_history
+  - 输入 exit 或按 Ctrl+D 可以退出
+  - 图形前端可运行 `zz-sim-data-tool --frontend`：无提示符，每行一条命令，
+    每条回复为 `<字节数>\n<JSON>\n`，内含命令的输出；
+    命令与 REPL 相同，需确认的操作视为已确认
+  - 脚本中可运行批处理模式，不显示欢迎语与提示符，任一命令失败即停止并以状态 1 退出：
+      zz-sim-data-tool --exec "year 1260" --exec "die 张三" --exec save
+      zz-sim-data-tool --script 命令文件   （- 表示标准输入，# 开头为注释）
+    需确认的操作（delete、prune、inherit 等）默认视为取消，加 --yes 自动确认
+===================================================="#;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 命令执行器：REPL 与批处理模式共用的会话状态
+pub struct Session {
+    pub doc: FamilyDocument,
+    /// 数据的存储后端，数据文件切换（沙盒、教程、save 换格式）时随之重建
+    pub store: Box<dyn FamilyStore>,
+    /// 存储位置，文件存储时为数据文件路径
+    pub data_file: String,
+    pub current_year: Option<i32>,
+    pub output_format: OutputFormat,
+    pub archiver: ArchiveWriter,
+    pub tutorial: Option<Tutorial>,
+    pub sandbox: Option<Sandbox>,
+    /// 需要确认的操作如何作答
+    pub confirm: Confirm,
+    /// 用户配置（列宽、count 是否计入已故、归档目录等），由 [`configure`](Self::configure) 设置
+    pub config: Config,
+    /// 是否在数据文件旁记录命令耗时（`perf on` 或环境变量 ZZ_SIM_METRICS）
+    pub metrics: bool,
+    started: Instant,
+    commands_run: usize,
+    /// 撤销/重做历史
+    history: UndoHistory,
+    /// 上次保存以来改动数据的命令数，为 0 时没有未保存的改动
+    unsaved: usize,
+    /// 每累计多少条改动自动保存，`None` 为关闭
+    autosave: Option<usize>,
+    /// 数据文件变化时自动重新加载配置
+    config_watch: bool,
+    /// 上次检查时数据文件的修改时间
+    config_mtime: Option<SystemTime>,
+    /// 上次检查时用户配置文件的修改时间
+    user_config_mtime: Option<SystemTime>,
+    /// 上一条命令的关键字，供教程检查步骤
+    last_command: String,
+    /// 已提示过的规模与重名警告的项（见 [`Exceeded::key`](zz_sim_data_tool::limits::Exceeded::key)），
+    /// 之后只提示新出现的项
+    shown_warnings: Vec<String>,
+    /// 正在进行的会话录制（`record start`）
+    recorder: Option<Recorder>,
+    /// 当前命令是否打印了错误或用法提示，由 `fail!` 设置
+    failed: bool,
+}
+
+/// 需要确认或补充输入时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirm {
+    /// 交互式询问
+    Ask,
+    /// 交互模式，但配置关闭了确认（`confirm = off`）：全部确认，补充输入仍可询问
+    Auto,
+    /// 批处理模式加 --yes：全部确认
+    Yes,
+    /// 批处理模式：全部拒绝
+    No,
+}
+
+/// 一条命令的执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Done,
+    /// 打印了错误或用法提示，批处理模式据此以非零状态退出
+    Failed,
+    /// exit / quit
+    Exit,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+/// 打印失败消息并把当前命令记为失败
+///
+/// 失败消息多以状态符号开头，按主题替换；`$failed` 为会话的 `failed` 字段。
+macro_rules! fail {
+    ($failed:ident, $($arg:tt)*) => {{
+        *$failed = true;
+        statusln!($($arg)*);
+    }};
+}
+
+impl Session {
+    pub fn new(doc: FamilyDocument, store: Box<dyn FamilyStore>, confirm: Confirm) -> Self {
+        let data_file = store.location();
+        let config_mtime = modified(&data_file);
+        Session {
+            doc,
+            store,
+            data_file,
+            current_year: None,
+            output_format: OutputFormat::default(),
+            archiver: ArchiveWriter::new(),
+            tutorial: None,
+            sandbox: None,
+            confirm,
+            config: Config::default(),
+            metrics: false,
+            started: Instant::now(),
+            commands_run: 0,
+            history: UndoHistory::default(),
+            unsaved: 0,
+            autosave: None,
+            config_watch: true,
+            config_mtime,
+            user_config_mtime: config::config_path().and_then(modified),
+            last_command: String::new(),
+            recorder: None,
+            shown_warnings: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// 应用用户配置：自动保存条数、交互模式下是否确认、归档目录
+    pub fn configure(&mut self, config: Config) {
+        self.autosave = config.autosave;
+        self.confirm = match (self.confirm, config.confirm) {
+            (Confirm::Ask, false) => Confirm::Auto,
+            (Confirm::Auto, true) => Confirm::Ask,
+            (confirm, _) => confirm,
+        };
+        self.config = config;
+        self.store = Box::new(file_store(
+            &self.config,
+            &self.data_file,
+            self.sandbox.is_some() || self.tutorial.is_some(),
+        ));
+    }
+
+    /// 执行一行命令
+    pub fn execute(&mut self, line: &str) -> Outcome {
+        // record 命令本身不录入
+        let recording = self.recorder.is_some()
+            && line.split_whitespace().next().map(str::to_lowercase) != Some("record".to_string());
+        if !recording {
+            return self.run(line);
+        }
+
+        let before = record::tree_hash(&self.doc);
+        record::begin(false);
+        let outcome = self.run(line);
+        let (output, input) = record::finish();
+        let step = Step {
+            command: line.trim().to_string(),
+            input,
+            output,
+            outcome: outcome.label().to_string(),
+            before,
+            after: record::tree_hash(&self.doc),
+        };
+        if let Some(Err(e)) = self.recorder.as_mut().map(|r| r.append(&step)) {
+            statusln!("⚠️  {}，已停止录制", e);
+            self.recorder = None;
+        }
+        outcome
+    }
+
+    fn run(&mut self, line: &str) -> Outcome {
+        let line = line.trim();
+        let tokens = match parser::tokenize(line) {
+            Ok(tokens) if !tokens.is_empty() => tokens,
+            Ok(_) => return Outcome::Done,
+            Err(e) => {
+                statusln!("❌ 命令解析失败：{}", e);
+                return Outcome::Failed;
+            }
+        };
+        let command = tokens[0].to_lowercase();
+        let mut args: Vec<&str> = tokens[1..].iter().map(String::as_str).collect();
+        self.last_command = command.clone();
+
+        // --unlock 对所有改动命令生效，在此统一取出
+        let unlock = args.contains(&"--unlock");
+        args.retain(|a| *a != "--unlock");
+
+        self.poll_config();
+
+        if let Err(e) = self.require_unique_targets(&command, &args) {
+            statusln!("❌ {}", e);
+            return Outcome::Failed;
+        }
+
+        // 能试执行的命令先检查锁定，避免确认、归档之后才被拒绝
+        if !unlock && explain::COMMANDS.contains(&command.as_str()) {
+            let mut tokens = args.clone();
+            tokens.insert(0, &command);
+            if let Ok(plan) = explain::plan(&self.doc, &tokens, self.current_year) {
+                if !plan.locked.is_empty() {
+                    statusln!("❌ {}", BranchLocks::refusal(&plan.locked));
+                    return Outcome::Failed;
+                }
+            }
+        }
+
+        // 改动前的快照
+        let before = self.doc.clone();
+        let data_file = self.data_file.clone();
+
+        let start = Instant::now();
+        self.failed = false;
+        let flow = self.dispatch(&command, args, line);
+        let mut failed = self.failed;
+        // 新添加的成员随即有 ID，下一条命令即可用 #ID 指定
+        self.doc.assign_ids();
+        if data_file != self.data_file {
+            self.store = Box::new(file_store(
+                &self.config,
+                &self.data_file,
+                self.sandbox.is_some() || self.tutorial.is_some(),
+            ));
+        }
+
+        // 其余命令（导入、合并等）执行后检查，改动了锁定分支则撤回
+        let violated =
+            if unlock || LOCK_EXEMPT.contains(&command.as_str()) || data_file != self.data_file {
+                Vec::new()
+            } else {
+                before
+                    .locked_branches
+                    .violations(&before.tree, &self.doc.tree)
+            };
+
+        if !violated.is_empty() {
+            statusln!("❌ {}", BranchLocks::refusal(&violated));
+            outln!("   本条命令的改动已撤回");
+            self.doc = before;
+            failed = true;
+        } else if command == "save" || command == "watch-game" {
+            // save 切换格式时数据文件随之改名，内容不变，历史仍然适用
+            self.sync_unsaved();
+        } else if data_file != self.data_file {
+            // 进出沙盒或教程，历史不再适用
+            self.history.clear();
+            self.sync_unsaved();
+        } else if !self.doc.same_content(&before) {
+            self.doc.locked_branches.retain_existing(&self.doc.tree);
+            if command == "undo" || command == "redo" {
+                // undo/redo 自身不记入历史；撤销回保存时的状态即没有未保存的改动
+                self.sync_unsaved();
+            } else {
+                self.history.record(line, before);
+                self.unsaved += 1;
+                self.autosave();
+            }
+        }
+        self.commands_run += 1;
+        if self.metrics {
+            self.record(Record::command(
+                &command,
+                start.elapsed(),
+                self.doc.tree.size(),
+                self.doc.tree.total_count(),
+                !failed,
+            ));
+        }
+
+        match flow {
+            ControlFlow::Break(()) => Outcome::Exit,
+            ControlFlow::Continue(()) if failed => Outcome::Failed,
+            ControlFlow::Continue(()) => Outcome::Done,
+        }
+    }
+
+    /// 数据文件或用户配置文件被修改（协作者或外部编辑）后自动重新加载配置
+    ///
+    /// 每条命令执行前检查；本程序自己保存时配置相同，不会有提示。
+    fn poll_config(&mut self) {
+        let mtime = modified(&self.data_file);
+        let user_mtime = config::config_path().and_then(modified);
+        let changed = (self.config_mtime.is_some() && mtime != self.config_mtime)
+            || user_mtime != self.user_config_mtime;
+        self.config_mtime = mtime;
+        self.user_config_mtime = user_mtime;
+        if !changed || !self.config_watch {
+            return;
+        }
+
+        match self.reload_config() {
+            Ok(changes) if changes.is_empty() => {}
+            Ok(changes) => {
+                statusln!("🔄 配置文件已更新，已重新加载配置：");
+                for c in &changes {
+                    outln!("  - {}", c);
+                }
+            }
+            // 文件可能正被写入，下次再试
+            Err(_) => {
+                self.config_mtime = None;
+                self.user_config_mtime = None;
+            }
+        }
+    }
+
+    /// 改动类命令指定的成员不能是重名的姓名，见 [`MEMBER_TARGETS`]
+    fn require_unique_targets(&self, command: &str, args: &[&str]) -> Result<(), ZzError> {
+        // position succeed 的参数是职位
+        if command == "position" && args.first() == Some(&"succeed") {
+            return Ok(());
+        }
+        let count = MEMBER_TARGETS
+            .iter()
+            .find(|(c, _)| *c == command)
+            .map_or(0, |(_, n)| *n);
+        args.iter()
+            .take(count)
+            .filter(|a| !a.starts_with("--"))
+            .try_for_each(|name| self.doc.tree.require_unique(name))
+    }
+
+    /// 重新读取数据文件中的配置与用户配置文件
+    ///
+    /// 用户配置只更新与文件不同的项：`autosave` 未变时保留本次会话中设置的值。
+    ///
+    /// # Returns
+    /// 每项变化的描述。
+    fn reload_config(&mut self) -> Result<Vec<String>, String> {
+        let mut changes: Vec<String> = self
+            .doc
+            .reload_config(self.store.as_ref())
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|c| c.describe_change())
+            .collect();
+
+        let config = Config::load()?;
+        for key in Config::KEYS {
+            let (old, new) = (self.config.get(key), config.get(key));
+            if old != new {
+                changes.push(format!(
+                    "用户配置 {}：{} → {}",
+                    key,
+                    old.unwrap_or_default(),
+                    new.unwrap_or_default()
+                ));
+            }
+        }
+        if config != self.config {
+            let autosave = self.autosave;
+            let keep_autosave = config.autosave == self.config.autosave;
+            self.configure(config);
+            if keep_autosave {
+                self.autosave = autosave;
+            }
+        }
+        Ok(changes)
+    }
+
+    /// 是否有未保存的改动
+    pub fn is_dirty(&self) -> bool {
+        self.unsaved > 0
+    }
+
+    /// 按磁盘上的数据文件重新判断有无未保存的改动
+    fn sync_unsaved(&mut self) {
+        self.unsaved = usize::from(!self.store.matches(&self.doc));
+    }
+
+    /// 未保存的改动达到 autosave 设定的条数时自动保存
+    fn autosave(&mut self) {
+        let Some(every) = self.autosave else {
+            return;
+        };
+        if self.unsaved < every {
+            return;
+        }
+        match self.store.save(&self.doc) {
+            Ok(()) => {
+                self.unsaved = 0;
+                statusln!("💾 已自动保存到 {}", self.data_file);
+            }
+            Err(e) => statusln!("⚠️  自动保存失败：{}", e),
+        }
+    }
+
+    /// 退出前检查未保存的改动
+    ///
+    /// 询问是否保存：y 保存后退出，n 放弃改动退出，其他输入取消退出。
+    /// 输入已结束（EOF）时无法询问，直接退出并提示改动未保存。
+    ///
+    /// # Returns
+    /// 是否退出。
+    pub fn confirm_exit(&mut self) -> bool {
+        if !self.is_dirty() {
+            return true;
+        }
+
+        status!("⚠️  有未保存的改动，保存后退出？(y 保存 / n 不保存 / 其他键取消): ");
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        match record::read_line(&mut input) {
+            Ok(0) | Err(_) => {
+                outln!();
+                statusln!("⚠️  输入已结束，改动未保存");
+                true
+            }
+            Ok(_) => match input.trim().to_lowercase().as_str() {
+                "y" => match self.store.save(&self.doc) {
+                    Ok(()) => {
+                        statusln!("💾 已保存到 {}", self.data_file);
+                        true
+                    }
+                    Err(e) => {
+                        statusln!("❌ 保存失败，未退出：{}", e);
+                        false
+                    }
+                },
+                "n" => true,
+                _ => {
+                    statusln!("ℹ️ 已取消退出");
+                    false
+                }
+            },
+        }
+    }
+
+    /// 追加一条指标记录，写入失败时提示并停止记录
+    fn record(&mut self, record: Record) {
+        let path = metrics::log_path(&self.data_file);
+        if let Err(e) = metrics::append(&path, &record) {
+            statusln!(
+                "⚠️  写入指标日志 {} 失败，已停止记录：{}",
+                path.display(),
+                e
+            );
+            self.metrics = false;
+        }
+    }
+
+    /// 命令之间的提示：后台归档完成情况、教程步骤检查与规模、重名警告
+    ///
+    /// REPL 在每次读取输入前调用，批处理模式在每条命令后调用。
+    pub fn report(&mut self) {
+        for outcome in self.archiver.poll() {
+            statusln!("{}", outcome);
+        }
+
+        // 教程进行中时，检查上一条命令是否完成了当前步骤
+        if let Some(t) = self.tutorial.as_mut() {
+            if !self.last_command.is_empty()
+                && t.check(&self.last_command, &self.doc, &self.data_file)
+            {
+                if let Some(t) = self.tutorial.take() {
+                    t.finish(&mut self.doc, &mut self.data_file, &mut self.current_year);
+                    self.history.clear();
+                    self.sync_unsaved();
+                }
+            }
+        }
+
+        // 启动时全部提示，之后只提示新出现的项；同一项人数变化不再重复提示
+        let mut warnings: Vec<(String, String)> = self
+            .doc
+            .limits
+            .check(&self.doc.tree)
+            .iter()
+            .map(|e| (e.key(), e.describe()))
+            .collect();
+        warnings.extend(self.doc.tree.duplicates().iter().map(|d| {
+            (
+                format!("duplicate:{}", d.name),
+                format!(
+                    "重名{}，只写姓名时查看类命令指第一处，修改类命令需写明路径或 ID",
+                    d.describe()
+                ),
+            )
+        }));
+        for (_, w) in warnings
+            .iter()
+            .filter(|(key, _)| !self.shown_warnings.contains(key))
+        {
+            statusln!("⚠️  {}", w);
+        }
+        self.shown_warnings = warnings.into_iter().map(|(key, _)| key).collect();
+        self.last_command.clear();
+    }
+
+    /// 当前会话模式，用于提示符与 status
+    pub fn mode(&self) -> &'static str {
+        if self.tutorial.is_some() {
+            "tutorial"
+        } else if self.sandbox.is_some() {
+            "sandbox"
+        } else {
+            "normal"
+        }
+    }
+
+    /// 等待后台归档写完
+    pub fn finish(mut self) {
+        if self.metrics {
+            self.record(Record::session(self.started.elapsed(), self.commands_run));
+        }
+        if self.archiver.pending() > 0 {
+            statusln!("⏳ 等待 {} 个归档写入完成…", self.archiver.pending());
+        }
+        for outcome in self.archiver.finish() {
+            statusln!("{}", outcome);
+        }
+    }
+
+    /// 分派命令；`exit` 时返回 `Break`
+    ///
+    /// 各命令打印错误或用法提示时置 `failed`。
+    fn dispatch(&mut self, command: &str, args: Vec<&str>, line: &str) -> ControlFlow<()> {
+        match command {
+            "help" => self.cmd_help(),
+            "exit" | "quit" => ControlFlow::Break(()),
+            "count" => self.cmd_count(),
+            "exists" => self.cmd_exists(args),
+            "search" => self.cmd_search(args),
+            "show" => self.cmd_show(args),
+            "fold" => self.cmd_fold(args),
+            "add" => self.cmd_add(args),
+            "save" => self.cmd_save(args),
+            "verify-save" => self.cmd_verify_save(args),
+            "position" if args.first() == Some(&"succeed") => self.cmd_position_succeed(args),
+            "positions" => self.cmd_positions(args),
+            "position" => self.cmd_position(args),
+            "catalog" => self.cmd_catalog(args),
+            "year" => self.cmd_year(args),
+            "summary" => self.cmd_summary(),
+            "status" => self.cmd_status(args),
+            "meta" => self.cmd_meta(args),
+            "format" => self.cmd_format(args),
+            "fix" => self.cmd_fix(args),
+            "check" => self.cmd_check(args),
+            "stats" => self.cmd_stats(args),
+            "age" => self.cmd_age(args),
+            "calc" => self.cmd_calc(args),
+            "compare-branches" => self.cmd_compare_branches(args),
+            "list" => self.cmd_list(args),
+            "find" => self.cmd_find(args),
+            "profile" => self.cmd_profile(args),
+            "calendar" => self.cmd_calendar(args),
+            "query" => self.cmd_query(args),
+            "info" => self.cmd_info(args),
+            "path" => self.cmd_path(args),
+            "ancestors" => self.cmd_ancestors(args),
+            "siblings" => self.cmd_siblings(args),
+            "cousins" => self.cmd_cousins(args),
+            "relationship" => self.cmd_relationship(args),
+            "prune" => self.cmd_prune(),
+            "delete" => self.cmd_delete(args),
+            "move" => self.cmd_move(args),
+            "merge-members" => self.cmd_merge_members(args),
+            "suggest-prune" => self.cmd_suggest_prune(args),
+            "rename" => self.cmd_rename(args),
+            "die" => self.cmd_die(args),
+            "edit" => self.cmd_edit(args),
+            "missing" => self.cmd_missing(args),
+            "found" => self.cmd_found(args),
+            "clear" => self.cmd_clear(),
+            "inherit" => self.cmd_inherit(args),
+            "config" => self.cmd_config(args),
+            "autosave" => self.cmd_autosave(args),
+            "theme" => self.cmd_theme(args),
+            "export" => self.cmd_export(args),
+            "diff" => self.cmd_diff(args),
+            "record" => self.cmd_record(args),
+            "replay" => self.cmd_replay(args),
+            "report" => self.cmd_report(args),
+            "explain" => self.cmd_explain(args),
+            "recover" => self.cmd_recover(args),
+            "edit-file" => self.cmd_edit_file(args),
+            "undo" => self.cmd_undo(args),
+            "redo" => self.cmd_redo(args),
+            "why-not-heir" => self.cmd_why_not_heir(args),
+            "pick" => self.cmd_pick(args),
+            "succession" => self.cmd_succession(args),
+            "health-weights" => self.cmd_health_weights(args),
+            "lock" => self.cmd_lock(args),
+            "unlock" => self.cmd_unlock(args),
+            "protect" => self.cmd_protect(args),
+            "doctor" => self.cmd_doctor(),
+            "perf" => self.cmd_perf(args),
+            "limits" => self.cmd_limits(args),
+            "tutorial" => self.cmd_tutorial(args),
+            "sandbox" => self.cmd_sandbox(args),
+            "example" => self.cmd_example(args),
+            "watch-game" => self.cmd_watch_game(args),
+            "import" => self.cmd_import(args),
+            _ => {
+                self.failed = true;
+                statusln!("未知命令: '{line}'. 输入 'help' 查看可用命令。");
+                ControlFlow::Continue(())
+            }
+        }
+    }
+}
+
+impl Confirm {
+    /// 能否从终端读取补充输入（交互模式）
+    pub fn interactive(self) -> bool {
+        matches!(self, Confirm::Ask | Confirm::Auto)
+    }
+
+    /// 写入会话录制文件的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Confirm::Ask => "ask",
+            Confirm::Auto => "auto",
+            Confirm::Yes => "yes",
+            Confirm::No => "no",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Confirm> {
+        [Confirm::Ask, Confirm::Auto, Confirm::Yes, Confirm::No]
+            .into_iter()
+            .find(|c| c.label() == label)
+    }
+
+    /// 询问 y/n
+    pub fn ask(self, question: &str) -> bool {
+        out!("{}(y/n): ", question);
+        match self {
+            Confirm::Ask => {
+                io::stdout().flush().unwrap();
+                let mut input = String::new();
+                record::read_line(&mut input).ok();
+                input.trim().to_lowercase() == "y"
+            }
+            Confirm::Yes => {
+                outln!("y（--yes）");
+                true
+            }
+            Confirm::Auto => {
+                outln!("y（已关闭确认：config set confirm on 可恢复）");
+                true
+            }
+            Confirm::No => {
+                outln!("n（批处理模式，加 --yes 自动确认）");
+                false
+            }
+        }
+    }
+}
+
+impl Outcome {
+    /// 写入会话录制文件的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Outcome::Done => "done",
+            Outcome::Failed => "failed",
+            Outcome::Exit => "exit",
+        }
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 各命令的实现，由 [`Session::dispatch`] 分派
+impl Session {
+    fn cmd_help(&mut self) -> ControlFlow<()> {
+        outln!("{HELP_TEXT}");
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_count(&mut self) -> ControlFlow<()> {
+        let Session { doc, config, .. } = self;
+        let count = if config.count_dead {
+            doc.tree.total_count()
+        } else {
+            doc.tree.size()
+        };
+        outln!("总共的家族人数：{}.", count);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_exists(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc, store, failed, ..
+        } = self;
+        match args.as_slice() {
+            [name] => {
+                if doc.tree.exists(name) {
+                    outln!("【{name}】存在于家族中。");
+                } else {
+                    outln!("【{name}】不存在于家族中。");
+                }
+            }
+            [name, "--everywhere"] => {
+                let mut locations = Vec::new();
+
+                if doc.tree.exists(name) {
+                    locations.push("当前家族树".to_string());
+                }
+                for entry in doc.find_in_trash(name) {
+                    locations.push(format!("回收站（{} 年删除）", entry.deleted_year));
+                }
+                for year in store.list_archives() {
+                    if store.load_archive(year).is_ok_and(|a| a.tree.exists(name)) {
+                        locations.push(format!("归档 {}", year));
+                    }
+                }
+
+                if locations.is_empty() {
+                    outln!("【{name}】在家族树、回收站和归档中均不存在。");
+                } else {
+                    outln!("【{name}】的下落：");
+                    for location in locations {
+                        outln!("  - {}", location);
+                    }
+                }
+            }
+            _ => fail!(failed, "用法: exists <name> [--everywhere]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_search(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [keyword] => print_report(&search::search(&doc.tree, keyword).describe()),
+            _ => fail!(failed, "用法：search <关键字>"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_show(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            output_format,
+            confirm,
+            config,
+            failed,
+            ..
+        } = self;
+        let mut folding = Folding {
+            limit: doc.settings.fold_children,
+            expand: Vec::new(),
+            depth: None,
+            living: None,
+        };
+        let mut names = Vec::new();
+        let mut positions_at = None;
+        let mut options = args.iter();
+        while let Some(option) = options.next() {
+            match *option {
+                "--all" => folding.limit = None,
+                "--alive" => folding.living = Some(true),
+                "--dead" => folding.living = Some(false),
+                "--positions-at" => match options.next().map(|y| model::parse_year(y)) {
+                    Some(Ok(year)) => positions_at = Some(year),
+                    Some(Err(e)) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                    None => {
+                        fail!(failed, "❌ --positions-at 后缺少年份");
+                        return ControlFlow::Continue(());
+                    }
+                },
+                "--expand" => match options.next() {
+                    Some(name) => folding.expand.push(name.to_string()),
+                    None => {
+                        fail!(failed, "❌ --expand 后缺少成员姓名，如 show --expand 张三");
+                        return ControlFlow::Continue(());
+                    }
+                },
+                "--depth" => match options.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(depth)) => folding.depth = Some(depth),
+                    _ => {
+                        fail!(
+                            failed,
+                            "❌ --depth 后应为代数（非负整数），如 show --depth 2"
+                        );
+                        return ControlFlow::Continue(());
+                    }
+                },
+                name => names.push(name),
+            }
+        }
+        if let Some(name) = folding.expand.iter().find(|n| !doc.tree.exists(n)) {
+            fail!(failed, "❌ 未找到成员【{}】", name);
+            return ControlFlow::Continue(());
+        }
+
+        // 有任职记录时职位按当前年份（或 --positions-at 指定的年份）显示
+        let tree = match positions_at.or(*current_year) {
+            Some(year) => Cow::Owned(doc.tree.positions_at(year)),
+            None => Cow::Borrowed(&doc.tree),
+        };
+        let living = folding.living;
+        // 录制与回放时不着色，输出与终端无关
+        let renderer = output_format.renderer_with(TerminalTable {
+            folding,
+            widths: config.columns,
+            color: config.color && theme::color() && !record::capturing(),
+            max_width: table_width(),
+            ascii: theme::current() == theme::Theme::Ascii,
+        });
+        let shown = match names.as_slice() {
+            [] => &*tree,
+            [name] => match tree.find_member_by_name(name) {
+                Some(member) => member,
+                None => {
+                    fail!(failed, "未找到【{}】", name);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法: show [<name>] [--alive | --dead] [--depth <代数>] [--expand <姓名>]... [--all] [--positions-at <年份>]");
+                return ControlFlow::Continue(());
+            }
+        };
+        let mut note = None;
+        if let Some(living) = living {
+            let label = if living { "在世" } else { "已故" };
+            let count = shown
+                .members()
+                .into_iter()
+                .filter(|m| m.is_dead != living)
+                .count();
+            if count == 0 {
+                outln!("没有{}成员", label);
+                return ControlFlow::Continue(());
+            }
+            if *output_format == OutputFormat::Table {
+                note = Some(format!("{}成员 {} 人，只列姓名的是其先辈", label, count));
+            } else {
+                statusln!("ℹ️ --alive、--dead 仅作用于终端表格，以下为全部成员");
+            }
+        }
+        let mut out = renderer.render_tree(shown);
+        if let Some(note) = note {
+            out.push_str(&note);
+            out.push('\n');
+        }
+        pager::page(&out, confirm.interactive() && config.pager);
+        if let Some(year) = positions_at {
+            let lineup: Vec<String> = shown
+                .members()
+                .into_iter()
+                .filter_map(|m| Some(format!("{}【{}】", m.position.as_deref()?, m.name)))
+                .collect();
+            if lineup.is_empty() {
+                outln!("{} 年无人任职", year);
+            } else {
+                outln!("{} 年在任：{}", year, lineup.join("、"));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_fold(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => match doc.settings.fold_children {
+                Some(n) => outln!("show 中每人最多显示 {} 名子嗣，其余折叠", n),
+                None => outln!("show 不折叠子嗣"),
+            },
+            ["off"] => {
+                doc.settings.fold_children = None;
+                statusln!("✅ show 不再折叠子嗣");
+            }
+            [n] => match n.parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    doc.settings.fold_children = Some(n);
+                    statusln!("✅ show 中每人最多显示 {} 名子嗣", n);
+                }
+                _ => fail!(failed, "❌ 无效的人数：{}", n),
+            },
+            _ => fail!(failed, "用法：fold [<人数> | off]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_add(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            confirm,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [parent, "--from", path] => {
+                let json = match fs::read_to_string(path) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        fail!(failed, "❌ 读取 {} 失败：{}", path, e);
+                        return ControlFlow::Continue(());
+                    }
+                };
+                match add_json(doc, parent, &json) {
+                    Ok(n) => statusln!("✅ 已从 {} 为【{}】添加 {} 名子嗣", path, parent, n),
+                    Err(e) => fail!(failed, "❌ {}", e),
+                }
+            }
+            [parent, "--edit"] => {
+                let Some(template) = child_template(doc, parent) else {
+                    fail!(failed, "❌ 未找到成员【{}】", parent);
+                    return ControlFlow::Continue(());
+                };
+                let mut json = template.clone();
+                loop {
+                    json = match editor::edit_text(&json, "json") {
+                        Ok(json) => json,
+                        Err(e) => {
+                            fail!(failed, "❌ {}", e);
+                            break;
+                        }
+                    };
+                    if json.trim() == template.trim() || json.trim() == "[]" {
+                        statusln!("ℹ️ 未做改动，没有添加子嗣");
+                        break;
+                    }
+                    match add_json(doc, parent, &json) {
+                        Ok(n) => {
+                            statusln!("✅ 已为【{}】添加 {} 名子嗣", parent, n);
+                            break;
+                        }
+                        Err(e) => {
+                            statusln!("❌ {}", e);
+                            // 非交互时重新打开也无法修改，直接放弃
+                            if !confirm.interactive() || !confirm.ask("重新打开编辑器修改？")
+                            {
+                                fail!(failed, "ℹ️ 已放弃添加");
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            [parent, children] => match add_json(doc, parent, children) {
+                Ok(n) => statusln!("✅ 已为【{}】添加 {} 名子嗣", parent, n),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            [] if confirm.interactive() => {
+                statusln!("📝 添加子嗣模式");
+
+                // 1. 获取父节点
+                let parent_name = loop {
+                    out!("请输入成员姓名：");
+                    io::stdout().flush().unwrap();
+
+                    let mut input = String::new();
+                    record::read_line(&mut input).ok();
+                    let name = input.trim();
+
+                    if name.is_empty() {
+                        continue;
+                    }
+
+                    if doc.tree.exists(name) {
+                        break Some(name.to_string());
+                    } else {
+                        outln!("【{name}】不存在，请重新输入");
+                    }
+                };
+
+                let Some(parent) = parent_name else {
+                    return ControlFlow::Continue(());
+                };
+
+                // 2. 逐项询问子嗣信息
+                if let Err(e) = guided_add(doc, &parent, *confirm) {
+                    fail!(failed, "❌ {}", e);
+                }
+            }
+            [parent] if confirm.interactive() => {
+                if !doc.tree.exists(parent) {
+                    fail!(failed, "❌ 未找到成员【{}】", parent);
+                    return ControlFlow::Continue(());
+                }
+                if let Err(e) = guided_add(doc, parent, *confirm) {
+                    fail!(failed, "❌ {}", e);
+                }
+            }
+            [] | [_] => fail!(failed, "❌ 批处理模式下请使用 add <姓名> '<JSON 数组>'"),
+            _ => fail!(
+                failed,
+                "用法：add [<姓名> ['<JSON 数组>' | --from <文件> | --edit]]"
+            ),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_save(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            data_file,
+            tutorial,
+            sandbox,
+            failed,
+            ..
+        } = self;
+        let format = match args.as_slice() {
+            [] => None,
+            [format] => match format.parse::<FileFormat>() {
+                Ok(format) => Some(format),
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法：save [json|yaml|msgpack]");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        // 指定的格式与当前文件不同时另存为同名的新扩展名文件
+        let path = match format {
+            Some(format) if format != FileFormat::of(Path::new(data_file)) => {
+                if sandbox.is_some() || tutorial.is_some() {
+                    fail!(failed, "❌ 沙盒或教程中不能切换数据文件格式");
+                    return ControlFlow::Continue(());
+                }
+                Path::new(data_file).with_extension(format.extension())
+            }
+            _ => {
+                if let Err(e) = store.save(doc) {
+                    fail!(failed, "❌ 保存失败: {}", e);
+                }
+                return ControlFlow::Continue(());
+            }
+        };
+        if let Err(e) = FileStore::new(&path).save(doc) {
+            fail!(failed, "❌ 保存失败: {}", e);
+        } else {
+            statusln!(
+                "✅ 已以 {} 格式保存到 {}，原文件 {} 保留不变",
+                FileFormat::of(&path),
+                path.display(),
+                data_file
+            );
+            outln!(
+                "   之后启动时请用 --file {} 或将 ZZ_SIM_FAMILY_DATA 指向它",
+                path.display()
+            );
+            *data_file = path.display().to_string();
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_verify_save(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            data_file,
+            failed,
+            ..
+        } = self;
+        if !args.is_empty() {
+            fail!(failed, "用法：verify-save");
+            return ControlFlow::Continue(());
+        }
+        match verify::verify_store(doc, store.as_ref()) {
+            Ok(divergences) if divergences.is_empty() => {
+                statusln!("✅ {} 与内存中的数据一致", data_file)
+            }
+            Ok(divergences) => {
+                statusln!(
+                    "⚠️  {} 与内存中的数据有 {} 处不同：",
+                    data_file,
+                    divergences.len()
+                );
+                for d in &divergences {
+                    outln!("  - {}", d);
+                }
+                outln!("   以内存为准请执行 save；以磁盘为准请退出后重新打开（不保存）");
+                *failed = true;
+            }
+            Err(e) => fail!(failed, "❌ 无法读取磁盘上的数据：{}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_position_succeed(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        const USAGE: &str = "用法：position succeed <职位> [--by generation|age|power] [--assign]";
+        /// 最多列出的候选人数
+        const MAX_LISTED: usize = 10;
+
+        let mut title = None;
+        let mut by = RankBy::default();
+        let mut assign = false;
+        let mut options = args[1..].iter();
+        while let Some(option) = options.next() {
+            match *option {
+                "--assign" => assign = true,
+                "--by" => match options.next().map(|v| v.parse::<RankBy>()) {
+                    Some(Ok(v)) => by = v,
+                    Some(Err(e)) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                    None => {
+                        fail!(failed, "{USAGE}");
+                        return ControlFlow::Continue(());
+                    }
+                },
+                t if title.is_none() && !t.starts_with("--") => title = Some(t),
+                _ => {
+                    fail!(failed, "{USAGE}");
+                    return ControlFlow::Continue(());
+                }
+            }
+        }
+        let Some(title) = title else {
+            fail!(failed, "{USAGE}");
+            return ControlFlow::Continue(());
+        };
+
+        if let Some(holder) = catalog::holder(&doc.tree, title) {
+            fail!(
+                failed,
+                "❌ 【{}】现由【{}】担任，并未出缺",
+                title,
+                holder.name
+            );
+            return ControlFlow::Continue(());
+        }
+        if doc.position_catalog.get(title).is_none() {
+            statusln!("ℹ️ 【{}】不在职位目录中，不设任职条件", title);
+        }
+
+        let candidates = doc
+            .position_catalog
+            .candidates(&doc.tree, title, *current_year, by);
+        if candidates.is_empty() {
+            fail!(failed, "❌ 没有符合【{}】任职条件且未任职的在世成员", title);
+            return ControlFlow::Continue(());
+        }
+
+        match catalog::former_holder(&doc.tree, title) {
+            Some(former) => outln!(
+                "【{}】继任人选（前任【{}】的后代优先，其余按{}排序）：",
+                title,
+                former.name,
+                by
+            ),
+            None => outln!("【{}】继任人选（按{}排序）：", title, by),
+        }
+        for (i, c) in candidates.iter().take(MAX_LISTED).enumerate() {
+            let m = c.member;
+            let age = current_year
+                .map(|year| format!("{} 岁", year - m.birth_year))
+                .unwrap_or_else(|| format!("{} 年生", m.birth_year));
+            outln!(
+                "  {}. {}{}{}威望 {:+}{}",
+                i + 1,
+                render::pad(&m.name, 12),
+                render::pad(&format!("{}（第 {} 代）", m.member_type, c.depth), 20),
+                render::pad(&age, 10),
+                m.hoser_power_add,
+                if c.heir_of_former {
+                    "  前任后代"
+                } else {
+                    ""
+                }
+            );
+            for w in &c.warnings {
+                statusln!("     ⚠️  {}", w);
+            }
+        }
+        if candidates.len() > MAX_LISTED {
+            outln!("  … 共 {} 人符合条件", candidates.len());
+        }
+
+        if assign {
+            let heir = candidates[0].member.name.clone();
+            match doc.tree.add_position(&heir, title, *current_year) {
+                Ok(_) => statusln!("✅ 【{}】继任{}", heir, title),
+                Err(e) => fail!(failed, "❌ {}", e),
+            }
+        } else {
+            outln!(
+                "加 --assign 由第一位继任，或执行 position <姓名> {} 指定",
+                title
+            );
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_positions(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            ["vacancies"] => {
+                let vacancies = doc.position_catalog.vacancies(&doc.tree);
+                if doc.position_catalog.is_empty() {
+                    outln!("职位目录为空，可用 catalog add <职位> 添加");
+                } else if vacancies.is_empty() {
+                    outln!("目录中的职位均有在世成员担任");
+                } else {
+                    outln!("出缺职位（{}）：", vacancies.len());
+                    for v in &vacancies {
+                        match &v.former {
+                            Some(former) => {
+                                outln!("  {}：前任【{}】已故", v.title, former)
+                            }
+                            None => outln!("  {}：从未有人担任", v.title),
+                        }
+                    }
+                    outln!("执行 position succeed <职位> 查看继任人选");
+                }
+            }
+            _ => fail!(failed, "用法：positions vacancies"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_position(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let force = args.contains(&"--force");
+        let args: Vec<&str> = args.iter().copied().filter(|a| *a != "--force").collect();
+        if args.len() != 2 {
+            fail!(failed, "用法: position <姓名> <职位> [--force]");
+            return ControlFlow::Continue(());
+        }
+
+        let name = args[0];
+        let position = args[1];
+
+        if let Some(member) = doc.tree.find_member_by_name(name) {
+            match doc.position_catalog.check(member, position, *current_year) {
+                Ok(warnings) => {
+                    for w in warnings {
+                        statusln!("⚠️  {}", w);
+                    }
+                }
+                Err(violations) => {
+                    for v in &violations {
+                        statusln!("⚠️  {}", v);
+                    }
+                    if !force {
+                        fail!(failed, "❌ 不满足任职条件，如需强制任命请加 --force");
+                        return ControlFlow::Continue(());
+                    }
+                }
+            }
+        }
+
+        match doc.tree.add_position(name, position, *current_year) {
+            Ok(_) => statusln!("✅ 已为【{}】设置职位：{}", name, position),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_catalog(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.position_catalog.describe()),
+            ["add", title, options @ ..] => {
+                let mut rule = PositionRule {
+                    title: title.to_string(),
+                    min_age: None,
+                    alive_only: false,
+                };
+
+                let mut options = options.iter();
+                let mut valid = true;
+                while let Some(option) = options.next() {
+                    match *option {
+                        "--alive-only" => rule.alive_only = true,
+                        "--min-age" => match options.next().and_then(|v| v.parse().ok()) {
+                            Some(age) => rule.min_age = Some(age),
+                            None => valid = false,
+                        },
+                        _ => valid = false,
+                    }
+                }
+
+                if valid {
+                    doc.position_catalog.upsert(rule);
+                    statusln!("✅ 已更新职位目录：{}", title);
+                } else {
+                    fail!(
+                        failed,
+                        "用法：catalog add <职位> [--min-age <岁>] [--alive-only]"
+                    );
+                }
+            }
+            ["remove", title] => match doc.position_catalog.remove(title) {
+                Ok(_) => statusln!("✅ 已从职位目录移除：{}", title),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(
+                failed,
+                "用法：catalog [add <职位> [--min-age <岁>] [--alive-only] | remove <职位>]"
+            ),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_year(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        if args.is_empty() {
+            match *current_year {
+                Some(y) => outln!("当前年份：{}", doc.settings.format_year(y)),
+                None => statusln!("⚠️  尚未设置当前年份"),
+            }
+        } else {
+            match model::parse_year(args[0]) {
+                Ok(year) => {
+                    *current_year = Some(year);
+                    statusln!("✅ 当前年份设置为 {}", doc.settings.format_year(year));
+                }
+                Err(e) => fail!(failed, "❌ {}", e),
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_summary(&mut self) -> ControlFlow<()> {
+        let Session { doc, .. } = self;
+        out!("{}", theme::frame(&doc.summary()));
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_status(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let mode = self.mode();
+        let Session {
+            doc,
+            store,
+            current_year,
+            output_format,
+            archiver,
+            failed,
+            ..
+        } = self;
+        let status = status::Status::collect(
+            doc,
+            store.as_ref(),
+            *current_year,
+            mode,
+            *output_format,
+            archiver.pending(),
+        );
+        match args.as_slice() {
+            [] => status.print(),
+            ["--json"] => status.print_json(),
+            _ => fail!(failed, "用法：status [--json]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_meta(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.meta.describe()),
+            ["clear", field] => match doc.meta.set(field, None) {
+                Ok(_) => statusln!("✅ 已清除 {}", field),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            [field, value @ ..] if !value.is_empty() => {
+                let value = value.join(" ");
+                match doc.meta.set(field, Some(&value)) {
+                    Ok(_) => statusln!("✅ 已设置 {}：{}", field, value),
+                    Err(e) => fail!(failed, "❌ {}", e),
+                }
+            }
+            _ => fail!(failed, "用法：meta [<字段> <值> | clear <字段>]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_format(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            output_format,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => outln!("当前输出格式：{}", *output_format),
+            [format] => match format.parse::<OutputFormat>() {
+                Ok(format) => {
+                    *output_format = format;
+                    statusln!("✅ 输出格式设置为 {}", *output_format);
+                }
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(failed, "用法：format [table|json|csv|markdown]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_fix(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            confirm,
+            failed,
+            ..
+        } = self;
+        let dry_run = match args.as_slice() {
+            [] => false,
+            ["--dry-run"] => true,
+            _ => {
+                fail!(failed, "用法：fix [--dry-run]");
+                return ControlFlow::Continue(());
+            }
+        };
+        let mut fixed = doc.tree.clone();
+        let changes = fixed.fix_labels();
+        if changes.is_empty() {
+            statusln!("✅ 全部称谓与所在位置一致，无需修正");
+            return ControlFlow::Continue(());
+        }
+        statusln!(
+            "🏷️ 将按树深度与经由的性别更正 {} 名成员的称谓：",
+            changes.len()
+        );
+        for line in model::describe_relabels(&changes).lines() {
+            outln!("   {}", line);
+        }
+        if dry_run {
+            statusln!("ℹ️ 预览模式，未做改动；去掉 --dry-run 后执行");
+        } else if confirm.ask("应用这些更正？") {
+            doc.tree = fixed;
+            statusln!("✅ 已更正 {} 名成员的称谓（可 undo 撤销）", changes.len());
+        } else {
+            fail!(failed, "❌ 已取消");
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_check(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => {}
+            ["power-range"] => {
+                match doc.settings.power_range {
+                    Some((min, max)) => outln!("威望加成的合理范围：{} ~ {}", min, max),
+                    None => outln!("未设置威望加成的合理范围，check 不检查威望"),
+                }
+                return ControlFlow::Continue(());
+            }
+            ["power-range", "off"] => {
+                doc.settings.power_range = None;
+                statusln!("✅ check 不再检查威望加成");
+                return ControlFlow::Continue(());
+            }
+            ["power-range", min, max] => {
+                match (min.parse::<i32>(), max.parse::<i32>()) {
+                    (Ok(min), Ok(max)) if min <= max => {
+                        doc.settings.power_range = Some((min, max));
+                        statusln!("✅ 威望加成的合理范围设置为 {} ~ {}", min, max);
+                    }
+                    _ => fail!(
+                        failed,
+                        "❌ 无效的范围：{} ~ {}，下限须为整数且不大于上限",
+                        min,
+                        max
+                    ),
+                }
+                return ControlFlow::Continue(());
+            }
+            _ => {
+                fail!(failed, "用法：check [power-range [<下限> <上限> | off]]");
+                return ControlFlow::Continue(());
+            }
+        }
+        let report = check::check(&doc.tree, doc.settings.power_range);
+        if report.findings.is_empty() {
+            statusln!("✅ 校验通过，未发现问题");
+            return ControlFlow::Continue(());
+        }
+        for finding in &report.findings {
+            let marker = match finding.severity {
+                check::Severity::Error => "❌",
+                check::Severity::Warning => "⚠️ ",
+            };
+            statusln!("{} {}", marker, finding.describe());
+        }
+        outln!("{}", report.summary());
+        if report.count(check::Severity::Error) > 0 {
+            *failed = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_stats(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        let cohort = args.contains(&"--cohort");
+        args.retain(|a| *a != "--cohort");
+        let names = args.contains(&"--names");
+        args.retain(|a| *a != "--names");
+        // --csv 后可跟文件名，省略时输出到终端
+        let csv = match args.iter().position(|a| *a == "--csv") {
+            Some(i) => {
+                let path = args.get(i + 1).copied().filter(|p| !p.starts_with("--"));
+                args.drain(i..i + 1 + usize::from(path.is_some()));
+                Some(path)
+            }
+            None => None,
+        };
+        if csv.is_some() && !cohort {
+            fail!(failed, "❌ --csv 需与 --cohort 一起使用");
+            return ControlFlow::Continue(());
+        }
+        if names && cohort {
+            fail!(failed, "❌ --names 不能与 --cohort 同时使用");
+            return ControlFlow::Continue(());
+        }
+        let health = args.contains(&"--health");
+        args.retain(|a| *a != "--health");
+        let trend = args.contains(&"--trend");
+        args.retain(|a| *a != "--trend");
+        if trend && !health {
+            fail!(failed, "❌ --trend 需与 --health 一起使用");
+            return ControlFlow::Continue(());
+        }
+        if health {
+            if cohort || names || !args.is_empty() {
+                fail!(
+                    failed,
+                    "❌ --health 只统计全家族，不能指定成员或与 --cohort、--names 同时使用"
+                );
+                return ControlFlow::Continue(());
+            }
+            let assess = |tree: &FamilyMember, year| {
+                health::assess(tree, &doc.settings.succession, &doc.settings.health, year)
+            };
+            if !trend {
+                out!("{}", assess(&tree, year).describe());
+                return ControlFlow::Continue(());
+            }
+            // 各年份都按当前的继承规则与权重计算，便于比较
+            let mut rows = Vec::new();
+            for archived in store.list_archives() {
+                match store.load_archive(archived) {
+                    Ok(a) => rows.push((archived.to_string(), assess(&a.tree, Some(archived)))),
+                    Err(e) => statusln!("⚠️  跳过 {} 年的归档：{}", archived, e),
+                }
+            }
+            let label = year.map_or("当前".to_string(), |y| format!("当前（{}）", y));
+            rows.push((label, assess(&tree, year)));
+            out!("{}", health::describe_trend(&rows));
+            return ControlFlow::Continue(());
+        }
+
+        let member = match args.as_slice() {
+            [] => tree.as_ref(),
+            [name] => match tree.find_member_by_name(name) {
+                Some(member) => member,
+                None => {
+                    fail!(failed, "❌ 未找到成员【{}】", name);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法：stats [<姓名>] [--as-of <年份>] [--cohort [--csv [<文件>]] | --names | --health [--trend]]");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        match csv {
+            None if names => print_report(&naming::analyze(member).describe()),
+            None if cohort => stats::print_cohorts(&member.cohorts(), year),
+            None => stats::print(&member.stats()),
+            Some(path) => {
+                let content = stats::cohort_csv(&member.cohorts(), year);
+                match path {
+                    None => out!("{}", content),
+                    Some(path) => match fs::write(path, format!("\u{feff}{}", content)) {
+                        Ok(()) => statusln!("✅ 已导出到 {}", path),
+                        Err(e) => fail!(failed, "❌ 写入 {} 失败：{}", path, e),
+                    },
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_age(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：age <姓名> [--as-of <年份>]");
+            return ControlFlow::Continue(());
+        };
+        let Some(year) = year else {
+            fail!(
+                failed,
+                "❌ 请先设置年份：year <年份>，或使用 --as-of <年份>"
+            );
+            return ControlFlow::Continue(());
+        };
+
+        match tree.find_member_by_name(name) {
+            Some(member) if member.is_dead => outln!(
+                "【{}】{} 年时已故（{} 年生）",
+                name,
+                year,
+                member.birth_year
+            ),
+            Some(member) => {
+                outln!("【{}】{} 年时 {} 岁", name, year, year - member.birth_year)
+            }
+            None if doc.tree.exists(name) => outln!("【{}】{} 年时尚未出生", name, year),
+            None => fail!(failed, "❌ 未找到成员【{}】", name),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_calc(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        if args.is_empty() {
+            fail!(failed, "用法：calc <表达式> [--as-of <年份>]");
+            return ControlFlow::Continue(());
+        }
+        match calc::evaluate(&args.join(" "), &tree, year) {
+            Ok(calculation) => out!("{}", calculation.describe()),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_compare_branches(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let [a, b] = args.as_slice() else {
+            fail!(failed, "用法：compare-branches <姓名> <姓名>");
+            return ControlFlow::Continue(());
+        };
+
+        let (Some(branch_a), Some(branch_b)) = (
+            doc.tree.find_member_by_name(a),
+            doc.tree.find_member_by_name(b),
+        ) else {
+            for name in [a, b] {
+                if !doc.tree.exists(name) {
+                    fail!(failed, "❌ 未找到成员【{}】", name);
+                }
+            }
+            return ControlFlow::Continue(());
+        };
+
+        if a == b {
+            fail!(failed, "❌ 请指定两个不同的成员");
+        } else if branch_a.exists(b) || branch_b.exists(a) {
+            fail!(failed, "❌ 【{}】与【{}】一支相互包含，无法对比", a, b);
+        } else {
+            stats::compare(branch_a, branch_b, *current_year);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_list(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            confirm,
+            config,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        // 第一个参数不是选项时为代际
+        let generation = match args.first() {
+            Some(arg) if !arg.starts_with("--") => {
+                let generation = arg.parse::<MemberType>().ok().map(|t| t.generation);
+                let Some(generation) = generation.filter(|g| *g != Generation::其他) else {
+                    fail!(
+                        failed,
+                        "❌ 无法识别的代际【{}】，如 list 儿、list 曾孙",
+                        arg
+                    );
+                    return ControlFlow::Continue(());
+                };
+                args.remove(0);
+                Some(generation)
+            }
+            _ => None,
+        };
+        let mut options = match columns::ListOptions::parse(&args, &doc.settings.column_profiles) {
+            Ok(o) => o,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                fail!(
+                    failed,
+                    "用法：list [<代际>] [--columns <列,列,...> | --profile <方案>] [--sort <列>] [--desc] [--as-of <年份>]"
+                );
+                return ControlFlow::Continue(());
+            }
+        };
+
+        options.max_width = table_width();
+        let mut rows = render::rows(&tree);
+        let Some(generation) = generation else {
+            let out = format!("{}共 {} 人\n", options.render(&rows, year), rows.len());
+            pager::page(&theme::frame(&out), confirm.interactive() && config.pager);
+            return ControlFlow::Continue(());
+        };
+        rows.retain(|r| r.member.member_type.generation == generation);
+        if rows.is_empty() {
+            outln!("没有{:?}辈的成员", generation);
+            return ControlFlow::Continue(());
+        }
+        if !args.iter().any(|a| matches!(*a, "--columns" | "--profile")) {
+            options.columns = vec![
+                Column::Name,
+                Column::Birth,
+                Column::Type,
+                Column::Status,
+                Column::Parent,
+                Column::Position,
+                Column::Power,
+            ];
+            if year.is_some() {
+                options.columns.insert(2, Column::Age);
+            }
+        }
+        if options.sort.is_none() {
+            options.sort = Some(Column::Birth);
+        }
+        let living: Vec<String> = rows
+            .iter()
+            .filter(|r| !r.member.is_dead)
+            .map(|r| format!("【{}】", r.member.name))
+            .collect();
+        let out = format!(
+            "{}{:?}辈共 {} 人，在世 {} 人：{}\n",
+            options.render(&rows, year),
+            generation,
+            rows.len(),
+            living.len(),
+            if living.is_empty() {
+                "无".to_string()
+            } else {
+                living.concat()
+            }
+        );
+        pager::page(&theme::frame(&out), confirm.interactive() && config.pager);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_find(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            confirm,
+            config,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        // 以 -- 开头的参数及其值交给 ListOptions，其余为条件
+        let mut filters = Vec::new();
+        let mut list_args = Vec::new();
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            if matches!(arg, "--columns" | "--profile" | "--sort") {
+                list_args.push(arg);
+                list_args.extend(iter.next());
+            } else if arg.starts_with("--") {
+                list_args.push(arg);
+            } else {
+                match arg.parse::<find::Filter>() {
+                    Ok(filter) => filters.push(filter),
+                    Err(e) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                }
+            }
+        }
+        if filters.is_empty() {
+            fail!(failed, "用法：find <条件>... [--columns <列,列,...> | --profile <方案>] [--sort <列>] [--desc] [--as-of <年份>]");
+            return ControlFlow::Continue(());
+        }
+        if year.is_none() && filters.iter().any(|f| f.needs_year()) {
+            fail!(failed, "❌ 按年龄筛选需先设置年份：year <年份>");
+            return ControlFlow::Continue(());
+        }
+        let mut options =
+            match columns::ListOptions::parse(&list_args, &doc.settings.column_profiles) {
+                Ok(o) => o,
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            };
+
+        options.max_width = table_width();
+        let rows = find::find(&tree, &filters, year);
+        let conditions: Vec<String> = filters.iter().map(|f| f.to_string()).collect();
+        if rows.is_empty() {
+            outln!("没有满足条件的成员（{}）", conditions.join(" "));
+            return ControlFlow::Continue(());
+        }
+        let out = format!(
+            "{}共 {} 人（{}）\n",
+            options.render(&rows, year),
+            rows.len(),
+            conditions.join(" ")
+        );
+        pager::page(&theme::frame(&out), confirm.interactive() && config.pager);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_profile(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] if doc.settings.column_profiles.is_empty() => {
+                outln!("尚未保存列方案，用 profile save <方案> <列,列,...> 添加")
+            }
+            [] => {
+                for (name, columns) in &doc.settings.column_profiles {
+                    let keys: Vec<&str> = columns.iter().map(|c| c.key()).collect();
+                    outln!("{}：{}", name, keys.join(","));
+                }
+            }
+            ["save", name, list] => match columns::Column::parse_list(list) {
+                Ok(columns) if !columns.is_empty() => {
+                    doc.settings
+                        .column_profiles
+                        .insert(name.to_string(), columns);
+                    statusln!("✅ 已保存列方案【{}】，使用：list --profile {}", name, name);
+                }
+                Ok(_) => fail!(failed, "❌ 列方案至少需要一列"),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            ["remove", name] => match doc.settings.column_profiles.remove(*name) {
+                Some(_) => statusln!("✅ 已删除列方案【{}】", name),
+                None => fail!(failed, "❌ 没有名为【{}】的列方案", name),
+            },
+            _ => fail!(
+                failed,
+                "用法：profile [save <方案> <列,列,...> | remove <方案>]"
+            ),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_calendar(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => match &doc.settings.calendar {
+                Some(c) => outln!(
+                    "纪年：{}，偏移 {}（游戏内年份 = 数据年份 + 偏移）",
+                    c.era.as_deref().unwrap_or("-"),
+                    c.offset
+                ),
+                None => outln!("未设置纪年"),
+            },
+            ["off"] => {
+                doc.settings.calendar = None;
+                statusln!("✅ 已清除纪年");
+            }
+            [offset, era @ ..] if era.len() <= 1 => match offset.parse::<i32>() {
+                Ok(offset) => {
+                    doc.settings.calendar = Some(settings::Calendar {
+                        offset,
+                        era: era.first().map(|e| e.to_string()),
+                    });
+                    statusln!(
+                        "✅ 已设置纪年，例如 {}",
+                        doc.settings
+                            .format_year(current_year.unwrap_or(doc.tree.birth_year))
+                    );
+                }
+                Err(_) => fail!(failed, "❌ 无效的偏移：{}", offset),
+            },
+            _ => fail!(failed, "用法：calendar [<偏移> [<纪年名>] | off]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_query(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let (source, json) = match args.as_slice() {
+            [source] => (*source, false),
+            [source, "--json"] | ["--json", source] => (*source, true),
+            _ => {
+                fail!(failed, "用法：query '<JSONPath>' [--json]");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let query = match query::Query::parse(source) {
+            Ok(q) => q,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let value = serde_json::to_value(doc).unwrap_or_default();
+        let results = query.eval(&value);
+        if json {
+            outln!(
+                "{}",
+                serde_json::to_string_pretty(&results).unwrap_or_default()
+            );
+        } else if results.is_empty() {
+            outln!("没有匹配的结果");
+        } else {
+            out!("{}", theme::frame(&query::render_table(&results)));
+            outln!("共 {} 条结果", results.len());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_info(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：info <姓名>");
+            return ControlFlow::Continue(());
+        };
+        match info::lookup(&doc.tree, name, *current_year) {
+            Ok(info) => out!("{}", info.describe()),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_path(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        if args.len() != 1 {
+            fail!(failed, "用法: path <姓名>");
+        } else {
+            match doc.tree.path_to(args[0]) {
+                Some(path) => {
+                    let names: Vec<&str> = path.iter().map(|m| m.name.as_str()).collect();
+                    outln!("{}", names.join(" → "));
+                }
+                None => fail!(failed, "❌ 未找到【{}】", args[0]),
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_ancestors(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：ancestors <姓名>");
+            return ControlFlow::Continue(());
+        };
+        let Some(path) = doc.tree.path_to(name) else {
+            fail!(failed, "❌ 未找到【{}】", name);
+            return ControlFlow::Continue(());
+        };
+        let member = path[path.len() - 1];
+        outln!(
+            "【{}】（{}，{} 年生，{}）",
+            member.name,
+            member.member_type,
+            member.birth_year,
+            member.status()
+        );
+        if path.len() == 1 {
+            outln!("是家主，没有祖辈");
+            return ControlFlow::Continue(());
+        }
+        for (up, ancestor) in path[..path.len() - 1].iter().rev().enumerate() {
+            outln!(
+                "  上 {} 代  {}{}{} 年生  {}",
+                up + 1,
+                render::pad(&ancestor.name, 12),
+                render::pad(&ancestor.member_type.to_string(), 8),
+                ancestor.birth_year,
+                ancestor.status()
+            );
+        }
+        if path.len() > 2 {
+            outln!("属【{}】一房", path[1].name);
+        }
+        // 外系成员经某位女性成员传下，指出是哪一位
+        if member.member_type.lineage == Lineage::Foreign {
+            if let Some(daughter) = path.iter().find(|m| m.member_type.gender == Gender::Female) {
+                outln!(
+                    "外系：经【{}】（{}）一支传下",
+                    daughter.name,
+                    daughter.member_type
+                );
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_siblings(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：siblings <姓名>");
+            return ControlFlow::Continue(());
+        };
+        let Some(siblings) = doc.tree.siblings(name) else {
+            fail!(failed, "❌ 未找到【{}】", name);
+            return ControlFlow::Continue(());
+        };
+        let Some(parent) = doc.tree.parent_of(name) else {
+            outln!("【{}】是家主，没有兄弟姐妹", name);
+            return ControlFlow::Continue(());
+        };
+        if siblings.is_empty() {
+            outln!("【{}】是【{}】唯一的子嗣，没有兄弟姐妹", name, parent.name);
+            return ControlFlow::Continue(());
+        }
+        // 同年出生者排在本人之后
+        let member = doc.tree.find_member_by_name(name).expect("成员存在");
+        let rank = siblings
+            .iter()
+            .filter(|s| s.birth_year < member.birth_year)
+            .count()
+            + 1;
+        outln!(
+            "【{}】的兄弟姐妹 {} 人（【{}】之子女，本人排行第 {}，共 {} 人）：",
+            name,
+            siblings.len(),
+            parent.name,
+            rank,
+            siblings.len() + 1
+        );
+        print_kin(&doc.tree, name, &siblings, false);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_cousins(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：cousins <姓名>");
+            return ControlFlow::Continue(());
+        };
+        let Some(cousins) = doc.tree.cousins(name) else {
+            fail!(failed, "❌ 未找到【{}】", name);
+            return ControlFlow::Continue(());
+        };
+        if cousins.is_empty() {
+            outln!("【{}】没有堂表兄弟姐妹", name);
+            return ControlFlow::Continue(());
+        }
+        outln!(
+            "【{}】的堂表兄弟姐妹 {} 人（按出生先后）：",
+            name,
+            cousins.len()
+        );
+        print_kin(&doc.tree, name, &cousins, true);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_relationship(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let [a, b] = args.as_slice() else {
+            fail!(failed, "用法：relationship <甲> <乙>");
+            return ControlFlow::Continue(());
+        };
+        match kinship::relate(&doc.tree, a, b) {
+            Ok(kinship) => out!("{}", kinship.describe()),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_prune(&mut self) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            confirm,
+            failed,
+            ..
+        } = self;
+        match *current_year {
+            None => {
+                fail!(failed, "❌ 请先设置年份：year <年份>");
+            }
+            Some(year) => {
+                // 先在副本上试算，确认后再替换
+                let mut pruned = doc.tree.clone();
+                let removed = match pruned.prune_future_births(year) {
+                    Ok(removed) => removed,
+                    Err(e) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                };
+                if removed.is_empty() {
+                    outln!("没有 {} 年后出生的成员", year);
+                    return ControlFlow::Continue(());
+                }
+
+                let count: usize = removed.iter().map(|m| m.total_count()).sum();
+                statusln!(
+                    "⚠️  即将删除 {} 年后出生的所有成员（用于退档），共 {} 人",
+                    year,
+                    count
+                );
+                if confirm.ask("确认删除？") {
+                    doc.tree = pruned;
+                    let names: Vec<String> =
+                        removed.iter().map(|m| format!("【{}】", m.name)).collect();
+                    statusln!("🗑️ 已移入回收站：{}", names.join("、"));
+                    doc.trash_members(removed, year);
+                } else {
+                    fail!(failed, "❌ 已取消");
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_delete(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            confirm,
+            failed,
+            ..
+        } = self;
+        let [name] = args.as_slice() else {
+            fail!(failed, "用法：delete <姓名>");
+            return ControlFlow::Continue(());
+        };
+        let Some(year) = *current_year else {
+            fail!(failed, "❌ 请先设置年份：year <年份>");
+            return ControlFlow::Continue(());
+        };
+        if doc.tree.is_root(name) {
+            fail!(failed, "❌ 家主不可删除，更换家主请使用 inherit");
+            return ControlFlow::Continue(());
+        }
+        let Some(member) = doc.tree.find_member_by_name(name) else {
+            fail!(failed, "❌ 未找到成员【{}】", name);
+            return ControlFlow::Continue(());
+        };
+
+        let descendants = member.total_count() - 1;
+        if descendants == 0 {
+            statusln!("⚠️  即将删除【{}】", name);
+        } else {
+            statusln!(
+                "⚠️  即将删除【{}】及其 {} 名后代，共 {} 人",
+                name,
+                descendants,
+                descendants + 1
+            );
+        }
+        if !confirm.ask("确认删除？") {
+            fail!(failed, "❌ 已取消");
+            return ControlFlow::Continue(());
+        }
+
+        if let Some(removed) = doc.tree.remove(name) {
+            doc.trash_members(vec![removed], year);
+            statusln!("🗑️ 已将【{}】一支移入回收站", name);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_move(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        let [name, new_parent] = args.as_slice() else {
+            fail!(failed, "用法：move <姓名> <新父辈>");
+            return ControlFlow::Continue(());
+        };
+        let before = doc.tree.clone();
+
+        if let Err(e) = doc.tree.reparent(name, new_parent) {
+            fail!(failed, "❌ {}", e);
+            return ControlFlow::Continue(());
+        }
+
+        // name 可能是移动前的路径写法，移动后的成员是新父辈的最后一名子嗣
+        let parent = doc.tree.find_member_by_name(new_parent).expect("新父辈");
+        let member = parent.children.last().expect("刚移动的成员");
+        statusln!(
+            "✅ 已将【{}】一支（{} 人）移到【{}】名下",
+            name,
+            member.total_count(),
+            new_parent
+        );
+        print_relabels(&before.label_changes(&doc.tree));
+        if member.birth_year < parent.birth_year {
+            statusln!(
+                "⚠️  【{}】({}) 早于新父辈【{}】({}) 出生，请确认",
+                name,
+                member.birth_year,
+                new_parent,
+                parent.birth_year
+            );
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_merge_members(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            confirm,
+            failed,
+            ..
+        } = self;
+        let [keep, duplicate] = args.as_slice() else {
+            fail!(failed, "用法：merge-members <保留> <移除>");
+            return ControlFlow::Continue(());
+        };
+        // 先在副本上合并，校验不通过时不必询问
+        let mut merged = doc.tree.clone();
+        let moved = match merged.merge_members(keep, duplicate) {
+            Ok(moved) => moved,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        let removed = doc.tree.find_member_by_name(duplicate).expect("已确认存在");
+        statusln!(
+            "⚠️  即将把【{}】({}) 的 {} 名子女与 {} 条任职记录并入【{}】，并移除【{}】",
+            duplicate,
+            removed.birth_year,
+            moved,
+            removed.position_history.len(),
+            keep,
+            duplicate
+        );
+        print_relabels(&doc.tree.label_changes(&merged));
+        // 重新校验：只列出合并后新出现的问题（如子女早于新父辈出生）
+        let power_range = doc.settings.power_range;
+        let existing = check::check(&doc.tree, power_range);
+        let report = check::check(&merged, power_range);
+        let issues = report.introduced(&existing);
+        if !issues.is_empty() {
+            statusln!("⚠️  合并后将出现 {} 个数据问题：", issues.len());
+            for finding in &issues {
+                outln!("  - {}", finding.describe());
+            }
+        }
+        if !confirm.ask("确认合并？") {
+            fail!(failed, "❌ 已取消");
+            return ControlFlow::Continue(());
+        }
+
+        doc.tree = merged;
+        statusln!("✅ 已将【{}】并入【{}】", duplicate, keep);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_suggest_prune(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            confirm,
+            failed,
+            ..
+        } = self;
+        let older_than = match args.as_slice() {
+            [] => 60,
+            ["--older-than", n] => match n.parse::<u16>() {
+                Ok(n) => n,
+                Err(_) => {
+                    fail!(failed, "❌ 无效的年数");
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法：suggest-prune [--older-than <年数>]");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let Some(year) = *current_year else {
+            fail!(failed, "❌ 请先设置年份：year <年份>");
+            return ControlFlow::Continue(());
+        };
+
+        // 按 ID 记下各支，移除时不会因重名误删在世的同名成员
+        let candidates: Vec<(Option<u64>, String, usize, i32)> = doc
+            .tree
+            .dead_branches()
+            .into_iter()
+            .filter(|b| year - b.latest_birth_year() >= i32::from(older_than))
+            .map(|b| (b.id, b.name.clone(), b.total_count(), b.latest_birth_year()))
+            .collect();
+
+        if candidates.is_empty() {
+            outln!(
+                "没有全部成员已故、且最晚出生者距今满 {} 年的分支",
+                older_than
+            );
+            return ControlFlow::Continue(());
+        }
+
+        let removed: usize = candidates.iter().map(|(_, _, n, _)| n).sum();
+        outln!(
+            "以下分支全部成员已故，且最晚出生者距今已满 {} 年：",
+            older_than
+        );
+        for (_, name, count, latest) in &candidates {
+            outln!(
+                "  - 【{}】一支，共 {} 人，最晚出生于 {} 年",
+                name,
+                count,
+                latest
+            );
+        }
+        let total = doc.tree.total_count();
+        outln!(
+            "移除后家族树由 {} 人缩减为 {} 人（减少 {} 人）",
+            total,
+            total - removed,
+            removed
+        );
+
+        if !confirm.ask("移入回收站？") {
+            fail!(failed, "❌ 已取消");
+            return ControlFlow::Continue(());
+        }
+
+        let branches: Vec<_> = candidates
+            .iter()
+            .filter_map(|(id, _, _, _)| id.and_then(|id| doc.tree.remove_by_id(id)))
+            .collect();
+        let count = branches.len();
+        doc.trash_members(branches, year);
+        statusln!("🗑️ 已将 {} 支移入回收站", count);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_rename(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            confirm,
+            failed,
+            ..
+        } = self;
+        if args.len() != 2 {
+            fail!(failed, "用法：rename <旧名> <新名>");
+        } else {
+            let old_name = args[0];
+            let new_name = args[1];
+            let guard = doc
+                .root_protection
+                .guard(RootAction::Rename, &doc.tree, old_name);
+            if guard == Guard::Refuse {
+                fail!(
+                    failed,
+                    "❌ 保护策略禁止为家主改名（protect rename 可修改策略）"
+                );
+                return ControlFlow::Continue(());
+            }
+
+            // 旧名可能是路径或 #ID，提示用成员的实际姓名
+            let resolved = doc
+                .tree
+                .find_member_by_name(old_name)
+                .map(|m| m.name.clone());
+            match doc.tree.rename(old_name, new_name) {
+                Ok(_) => {
+                    let old_name = resolved.as_deref().unwrap_or(old_name);
+                    statusln!("✅ 已将【{}】改名为【{}】", old_name, new_name)
+                }
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            }
+
+            // 家主改名后族名可能随之改变
+            if guard == Guard::Prompt {
+                prompt_surname_update(doc, new_name, *confirm);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_die(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            current_year,
+            archiver,
+            confirm,
+            failed,
+            ..
+        } = self;
+        if args.len() != 1 {
+            fail!(failed, "用法：die <姓名>");
+        } else {
+            let name = args[0];
+            let guard = doc.root_protection.guard(RootAction::Die, &doc.tree, name);
+            if guard == Guard::Refuse {
+                fail!(failed, "❌ 保护策略禁止将家主标记为死亡，请先 inherit 传位");
+                return ControlFlow::Continue(());
+            }
+
+            let missing_since = doc
+                .tree
+                .find_member_by_name(name)
+                .and_then(|m| m.missing_since);
+            match doc.tree.mark_dead(name) {
+                Ok(_) => match missing_since {
+                    Some(year) => statusln!(
+                        "✅ 已将【{}】标记为死亡（此前自 {} 年起下落不明）。",
+                        name,
+                        year
+                    ),
+                    None => statusln!("✅ 已将【{}】标记为死亡。", name),
+                },
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            }
+
+            let title = doc
+                .tree
+                .find_member_by_name(name)
+                .and_then(|m| m.position.clone());
+            if let Some(title) = title {
+                if catalog::holder(&doc.tree, &title).is_none() {
+                    statusln!(
+                        "ℹ️ 【{}】出缺，可执行 position succeed {} 查看继任人选",
+                        title,
+                        title
+                    );
+                }
+            }
+
+            if guard == Guard::Prompt {
+                prompt_succession(doc, archiver, store.as_ref(), *current_year, *confirm);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_edit(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            current_year,
+            archiver,
+            confirm,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [name, field, value @ ..] if !value.is_empty() => {
+                let field = match field.parse() {
+                    Ok(field) => field,
+                    Err(e) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                };
+                let value = value.join(" ");
+                if let Err(e) = edit_member(doc, name, field, &value) {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+                if field == edit::MemberField::Dead && value_is_dead(doc, name) {
+                    after_death(doc, archiver, store.as_ref(), *current_year, *confirm, name);
+                }
+            }
+            [name] if confirm.interactive() => {
+                let Some(member) = doc.tree.find_member_by_name(name) else {
+                    fail!(failed, "❌ 未找到成员【{}】", name);
+                    return ControlFlow::Continue(());
+                };
+                let was_dead = member.is_dead;
+                statusln!("📝 编辑【{}】：逐项输入新值，直接回车保留原值", name);
+                for field in edit::MemberField::ALL {
+                    loop {
+                        let current = doc
+                            .tree
+                            .find_member_by_name(name)
+                            .map(|m| field.value(m))
+                            .unwrap_or_default();
+                        out!("{}（{}）[{}]：", field.label(), field.hint(), current);
+                        io::stdout().flush().unwrap();
+                        let mut input = String::new();
+                        record::read_line(&mut input).ok();
+                        let input = input.trim();
+                        if input.is_empty() || input == current {
+                            break;
+                        }
+                        match edit_member(doc, name, field, input) {
+                            Ok(()) => break,
+                            Err(e) => statusln!("❌ {}，请重新输入", e),
+                        }
+                    }
+                }
+                if !was_dead && value_is_dead(doc, name) {
+                    after_death(doc, archiver, store.as_ref(), *current_year, *confirm, name);
+                }
+            }
+            [_] => fail!(failed, "❌ 批处理模式下请使用 edit <姓名> <字段> <值>"),
+            _ => {
+                let keys: Vec<&str> = edit::MemberField::ALL.iter().map(|f| f.key()).collect();
+                fail!(
+                    failed,
+                    "用法：edit <姓名> [<字段> <值>]，字段：{}",
+                    keys.join(", ")
+                );
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_missing(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let year = match args.as_slice() {
+            [_] => *current_year,
+            [_, year] => match model::parse_year(year) {
+                Ok(year) => Some(year),
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法：missing <姓名> [<年份>]");
+                return ControlFlow::Continue(());
+            }
+        };
+        let Some(year) = year else {
+            fail!(
+                failed,
+                "❌ 请给出失踪年份：missing <姓名> <年份>，或先设置 year"
+            );
+            return ControlFlow::Continue(());
+        };
+        let name = args[0];
+        match doc.tree.mark_missing(name, year) {
+            Ok(()) => {
+                statusln!("✅ 已将【{}】标记为自 {} 年起下落不明。", name, year);
+                if doc.tree.is_root(name) {
+                    statusln!("ℹ️ 家主下落不明，如需另立家主请执行 inherit <姓名>");
+                }
+            }
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_found(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        if args.len() != 1 {
+            fail!(failed, "用法：found <姓名>");
+            return ControlFlow::Continue(());
+        }
+        match doc.tree.mark_found(args[0]) {
+            Ok(since) => statusln!("✅ 已寻回【{}】（自 {} 年起下落不明）。", args[0], since),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_clear(&mut self) -> ControlFlow<()> {
+        out!("\x1B[2J\x1B[1;1H");
+        io::stdout().flush().unwrap();
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_inherit(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            current_year,
+            archiver,
+            confirm,
+            failed,
+            ..
+        } = self;
+        if args.len() != 1 {
+            fail!(failed, "用法：inherit <姓名>");
+            return ControlFlow::Continue(());
+        }
+
+        let Some(year) = *current_year else {
+            fail!(failed, "❌ 请先执行 year <年份>");
+            return ControlFlow::Continue(());
+        };
+
+        if !check_heir(doc, year, args[0]) {
+            *failed = true;
+            return ControlFlow::Continue(());
+        }
+
+        if !confirm.ask(&format!("当前年份 {}，是否归档并继承？", year)) {
+            fail!(failed, "ℹ️ 已取消");
+            return ControlFlow::Continue(());
+        }
+
+        if !inherit(doc, archiver, store.as_ref(), year, args[0]) {
+            *failed = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_config(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        if args == ["reload"] {
+            match self.reload_config() {
+                Ok(changes) if changes.is_empty() => outln!("配置没有变化"),
+                Ok(changes) => {
+                    statusln!("✅ 已重新加载配置，{} 项变化：", changes.len());
+                    for c in &changes {
+                        outln!("  - {}", c);
+                    }
+                }
+                Err(e) => {
+                    let failed = &mut self.failed;
+                    fail!(failed, "❌ {}", e)
+                }
+            }
+            return ControlFlow::Continue(());
+        }
+        let Session {
+            store,
+            data_file,
+            tutorial,
+            sandbox,
+            confirm,
+            config,
+            autosave,
+            config_watch,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => {
+                outln!(
+                    "配置来自数据文件 {}，自动重新加载：{}",
+                    data_file,
+                    if *config_watch { "开启" } else { "关闭" }
+                );
+                out!("{}", config.describe());
+            }
+
+            ["watch", "on"] => {
+                *config_watch = true;
+                statusln!("✅ 数据文件变化时将自动重新加载配置");
+            }
+            ["watch", "off"] => {
+                *config_watch = false;
+                statusln!("✅ 已关闭自动重新加载配置");
+            }
+            ["get", key] => match config.get(key) {
+                Ok(value) => outln!("{} = {}", key, value),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            ["set", key, _] | ["unset", key] => {
+                let mut updated = config.clone();
+                let changed = match args.as_slice() {
+                    [_, _, value] => updated.set(key, value),
+                    _ => updated.unset(key),
+                };
+                let path = match changed.and_then(|_| updated.save()) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                };
+                *config = updated;
+                statusln!(
+                    "✅ {} = {}（已写入 {}）",
+                    key,
+                    config.get(key).unwrap_or_default(),
+                    path.display()
+                );
+                match *key {
+                    "autosave" => *autosave = config.autosave,
+                    "confirm" => {
+                        *confirm = match (*confirm, config.confirm) {
+                            (Confirm::Ask, false) => Confirm::Auto,
+                            (Confirm::Auto, true) => Confirm::Ask,
+                            (confirm, _) => confirm,
+                        }
+                    }
+                    "archive_dir" => {
+                        let isolated = sandbox.is_some() || tutorial.is_some();
+                        *store = Box::new(file_store(config, data_file, isolated));
+                        if isolated {
+                            outln!("   沙盒/教程中归档仍写在临时数据文件旁，退出后生效");
+                        }
+                    }
+                    "data_file" | "language" => outln!("   下次启动时生效"),
+                    _ => {}
+                }
+            }
+            _ => {
+                fail!(
+                    failed,
+                "用法：config [reload | watch <on|off> | get <键> | set <键> <值> | unset <键>]"
+            )
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_autosave(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            data_file,
+            autosave,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => match autosave {
+                Some(n) => outln!("自动保存：每 {} 条改动保存一次", n),
+                None => outln!("自动保存：关闭"),
+            },
+            ["off"] => {
+                *autosave = None;
+                statusln!("✅ 已关闭自动保存");
+            }
+            [n] => match n.parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    *autosave = Some(n);
+                    statusln!("✅ 每 {} 条改动自动保存到 {}", n, data_file);
+                }
+                _ => fail!(failed, "❌ 无效的条数：{}", n),
+            },
+            _ => fail!(failed, "用法：autosave [<条数> | off]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_theme(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { failed, .. } = self;
+        match args.as_slice() {
+            [] => outln!("当前主题：{}", theme::current()),
+            [name] => match name.parse() {
+                Ok(chosen) => {
+                    theme::set(chosen);
+                    statusln!("✅ 已切换为 {} 主题", chosen);
+                }
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(failed, "用法：theme [emoji|ascii|minimal]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_export(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let collapse = args.contains(&"--collapse-dead");
+        args.retain(|a| *a != "--collapse-dead");
+        let mut min_generation = 1;
+        if let Some(i) = args.iter().position(|a| *a == "--min-generation") {
+            match args.get(i + 1).and_then(|n| n.parse().ok()) {
+                Some(n) if collapse => min_generation = n,
+                Some(_) => {
+                    fail!(failed, "❌ --min-generation 需与 --collapse-dead 一起使用");
+                    return ControlFlow::Continue(());
+                }
+                None => {
+                    fail!(failed, "❌ --min-generation 后应为代数");
+                    return ControlFlow::Continue(());
+                }
+            }
+            args.drain(i..i + 2);
+        }
+
+        // HTML 报告需要族名、年份等整份数据，单独处理
+        if args.first() == Some(&"html") {
+            let [_, dir] = args.as_slice() else {
+                fail!(failed, "用法：export html <目录>");
+                return ControlFlow::Continue(());
+            };
+            if collapse {
+                fail!(failed, "❌ --collapse-dead 只适用于 dot、mermaid、svg 导出");
+                return ControlFlow::Continue(());
+            }
+            let path = Path::new(dir).join("index.html");
+            let written = fs::create_dir_all(dir)
+                .and_then(|()| fs::write(&path, html::report(doc, *current_year)));
+            match written {
+                Ok(()) => {
+                    statusln!("✅ 已生成报告 {}", path.display());
+                    outln!("   可直接用浏览器打开，或连同目录分享给他人");
+                }
+                Err(e) => fail!(failed, "❌ 写入 {} 失败：{}", path.display(), e),
+            }
+            return ControlFlow::Continue(());
+        }
+
+        let (renderer, format): (Box<dyn Renderer>, _) = match args.first() {
+            Some(&"dot") => (Box::new(render::Dot), "dot"),
+            Some(&"mermaid") => (Box::new(render::Mermaid), "mermaid"),
+            Some(&"gedcom") => (Box::new(render::Gedcom), "gedcom"),
+            Some(&"csv") => (Box::new(render::Csv), "csv"),
+            Some(&"md") => (Box::new(render::Markdown), "md"),
+            Some(&"svg") => (Box::new(render::Svg), "svg"),
+            _ => {
+                fail!(failed, "用法：export <csv|dot|mermaid|gedcom|md|html|svg> [<文件>] [--collapse-dead [--min-generation <代数>]]");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        // md 可只导出某人一支：第一个参数是成员姓名时作为起点，其余为文件
+        let mut root = &doc.tree;
+        if format == "md" {
+            if let Some(member) = args.get(1).and_then(|n| doc.tree.find_member_by_name(n)) {
+                root = member;
+                args.remove(1);
+            }
+        }
+
+        // 折叠只用于分享的图表，数据交换格式须保留全部成员
+        let (tree, collapsed) = if collapse {
+            if !matches!(format, "dot" | "mermaid" | "svg") {
+                fail!(failed, "❌ --collapse-dead 只适用于 dot、mermaid、svg 导出");
+                return ControlFlow::Continue(());
+            }
+            let (tree, n) = render::collapse_dead(root, min_generation);
+            (Cow::Owned(tree), n)
+        } else {
+            (Cow::Borrowed(root), 0)
+        };
+        let mut content = renderer.render_tree(&tree);
+        // Mermaid 默认包在代码块中，可直接粘贴到 Markdown；.mmd 文件输出原文
+        if format == "mermaid" && !args.get(1).is_some_and(|p| p.ends_with(".mmd")) {
+            content = format!("```mermaid\n{}```\n", content);
+        }
+        // 带 BOM 的 UTF-8，Excel 打开时中文不乱码
+        if format == "csv" && args.len() > 1 {
+            content.insert(0, '\u{feff}');
+        }
+        match &args[1..] {
+            [] => out!("{}", content),
+            [path] => match fs::write(path, content) {
+                Ok(()) => {
+                    statusln!("✅ 已导出到 {}", path);
+                    if collapse {
+                        outln!(
+                            "   已将 {} 支全部已故的远支（距家主 {} 代及以上）折叠为汇总节点",
+                            collapsed,
+                            min_generation
+                        );
+                    }
+                    if format == "dot" {
+                        outln!("   可用 dot -Tsvg {} -o family.svg 渲染", path);
+                    }
+                }
+                Err(e) => fail!(failed, "❌ 写入 {} 失败：{}", path, e),
+            },
+            _ if format == "md" => fail!(failed, "用法：export md [<姓名>] [<文件>]"),
+            _ => fail!(failed, "用法：export {} [<文件>]", format),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_diff(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { store, failed, .. } = self;
+        let mut args = args;
+        let mut dot = None;
+        if let Some(i) = args.iter().position(|a| *a == "--dot") {
+            let Some(path) = args.get(i + 1).copied() else {
+                fail!(failed, "❌ --dot 后应为文件路径");
+                return ControlFlow::Continue(());
+            };
+            dot = Some(path);
+            args.drain(i..i + 2);
+        }
+        let [from, to] = args.as_slice() else {
+            fail!(failed, "用法：diff <年份A> <年份B> [--dot <文件>]");
+            return ControlFlow::Continue(());
+        };
+        let load = |year: &str| -> Result<FamilyDocument, String> {
+            let year = model::parse_year(year).map_err(|e| e.to_string())?;
+            store
+                .load_archive(year)
+                .map_err(|e| format!("读取 {} 年的归档失败：{}", year, e))
+        };
+        let (old, new) = match (load(from), load(to)) {
+            (Ok(old), Ok(new)) => (old, new),
+            (Err(e), _) | (_, Err(e)) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        let changes = diff::compare(&old.tree, &new.tree);
+        outln!("{} 年 → {} 年：", from, to);
+        out!("{}", changes.describe());
+        if let Some(path) = dot {
+            let title = format!("{} 年 → {} 年", from, to);
+            match fs::write(path, changes.to_dot(&old.tree, &new.tree, &title)) {
+                Ok(()) => {
+                    statusln!("✅ 已导出差异图到 {}", path);
+                    outln!("   可用 dot -Tsvg {} -o diff.svg 渲染", path);
+                }
+                Err(e) => fail!(failed, "❌ 写入 {} 失败：{}", path, e),
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_record(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            data_file,
+            current_year,
+            confirm,
+            recorder,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => match recorder {
+                Some(r) => outln!(
+                    "正在录制到 {}，已录制 {} 条命令",
+                    r.path().display(),
+                    r.steps()
+                ),
+                None => outln!("未在录制，record start <文件> 开始"),
+            },
+            ["start", path] => {
+                if let Some(r) = recorder {
+                    fail!(
+                        failed,
+                        "❌ 已在录制到 {}，请先 record stop",
+                        r.path().display()
+                    );
+                    return ControlFlow::Continue(());
+                }
+                let header = record::Header::new(data_file, *current_year, confirm.label(), doc);
+                match Recorder::start(Path::new(path), &header) {
+                    Ok(r) => {
+                        *recorder = Some(r);
+                        statusln!("⏺️ 开始录制会话到 {}（含当前数据的完整副本）", path);
+                        outln!("   之后的每条命令、输出与前后数据哈希都会写入，record stop 结束");
+                    }
+                    Err(e) => fail!(failed, "❌ {}", e),
+                }
+            }
+            ["stop"] => match recorder.take() {
+                Some(r) => {
+                    statusln!(
+                        "✅ 已停止录制，{} 条命令已写入 {}",
+                        r.steps(),
+                        r.path().display()
+                    );
+                    outln!("   可用 replay {} 重现", r.path().display());
+                }
+                None => fail!(failed, "❌ 未在录制"),
+            },
+            _ => fail!(failed, "用法：record [start <文件> | stop]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_replay(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            recorder, failed, ..
+        } = self;
+        let (path, show) = match args.as_slice() {
+            [path] => (*path, false),
+            [path, "--show"] | ["--show", path] => (*path, true),
+            _ => {
+                fail!(failed, "用法：replay <文件> [--show]");
+                return ControlFlow::Continue(());
+            }
+        };
+        if recorder.is_some() {
+            fail!(failed, "❌ 录制中不能回放，请先 record stop");
+            return ControlFlow::Continue(());
+        }
+        match replay(path, show) {
+            Ok((steps, 0)) => statusln!("✅ 回放完成：{} 条命令的结果均与录制一致", steps),
+            Ok((steps, diverged)) => fail!(
+                failed,
+                "❌ 回放完成：{} 条命令中有 {} 条与录制不同",
+                steps,
+                diverged
+            ),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_report(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let (source, output) = match args.as_slice() {
+            [source] => (*source, None),
+            [source, output] => (*source, Some(*output)),
+            _ => {
+                fail!(failed, "用法：report <模板文件> [<输出文件>]");
+                return ControlFlow::Continue(());
+            }
+        };
+        let rendered = fs::read_to_string(source)
+            .map_err(|e| format!("读取模板 {} 失败：{}", source, e))
+            .and_then(|text| template::Template::parse(&text))
+            .and_then(|t| t.render(&template::context(doc, *current_year)))
+            .map_err(|e| format!("{}：{}", source, e));
+        let content = match rendered {
+            Ok(content) => content,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        match output {
+            None => out!("{}", content),
+            Some(path) => match fs::write(path, content) {
+                Ok(()) => statusln!("✅ 已按模板 {} 生成报表：{}", source, path),
+                Err(e) => fail!(failed, "❌ 写入 {} 失败：{}", path, e),
+            },
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_explain(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        match explain::plan(doc, &args, *current_year) {
+            Ok(plan) => print_report(&plan.describe()),
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_recover(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            archiver,
+            confirm,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let keep_trash = args.contains(&"--keep-trash");
+        args.retain(|a| *a != "--keep-trash");
+        let year = match args.as_slice() {
+            [] => None,
+            [year] => match model::parse_year(year) {
+                Ok(year) => Some(year),
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(failed, "用法：recover [<归档年份>] [--keep-trash]");
+                return ControlFlow::Continue(());
+            }
+        };
+        if !recover::run(
+            doc,
+            store.as_ref(),
+            year,
+            archiver.pending(),
+            keep_trash,
+            *confirm,
+        ) {
+            *failed = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_edit_file(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            data_file,
+            confirm,
+            failed,
+            ..
+        } = self;
+        if !args.is_empty() {
+            fail!(failed, "用法：edit-file");
+            return ControlFlow::Continue(());
+        }
+        if !editor::run(doc, data_file, *confirm) {
+            *failed = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_undo(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            history,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => match history.undo(doc) {
+                Some(label) => statusln!("↩️ 已撤销：{}", label),
+                None => fail!(failed, "❌ 没有可撤销的改动"),
+            },
+            ["--list"] => {
+                let undo = history.undo_labels();
+                let redo = history.redo_labels();
+                if undo.is_empty() && redo.is_empty() {
+                    outln!("本次会话还没有改动");
+                }
+                for (i, label) in undo.iter().enumerate() {
+                    outln!("  撤销 {}：{}", i + 1, label);
+                }
+                for (i, label) in redo.iter().enumerate() {
+                    outln!("  重做 {}：{}", i + 1, label);
+                }
+            }
+            _ => fail!(failed, "用法：undo [--list]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_redo(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            history,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => match history.redo(doc) {
+                Some(label) => statusln!("↪️ 已重做：{}", label),
+                None => fail!(failed, "❌ 没有可重做的改动"),
+            },
+            _ => fail!(failed, "用法：redo"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_why_not_heir(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        if args.len() != 1 {
+            fail!(failed, "用法：why-not-heir <姓名> [--as-of <年份>]");
+            return ControlFlow::Continue(());
+        }
+
+        match doc.settings.succession.explain(&tree, args[0], year) {
+            Ok(checks) => {
+                for c in &checks {
+                    let mark = match c.passed {
+                        Some(true) => "✅",
+                        Some(false) => "❌",
+                        None => "➖",
+                    };
+                    statusln!("{} {}：{}", mark, c.rule, c.detail);
+                }
+                if checks.iter().any(|c| c.passed == Some(false)) {
+                    outln!("结论：【{}】不能继承家主", args[0]);
+                } else {
+                    outln!("结论：【{}】可以继承家主", args[0]);
+                }
+            }
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_pick(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            current_year,
+            failed,
+            ..
+        } = self;
+        const USAGE: &str = "用法：pick [--filter <条件>]... [--weight power|age|uniform] [--seed <种子>] [--as-of <年份>]";
+
+        let mut args = args;
+        let (tree, year) = match as_of_view(&mut args, &doc.tree, *current_year) {
+            Ok(view) => view,
+            Err(e) => {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        };
+        let mut conditions = Vec::new();
+        let mut weight = pick::Weight::default();
+        let mut seed = None;
+        let mut options = args.iter();
+        while let Some(option) = options.next() {
+            let parsed = match (*option, options.next()) {
+                ("--filter", Some(v)) => v.parse().map(|c| conditions.push(c)),
+                ("--weight", Some(v)) => v.parse().map(|w| weight = w),
+                ("--seed", Some(v)) => v
+                    .parse()
+                    .map(|n| seed = Some(n))
+                    .map_err(|_| "种子应为非负整数".to_string()),
+                _ => {
+                    fail!(failed, "{USAGE}");
+                    return ControlFlow::Continue(());
+                }
+            };
+            if let Err(e) = parsed {
+                fail!(failed, "❌ {}", e);
+                return ControlFlow::Continue(());
+            }
+        }
+
+        // 未给种子时取当前时间，并给出种子以便复现
+        let given = seed.is_some();
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64)
+        });
+        match pick::pick(&tree, &conditions, weight, year, seed) {
+            Ok(result) => {
+                out!("{}", result.describe());
+                if !given {
+                    outln!("   加 --seed {} 可重现本次结果", seed);
+                }
+            }
+            Err(e) => fail!(failed, "❌ {}", e),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_succession(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.settings.succession.describe()),
+            [key, value] => match doc.settings.succession.set(key, value) {
+                Ok(_) => statusln!("✅ 已设置继承规则 {}：{}", key, value),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(
+                    failed,
+                "用法：succession [max-gen <代数> | alive-only|direct-only <on|off> | min-age <岁|off>]"
+            ),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_health_weights(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.settings.health.describe()),
+            [key, value] => match doc.settings.health.set(key, value) {
+                Ok(_) => statusln!("✅ 已设置健康度权重 {}：{}", key, value),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(
+                failed,
+                "用法：health-weights [living|heirs|power|age <权重>]"
+            ),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_lock(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => print_report(&doc.locked_branches.describe(&doc.tree)),
+            [name] => {
+                let Some(member) = doc.tree.find_member_by_name(name) else {
+                    fail!(failed, "❌ 未找到成员【{}】", name);
+                    return ControlFlow::Continue(());
+                };
+                let Some(id) = member.id else {
+                    fail!(failed, "❌ 【{}】还没有 ID，无法锁定", member.name);
+                    return ControlFlow::Continue(());
+                };
+                let count = member.total_count();
+                let name = &member.name;
+                if doc.locked_branches.lock(id) {
+                    statusln!(
+                        "🔒 已锁定【{}】一支（{} 人），改动其中成员需加 --unlock",
+                        name,
+                        count
+                    );
+                } else {
+                    statusln!("ℹ️ 【{}】一支已经锁定", name);
+                }
+            }
+            _ => fail!(failed, "用法：lock [<姓名>]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_unlock(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [name] => {
+                let Some(member) = doc.tree.find_member_by_name(name) else {
+                    fail!(failed, "❌ 未找到成员【{}】", name);
+                    return ControlFlow::Continue(());
+                };
+                let name = &member.name;
+                if member.id.is_some_and(|id| doc.locked_branches.unlock(id)) {
+                    statusln!("✅ 已解除【{}】一支的锁定", name);
+                } else {
+                    match doc.locked_branches.covering(&doc.tree, args[0]) {
+                        Some(top) => fail!(
+                            failed,
+                            "❌ 【{}】不是锁定分支的顶端，所在的锁定分支为【{}】",
+                            name,
+                            top.name
+                        ),
+                        None => fail!(failed, "❌ 【{}】不在锁定的分支中", name),
+                    }
+                }
+            }
+            _ => fail!(failed, "用法：unlock <姓名>"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_protect(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.root_protection.describe()),
+            [key, value] => match doc.root_protection.set(key, value) {
+                Ok(_) => statusln!("✅ 已设置家主保护策略 {}：{}", key, value),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(failed, "用法：protect [die|rename <allow|prompt|refuse>]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_doctor(&mut self) -> ControlFlow<()> {
+        let Session {
+            data_file,
+            tutorial,
+            sandbox,
+            config,
+            failed,
+            ..
+        } = self;
+        let archives = file_store(config, data_file, sandbox.is_some() || tutorial.is_some());
+        if !doctor::run(data_file, None, archives.archive_dir()) {
+            *failed = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_perf(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            data_file,
+            metrics,
+            failed,
+            ..
+        } = self;
+        match args.as_slice() {
+            [] => outln!(
+                "指标记录：{}，日志：{}",
+                if *metrics { "开启" } else { "关闭" },
+                metrics::log_path(data_file).display()
+            ),
+            ["on"] => {
+                *metrics = true;
+                statusln!(
+                    "✅ 已开启指标记录，写入 {}",
+                    metrics::log_path(data_file).display()
+                );
+            }
+            ["off"] => {
+                *metrics = false;
+                statusln!("✅ 已关闭指标记录");
+            }
+            ["report", rest @ ..] => {
+                let top = match rest {
+                    [] => 10,
+                    ["--top", n] => match n.parse() {
+                        Ok(n) => n,
+                        Err(_) => {
+                            fail!(failed, "❌ 无效的条数：{}", n);
+                            return ControlFlow::Continue(());
+                        }
+                    },
+                    _ => {
+                        fail!(failed, "用法：perf report [--top <条数>]");
+                        return ControlFlow::Continue(());
+                    }
+                };
+                let path = metrics::log_path(data_file);
+                if !path.exists() {
+                    fail!(failed, "❌ 尚无指标日志，先执行 perf on 开启记录");
+                    return ControlFlow::Continue(());
+                }
+                match PerfReport::load(&path, top) {
+                    Ok(report) => print_report(&report.describe()),
+                    Err(e) => fail!(failed, "❌ {}", e),
+                }
+            }
+            _ => fail!(failed, "用法：perf [on | off | report [--top <条数>]]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_limits(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        match args.as_slice() {
+            [] => out!("{}", doc.limits.describe()),
+            [key, value] => match doc.limits.set(key, value) {
+                Ok(_) => statusln!("✅ 已设置 {} 上限：{}", key, value),
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            _ => fail!(failed, "用法：limits [living|children <数量|off>]"),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_tutorial(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            data_file,
+            current_year,
+            tutorial,
+            sandbox,
+            confirm,
+            last_command,
+            failed,
+            ..
+        } = self;
+        match (args.as_slice(), tutorial.take()) {
+            ([], None) if !confirm.interactive() => {
+                fail!(failed, "❌ 批处理模式不支持教程");
+            }
+            ([], None) if sandbox.is_some() => {
+                fail!(
+                    failed,
+                    "❌ 沙盒中无法开始教程，请先 sandbox commit 或 sandbox discard"
+                );
+            }
+            ([], None) => {
+                match Tutorial::start(doc, data_file, *current_year) {
+                    Ok(t) => *tutorial = Some(t),
+                    Err(e) => fail!(failed, "❌ {}", e),
+                }
+                last_command.clear();
+            }
+            (["exit"], Some(t)) => t.finish(doc, data_file, current_year),
+            (_, t) => {
+                if t.is_some() {
+                    statusln!("ℹ️ 教程进行中，输入 `tutorial exit` 退出");
+                } else {
+                    fail!(failed, "用法：tutorial | tutorial exit");
+                }
+                *tutorial = t;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_sandbox(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            data_file,
+            current_year,
+            tutorial,
+            sandbox,
+            failed,
+            ..
+        } = self;
+        match (args.as_slice(), sandbox.take()) {
+            ([], None) if tutorial.is_some() => {
+                fail!(failed, "❌ 教程进行中，无法进入沙盒");
+            }
+            ([], None) => match Sandbox::enter("sandbox", doc, data_file, *current_year) {
+                Ok(s) => {
+                    *sandbox = Some(s);
+                    statusln!("🧪 已进入沙盒，save 只会写入临时文件：{}", data_file);
+                    outln!("   完成后输入 `sandbox commit` 保留改动，或 `sandbox discard` 放弃。");
+                }
+                Err(e) => fail!(failed, "❌ {}", e),
+            },
+            (["commit"], Some(s)) => {
+                s.commit(data_file);
+                statusln!("✅ 已保留沙盒中的改动，执行 save 写入 {}", data_file);
+            }
+            (["discard"], Some(s)) => {
+                s.discard(doc, data_file, current_year);
+                statusln!("ℹ️ 已放弃沙盒中的改动");
+            }
+            (_, s) => {
+                if s.is_some() {
+                    fail!(failed, "用法：sandbox commit | sandbox discard");
+                } else {
+                    fail!(failed, "用法：sandbox");
+                }
+                *sandbox = s;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_example(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { failed, .. } = self;
+        let mut path = None;
+        let mut size = example::DEFAULT_SIZE;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match *arg {
+                "--size" => match iter.next().and_then(|v| v.parse().ok()) {
+                    Some(n) => size = n,
+                    None => {
+                        fail!(failed, "用法：example [<路径>] [--size <人数>]");
+                        return ControlFlow::Continue(());
+                    }
+                },
+                other if path.is_none() => path = Some(other),
+                _ => {
+                    fail!(failed, "用法：example [<路径>] [--size <人数>]");
+                    return ControlFlow::Continue(());
+                }
+            }
+        }
+        example::run(Path::new(path.unwrap_or(cli::DEFAULT_DATA_FILE)), size);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_watch_game(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session {
+            doc,
+            store,
+            data_file,
+            current_year,
+            tutorial,
+            sandbox,
+            confirm,
+            config,
+            failed,
+            ..
+        } = self;
+        if !confirm.interactive() {
+            fail!(failed, "❌ 批处理模式不支持 watch-game（需按回车结束监视）");
+            return ControlFlow::Continue(());
+        }
+        let on_conflict = match args.as_slice() {
+            [_] => OnConflict::default(),
+            [_, "--on-conflict", policy] => match policy.parse() {
+                Ok(policy) => policy,
+                Err(e) => {
+                    fail!(failed, "❌ {}", e);
+                    return ControlFlow::Continue(());
+                }
+            },
+            _ => {
+                fail!(
+                    failed,
+                    "用法：watch-game <目录> [--on-conflict skip|update|error]"
+                );
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let Some(year) = *current_year else {
+            fail!(failed, "❌ 请先执行 year <年份>");
+            return ControlFlow::Continue(());
+        };
+
+        let dir = Path::new(args[0]);
+        let archives = file_store(config, data_file, sandbox.is_some() || tutorial.is_some());
+        if let Err(e) = watch::check_dir(dir, data_file, archives.archive_dir()) {
+            fail!(failed, "❌ {}", e);
+            return ControlFlow::Continue(());
+        }
+
+        watch::watch_game(dir, doc, store.as_ref(), year, on_conflict);
+        ControlFlow::Continue(())
+    }
+
+    fn cmd_import(&mut self, args: Vec<&str>) -> ControlFlow<()> {
+        let Session { doc, failed, .. } = self;
+        // --quarantine：跳过有误的记录，导入其余记录，有误的写入 import_rejects.json
+        let quarantine = args.contains(&"--quarantine");
+        let args: Vec<&str> = args.into_iter().filter(|a| *a != "--quarantine").collect();
+        let report_rejects = |path: &str, rejects: &[import::Reject]| {
+            if rejects.is_empty() {
+                return;
+            }
+            for reject in rejects {
+                statusln!("  ⚠️ {}", reject.describe());
+            }
+            match import::write_rejects(path, rejects) {
+                Ok(written) => statusln!(
+                    "⚠️  {} 条记录未导入，已写入 {}",
+                    rejects.len(),
+                    written.display()
+                ),
+                Err(e) => estatusln!(
+                    "❌ {} 条记录未导入，写入 {} 失败：{}",
+                    rejects.len(),
+                    import::REJECTS_FILE,
+                    e
+                ),
+            }
+        };
+
+        match args.as_slice() {
+            ["positions", path] => {
+                match import::import_positions(&mut doc.tree, path, quarantine) {
+                    Ok((n, rejects)) => {
+                        statusln!("✅ 已导入 {} 条任职记录", n);
+                        report_rejects(path, &rejects);
+                    }
+                    Err(errors) => {
+                        for e in &errors {
+                            fail!(failed, "❌ {}", e);
+                        }
+                        fail!(
+                            failed,
+                            "❌ 共 {} 处错误，未导入任何记录（可加 --quarantine 只导入无误的行）",
+                            errors.len()
+                        );
+                    }
+                }
+            }
+            ["branch", path] => match import::import_branch(&mut doc.tree, path, quarantine) {
+                Ok((names, rejects)) => {
+                    statusln!("✅ 已导入 {} 名成员", names.len());
+                    for name in &names {
+                        if let Some(path) = doc.tree.path_to(name) {
+                            let names: Vec<&str> = path.iter().map(|m| m.name.as_str()).collect();
+                            let member = path.last().expect("路径含成员本身");
+                            outln!("  {}（{}）", names.join(" → "), member.member_type);
+                        }
+                    }
+                    report_rejects(path, &rejects);
+                }
+                Err(errors) => {
+                    for e in &errors {
+                        fail!(failed, "❌ {}", e);
+                    }
+                    fail!(
+                        failed,
+                        "❌ 共 {} 处错误，未导入任何成员（可加 --quarantine 只导入无误的行）",
+                        errors.len()
+                    );
+                }
+            },
+            ["members", path, rest @ ..] => {
+                let on_conflict = match rest {
+                    [] => OnConflict::default(),
+                    ["--on-conflict", policy] => match policy.parse() {
+                        Ok(policy) => policy,
+                        Err(e) => {
+                            fail!(failed, "❌ {}", e);
+                            return ControlFlow::Continue(());
+                        }
+                    },
+                    _ => {
+                        fail!(failed, "用法：import members <JSON 文件> [--on-conflict skip|update|error] [--quarantine]");
+                        return ControlFlow::Continue(());
+                    }
+                };
+
+                let loaded = if quarantine {
+                    import::load_members_quarantined(path)
+                } else {
+                    FamilyDocument::load(Path::new(path)).map(|doc| (doc, Vec::new()))
+                };
+                let (incoming, rejects) = match loaded {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        fail!(failed, "❌ {}", e);
+                        return ControlFlow::Continue(());
+                    }
+                };
+
+                match merge::merge(&mut doc.tree, &incoming.tree, on_conflict) {
+                    Ok(report) => {
+                        statusln!("📥 {}", report.summary());
+                        print_report(&report.describe());
+                        report_rejects(path, &rejects);
+                    }
+                    Err(conflicts) => {
+                        for conflict in &conflicts {
+                            fail!(failed, "❌ {}", conflict);
+                        }
+                        fail!(failed, "❌ 共 {} 处冲突，未导入任何成员", conflicts.len());
+                    }
+                }
+            }
+            _ => {
+                fail!(failed, "用法：import positions <CSV 文件> [--quarantine]");
+                outln!("      import branch <CSV 文件> [--quarantine]");
+                outln!(
+                    "      import members <JSON 文件> [--on-conflict skip|update|error] [--quarantine]"
+                );
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 在录制数据的临时副本上逐条重新执行录制的命令，逐条与录制时的结果比较
+///
+/// 确认与补充输入使用录制时的应答。`show` 为 false 时不打印各命令的输出，
+/// 只报告与录制时不同之处。
+///
+/// # Returns
+/// 执行的命令数与结果不同的命令数。
+fn replay(path: &str, show: bool) -> Result<(usize, usize), String> {
+    let (header, steps) = record::load(Path::new(path))?;
+    let confirm = Confirm::from_label(&header.confirm)
+        .ok_or_else(|| format!("未知的确认方式【{}】", header.confirm))?;
+    let mut data_file = String::new();
+    // 临时目录随 sandbox 一起删除
+    let sandbox = Sandbox::enter("replay", &header.doc, &mut data_file, None)?;
+    let mut session = Session::new(
+        header.doc.clone(),
+        Box::new(FileStore::new(&data_file)),
+        confirm,
+    );
+    session.current_year = header.current_year;
+
+    statusln!(
+        "▶️ 回放 {}：{} 条命令，录制于 {}（版本 {}，年份 {}）",
+        path,
+        steps.len(),
+        header.data_file,
+        header.tool,
+        header
+            .current_year
+            .map_or("未设置".to_string(), |y| y.to_string())
+    );
+    let mut diverged = 0;
+    let mut executed = 0;
+    for (i, step) in steps.iter().enumerate() {
+        outln!("[{}/{}] {}", i + 1, steps.len(), step.command);
+        let before = record::tree_hash(&session.doc);
+        record::feed(step.input.clone());
+        record::begin(!show);
+        let outcome = session.execute(&step.command);
+        let (output, _) = record::finish();
+        let after = record::tree_hash(&session.doc);
+        executed += 1;
+
+        let mut differences = Vec::new();
+        if before != step.before {
+            differences.push(format!(
+                "执行前数据不同（录制 {}，回放 {}）",
+                step.before, before
+            ));
+        }
+        if after != step.after {
+            differences.push(format!(
+                "执行后数据不同（录制 {}，回放 {}）",
+                step.after, after
+            ));
+        }
+        if outcome.label() != step.outcome {
+            differences.push(format!(
+                "结果不同（录制 {}，回放 {}）",
+                step.outcome,
+                outcome.label()
+            ));
+        }
+        if output != step.output {
+            let recorded: Vec<&str> = step.output.lines().collect();
+            let replayed: Vec<&str> = output.lines().collect();
+            let line = (0..recorded.len().max(replayed.len()))
+                .find(|&n| recorded.get(n) != replayed.get(n))
+                .unwrap_or(0);
+            differences.push(format!(
+                "输出第 {} 行不同：\n      录制：{}\n      回放：{}",
+                line + 1,
+                recorded.get(line).unwrap_or(&"（无）"),
+                replayed.get(line).unwrap_or(&"（无）")
+            ));
+        }
+        if differences.is_empty() {
+            statusln!("  ✅ 与录制一致");
+        } else {
+            diverged += 1;
+            for d in &differences {
+                statusln!("  ❌ {}", d);
+            }
+        }
+        if outcome == Outcome::Exit {
+            break;
+        }
+    }
+    drop(sandbox);
+    Ok((executed, diverged))
+}
+
+/// 数据文件的存储：配置了归档目录时归档写入该目录，沙盒与教程（`isolated`）中仍写在临时数据文件旁
+pub fn file_store(config: &Config, data_file: &str, isolated: bool) -> FileStore {
+    match config.archive_dir_for(data_file).filter(|_| !isolated) {
+        Some(dir) => FileStore::with_archive_dir(data_file, dir),
+        None => FileStore::new(data_file),
+    }
+}
+
+/// 文件的修改时间，无法读取时为 `None`
+fn modified(path: impl AsRef<Path>) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 表格总宽上限：输出到终端时为终端宽度，录制与回放时不限，使输出与终端无关
+fn table_width() -> Option<usize> {
+    if record::capturing() {
+        return None;
+    }
+    pager::terminal_width()
+}
+
+/// 输出多行报告：带状态符号的行按主题替换符号，其余数据行原样输出
+fn print_report(text: &str) {
+    for line in text.lines() {
+        if theme::is_marked(line) {
+            statusln!("{}", line);
+        } else {
+            outln!("{}", line);
+        }
+    }
+}
+
+/// 打印结构改动带来的称谓变化，没有变化时不输出
+fn print_relabels(changes: &[Relabel]) {
+    if changes.is_empty() {
+        return;
+    }
+    statusln!("🏷️ 称谓随之调整：");
+    for line in model::describe_relabels(changes).lines() {
+        outln!("   {}", line);
+    }
+}
+
+/// 校验继承资格，归档后让成员继承家主，继位成功时返回 true
+fn inherit(
+    doc: &mut FamilyDocument,
+    archiver: &mut ArchiveWriter,
+    store: &dyn FamilyStore,
+    year: i32,
+    name: &str,
+) -> bool {
+    if !check_heir(doc, year, name) {
+        return false;
+    }
+
+    // 归档（后台写入，完成后提示）
+    match archiver.submit(PathBuf::from(store.archive_location(year)), doc) {
+        Ok(path) => statusln!("🗃️ 正在后台归档到 {}", path.display()),
+        Err(e) => {
+            statusln!("❌ 归档失败，未继承：{}", e);
+            return false;
+        }
+    }
+
+    // 继承
+    match doc.tree.inherit(name) {
+        Ok(new_tree) => {
+            let changes = doc.tree.label_changes(&new_tree);
+            doc.tree = new_tree;
+            statusln!("✅ 【{}】已继位", name);
+            print_relabels(&changes);
+            true
+        }
+        Err(e) => {
+            estatusln!("❌ {}", e);
+            false
+        }
+    }
+}
+
+/// 取出参数中的 `--as-of <年份>`，返回用于求值的家族树与年份
+///
+/// 未指定时为当前家族树与会话年份；指定时为推演到该年的副本，
+/// 会话年份不变。
+fn as_of_view<'a>(
+    args: &mut Vec<&str>,
+    tree: &'a FamilyMember,
+    current_year: Option<i32>,
+) -> Result<(Cow<'a, FamilyMember>, Option<i32>), ZzError> {
+    let Some(index) = args.iter().position(|a| *a == "--as-of") else {
+        return Ok((Cow::Borrowed(tree), current_year));
+    };
+    let year = args
+        .get(index + 1)
+        .ok_or_else(|| ZzError::Invalid("--as-of 后缺少年份".to_string()))?;
+    let year = model::parse_year(year)?;
+    args.drain(index..index + 2);
+
+    Ok((Cow::Owned(tree.as_of(year)?), Some(year)))
+}
+
+/// 逐行列出亲属：称呼、出生年与是否在世，`with_parent` 时另列其父辈
+fn print_kin(tree: &FamilyMember, name: &str, kin: &[&FamilyMember], with_parent: bool) {
+    for m in kin {
+        let term = kinship::relate(tree, name, &m.name)
+            .ok()
+            .and_then(|k| k.term)
+            .unwrap_or_default();
+        let parent = match tree.parent_of(&m.name) {
+            Some(p) if with_parent => {
+                let child = match m.member_type.gender {
+                    Gender::Male => "子",
+                    Gender::Female => "女",
+                };
+                format!("  【{}】之{}", p.name, child)
+            }
+            _ => String::new(),
+        };
+        outln!(
+            "  {}{}{} 年生  {}{}",
+            render::pad(&m.name, 12),
+            render::pad(&term, 8),
+            m.birth_year,
+            m.status(),
+            parent
+        );
+    }
+}
+
+/// 校验继承资格，不符合时逐条打印原因
+fn check_heir(doc: &FamilyDocument, year: i32, name: &str) -> bool {
+    match doc.settings.succession.check(&doc.tree, name, Some(year)) {
+        Ok(()) => true,
+        Err(ZzError::InvalidInheritance { reasons, .. }) => {
+            statusln!("❌ 【{}】不符合继承规则：", name);
+            for r in &reasons {
+                outln!("   - {}", r);
+            }
+            outln!("   输入 why-not-heir {} 查看完整判定", name);
+            false
+        }
+        Err(e) => {
+            statusln!("❌ {}", e);
+            false
+        }
+    }
+}
+
+/// 逐项询问并添加子嗣，称谓按父辈推算，每人确认后添加
+///
+/// 姓名处直接回车结束，输入 `json` 改为粘贴 JSON 数组。
+fn guided_add(doc: &mut FamilyDocument, parent: &str, confirm: Confirm) -> Result<(), ZzError> {
+    let (parent_type, parent_birth) = doc
+        .tree
+        .find_member_by_name(parent)
+        .map(|p| (p.member_type, p.birth_year))
+        .ok_or_else(|| ZzError::MemberNotFound(parent.to_string()))?;
+    statusln!(
+        "✅ 找到【{}】（{}，{} 年生），逐项输入子嗣信息",
+        parent,
+        parent_type,
+        parent_birth
+    );
+    outln!("   姓名处直接回车结束，输入 json 改为粘贴 JSON 数组");
+
+    let mut added = 0;
+    loop {
+        let name = read_answer("姓名：");
+        match name.as_str() {
+            "" => break,
+            "json" => {
+                let json = read_answer("> ");
+                let n = add_json(doc, parent, &json)?;
+                statusln!("✅ 已为【{}】添加 {} 名子嗣", parent, n);
+                return Ok(());
+            }
+            _ if doc.tree.exists(&name) => {
+                outln!("【{}】已存在，请换一个姓名", name);
+                continue;
+            }
+            _ => {}
+        }
+
+        // 直接回车放弃此人，输入结束时也由此退出
+        let birth_year = loop {
+            let answer = read_answer("出生年（直接回车放弃此人）：");
+            if answer.is_empty() {
+                break None;
+            }
+            match model::parse_year(&answer) {
+                Ok(year) if year < parent_birth => {
+                    statusln!("❌ 早于父辈的出生年 {}，请重新输入", parent_birth)
+                }
+                Ok(year) => break Some(year),
+                Err(e) => statusln!("❌ {}，请重新输入", e),
+            }
+        };
+        let Some(birth_year) = birth_year else {
+            continue;
+        };
+        let gender = loop {
+            match read_answer("性别（男/女）[男]：").as_str() {
+                "" | "男" | "m" | "male" => break Gender::Male,
+                "女" | "f" | "female" => break Gender::Female,
+                other => statusln!("❌ 无效的性别【{}】，请输入 男 或 女", other),
+            }
+        };
+        let power = loop {
+            let answer = read_answer("威望加成 [0]：");
+            if answer.is_empty() {
+                break 0;
+            }
+            match answer.parse() {
+                Ok(power) => break power,
+                Err(_) => statusln!("❌ 威望加成应为整数，请重新输入"),
+            }
+        };
+
+        let child = FamilyMember::builder()
+            .name(name)
+            .birth_year(birth_year)
+            .gender(gender)
+            .child_of(&parent_type)
+            .hoser_power_add(power)
+            .build()?;
+        let summary = format!(
+            "【{}】，{} 年生，{}，威望+{}",
+            child.name, child.birth_year, child.member_type, child.hoser_power_add
+        );
+        if confirm.ask(&format!("添加{}？", summary)) {
+            doc.tree.add_child(parent, child)?;
+            added += 1;
+            statusln!("✅ 已添加{}", summary);
+        } else {
+            statusln!("ℹ️ 未添加");
+        }
+    }
+    if added > 0 {
+        outln!("共为【{}】添加 {} 名子嗣", parent, added);
+    }
+    Ok(())
+}
+
+/// 按 JSON 数组添加子嗣，列出按父辈更正的称谓，返回添加的人数
+fn add_json(doc: &mut FamilyDocument, parent: &str, json: &str) -> Result<usize, ZzError> {
+    let count = |doc: &FamilyDocument| {
+        doc.tree
+            .find_member_by_name(parent)
+            .map_or(0, |p| p.children.len())
+    };
+    let before = count(doc);
+    let corrected = doc.tree.add_children(parent, json)?;
+    if !corrected.is_empty() {
+        statusln!("🏷️ 已按父辈更正称谓：");
+        for line in model::describe_relabels(&corrected).lines() {
+            outln!("   {}", line);
+        }
+    }
+    Ok(count(doc) - before)
+}
+
+/// `add --edit` 的模板：一名待填姓名的子嗣，出生年取父辈出生后 20 年，称谓按性别推算
+fn child_template(doc: &FamilyDocument, parent: &str) -> Option<String> {
+    let parent = doc.tree.find_member_by_name(parent)?;
+    Some(format!(
+        "[\n  {{\"name\": \"\", \"gender\": \"男\", \"birth_year\": {}, \"hoser_power_add\": 0}}\n]\n",
+        parent.birth_year + 20
+    ))
+}
+
+/// 提示并读取一行输入，去掉首尾空白
+fn read_answer(prompt: &str) -> String {
+    out!("{}", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    record::read_line(&mut input).ok();
+    input.trim().to_string()
+}
+
+/// 修改成员的一个字段并报告结果，称谓改动时列出随之调整的后代称谓
+///
+/// 将家主标记为已故受家主保护策略约束，与 `die` 相同。
+fn edit_member(
+    doc: &mut FamilyDocument,
+    name: &str,
+    field: edit::MemberField,
+    value: &str,
+) -> Result<(), String> {
+    if field == edit::MemberField::Dead && matches!(value, "true" | "是") {
+        let guard = doc.root_protection.guard(RootAction::Die, &doc.tree, name);
+        if guard == Guard::Refuse {
+            return Err("保护策略禁止将家主标记为死亡，请先 inherit 传位".to_string());
+        }
+    }
+    let before = doc.tree.clone();
+    let change = edit::set(&mut doc.tree, name, field, value).map_err(|e| e.to_string())?;
+    statusln!("✅ 已修改{}", change.describe());
+    let mut relabels = before.label_changes(&doc.tree);
+    // name 可能是路径写法，按解析出的成员排除本人
+    if let Some(member) = doc.tree.find_member_by_name(name) {
+        relabels.retain(|r| r.name != member.name);
+    }
+    print_relabels(&relabels);
+    Ok(())
+}
+
+fn value_is_dead(doc: &FamilyDocument, name: &str) -> bool {
+    doc.tree
+        .find_member_by_name(name)
+        .is_some_and(|m| m.is_dead)
+}
+
+/// 经 edit 标记为已故后，与 `die` 相同地提示职位出缺与家主继承
+fn after_death(
+    doc: &mut FamilyDocument,
+    archiver: &mut ArchiveWriter,
+    store: &dyn FamilyStore,
+    current_year: Option<i32>,
+    confirm: Confirm,
+    name: &str,
+) {
+    let title = doc
+        .tree
+        .find_member_by_name(name)
+        .and_then(|m| m.position.clone());
+    if let Some(title) = title {
+        if catalog::holder(&doc.tree, &title).is_none() {
+            statusln!(
+                "ℹ️ 【{}】出缺，可执行 position succeed {} 查看继任人选",
+                title,
+                title
+            );
+        }
+    }
+    let guard = doc.root_protection.guard(RootAction::Die, &doc.tree, name);
+    if guard == Guard::Prompt {
+        prompt_succession(doc, archiver, store, current_year, confirm);
+    }
+}
+
+/// 家主死亡后询问继承人
+fn prompt_succession(
+    doc: &mut FamilyDocument,
+    archiver: &mut ArchiveWriter,
+    store: &dyn FamilyStore,
+    current_year: Option<i32>,
+    confirm: Confirm,
+) {
+    let Some(year) = current_year else {
+        statusln!("⚠️  家主已故，请设置 year 后执行 inherit <姓名> 指定继承人");
+        return;
+    };
+    if !confirm.interactive() {
+        statusln!("⚠️  家主已故，请执行 inherit <姓名> 指定继承人");
+        return;
+    }
+
+    out!("家主已故，输入继承人姓名立即继位（直接回车跳过）：");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    record::read_line(&mut input).ok();
+    let heir = input.trim();
+
+    if heir.is_empty() {
+        statusln!("ℹ️ 暂不继承，稍后可执行 inherit <姓名>");
+    } else {
+        // 继位失败时已打印原因，家主死亡本身已生效
+        inherit(doc, archiver, store, year, heir);
+    }
+}
+
+/// 家主改名后，若族名与新名字的姓不一致，询问是否更新族名
+fn prompt_surname_update(doc: &mut FamilyDocument, new_name: &str, confirm: Confirm) {
+    let Some(surname) = doc.meta.surname.as_deref() else {
+        return;
+    };
+    let surname = surname.trim_end_matches('氏');
+    if new_name.starts_with(surname) {
+        return;
+    }
+    let Some(new_surname) = new_name.chars().next().map(String::from) else {
+        return;
+    };
+
+    if confirm.ask(&format!(
+        "家主改名后姓氏不再是【{}】，是否将族名改为【{}】？",
+        surname, new_surname
+    )) {
+        doc.meta.surname = Some(new_surname);
+        statusln!("✅ 族名已更新为 {}", doc.meta.title());
+    }
+}
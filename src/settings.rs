@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::columns::Column;
+use crate::health::HealthWeights;
+use crate::succession::SuccessionPolicy;
+
+/// 随数据文件保存的显示与规则设置
+///
+/// 放在数据文件中而不是各人的本地配置里，
+/// 共享同一份家族文件的协作者看到的继承规则、纪年与列方案完全一致。
+/// 本地配置（`config.toml`）不覆盖这些设置，只影响各人的会话行为（自动保存、确认、分页等）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentSettings {
+    #[serde(default, skip_serializing_if = "SuccessionPolicy::is_default")]
+    pub succession: SuccessionPolicy,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<Calendar>,
+
+    /// 命名的列方案，供 `list --profile` 使用
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_profiles: BTreeMap<String, Vec<Column>>,
+
+    /// show 中每个成员最多显示的子嗣数，超出部分折叠
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fold_children: Option<usize>,
+
+    /// `stats --health` 各项的权重
+    #[serde(default, skip_serializing_if = "HealthWeights::is_default")]
+    pub health: HealthWeights,
+
+    /// `check` 中威望加成的合理范围 `(下限, 上限)`，`None` 为不检查
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_range: Option<(i32, i32)>,
+}
+
+/// 纪年偏移
+///
+/// 游戏内纪年 = 数据中的年份 + `offset`，显示年份时一并给出。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calendar {
+    pub offset: i32,
+    /// 纪年名称，如"天启"
+    #[serde(default)]
+    pub era: Option<String>,
+}
+
+impl DocumentSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 显示年份，设置了纪年时附上游戏内纪年，如 `1280 年（天启 80 年）`
+    pub fn format_year(&self, year: i32) -> String {
+        match &self.calendar {
+            Some(calendar) => format!(
+                "{} 年（{}{} 年）",
+                year,
+                calendar
+                    .era
+                    .as_deref()
+                    .map(|e| format!("{} ", e))
+                    .unwrap_or_default(),
+                year + calendar.offset
+            ),
+            None => format!("{} 年", year),
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt::Write;
+
+use zz_sim_data_tool::model::{Cohort, FamilyMember, FamilyStats, VitalStatus};
+use zz_sim_data_tool::render::pad;
+
+use crate::theme;
+
+/// 对比表的指标列宽
+const LABEL_WIDTH: usize = 20;
+/// 对比表每个分支的列宽
+const BRANCH_WIDTH: usize = 24;
+
+/// 打印家族统计
+pub fn print(stats: &FamilyStats) {
+    let border = theme::frame(&"━".repeat(80)).into_owned();
+    outln!("{border}");
+
+    outln!(
+        "成员：共 {} 人，在世 {} 人，已故 {} 人",
+        stats.total,
+        stats.living,
+        stats.dead
+    );
+    if stats.missing > 0 {
+        outln!("  其中下落不明 {} 人（计入在世）", stats.missing);
+    }
+
+    outln!("各代人数：");
+    for (generation, count) in &stats.by_generation {
+        outln!("  {}：{} 人", generation, count);
+    }
+
+    outln!(
+        "性别：男 {} 人，女 {} 人（男女比 {}）",
+        stats.male,
+        stats.female,
+        ratio(stats.male, stats.female)
+    );
+    outln!(
+        "血统：内系 {} 人（{}），外系 {} 人（{}）",
+        stats.direct,
+        percent(stats.direct, stats.total),
+        stats.foreign,
+        percent(stats.foreign, stats.total)
+    );
+
+    outln!(
+        "子嗣：人均 {:.2} 个；有子嗣者 {} 人，平均 {:.2} 个",
+        average(stats.children, stats.total),
+        stats.parents,
+        average(stats.children, stats.parents)
+    );
+    outln!("威望合计：{}", stats.total_power);
+
+    outln!("{border}");
+}
+
+/// 并列对比两个分支
+///
+/// 平均年龄只计在世成员，需要当前年份；职位列出分支内所有在任的成员。
+pub fn compare(a: &FamilyMember, b: &FamilyMember, current_year: Option<i32>) {
+    let branches = [a, b];
+    let stats = branches.map(|m| m.stats());
+
+    let border = theme::frame(&"━".repeat(LABEL_WIDTH + BRANCH_WIDTH * 2)).into_owned();
+    outln!("{border}");
+    outln!(
+        "{}{}{}",
+        pad("", LABEL_WIDTH),
+        pad(&format!("【{}】一支", a.name), BRANCH_WIDTH),
+        pad(&format!("【{}】一支", b.name), BRANCH_WIDTH)
+    );
+    outln!("{border}");
+
+    let row = |label: &str, values: [String; 2]| {
+        outln!(
+            "{}{}{}",
+            pad(label, LABEL_WIDTH),
+            pad(&values[0], BRANCH_WIDTH),
+            pad(&values[1], BRANCH_WIDTH)
+        );
+    };
+
+    row("成员", stats.each_ref().map(|s| s.total.to_string()));
+    row("在世", stats.each_ref().map(|s| s.living.to_string()));
+    row(
+        "平均年龄（在世）",
+        branches.map(|m| match current_year {
+            Some(year) => average_age(m, year),
+            None => "-".to_string(),
+        }),
+    );
+    row(
+        "威望合计",
+        stats.each_ref().map(|s| s.total_power.to_string()),
+    );
+    row("任职人数", branches.map(|m| positions(m).len().to_string()));
+    outln!("{border}");
+
+    for m in branches {
+        let held = positions(m);
+        if held.is_empty() {
+            outln!("【{}】一支职位：无", m.name);
+        } else {
+            outln!("【{}】一支职位：{}", m.name, held.join("、"));
+        }
+    }
+    if current_year.is_none() {
+        outln!("（未设置当前年份，无法计算平均年龄，可先执行 year <年份>）");
+    }
+}
+
+/// 代际队列表的列宽
+const COHORT_WIDTH: usize = 12;
+
+/// 按代际对比各项指标，并给出最早一代到最近完整一代的变化
+///
+/// 最年轻一代多数尚未生育，不计入趋势。
+pub fn print_cohorts(cohorts: &[Cohort], current_year: Option<i32>) {
+    let headers = [
+        "代际",
+        "人数",
+        "在世",
+        "已故比例",
+        "人均子嗣",
+        "外系占比",
+        "平均威望",
+        "在世均龄",
+    ];
+    let border = theme::frame(&"━".repeat(COHORT_WIDTH * headers.len())).into_owned();
+    outln!("{border}");
+    outln!("{}", headers.map(|h| pad(h, COHORT_WIDTH)).concat());
+    outln!("{border}");
+    for c in cohorts {
+        let row = cohort_row(c, current_year);
+        outln!("{}", row.map(|v| pad(&v, COHORT_WIDTH)).concat());
+    }
+    outln!("{border}");
+
+    if let [first, .., last, _] = cohorts {
+        outln!(
+            "趋势（{} → {}，不计最年轻一代）：人均子嗣 {:.2} → {:.2}，平均威望 {:.1} → {:.1}，外系占比 {} → {}",
+            first.generation,
+            last.generation,
+            average(first.children, first.members),
+            average(last.children, last.members),
+            average_power(first),
+            average_power(last),
+            percent(first.foreign, first.members),
+            percent(last.foreign, last.members)
+        );
+    }
+    outln!("（数据中没有卒年，无法统计寿命）");
+    if current_year.is_none() {
+        outln!("（未设置当前年份，无法计算在世均龄，可先执行 year <年份>）");
+    }
+}
+
+/// 代际队列的 CSV，列与 [`print_cohorts`] 的表格一致
+pub fn cohort_csv(cohorts: &[Cohort], current_year: Option<i32>) -> String {
+    let mut out = String::from(
+        "generation,members,living,dead_share,avg_children,foreign_share,avg_power,avg_living_age\n",
+    );
+    for c in cohorts {
+        let dead = c.members - c.living;
+        writeln!(
+            out,
+            "{},{},{},{:.4},{:.4},{:.4},{:.4},{}",
+            c.generation,
+            c.members,
+            c.living,
+            share(dead, c.members),
+            average(c.children, c.members),
+            share(c.foreign, c.members),
+            average_power(c),
+            living_age(c, current_year).map_or(String::new(), |a| format!("{:.1}", a))
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn cohort_row(c: &Cohort, current_year: Option<i32>) -> [String; 8] {
+    [
+        c.generation.clone(),
+        c.members.to_string(),
+        c.living.to_string(),
+        percent(c.members - c.living, c.members),
+        format!("{:.2}", average(c.children, c.members)),
+        percent(c.foreign, c.members),
+        format!("{:.1}", average_power(c)),
+        living_age(c, current_year).map_or("-".to_string(), |a| format!("{:.1}", a)),
+    ]
+}
+
+fn average_power(c: &Cohort) -> f64 {
+    if c.members == 0 {
+        0.0
+    } else {
+        c.total_power as f64 / c.members as f64
+    }
+}
+
+/// 在世成员的平均年龄，未设置年份或无人在世时为 `None`
+fn living_age(c: &Cohort, current_year: Option<i32>) -> Option<f64> {
+    let year = current_year?;
+    (c.living > 0).then(|| f64::from(year) - c.living_birth_years as f64 / c.living as f64)
+}
+
+fn share(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// 分支内在世成员的平均年龄
+fn average_age(branch: &FamilyMember, year: i32) -> String {
+    let ages: Vec<i32> = branch
+        .members()
+        .into_iter()
+        .filter(|m| !m.is_dead)
+        .map(|m| year - m.birth_year)
+        .collect();
+
+    if ages.is_empty() {
+        "-".to_string()
+    } else {
+        let sum: i64 = ages.iter().map(|&a| i64::from(a)).sum();
+        format!("{:.1}", sum as f64 / ages.len() as f64)
+    }
+}
+
+/// 分支内在世成员（含下落不明）担任的职位，如 `太守（张大）`
+fn positions(branch: &FamilyMember) -> Vec<String> {
+    branch
+        .members()
+        .into_iter()
+        .filter(|m| m.status() != VitalStatus::Dead)
+        .filter_map(|m| m.position.as_ref().map(|p| format!("{}（{}）", p, m.name)))
+        .collect()
+}
+
+/// 男女比，如 `1.50 : 1`；女性为 0 时无法计算
+fn ratio(a: usize, b: usize) -> String {
+    if b == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.2} : 1", a as f64 / b as f64)
+    }
+}
+
+fn percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", part as f64 * 100.0 / total as f64)
+    }
+}
+
+fn average(sum: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        sum as f64 / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zz_sim_data_tool::model::Gender;
+
+    fn member(name: &str, position: &str, is_dead: bool) -> FamilyMember {
+        FamilyMember::builder()
+            .name(name)
+            .birth_year(1220)
+            .gender(Gender::Male)
+            .position(position)
+            .dead(is_dead)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn positions_skip_dead_holders() {
+        let mut branch = member("张大", "太守", false);
+        branch.children = vec![member("张二", "主簿", true), member("张三", "校尉", false)];
+        assert_eq!(positions(&branch), ["太守（张大）", "校尉（张三）"]);
+
+        // 下落不明不算已故
+        branch.children[1].missing_since = Some(1250);
+        assert_eq!(positions(&branch).len(), 2);
+    }
+}
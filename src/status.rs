@@ -0,0 +1,67 @@
+use serde::Serialize;
+
+use zz_sim_data_tool::document::FamilyDocument;
+use zz_sim_data_tool::render::OutputFormat;
+use zz_sim_data_tool::store::FamilyStore;
+
+/// 会话运行状态
+///
+/// 与 `summary` 的数据概览相对，报告数据文件、年份、归档等运行信息。
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub data_file: String,
+    /// 内存中的数据与数据文件不一致（有未保存的改动）
+    pub dirty: bool,
+    pub current_year: Option<i32>,
+    /// normal、sandbox 或 tutorial
+    pub mode: &'static str,
+    pub output_format: String,
+    pub archive_count: usize,
+    /// 尚未完成的后台归档写入数
+    pub pending_writes: usize,
+}
+
+impl Status {
+    pub fn collect(
+        doc: &FamilyDocument,
+        store: &dyn FamilyStore,
+        current_year: Option<i32>,
+        mode: &'static str,
+        output_format: OutputFormat,
+        pending_writes: usize,
+    ) -> Self {
+        Status {
+            data_file: store.location(),
+            dirty: !store.matches(doc),
+            current_year,
+            mode,
+            output_format: output_format.to_string(),
+            archive_count: store.list_archives().len(),
+            pending_writes,
+        }
+    }
+
+    /// 打印为单行 JSON，便于在 shell 提示符等脚本中解析
+    pub fn print_json(&self) {
+        outln!("{}", serde_json::to_string(self).unwrap_or_default());
+    }
+
+    /// 打印为易读的文本
+    pub fn print(&self) {
+        outln!("数据文件：{}", self.data_file);
+        outln!("未保存改动：{}", if self.dirty { "有" } else { "无" });
+        match self.current_year {
+            Some(year) => outln!("当前年份：{}", year),
+            None => outln!("当前年份：-"),
+        }
+        let mode = match self.mode {
+            "sandbox" => "沙盒",
+            "tutorial" => "教程",
+            _ => "正常",
+        };
+        outln!("会话模式：{}", mode);
+        outln!("输出格式：{}", self.output_format);
+        outln!("归档数：{}", self.archive_count);
+        outln!("后台写入中：{}", self.pending_writes);
+    }
+}
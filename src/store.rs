@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::document::FamilyDocument;
+use crate::error::ZzError;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 家族数据的存储后端
+///
+/// 命令只通过此接口读写数据与归档，新增后端（SQLite、按分支分目录等）
+/// 时实现此 trait 即可，不必改动命令代码。归档按年份区分，同一年份再次归档时覆盖。
+pub trait FamilyStore {
+    /// 存储位置，显示在提示中；文件存储为文件路径
+    fn location(&self) -> String;
+
+    /// 读取数据并迁移到当前格式
+    fn load(&self) -> Result<FamilyDocument, ZzError>;
+
+    /// 写入数据
+    fn save(&self, doc: &FamilyDocument) -> Result<(), ZzError>;
+
+    /// 写入指定年份的归档
+    ///
+    /// # Returns
+    /// 归档位置。
+    fn archive(&self, year: i32, doc: &FamilyDocument) -> Result<String, ZzError>;
+
+    /// 已有归档的年份，升序
+    fn list_archives(&self) -> Vec<i32>;
+
+    /// 指定年份归档的位置，归档不存在时也返回写入时将使用的位置
+    fn archive_location(&self, year: i32) -> String;
+
+    /// 读取指定年份的归档
+    fn load_archive(&self, year: i32) -> Result<FamilyDocument, ZzError>;
+
+    /// 已保存的数据是否与 `doc` 一致，读取失败时视为不一致
+    fn matches(&self, doc: &FamilyDocument) -> bool {
+        self.load().is_ok_and(|saved| saved.same_content(doc))
+    }
+}
+
+/// 单个数据文件的存储
+///
+/// 格式按扩展名决定（见 [`FileFormat`](crate::document::FileFormat)），
+/// 归档默认写入数据文件同级的 `archives/` 目录，可用 [`FileStore::with_archive_dir`] 另行指定。
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    archives: PathBuf,
+}
+
+/// 内存存储，不落盘，供测试与脚本使用
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    doc: RefCell<Option<FamilyDocument>>,
+    archives: RefCell<BTreeMap<i32, FamilyDocument>>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let archives = archive::archive_dir(&path.display().to_string());
+        FileStore { path, archives }
+    }
+
+    /// 归档写入 `archives` 目录
+    pub fn with_archive_dir(path: impl Into<PathBuf>, archives: impl Into<PathBuf>) -> Self {
+        FileStore {
+            path: path.into(),
+            archives: archives.into(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn archive_dir(&self) -> &Path {
+        &self.archives
+    }
+
+    fn data_file(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+impl MemoryStore {
+    /// 以 `doc` 作为已保存的数据
+    pub fn new(doc: FamilyDocument) -> Self {
+        MemoryStore {
+            doc: RefCell::new(Some(doc)),
+            archives: RefCell::default(),
+        }
+    }
+}
+
+// ============================================================================
+// Trait Implementations
+// ============================================================================
+
+impl FamilyStore for FileStore {
+    fn location(&self) -> String {
+        self.data_file()
+    }
+
+    fn load(&self) -> Result<FamilyDocument, ZzError> {
+        FamilyDocument::load(&self.path)
+    }
+
+    fn save(&self, doc: &FamilyDocument) -> Result<(), ZzError> {
+        doc.save(&self.path).map_err(|source| ZzError::Io {
+            path: self.path.clone(),
+            source,
+        })
+    }
+
+    fn archive(&self, year: i32, doc: &FamilyDocument) -> Result<String, ZzError> {
+        archive::write_archive_in(&self.archives, year, doc)
+            .map(|path| path.display().to_string())
+            .map_err(|source| ZzError::Io {
+                path: archive::archive_file(&self.archives, year),
+                source,
+            })
+    }
+
+    fn list_archives(&self) -> Vec<i32> {
+        archive::list_archives_in(&self.archives)
+            .into_iter()
+            .map(|(year, _)| year)
+            .collect()
+    }
+
+    fn archive_location(&self, year: i32) -> String {
+        archive::archive_file(&self.archives, year)
+            .display()
+            .to_string()
+    }
+
+    fn load_archive(&self, year: i32) -> Result<FamilyDocument, ZzError> {
+        FamilyDocument::load(&archive::archive_file(&self.archives, year))
+    }
+}
+
+impl FamilyStore for MemoryStore {
+    fn location(&self) -> String {
+        "（内存）".to_string()
+    }
+
+    fn load(&self) -> Result<FamilyDocument, ZzError> {
+        self.doc
+            .borrow()
+            .clone()
+            .ok_or_else(|| ZzError::Invalid("内存存储中还没有数据".to_string()))
+    }
+
+    fn save(&self, doc: &FamilyDocument) -> Result<(), ZzError> {
+        *self.doc.borrow_mut() = Some(doc.clone());
+        Ok(())
+    }
+
+    fn archive(&self, year: i32, doc: &FamilyDocument) -> Result<String, ZzError> {
+        self.archives.borrow_mut().insert(year, doc.clone());
+        Ok(self.archive_location(year))
+    }
+
+    fn list_archives(&self) -> Vec<i32> {
+        self.archives.borrow().keys().copied().collect()
+    }
+
+    fn archive_location(&self, year: i32) -> String {
+        format!("（内存）{} 年归档", year)
+    }
+
+    fn load_archive(&self, year: i32) -> Result<FamilyDocument, ZzError> {
+        self.archives
+            .borrow()
+            .get(&year)
+            .cloned()
+            .ok_or_else(|| ZzError::Invalid(format!("没有 {} 年的归档", year)))
+    }
+}
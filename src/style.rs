@@ -0,0 +1,108 @@
+use crate::render;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 前景色，对应 ANSI 的 8 色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+/// 单元格样式
+///
+/// 默认无样式；各项可叠加，如已故的外系成员既暗淡又带颜色。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub dim: bool,
+    pub fg: Option<Color>,
+}
+
+/// 带样式的单元格
+///
+/// 按文字本身的显示宽度补齐列宽，转义序列只包住文字，不计入宽度，
+/// 因此着色与否表格都对齐。
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub text: String,
+    pub style: Style,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+        }
+    }
+}
+
+impl Style {
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// 用 ANSI 转义序列包住文字，无样式时原样返回
+    pub fn paint(&self, text: &str) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push(1);
+        }
+        if self.dim {
+            codes.push(2);
+        }
+        if let Some(color) = self.fg {
+            codes.push(color.code());
+        }
+        if codes.is_empty() || text.is_empty() {
+            return text.to_string();
+        }
+        let codes: Vec<String> = codes.iter().map(u8::to_string).collect();
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Cell {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// 补齐到 `width` 列，`color` 为 false 时不输出转义序列
+    pub fn render(&self, width: usize, color: bool) -> String {
+        let padded = render::pad(&self.text, width);
+        if !color {
+            return padded;
+        }
+        let fill = &padded[self.text.len()..];
+        format!("{}{}", self.style.paint(&self.text), fill)
+    }
+}
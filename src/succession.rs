@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZzError;
+use crate::model::{FamilyMember, VitalStatus};
+
+/// 继承规则
+///
+/// 默认规则与最初的 `inherit` 一致：只要求继承人在两代以内。
+/// 其余规则默认关闭，可通过 `succession` 命令开启，随数据文件保存。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuccessionPolicy {
+    /// 继承人距家主的最大代数
+    pub max_generation: u8,
+    /// 继承人须在世
+    pub alive_only: bool,
+    /// 继承人须为直系
+    pub direct_only: bool,
+    /// 继承人最低年龄，按当前年份计算
+    pub min_age: Option<u16>,
+}
+
+/// 单条规则的判定结果
+pub struct Check {
+    /// 规则名称
+    pub rule: &'static str,
+    /// `None` 表示该规则未启用或无法判定
+    pub passed: Option<bool>,
+    pub detail: String,
+}
+
+impl Default for SuccessionPolicy {
+    fn default() -> Self {
+        SuccessionPolicy {
+            max_generation: 2,
+            alive_only: false,
+            direct_only: false,
+            min_age: None,
+        }
+    }
+}
+
+impl SuccessionPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 逐条判定成员的继承资格（解释模式）
+    ///
+    /// # Returns
+    /// 每条规则一个结果；找不到成员时返回错误。
+    pub fn explain(
+        &self,
+        tree: &FamilyMember,
+        name: &str,
+        current_year: Option<i32>,
+    ) -> Result<Vec<Check>, ZzError> {
+        let member = tree
+            .find_member_by_name(name)
+            .ok_or_else(|| ZzError::MemberNotFound(name.to_string()))?;
+        let member_type = member.member_type;
+        let generation = u8::from(member_type.generation);
+
+        let mut checks = Vec::new();
+
+        checks.push(Check {
+            rule: "非现任家主",
+            passed: Some(member.name != tree.name),
+            detail: if member.name == tree.name {
+                "已是现任家主".to_string()
+            } else {
+                format!("现任家主为【{}】", tree.name)
+            },
+        });
+
+        checks.push(Check {
+            rule: "代际",
+            passed: Some(generation <= self.max_generation),
+            detail: format!(
+                "{}位于第 {} 代，规则要求 {} 代以内",
+                member_type, generation, self.max_generation
+            ),
+        });
+
+        checks.push(Check {
+            rule: "在世",
+            passed: self.alive_only.then_some(!member.is_dead),
+            detail: match (self.alive_only, member.is_dead) {
+                (false, _) => "未启用".to_string(),
+                (true, true) => "已故".to_string(),
+                (true, false) => "在世".to_string(),
+            },
+        });
+
+        // 下落不明者无法就位，不受开关影响
+        let missing = match member.status() {
+            VitalStatus::Missing { since_year } => Some(since_year),
+            _ => None,
+        };
+        checks.push(Check {
+            rule: "下落",
+            passed: Some(missing.is_none()),
+            detail: match missing {
+                Some(year) => format!("自 {} 年起下落不明", year),
+                None => "下落明确".to_string(),
+            },
+        });
+
+        let direct = member_type.is_direct();
+        checks.push(Check {
+            rule: "直系",
+            passed: self.direct_only.then_some(direct),
+            detail: match (self.direct_only, direct) {
+                (false, _) => "未启用".to_string(),
+                (true, true) => "直系".to_string(),
+                (true, false) => "外系".to_string(),
+            },
+        });
+
+        checks.push(match (self.min_age, current_year) {
+            (None, _) => Check {
+                rule: "年龄",
+                passed: None,
+                detail: "未启用".to_string(),
+            },
+            (Some(min_age), None) => Check {
+                rule: "年龄",
+                passed: None,
+                detail: format!("要求年满 {} 岁，但未设置当前年份，无法判定", min_age),
+            },
+            (Some(min_age), Some(year)) => {
+                let age = year - member.birth_year;
+                Check {
+                    rule: "年龄",
+                    passed: Some(age >= i32::from(min_age)),
+                    detail: format!("{} 年时 {} 岁，规则要求年满 {} 岁", year, age, min_age),
+                }
+            }
+        });
+
+        checks.push(Check {
+            rule: "嫡庶",
+            passed: None,
+            detail: "数据中没有嫡庶信息，不作判定".to_string(),
+        });
+
+        Ok(checks)
+    }
+
+    /// 判定成员能否继承
+    ///
+    /// # Returns
+    /// 不满足时返回 [`ZzError::InvalidInheritance`]，逐条列出不满足的规则。
+    pub fn check(
+        &self,
+        tree: &FamilyMember,
+        name: &str,
+        current_year: Option<i32>,
+    ) -> Result<(), ZzError> {
+        let reasons: Vec<String> = self
+            .explain(tree, name, current_year)?
+            .into_iter()
+            .filter(|c| c.passed == Some(false))
+            .map(|c| format!("{}：{}", c.rule, c.detail))
+            .collect();
+
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(ZzError::InvalidInheritance {
+                name: name.to_string(),
+                reasons,
+            })
+        }
+    }
+
+    /// 设置规则
+    ///
+    /// # param
+    /// * `key` - `max-gen`、`alive-only`、`direct-only` 或 `min-age`
+    /// * `value` - 代数、`on`/`off`，或年龄/`off`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let switch = |v: &str| match v {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(format!("无效的开关【{}】，可选：on, off", v)),
+        };
+
+        match key {
+            "max-gen" => {
+                self.max_generation = value
+                    .parse()
+                    .map_err(|_| format!("无效的代数：{}", value))?
+            }
+            "alive-only" => self.alive_only = switch(value)?,
+            "direct-only" => self.direct_only = switch(value)?,
+            "min-age" => {
+                self.min_age = match value {
+                    "off" => None,
+                    v => Some(v.parse().map_err(|_| format!("无效的年龄：{}", v))?),
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "未知规则【{}】，可选：max-gen, alive-only, direct-only, min-age",
+                    key
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// 当前规则的文字描述
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let switch = |on: bool| if on { "开启" } else { "关闭" };
+        writeln!(out, "最大代数（max-gen）：{}", self.max_generation).unwrap();
+        writeln!(out, "须在世（alive-only）：{}", switch(self.alive_only)).unwrap();
+        writeln!(out, "须直系（direct-only）：{}", switch(self.direct_only)).unwrap();
+        match self.min_age {
+            Some(age) => writeln!(out, "最低年龄（min-age）：{} 岁", age).unwrap(),
+            None => writeln!(out, "最低年龄（min-age）：关闭").unwrap(),
+        }
+        out
+    }
+}
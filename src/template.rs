@@ -0,0 +1,449 @@
+use std::borrow::Cow;
+
+use serde_json::{json, Value};
+
+use crate::document::FamilyDocument;
+use crate::model::{FamilyMember, Gender, Lineage};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// 编译后的报表模板
+///
+/// 语法取 Handlebars 的常用子集，写好的模板也可交给 Handlebars 渲染：
+/// - `{{路径}}` 输出值，路径以 `.` 分隔，如 `meta.surname`、`stats.by_generation.0`；
+///   `this` 为当前值，找不到的字段依次在外层查找；
+/// - `{{#each 路径}} … {{else}} … {{/each}}` 遍历数组，数组为空时输出 `else` 部分，
+///   块内可用 `@index`（从 0 起）、`@first`、`@last`；
+/// - `{{#if 路径}} … {{else}} … {{/if}}`、`{{#unless 路径}} … {{/unless}}`，
+///   `false`、`null`、`0`、空字符串与空数组为假，找不到的字段也为假；
+/// - `{{! 注释}}`、`{{!-- 注释 --}}`。
+///
+/// 输出不做 HTML 转义，`{{{路径}}}` 与 `{{路径}}` 相同。
+/// 独占一行的块标签与注释连同所在行一起去掉，不留空行。
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+enum Node {
+    Text(String),
+    Value {
+        path: String,
+        line: usize,
+    },
+    Each {
+        path: String,
+        line: usize,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    If {
+        path: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// 词法单元：文本或 `{{ }}` 标签（内容已去掉首尾空白）
+enum Token {
+    Text(String),
+    Tag { content: String, line: usize },
+}
+
+/// 块的结束方式
+enum End {
+    Eof,
+    Else(usize),
+    Close(String, usize),
+}
+
+/// 渲染时的一层作用域，`each` 块内带下标与数组长度
+struct Scope<'v> {
+    value: &'v Value,
+    index: Option<(usize, usize)>,
+}
+
+// ============================================================================
+// Method Implementations
+// ============================================================================
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut tokens = tokenize(source)?;
+        strip_standalone(&mut tokens);
+        let mut tokens = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        match end {
+            End::Eof => Ok(Template { nodes }),
+            End::Else(line) => Err(format!("第 {} 行：{{{{else}}}} 不在块内", line)),
+            End::Close(name, line) => Err(format!(
+                "第 {} 行：多余的 {{{{/{}}}}}，没有对应的开始标签",
+                line, name
+            )),
+        }
+    }
+
+    /// 以 `context`（通常由 [`context`] 生成）渲染模板
+    ///
+    /// # Returns
+    /// 输出文本；输出的字段不存在或 `each` 的对象不是数组时返回错误（含行号）。
+    pub fn render(&self, context: &Value) -> Result<String, String> {
+        let mut out = String::new();
+        let mut scopes = vec![Scope {
+            value: context,
+            index: None,
+        }];
+        render_nodes(&self.nodes, &mut scopes, &mut out)?;
+        Ok(out)
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// 模板可用的数据
+///
+/// - `meta`：族名（surname）、家训（motto）、始建年份（founding_year）、备注（notes）；
+/// - `stats`：与 `stats` 命令相同的汇总，`by_generation` 为 `[代际, 人数]` 数组；
+/// - `year`：当前年份，未设置时为 `null`；
+/// - `root`：家主，字段同下；
+/// - `members`：按先序平铺的全部成员，每人有 name、id、birth_year、type（称谓）、
+///   generation、gender（男/女）、lineage（内系/外系）、is_dead、status（在世/已故/失踪）、
+///   missing_since（失踪年份）、position、power、age（已故或未设置年份时为 `null`）、depth（家主为 0）、
+///   indent（每层两个全角空格）、parent、children（子女姓名）、child_count、
+///   descendants（后代人数）、position_history（year、title）。
+pub fn context(doc: &FamilyDocument, year: Option<i32>) -> Value {
+    let mut members = Vec::new();
+    flatten(&doc.tree, None, 0, year, &mut members);
+    json!({
+        "meta": doc.meta,
+        "stats": doc.tree.stats(),
+        "year": year,
+        "root": members[0].clone(),
+        "members": members,
+    })
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 按 `{{`、`}}` 切分模板，`{{{ }}}` 与 `{{!-- --}}` 按各自的结束符查找
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    while let Some(start) = rest.find("{{") {
+        let text = &rest[..start];
+        line += text.matches('\n').count();
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+
+        let after = &rest[start..];
+        let (open, close) = if after.starts_with("{{!--") {
+            ("{{", "--}}")
+        } else if after.starts_with("{{{") {
+            ("{{{", "}}}")
+        } else {
+            ("{{", "}}")
+        };
+        let inner = &after[open.len()..];
+        let end = inner
+            .find(close)
+            .ok_or_else(|| format!("第 {} 行：标签缺少结束的 {}", line, close))?;
+        tokens.push(Token::Tag {
+            content: inner[..end].trim().to_string(),
+            line,
+        });
+        line += inner[..end].matches('\n').count();
+        rest = &inner[end + close.len()..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// 块标签、`else` 与注释不产生输出，独占一行时去掉该行的缩进与换行
+fn strip_standalone(tokens: &mut [Token]) {
+    // 自上一个换行起是否只有空白
+    let mut line_blank = true;
+    for i in 0..tokens.len() {
+        let standalone = match &tokens[i] {
+            Token::Text(text) => {
+                line_blank = match text.rfind('\n') {
+                    Some(pos) => text[pos + 1..].trim().is_empty(),
+                    None => line_blank && text.trim().is_empty(),
+                };
+                continue;
+            }
+            Token::Tag { content, .. } => {
+                let block = content.starts_with(['#', '/', '!']) || content == "else";
+                let line_ends = match tokens.get(i + 1) {
+                    None => true,
+                    Some(Token::Text(next)) => match next.find('\n') {
+                        Some(pos) => next[..pos].trim().is_empty(),
+                        None => i + 2 == tokens.len() && next.trim().is_empty(),
+                    },
+                    Some(Token::Tag { .. }) => false,
+                };
+                block && line_blank && line_ends
+            }
+        };
+        if !standalone {
+            line_blank = false;
+            continue;
+        }
+
+        if let Some(Token::Text(prev)) = i.checked_sub(1).and_then(|p| tokens.get_mut(p)) {
+            let keep = prev.rfind('\n').map_or(0, |pos| pos + 1);
+            prev.truncate(keep);
+        }
+        if let Some(Token::Text(next)) = tokens.get_mut(i + 1) {
+            let skip = next.find('\n').map_or(next.len(), |pos| pos + 1);
+            next.drain(..skip);
+        }
+        line_blank = true;
+    }
+}
+
+/// 解析到文件末尾、`{{else}}` 或某个结束标签为止
+fn parse_nodes(tokens: &mut impl Iterator<Item = Token>) -> Result<(Vec<Node>, End), String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        let (content, line) = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag { content, line } => (content, line),
+        };
+
+        if content.starts_with('!') {
+            continue;
+        }
+        if content == "else" {
+            return Ok((nodes, End::Else(line)));
+        }
+        if let Some(name) = content.strip_prefix('/') {
+            return Ok((nodes, End::Close(name.trim().to_string(), line)));
+        }
+        let Some(block) = content.strip_prefix('#') else {
+            if content.is_empty() {
+                return Err(format!("第 {} 行：空标签", line));
+            }
+            nodes.push(Node::Value {
+                path: content,
+                line,
+            });
+            continue;
+        };
+
+        let (name, path) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+        let path = path.trim().to_string();
+        if !matches!(name, "each" | "if" | "unless") {
+            return Err(format!(
+                "第 {} 行：不支持的块【#{}】，可用：#each、#if、#unless",
+                line, name
+            ));
+        }
+        if path.is_empty() {
+            return Err(format!("第 {} 行：#{} 缺少路径", line, name));
+        }
+
+        let (then, end) = parse_nodes(tokens)?;
+        let (otherwise, end) = match end {
+            End::Else(_) => parse_nodes(tokens)?,
+            end => (Vec::new(), end),
+        };
+        match end {
+            End::Close(closed, _) if closed == name => {}
+            End::Close(closed, at) => {
+                return Err(format!(
+                    "第 {} 行：应为 {{{{/{}}}}}（第 {} 行开始），实为 {{{{/{}}}}}",
+                    at, name, line, closed
+                ))
+            }
+            End::Else(at) => return Err(format!("第 {} 行：重复的 {{{{else}}}}", at)),
+            End::Eof => {
+                return Err(format!(
+                    "第 {} 行：#{} 缺少结束的 {{{{/{}}}}}",
+                    line, name, name
+                ))
+            }
+        }
+
+        nodes.push(match name {
+            "each" => Node::Each {
+                path,
+                line,
+                body: then,
+                otherwise,
+            },
+            _ => Node::If {
+                path,
+                negate: name == "unless",
+                then,
+                otherwise,
+            },
+        });
+    }
+    Ok((nodes, End::Eof))
+}
+
+fn render_nodes<'v>(
+    nodes: &'v [Node],
+    scopes: &mut Vec<Scope<'v>>,
+    out: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value { path, line } => match lookup(path, scopes) {
+                Some(value) => out.push_str(&display(&value)),
+                None => return Err(format!("第 {} 行：找不到【{}】", line, path)),
+            },
+            Node::If {
+                path,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let truthy = lookup(path, scopes).is_some_and(|v| truthy(&v));
+                let branch = if truthy != *negate { then } else { otherwise };
+                render_nodes(branch, scopes, out)?;
+            }
+            Node::Each {
+                path,
+                line,
+                body,
+                otherwise,
+            } => {
+                let items: &'v [Value] = match lookup(path, scopes) {
+                    None => &[],
+                    Some(Cow::Borrowed(Value::Array(items))) => items,
+                    Some(Cow::Borrowed(Value::Null)) => &[],
+                    Some(_) => return Err(format!("第 {} 行：【{}】不是数组", line, path)),
+                };
+                if items.is_empty() {
+                    render_nodes(otherwise, scopes, out)?;
+                }
+                for (i, item) in items.iter().enumerate() {
+                    scopes.push(Scope {
+                        value: item,
+                        index: Some((i, items.len())),
+                    });
+                    let rendered = render_nodes(body, scopes, out);
+                    scopes.pop();
+                    rendered?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按路径取值：`@` 变量取最近的 `each`，`this` 取当前值，其余由内向外查找首段字段
+fn lookup<'v>(path: &str, scopes: &[Scope<'v>]) -> Option<Cow<'v, Value>> {
+    if let Some(var) = path.strip_prefix('@') {
+        let (index, len) = scopes.iter().rev().find_map(|s| s.index)?;
+        return match var {
+            "index" => Some(Cow::Owned(Value::from(index))),
+            "first" => Some(Cow::Owned(Value::from(index == 0))),
+            "last" => Some(Cow::Owned(Value::from(index + 1 == len))),
+            _ => None,
+        };
+    }
+
+    let current = scopes.last()?.value;
+    let mut segments = path.split('.');
+    match path.split('.').next()? {
+        "this" => {
+            segments.next();
+            descend(current, segments).map(Cow::Borrowed)
+        }
+        first => scopes
+            .iter()
+            .rev()
+            .find(|s| s.value.get(first).is_some())
+            .and_then(|s| descend(s.value, segments))
+            .map(Cow::Borrowed),
+    }
+}
+
+fn descend<'v, 'p>(
+    mut value: &'v Value,
+    segments: impl Iterator<Item = &'p str>,
+) -> Option<&'v Value> {
+    for segment in segments {
+        value = match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => value.get(segment)?,
+        };
+    }
+    Some(value)
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 先序平铺成员
+fn flatten(
+    member: &FamilyMember,
+    parent: Option<&str>,
+    depth: usize,
+    year: Option<i32>,
+    out: &mut Vec<Value>,
+) {
+    out.push(json!({
+        "name": member.name,
+        "id": member.id,
+        "birth_year": member.birth_year,
+        "type": member.member_type.to_string(),
+        "generation": format!("{:?}", member.member_type.generation),
+        "gender": match member.member_type.gender {
+            Gender::Male => "男",
+            Gender::Female => "女",
+        },
+        "lineage": match member.member_type.lineage {
+            Lineage::Direct => "内系",
+            Lineage::Foreign => "外系",
+        },
+        "is_dead": member.is_dead,
+        "status": member.status().to_string(),
+        "missing_since": member.missing_since,
+        "position": member.position,
+        "power": member.hoser_power_add,
+        "age": year.filter(|_| !member.is_dead).map(|y| y - member.birth_year),
+        "depth": depth,
+        "indent": "　".repeat(depth * 2),
+        "parent": parent,
+        "children": member.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+        "child_count": member.children.len(),
+        "descendants": member.total_count() - 1,
+        "position_history": member.position_history,
+    }));
+    for child in &member.children {
+        flatten(child, Some(&member.name), depth + 1, year, out);
+    }
+}
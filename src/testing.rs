@@ -0,0 +1,33 @@
+//! 单元测试共用的示例数据
+
+use crate::model::FamilyMember;
+
+/// 示例家族树（先序）：
+///
+/// ```text
+/// 张大（家主，1200）
+/// ├─ 张二（儿，1220）
+/// │  ├─ 张三（孙，1240）
+/// │  └─ 张小红（孙女，1242）
+/// │     └─ 李四（外曾孙，1260）
+/// └─ 张丽（女儿，1222，已故）
+///    └─ 王五（外孙，1245）
+/// ```
+pub const SAMPLE: &str = r#"
+{"name":"张大","birth_year":1200,"hoser_power_add":5,"member_type":"家主","position":"太守","children":[
+ {"name":"张二","birth_year":1220,"hoser_power_add":3,"member_type":"儿","children":[
+   {"name":"张三","birth_year":1240,"hoser_power_add":2,"member_type":"孙"},
+   {"name":"张小红","birth_year":1242,"hoser_power_add":1,"member_type":"孙女","children":[
+      {"name":"李四","birth_year":1260,"hoser_power_add":1,"member_type":"外曾孙"}]}]},
+ {"name":"张丽","birth_year":1222,"hoser_power_add":2,"member_type":"女儿","is_dead":true,"children":[
+   {"name":"王五","birth_year":1245,"hoser_power_add":4,"member_type":"外孙"}]}
+]}"#;
+
+pub fn sample() -> FamilyMember {
+    serde_json::from_str(SAMPLE).expect("示例数据可解析")
+}
+
+/// 先序排列的全部姓名
+pub fn names(tree: &FamilyMember) -> Vec<String> {
+    tree.names().into_iter().map(String::from).collect()
+}
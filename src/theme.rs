@@ -0,0 +1,244 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// 选择主题的环境变量
+pub const THEME_ENV: &str = "ZZ_SIM_THEME";
+
+/// 状态符号主题
+///
+/// 状态消息中的符号统一按 emoji 书写，经 `statusln!` 等宏输出时再按当前主题替换：
+/// `Ascii` 换成 `[OK]`、`[ERROR]` 等纯 ASCII 标记，终端表格的框线也换成 ASCII；
+/// `Minimal` 只把表示成败的符号换成 `OK`、`ERROR` 等单词，其余装饰性符号去掉，
+/// 适合写入日志。数据与导出内容不受主题影响。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Emoji,
+    Ascii,
+    Minimal,
+}
+
+/// 状态符号及其在 `Ascii`、`Minimal` 主题下的替代，空串表示去掉
+///
+/// 表示成败的符号在 `Minimal` 下保留为单词，装饰性的直接去掉。
+const MARKERS: &[(&str, &str, &str)] = &[
+    ("✅", "[OK]", "OK"),
+    ("❌", "[ERROR]", "ERROR"),
+    ("⚠️", "[WARN]", "WARN"),
+    ("⛔", "[BLOCKED]", "BLOCKED"),
+    ("➖", "[-]", "-"),
+    ("ℹ️", "[INFO]", ""),
+    ("🗃️", "[ARCHIVE]", ""),
+    ("🗑️", "[TRASH]", ""),
+    ("📥", "[IMPORT]", ""),
+    ("💾", "[SAVE]", ""),
+    ("🩺", "[DOCTOR]", ""),
+    ("🔎", "[CHECK]", ""),
+    ("🎓", "[TUTORIAL]", ""),
+    ("🎉", "[DONE]", ""),
+    ("📖", "[STEP]", ""),
+    ("👀", "[WATCH]", ""),
+    ("⏹️", "[STOP]", ""),
+    ("🔄", "[RELOAD]", ""),
+    ("⏳", "[WAIT]", ""),
+    ("📝", "[EDIT]", ""),
+    ("↩️", "[UNDO]", ""),
+    ("↪️", "[REDO]", ""),
+    ("🧪", "[SANDBOX]", ""),
+    ("🔒", "[LOCKED]", ""),
+    ("⏺️", "[REC]", ""),
+    ("▶️", "[REPLAY]", ""),
+    ("🔍", "[SEARCH]", ""),
+    ("🏷️", "[LABEL]", ""),
+];
+
+/// 表格框线的 ASCII 替代，宽度相同，表格不会错位
+const RULES: &[(char, char)] = &[('━', '='), ('─', '-')];
+
+static CURRENT: AtomicU8 = AtomicU8::new(Theme::Emoji as u8);
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// 当前主题
+pub fn current() -> Theme {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Theme::Ascii,
+        2 => Theme::Minimal,
+        _ => Theme::Emoji,
+    }
+}
+
+/// 切换主题，之后的输出都按新主题替换
+pub fn set(theme: Theme) {
+    CURRENT.store(theme as u8, Ordering::Relaxed);
+}
+
+/// 终端是否允许着色（不含 `config color` 开关）
+pub fn color() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// 设置终端是否允许着色
+pub fn set_color(color: bool) {
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+/// 按终端推断是否着色
+///
+/// 标准输出不是终端、`TERM=dumb` 或设置了非空的 `NO_COLOR`（见 no-color.org）时不着色。
+pub fn detect_color() -> bool {
+    let no_color = env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+    let dumb = env::var("TERM").is_ok_and(|t| t == "dumb");
+    !no_color && !dumb && io::stdout().is_terminal()
+}
+
+/// 按终端推断主题
+///
+/// `TERM=dumb`、Linux 控制台（`TERM=linux`，无 emoji 字形）或非 UTF-8 的
+/// locale 用 `Ascii`，其余用 `Emoji`。未设置 locale 时不据此判断。
+pub fn detect() -> Theme {
+    let term = env::var("TERM").unwrap_or_default();
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|v| !v.is_empty()));
+    let utf8 = locale.as_ref().is_none_or(|locale| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    });
+
+    if term == "dumb" || term == "linux" || !utf8 {
+        Theme::Ascii
+    } else {
+        Theme::Emoji
+    }
+}
+
+/// 行首（缩进之后）是否为状态符号，多行报告中据此区分状态行与数据行
+pub fn is_marked(line: &str) -> bool {
+    let line = line.trim_start();
+    MARKERS.iter().any(|(emoji, _, _)| line.starts_with(emoji))
+}
+
+/// 按当前主题替换状态消息中的符号
+///
+/// 只替换状态符号，文字中的制表符、箭头等原样保留。
+pub fn apply(text: &str) -> Cow<'_, str> {
+    let theme = current();
+    if theme == Theme::Emoji || text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = text.to_string();
+    for (emoji, ascii, minimal) in MARKERS {
+        if !out.contains(emoji) {
+            continue;
+        }
+        // 部分 emoji 后原本多留一个空格补足宽度，替换后统一为一个空格
+        let replacement = match (theme, *minimal) {
+            (Theme::Ascii, _) => format!("{} ", ascii),
+            (_, "") => String::new(),
+            (_, word) => format!("{} ", word),
+        };
+        let mut replaced = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        while let Some(i) = rest.find(emoji) {
+            replaced.push_str(&rest[..i]);
+            rest = rest[i + emoji.len()..].trim_start_matches(' ');
+            if rest.is_empty() || rest.starts_with('\n') {
+                replaced.push_str(replacement.trim_end());
+            } else {
+                replaced.push_str(&replacement);
+            }
+        }
+        replaced.push_str(rest);
+        out = replaced;
+    }
+
+    Cow::Owned(out)
+}
+
+/// `Ascii` 主题下把终端表格的框线换成 ASCII
+///
+/// 只替换整行都是框线的行（表头上下的分隔线），表格内容原样保留；
+/// 树形分支由 [`TerminalTable`](zz_sim_data_tool::render::TerminalTable) 按主题绘制。
+pub fn frame(text: &str) -> Cow<'_, str> {
+    let is_rule = |line: &str| {
+        !line.is_empty()
+            && line
+                .chars()
+                .all(|c| RULES.iter().any(|(rule, _)| *rule == c))
+    };
+    if current() != Theme::Ascii || !text.lines().any(is_rule) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (body, end) = match line.strip_suffix('\n') {
+            Some(body) => (body, "\n"),
+            None => (line, ""),
+        };
+        if is_rule(body) {
+            out.extend(body.chars().map(|c| {
+                RULES
+                    .iter()
+                    .find(|(rule, _)| *rule == c)
+                    .map_or(c, |(_, ascii)| *ascii)
+            }));
+        } else {
+            out.push_str(body);
+        }
+        out.push_str(end);
+    }
+    Cow::Owned(out)
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "emoji" => Ok(Theme::Emoji),
+            "ascii" => Ok(Theme::Ascii),
+            "minimal" => Ok(Theme::Minimal),
+            _ => Err(format!("未知主题【{}】，可选：emoji, ascii, minimal", s)),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Theme::Emoji => "emoji",
+            Theme::Ascii => "ascii",
+            Theme::Minimal => "minimal",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_theme_rewrites_markers_and_rules_but_not_data() {
+        set(Theme::Ascii);
+        assert_eq!(apply("✅ 已添加【甲→乙│丙】"), "[OK] 已添加【甲→乙│丙】");
+        assert_eq!(frame("━━━━\n姓名━━\n────\n"), "====\n姓名━━\n----\n");
+        set(Theme::Emoji);
+        assert_eq!(apply("✅ 已添加"), "✅ 已添加");
+        assert_eq!(frame("━━\n"), "━━\n");
+    }
+
+    #[test]
+    fn marked_lines_start_with_a_marker() {
+        assert!(is_marked("✅ 已保存"));
+        assert!(is_marked("  ⚠️  规则：说明"));
+        assert!(!is_marked("  张三（孙，1240 年生）✅"));
+        assert!(!is_marked("各代常用字："));
+    }
+}
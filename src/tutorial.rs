@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use crate::sandbox::Sandbox;
+use zz_sim_data_tool::document::FamilyDocument;
+use zz_sim_data_tool::model::FamilyMember;
+
+/// 教程步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Show,
+    Add,
+    Position,
+    Die,
+    Save,
+    Inherit,
+}
+
+impl Step {
+    const ALL: [Step; 6] = [
+        Step::Show,
+        Step::Add,
+        Step::Position,
+        Step::Die,
+        Step::Save,
+        Step::Inherit,
+    ];
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Step::Show => "输入 `show` 查看整棵家族树。",
+            Step::Add => {
+                "输入 `add`，按提示输入一位成员的姓名，再逐项填写子嗣的姓名、出生年、性别\n\
+                 与威望加成（称谓按父辈自动推算），例如姓名 教程小明、出生年 1300。"
+            }
+            Step::Position => {
+                "输入 `position <姓名> <职位>` 为任意成员设置职位，例如 `position 教程小明 县令`。"
+            }
+            Step::Die => "输入 `die <姓名>` 将任意一位在世成员标记为死亡。",
+            Step::Save => "输入 `save` 保存（教程中只会写入临时文件）。",
+            Step::Inherit => {
+                "先输入 `year <年份>` 设置当前年份，再输入 `inherit <姓名>` 并确认，\n\
+                 让一位两代以内的成员继承家主。"
+            }
+        }
+    }
+}
+
+/// 交互式教程
+///
+/// 在沙盒中进行，会话中的所有命令（包括 `save` 与 `inherit` 的归档）
+/// 都作用在临时副本上；结束或中途退出时放弃沙盒、恢复真实数据。
+/// 每条命令执行后检查当前步骤是否完成。
+pub struct Tutorial {
+    step: usize,
+    /// 当前步骤开始时的家族树，用于判断步骤是否完成
+    snapshot: FamilyMember,
+    sandbox: Sandbox,
+}
+
+impl Tutorial {
+    /// 开始教程
+    ///
+    /// 将 `data_file` 切换为临时副本，真实数据暂存在沙盒中。
+    pub fn start(
+        doc: &FamilyDocument,
+        data_file: &mut String,
+        current_year: Option<i32>,
+    ) -> Result<Self, String> {
+        let tutorial = Tutorial {
+            step: 0,
+            snapshot: doc.tree.clone(),
+            sandbox: Sandbox::enter("tutorial", doc, data_file, current_year)?,
+        };
+
+        statusln!("🎓 教程开始：以下操作都在临时副本上进行，不会改动真实数据。");
+        outln!("   随时输入 `tutorial exit` 退出教程。\n");
+        tutorial.print_step();
+
+        Ok(tutorial)
+    }
+
+    /// 检查刚执行的命令是否完成了当前步骤
+    ///
+    /// # Returns
+    /// 全部步骤完成时返回 `true`，此时应调用 [`Tutorial::finish`]。
+    pub fn check(&mut self, command: &str, doc: &FamilyDocument, data_file: &str) -> bool {
+        let step = Step::ALL[self.step];
+        let done = match step {
+            Step::Show => command == "show",
+            Step::Add => doc.tree.total_count() > self.snapshot.total_count(),
+            Step::Position => self.any_changed(&doc.tree, |a, b| a.position != b.position),
+            Step::Die => self.any_changed(&doc.tree, |a, b| a.is_dead != b.is_dead),
+            Step::Save => command == "save" && doc.matches_file(Path::new(data_file)),
+            Step::Inherit => doc.tree.name != self.snapshot.name,
+        };
+
+        if !done {
+            return false;
+        }
+
+        statusln!("✅ 第 {} 步完成！\n", self.step + 1);
+        self.step += 1;
+        self.snapshot = doc.tree.clone();
+
+        if self.step == Step::ALL.len() {
+            return true;
+        }
+
+        self.print_step();
+        false
+    }
+
+    /// 结束教程，恢复真实数据与年份
+    pub fn finish(
+        self,
+        doc: &mut FamilyDocument,
+        data_file: &mut String,
+        current_year: &mut Option<i32>,
+    ) {
+        let completed = self.step == Step::ALL.len();
+        self.sandbox.discard(doc, data_file, current_year);
+
+        if completed {
+            statusln!("🎉 教程完成！已切回真实数据，可以开始正式使用了。");
+        } else {
+            statusln!("ℹ️ 已退出教程，已切回真实数据。");
+        }
+    }
+
+    fn print_step(&self) {
+        statusln!(
+            "📖 第 {}/{} 步：{}",
+            self.step + 1,
+            Step::ALL.len(),
+            Step::ALL[self.step].instruction()
+        );
+    }
+
+    /// 与步骤开始时相比，是否有成员满足 `changed(旧, 新)`
+    fn any_changed(
+        &self,
+        tree: &FamilyMember,
+        changed: impl Fn(&FamilyMember, &FamilyMember) -> bool,
+    ) -> bool {
+        self.snapshot.names().into_iter().any(|name| {
+            match (
+                self.snapshot.find_member_by_name(name),
+                tree.find_member_by_name(name),
+            ) {
+                (Some(before), Some(after)) => changed(before, after),
+                _ => false,
+            }
+        })
+    }
+}
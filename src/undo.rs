@@ -0,0 +1,99 @@
+use std::mem;
+
+use crate::document::FamilyDocument;
+
+/// 默认保留的撤销步数
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// 撤销/重做历史
+///
+/// 以整份数据的快照记录每一步改动：修改前保存快照，撤销时与当前数据交换。
+/// 快照只在内存中，退出程序即丢失；新的改动会清空重做栈。
+#[derive(Debug, Clone)]
+pub struct UndoHistory {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    limit: usize,
+}
+
+/// 一步改动：触发改动的命令与改动前（或撤销前）的数据
+#[derive(Debug, Clone)]
+struct Step {
+    label: String,
+    snapshot: FamilyDocument,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
+
+impl UndoHistory {
+    pub fn new(limit: usize) -> Self {
+        UndoHistory {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// 记录一步改动
+    ///
+    /// # param
+    /// * `label` - 触发改动的命令，撤销时显示
+    /// * `before` - 改动前的数据
+    pub fn record(&mut self, label: &str, before: FamilyDocument) {
+        self.undo.push(Step {
+            label: label.to_string(),
+            snapshot: before,
+        });
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// 撤销最近一步，将 `current` 恢复为改动前的数据
+    ///
+    /// # Returns
+    /// 被撤销的命令；没有可撤销的改动时为 `None`。
+    pub fn undo(&mut self, current: &mut FamilyDocument) -> Option<String> {
+        let step = self.undo.pop()?;
+        Some(Self::swap(step, current, &mut self.redo))
+    }
+
+    /// 重做最近一次撤销
+    ///
+    /// # Returns
+    /// 被重做的命令；没有可重做的改动时为 `None`。
+    pub fn redo(&mut self, current: &mut FamilyDocument) -> Option<String> {
+        let step = self.redo.pop()?;
+        Some(Self::swap(step, current, &mut self.undo))
+    }
+
+    /// 可撤销的命令，最近的在前
+    pub fn undo_labels(&self) -> Vec<&str> {
+        self.undo.iter().rev().map(|s| s.label.as_str()).collect()
+    }
+
+    /// 可重做的命令，最近撤销的在前
+    pub fn redo_labels(&self) -> Vec<&str> {
+        self.redo.iter().rev().map(|s| s.label.as_str()).collect()
+    }
+
+    /// 清空历史（切换数据文件时调用）
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn swap(step: Step, current: &mut FamilyDocument, other: &mut Vec<Step>) -> String {
+        let previous = mem::replace(current, step.snapshot);
+        other.push(Step {
+            label: step.label.clone(),
+            snapshot: previous,
+        });
+        step.label
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::document::FamilyDocument;
+use crate::error::ZzError;
+use crate::store::FamilyStore;
+
+/// 磁盘与内存数据的一处差异
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// 字段路径，成员数组按姓名定位，如 `children[张二].is_dead`
+    pub path: String,
+    /// 磁盘上的值，`None` 表示磁盘上没有该字段或成员
+    pub disk: Option<Value>,
+    /// 内存中的值，`None` 表示内存中没有该字段或成员
+    pub memory: Option<Value>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}：磁盘 {}，内存 {}",
+            if self.path.is_empty() {
+                "$"
+            } else {
+                &self.path
+            },
+            brief(self.disk.as_ref()),
+            brief(self.memory.as_ref())
+        )
+    }
+}
+
+impl Divergence {
+    /// 以"旧值 → 新值"描述，`disk` 侧为旧值
+    pub fn describe_change(&self) -> String {
+        format!(
+            "{}：{} → {}",
+            if self.path.is_empty() {
+                "$"
+            } else {
+                &self.path
+            },
+            brief(self.disk.as_ref()),
+            brief(self.memory.as_ref())
+        )
+    }
+}
+
+/// 重新读取已保存的数据，与内存中的数据逐字段比较
+///
+/// 数据按 [`FamilyStore::load`] 读取并迁移，因此排版、字段顺序与旧格式版本
+/// 不算差异。
+///
+/// # Returns
+/// 全部差异，一致时为空；数据无法读取或解析（如写入不完整）时返回错误。
+pub fn verify_store(
+    doc: &FamilyDocument,
+    store: &dyn FamilyStore,
+) -> Result<Vec<Divergence>, ZzError> {
+    let on_disk = store.load()?;
+    let to_value = |d: &FamilyDocument| {
+        serde_json::to_value(d).map_err(|e| ZzError::Invalid(format!("序列化失败：{}", e)))
+    };
+
+    Ok(diff_values(&to_value(&on_disk)?, &to_value(doc)?))
+}
+
+/// 逐字段比较两个 JSON 值，`disk` 为旧值、`memory` 为新值
+pub fn diff_values(disk: &Value, memory: &Value) -> Vec<Divergence> {
+    let mut out = Vec::new();
+    diff(String::new(), disk, memory, &mut out);
+    out
+}
+
+fn diff(path: String, disk: &Value, memory: &Value, out: &mut Vec<Divergence>) {
+    match (disk, memory) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff(child, x, y, out),
+                    (x, y) => push(child, x, y, out),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if named(a) && named(b) => {
+            // 成员数组按姓名对齐，插入或删除一人不会让后面的成员全部错位
+            let mut names: Vec<&str> = a.iter().filter_map(name_of).collect();
+            for name in b.iter().filter_map(name_of) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            for name in names {
+                let find =
+                    |items: &'_ [Value]| items.iter().find(|v| name_of(v) == Some(name)).cloned();
+                let child = format!("{}[{}]", path, name);
+                match (find(a), find(b)) {
+                    (Some(x), Some(y)) => diff(child, &x, &y, out),
+                    (x, y) => push(child, x.as_ref(), y.as_ref(), out),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{}[{}]", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff(child, x, y, out),
+                    (x, y) => push(child, x, y, out),
+                }
+            }
+        }
+        (a, b) if a != b => push(path, Some(a), Some(b), out),
+        _ => {}
+    }
+}
+
+fn push(path: String, disk: Option<&Value>, memory: Option<&Value>, out: &mut Vec<Divergence>) {
+    out.push(Divergence {
+        path,
+        disk: disk.cloned(),
+        memory: memory.cloned(),
+    });
+}
+
+/// 数组元素都是姓名互不相同的对象（成员列表）
+fn named(items: &[Value]) -> bool {
+    let names: Option<BTreeSet<&str>> = items.iter().map(name_of).collect();
+    names.is_some_and(|names| names.len() == items.len())
+}
+
+fn name_of(value: &Value) -> Option<&str> {
+    value.get("name")?.as_str()
+}
+
+/// 差异值的简短显示，过长的对象截断
+fn brief(value: Option<&Value>) -> String {
+    const MAX_CHARS: usize = 60;
+
+    let Some(value) = value else {
+        return "（无）".to_string();
+    };
+    let s = value.to_string();
+    if s.chars().count() > MAX_CHARS {
+        format!("{}…", s.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        s
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use zz_sim_data_tool::document::FamilyDocument;
+use zz_sim_data_tool::merge::{self, OnConflict};
+use zz_sim_data_tool::store::FamilyStore;
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 监视游戏导出目录并自动导入
+///
+/// 启动时已存在的文件视为已处理，之后目录中新出现（或被改写）的 `.json`
+/// 文件会被解析并合并进家族树。每次导入前先归档当前数据，导入后立即保存。
+/// 已存在成员的字段不同时按 `on_conflict` 处理。按回车结束监视。
+pub fn watch_game(
+    dir: &Path,
+    doc: &mut FamilyDocument,
+    store: &dyn FamilyStore,
+    year: i32,
+    on_conflict: OnConflict,
+) {
+    let mut seen = scan(dir);
+
+    statusln!("👀 正在监视 {}（按回车结束）", dir.display());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line).ok();
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = scan(dir);
+        let mut fresh: Vec<&PathBuf> = current
+            .iter()
+            .filter(|(path, modified)| seen.get(*path) != Some(modified))
+            .map(|(path, _)| path)
+            .collect();
+        fresh.sort();
+
+        for path in fresh {
+            import_file(path, doc, store, year, on_conflict);
+        }
+
+        seen = current;
+    }
+
+    statusln!("⏹️ 已结束监视");
+}
+
+/// 检查监视目录
+///
+/// 不能监视数据文件或归档所在的目录：每次导入都会保存数据文件并写入归档，
+/// 这些文件又会被当作新的导出再次导入，形成自我合并的循环。
+pub fn check_dir(dir: &Path, data_file: &str, archive_dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("{} 不是目录", dir.display()));
+    }
+
+    let canonical = |p: &Path| fs::canonicalize(p).ok();
+    let watched = canonical(dir);
+    let data_dir = Path::new(data_file)
+        .parent()
+        .map(|p| {
+            if p.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                p
+            }
+        })
+        .and_then(canonical);
+    let archive_dir = canonical(archive_dir);
+
+    if watched.is_some() && (watched == data_dir || watched == archive_dir) {
+        return Err(format!(
+            "{} 是数据文件或归档所在目录，监视它会把自己的存档反复导入",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// 导入单个游戏导出文件
+fn import_file(
+    path: &Path,
+    doc: &mut FamilyDocument,
+    store: &dyn FamilyStore,
+    year: i32,
+    on_conflict: OnConflict,
+) {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    let incoming = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_str::<FamilyDocument>(&data).map_err(|e| e.to_string()))
+    {
+        Ok(incoming) => incoming,
+        Err(e) => {
+            statusln!("❌ [{}] 解析失败：{}", file_name, e);
+            return;
+        }
+    };
+
+    if let Err(e) = store.archive(year, doc) {
+        statusln!("❌ [{}] 归档失败，跳过导入：{}", file_name, e);
+        return;
+    }
+
+    let report = match merge::merge(&mut doc.tree, &incoming.tree, on_conflict) {
+        Ok(report) => report,
+        Err(conflicts) => {
+            statusln!(
+                "❌ [{}] 存在 {} 处冲突，未导入：",
+                file_name,
+                conflicts.len()
+            );
+            for conflict in &conflicts {
+                statusln!("    ⚠️  {}", conflict);
+            }
+            return;
+        }
+    };
+    statusln!("📥 [{}] {}", file_name, report.summary());
+    out!("{}", report.describe());
+
+    doc.assign_ids();
+    if let Err(e) = store.save(doc) {
+        statusln!("❌ 保存失败: {}", e);
+    }
+}
+
+/// 列出目录中的 JSON 文件及其修改时间
+fn scan(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的临时目录，名称含进程号避免并行测试互相干扰
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zz-watch-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn check_dir_refuses_data_and_archive_dirs() {
+        let data_dir = temp_dir("data");
+        let archive_dir = temp_dir("archive");
+        let data_file = data_dir.join("tree.json");
+        let data_file = data_file.to_str().unwrap();
+
+        assert!(check_dir(&data_dir, data_file, &archive_dir).is_err());
+        assert!(check_dir(&archive_dir, data_file, &archive_dir).is_err());
+        assert!(check_dir(&data_dir.join("missing"), data_file, &archive_dir).is_err());
+    }
+
+    #[test]
+    fn check_dir_allows_other_dirs() {
+        let data_dir = temp_dir("data2");
+        let archive_dir = temp_dir("archive2");
+        let exports = temp_dir("exports2");
+        let data_file = data_dir.join("tree.json");
+
+        assert!(check_dir(&exports, data_file.to_str().unwrap(), &archive_dir).is_ok());
+    }
+}